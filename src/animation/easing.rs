@@ -0,0 +1,164 @@
+use std::f32::consts::PI;
+
+/// Maps progress through a keyframe segment, from 0.0 to 1.0, to how far the
+/// value has moved. Every curve maps 0.0 to 0.0 and 1.0 to 1.0, though some
+/// overshoot in between.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    /// Pulls back a little before moving.
+    BackIn,
+    /// Overshoots a little before settling.
+    BackOut,
+    BackInOut,
+    /// A CSS `cubic-bezier(x1, y1, x2, y2)` timing function. The x values
+    /// are clamped to `0.0..=1.0` so the curve stays a function of time.
+    CubicBezier(f32, f32, f32, f32),
+    /// Jumps to the next of `n` equal steps at the end of each interval, like
+    /// CSS `steps(n, end)`. Zero steps behaves like one.
+    Steps(u32),
+    /// Holds the starting value until the segment ends.
+    Hold,
+}
+
+/// How far `BackIn` and `BackOut` overshoot, the usual 10%.
+const BACK: f32 = 1.70158;
+
+impl Easing {
+    /// CSS `ease`.
+    pub const EASE: Easing = Easing::CubicBezier(0.25, 0.1, 0.25, 1.0);
+    /// CSS `ease-in`.
+    pub const EASE_IN: Easing = Easing::CubicBezier(0.42, 0.0, 1.0, 1.0);
+    /// CSS `ease-out`.
+    pub const EASE_OUT: Easing = Easing::CubicBezier(0.0, 0.0, 0.58, 1.0);
+    /// CSS `ease-in-out`.
+    pub const EASE_IN_OUT: Easing = Easing::CubicBezier(0.42, 0.0, 0.58, 1.0);
+
+    /// Applies the curve to `t`, which is clamped to `0.0..=1.0` first.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        if t == 0.0 || t == 1.0 {
+            return t;
+        }
+
+        match *self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => out(t, |t| t * t),
+            Easing::QuadInOut => in_out(t, |t| t * t),
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => out(t, |t| t * t * t),
+            Easing::CubicInOut => in_out(t, |t| t * t * t),
+            Easing::SineIn => sine_in(t),
+            Easing::SineOut => out(t, sine_in),
+            Easing::SineInOut => in_out(t, sine_in),
+            Easing::ExpoIn => expo_in(t),
+            Easing::ExpoOut => out(t, expo_in),
+            Easing::ExpoInOut => in_out(t, expo_in),
+            Easing::BackIn => back_in(t),
+            Easing::BackOut => out(t, back_in),
+            Easing::BackInOut => in_out(t, back_in),
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+            Easing::Steps(n) => {
+                let n = n.max(1) as f32;
+                (t * n).floor() / n
+            }
+            Easing::Hold => 0.0,
+        }
+    }
+}
+
+/// The mirror image of an ease-in curve.
+fn out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+    1.0 - ease_in(1.0 - t)
+}
+
+/// An ease-in curve for the first half, then its mirror image.
+fn in_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+    if t < 0.5 {
+        ease_in(t * 2.0) / 2.0
+    } else {
+        1.0 - ease_in((1.0 - t) * 2.0) / 2.0
+    }
+}
+
+fn sine_in(t: f32) -> f32 {
+    1.0 - (t * PI / 2.0).cos()
+}
+
+fn expo_in(t: f32) -> f32 {
+    if t == 0.0 {
+        0.0
+    } else {
+        2f32.powf(10.0 * (t - 1.0))
+    }
+}
+
+fn back_in(t: f32) -> f32 {
+    t * t * ((BACK + 1.0) * t - BACK)
+}
+
+/// Solves the curve through (0, 0), (x1, y1), (x2, y2) and (1, 1) for the y
+/// at `x`.
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let (x1, x2) = (x1.clamp(0.0, 1.0), x2.clamp(0.0, 1.0));
+    // Polynomial coefficients of a 1D bezier from 0 to 1.
+    let coefficients = |p1: f32, p2: f32| {
+        let c = 3.0 * p1;
+        let b = 3.0 * (p2 - p1) - c;
+        (1.0 - c - b, b, c)
+    };
+    let (ax, bx, cx) = coefficients(x1, x2);
+    let (ay, by, cy) = coefficients(y1, y2);
+    let sample_x = |s: f32| ((ax * s + bx) * s + cx) * s;
+    let slope_x = |s: f32| (3.0 * ax * s + 2.0 * bx) * s + cx;
+
+    // Newton's method converges quickly unless the curve is nearly flat...
+    let mut s = x;
+    let mut solved = false;
+    for _ in 0..8 {
+        let error = sample_x(s) - x;
+        if error.abs() < 1e-6 {
+            solved = true;
+            break;
+        }
+        let slope = slope_x(s);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        s -= error / slope;
+    }
+
+    // ...so fall back to bisection, which always does since x only rises.
+    if !solved || !(0.0..=1.0).contains(&s) {
+        let (mut lo, mut hi) = (0.0, 1.0);
+        s = x;
+        for _ in 0..32 {
+            let value = sample_x(s);
+            if (value - x).abs() < 1e-6 {
+                break;
+            }
+            if value < x {
+                lo = s;
+            } else {
+                hi = s;
+            }
+            s = (lo + hi) / 2.0;
+        }
+    }
+
+    ((ay * s + by) * s + cy) * s
+}
@@ -0,0 +1,177 @@
+use math2d::{Color, Matrix3x2f, Point2f, Rectf, Sizef, Vector2f};
+
+use std::f32::consts::PI;
+
+/// A value which can be blended with another of the same type.
+pub trait Interpolate: Sized {
+    /// The value `t` of the way from `self` to `to`. `t` is usually in
+    /// `0.0..=1.0`, but easing curves which overshoot can push it outside,
+    /// and implementations should extrapolate rather than clamp.
+    fn interpolate(&self, to: &Self, t: f32) -> Self;
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, to: &f32, t: f32) -> f32 {
+        lerp(*self, *to, t)
+    }
+}
+
+impl Interpolate for Point2f {
+    fn interpolate(&self, to: &Point2f, t: f32) -> Point2f {
+        Point2f {
+            x: lerp(self.x, to.x, t),
+            y: lerp(self.y, to.y, t),
+        }
+    }
+}
+
+impl Interpolate for Vector2f {
+    fn interpolate(&self, to: &Vector2f, t: f32) -> Vector2f {
+        Vector2f {
+            x: lerp(self.x, to.x, t),
+            y: lerp(self.y, to.y, t),
+        }
+    }
+}
+
+impl Interpolate for Sizef {
+    fn interpolate(&self, to: &Sizef, t: f32) -> Sizef {
+        Sizef {
+            width: lerp(self.width, to.width, t),
+            height: lerp(self.height, to.height, t),
+        }
+    }
+}
+
+impl Interpolate for Rectf {
+    fn interpolate(&self, to: &Rectf, t: f32) -> Rectf {
+        Rectf {
+            left: lerp(self.left, to.left, t),
+            top: lerp(self.top, to.top, t),
+            right: lerp(self.right, to.right, t),
+            bottom: lerp(self.bottom, to.bottom, t),
+        }
+    }
+}
+
+/// Colors are blended premultiplied, so fading from a transparent color
+/// doesn't drag its (invisible) channels into the result.
+impl Interpolate for Color {
+    fn interpolate(&self, to: &Color, t: f32) -> Color {
+        let a = lerp(self.a, to.a, t);
+        if a <= 0.0 {
+            return Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.0,
+            };
+        }
+        let channel = |c0: f32, c1: f32| (lerp(c0 * self.a, c1 * to.a, t) / a).clamp(0.0, 1.0);
+        Color {
+            r: channel(self.r, to.r),
+            g: channel(self.g, to.g),
+            b: channel(self.b, to.b),
+            a: a.min(1.0),
+        }
+    }
+}
+
+/// Transforms are split into translation, rotation, scale and skew, which are
+/// blended separately and recombined. Blending the elements directly would
+/// squash a shape as it rotates; this way a rotation stays a rotation.
+///
+/// Matrices which can't be inverted can't be decomposed either, and are
+/// blended element-wise instead.
+impl Interpolate for Matrix3x2f {
+    fn interpolate(&self, to: &Matrix3x2f, t: f32) -> Matrix3x2f {
+        match (Decomposed::new(self), Decomposed::new(to)) {
+            (Some(from), Some(to)) => from.interpolate(&to, t).to_matrix(),
+            _ => Matrix3x2f {
+                a: lerp(self.a, to.a, t),
+                b: lerp(self.b, to.b, t),
+                c: lerp(self.c, to.c, t),
+                d: lerp(self.d, to.d, t),
+                x: lerp(self.x, to.x, t),
+                y: lerp(self.y, to.y, t),
+            },
+        }
+    }
+}
+
+/// A 2D affine transform split into parts which blend naturally. The
+/// transform applies `scale_x`, `scale_y` and `skew` first, then `rotation`,
+/// then `translation`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Decomposed {
+    pub translation: Vector2f,
+    /// Radians, clockwise on screen.
+    pub rotation: f32,
+    pub scale_x: f32,
+    /// Negative for transforms which mirror.
+    pub scale_y: f32,
+    /// How far the y axis leans along the x axis, per unit of y.
+    pub skew: f32,
+}
+
+impl Decomposed {
+    /// Splits `m` up, or `None` if it can't be inverted.
+    pub fn new(m: &Matrix3x2f) -> Option<Decomposed> {
+        let scale_x = m.a.hypot(m.b);
+        if scale_x == 0.0 || !scale_x.is_finite() {
+            return None;
+        }
+        let (cos, sin) = (m.a / scale_x, m.b / scale_x);
+
+        // The y axis, split into its parts along and across the x axis.
+        let along = m.c * cos + m.d * sin;
+        let scale_y = m.d * cos - m.c * sin;
+        if scale_y == 0.0 || !scale_y.is_finite() {
+            return None;
+        }
+
+        Some(Decomposed {
+            translation: Vector2f { x: m.x, y: m.y },
+            rotation: sin.atan2(cos),
+            scale_x,
+            scale_y,
+            skew: along / scale_y,
+        })
+    }
+
+    pub fn to_matrix(&self) -> Matrix3x2f {
+        let (sin, cos) = self.rotation.sin_cos();
+        let along = self.skew * self.scale_y;
+        Matrix3x2f {
+            a: self.scale_x * cos,
+            b: self.scale_x * sin,
+            c: along * cos - self.scale_y * sin,
+            d: along * sin + self.scale_y * cos,
+            x: self.translation.x,
+            y: self.translation.y,
+        }
+    }
+}
+
+/// Rotation takes the shorter way around.
+impl Interpolate for Decomposed {
+    fn interpolate(&self, to: &Decomposed, t: f32) -> Decomposed {
+        let mut turn = (to.rotation - self.rotation) % (2.0 * PI);
+        if turn > PI {
+            turn -= 2.0 * PI;
+        } else if turn < -PI {
+            turn += 2.0 * PI;
+        }
+        Decomposed {
+            translation: self.translation.interpolate(&to.translation, t),
+            rotation: self.rotation + turn * t,
+            scale_x: lerp(self.scale_x, to.scale_x, t),
+            scale_y: lerp(self.scale_y, to.scale_y, t),
+            skew: lerp(self.skew, to.skew, t),
+        }
+    }
+}
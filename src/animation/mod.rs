@@ -0,0 +1,17 @@
+//! Animating properties over time.
+//!
+//! Values blend with `Interpolate`, which decomposes transforms rather than
+//! blending their elements. A `Track` holds keyframes with an `Easing` for
+//! each segment, and a `Timeline` samples its tracks at its current time and
+//! hands the values to setters, such as a brush's opacity or transform.
+//! Time only moves when the timeline is told to, so sampling is deterministic.
+
+pub use self::easing::Easing;
+pub use self::interpolate::{Decomposed, Interpolate};
+pub use self::timeline::{AnimationId, Playback, Repeat, Timeline};
+pub use self::track::{Keyframe, Track};
+
+pub mod easing;
+pub mod interpolate;
+pub mod timeline;
+pub mod track;
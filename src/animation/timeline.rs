@@ -0,0 +1,321 @@
+use crate::animation::{Interpolate, Track};
+
+#[cfg(windows)]
+use crate::brush::{IBrush, SolidColorBrush};
+#[cfg(windows)]
+use math2d::{Color, Matrix3x2f};
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How many times an animation plays.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Repeat {
+    /// Plays this many times in total. Zero plays once.
+    Count(u32),
+    Forever,
+}
+
+/// When and how a track is played by a `Timeline`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Playback {
+    /// Seconds to wait, holding the first value, before starting.
+    pub delay: f32,
+    pub repeat: Repeat,
+    /// Plays every other repetition backwards.
+    pub alternate: bool,
+}
+
+impl Playback {
+    /// Plays once, straight away.
+    pub fn once() -> Playback {
+        Playback {
+            delay: 0.0,
+            repeat: Repeat::Count(1),
+            alternate: false,
+        }
+    }
+
+    pub fn forever() -> Playback {
+        Playback::once().with_repeat(Repeat::Forever)
+    }
+
+    /// Delays that aren't finite are treated as no delay.
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = if delay.is_finite() { delay } else { 0.0 };
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn with_alternate(mut self, alternate: bool) -> Self {
+        self.alternate = alternate;
+        self
+    }
+
+    /// Maps seconds since the animation was started to a time within a track
+    /// lasting `duration` seconds, and whether the animation has finished.
+    pub fn local_time(&self, elapsed: f64, duration: f32) -> (f32, bool) {
+        let elapsed = elapsed - self.delay as f64;
+        if elapsed < 0.0 || elapsed.is_nan() {
+            return (0.0, false);
+        }
+        let count = match self.repeat {
+            Repeat::Count(n) => Some(n.max(1) as u64),
+            Repeat::Forever => None,
+        };
+        if duration <= 0.0 {
+            return (0.0, count.is_some());
+        }
+
+        let duration = duration as f64;
+        let iteration = (elapsed / duration).floor() as u64;
+        let (iteration, within, finished) = match count {
+            Some(count) if iteration >= count => (count - 1, duration, true),
+            _ => (iteration, elapsed - iteration as f64 * duration, false),
+        };
+        let time = if self.alternate && iteration % 2 == 1 {
+            duration - within
+        } else {
+            within
+        };
+        (time as f32, finished)
+    }
+}
+
+impl Default for Playback {
+    fn default() -> Playback {
+        Playback::once()
+    }
+}
+
+/// Identifies an animation in a `Timeline`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AnimationId(usize);
+
+struct Animation {
+    id: AnimationId,
+    /// Timeline time when the animation was added.
+    start: Duration,
+    duration: f32,
+    playback: Playback,
+    /// Samples the track at a local time and passes the value on.
+    apply: Box<dyn FnMut(f32)>,
+    /// Whether the final value has already been applied.
+    finished: bool,
+}
+
+/// Plays tracks against a clock, passing each sampled value to a setter.
+///
+/// The timeline's time only changes through `advance`, `seek` and `tick`, and
+/// the values applied depend on nothing else, so the same calls always apply
+/// the same values. `tick` reads from a real clock; tests can use `advance`.
+pub struct Timeline {
+    elapsed: Duration,
+    last_tick: Option<Instant>,
+    paused: bool,
+    animations: Vec<Animation>,
+    next_id: usize,
+}
+
+impl Timeline {
+    pub fn new() -> Timeline {
+        Timeline {
+            elapsed: Duration::from_secs(0),
+            last_tick: None,
+            paused: false,
+            animations: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Starts playing `track` from the timeline's current time, calling
+    /// `setter` with its value every time the timeline moves. The first value
+    /// is applied straight away.
+    pub fn animate<T, F>(
+        &mut self,
+        track: Track<T>,
+        playback: Playback,
+        mut setter: F,
+    ) -> AnimationId
+    where
+        T: Interpolate + Clone + 'static,
+        F: FnMut(T) + 'static,
+    {
+        let id = AnimationId(self.next_id);
+        self.next_id += 1;
+        let mut animation = Animation {
+            id,
+            start: self.elapsed,
+            duration: track.end_time(),
+            playback,
+            apply: Box::new(move |time| {
+                if let Some(value) = track.sample(time) {
+                    setter(value);
+                }
+            }),
+            finished: false,
+        };
+        animation.update(self.elapsed);
+        self.animations.push(animation);
+        id
+    }
+
+    /// Stops an animation, leaving whatever it last set in place.
+    pub fn remove(&mut self, id: AnimationId) -> bool {
+        let count = self.animations.len();
+        self.animations.retain(|a| a.id != id);
+        self.animations.len() != count
+    }
+
+    pub fn contains(&self, id: AnimationId) -> bool {
+        self.animations.iter().any(|a| a.id == id)
+    }
+
+    pub fn clear(&mut self) {
+        self.animations.clear();
+    }
+
+    /// The number of animations, finished or not.
+    pub fn animation_count(&self) -> usize {
+        self.animations.len()
+    }
+
+    /// Whether every animation has applied its final value. Finished
+    /// animations are kept, so seeking back plays them again.
+    pub fn is_finished(&self) -> bool {
+        self.animations.iter().all(|a| a.finished)
+    }
+
+    /// Removes the animations which have finished.
+    pub fn remove_finished(&mut self) {
+        self.animations.retain(|a| !a.finished);
+    }
+
+    /// The time since the timeline was created, not counting pauses.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stops time moving forward in `advance` and `tick`. `seek` still works.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Moves time forward by `dt` and applies every animation.
+    pub fn advance(&mut self, dt: Duration) {
+        if !self.paused {
+            self.elapsed += dt;
+        }
+        self.update();
+    }
+
+    /// Jumps to `time`, forwards or backwards, and applies every animation.
+    /// Animations added after `time` hold their first value.
+    pub fn seek(&mut self, time: Duration) {
+        self.elapsed = time;
+        self.update();
+    }
+
+    /// Moves time forward by however long it has been since the last tick.
+    /// The first tick only starts the clock.
+    pub fn tick(&mut self, now: Instant) {
+        let dt = match self.last_tick {
+            Some(last) if now > last => now - last,
+            _ => Duration::from_secs(0),
+        };
+        self.last_tick = Some(now);
+        self.advance(dt);
+    }
+
+    fn update(&mut self) {
+        let elapsed = self.elapsed;
+        for animation in &mut self.animations {
+            animation.update(elapsed);
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Timeline {
+    /// Animates the opacity of `brush`, or of anything it was cloned from.
+    pub fn animate_opacity<B>(
+        &mut self,
+        brush: &B,
+        track: Track<f32>,
+        playback: Playback,
+    ) -> AnimationId
+    where
+        B: IBrush + Clone + 'static,
+    {
+        let mut brush = brush.clone();
+        self.animate(track, playback, move |opacity| brush.set_opacity(opacity))
+    }
+
+    /// Animates the transform of `brush`, or of anything it was cloned from.
+    pub fn animate_brush_transform<B>(
+        &mut self,
+        brush: &B,
+        track: Track<Matrix3x2f>,
+        playback: Playback,
+    ) -> AnimationId
+    where
+        B: IBrush + Clone + 'static,
+    {
+        let mut brush = brush.clone();
+        self.animate(track, playback, move |transform| {
+            brush.set_transform(&transform)
+        })
+    }
+
+    /// Animates the color of `brush`, or of anything it was cloned from.
+    pub fn animate_color(
+        &mut self,
+        brush: &SolidColorBrush,
+        track: Track<Color>,
+        playback: Playback,
+    ) -> AnimationId {
+        let mut brush = brush.clone();
+        self.animate(track, playback, move |color| brush.set_color(color))
+    }
+}
+
+impl Animation {
+    fn update(&mut self, now: Duration) {
+        let since_start = now.as_secs_f64() - self.start.as_secs_f64();
+        let (time, finished) = self.playback.local_time(since_start, self.duration);
+        // Once the final value is set there's nothing new to apply.
+        if finished && self.finished {
+            return;
+        }
+        (self.apply)(time);
+        self.finished = finished;
+    }
+}
+
+impl Default for Timeline {
+    fn default() -> Timeline {
+        Timeline::new()
+    }
+}
+
+impl fmt::Debug for Timeline {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Timeline")
+            .field("elapsed", &self.elapsed)
+            .field("paused", &self.paused)
+            .field("animations", &self.animations.len())
+            .finish()
+    }
+}
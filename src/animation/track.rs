@@ -0,0 +1,103 @@
+use crate::animation::{Easing, Interpolate};
+
+/// A value at a point in time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframe<T> {
+    /// Seconds from the start of the track.
+    pub time: f32,
+    pub value: T,
+    /// The curve used on the way from this keyframe to the next.
+    pub easing: Easing,
+}
+
+/// Keyframes sorted by time. Between two keyframes the value is interpolated
+/// using the first one's easing; before the first and after the last it
+/// holds still.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Interpolate + Clone> Track<T> {
+    pub fn new() -> Track<T> {
+        Track {
+            keyframes: Vec::new(),
+        }
+    }
+
+    /// A track going from `from` to `to` over `duration` seconds.
+    pub fn tween(from: T, to: T, duration: f32, easing: Easing) -> Track<T> {
+        Track::new()
+            .with_key_eased(0.0, from, easing)
+            .with_key(duration, to)
+    }
+
+    /// Adds a keyframe which moves on to the next one linearly.
+    pub fn with_key(self, time: f32, value: T) -> Self {
+        self.with_key_eased(time, value, Easing::Linear)
+    }
+
+    pub fn with_key_eased(mut self, time: f32, value: T, easing: Easing) -> Self {
+        self.add_key(time, value, easing);
+        self
+    }
+
+    /// Inserts a keyframe in time order. A keyframe at the same time as an
+    /// existing one goes after it, so two keyframes at one time make the
+    /// value jump.
+    ///
+    /// # Panics
+    ///
+    /// If `time` is NaN.
+    pub fn add_key(&mut self, time: f32, value: T, easing: Easing) {
+        assert!(!time.is_nan(), "keyframe times can't be NaN");
+        let index = self.keyframes.partition_point(|k| k.time <= time);
+        self.keyframes.insert(
+            index,
+            Keyframe {
+                time,
+                value,
+                easing,
+            },
+        );
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// The time of the last keyframe, or 0.0 if there are none.
+    pub fn end_time(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time.max(0.0))
+    }
+
+    /// The value at `time` seconds, or `None` if the track is empty. A NaN
+    /// time gives the first value.
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        if time < first.time || time.is_nan() {
+            return Some(first.value.clone());
+        }
+        if time >= last.time {
+            return Some(last.value.clone());
+        }
+
+        // The first keyframe after `time`; there's one before it too, since
+        // `time` is inside the track.
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        let (from, to) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let t = (time - from.time) / (to.time - from.time);
+        Some(from.value.interpolate(&to.value, from.easing.apply(t)))
+    }
+}
+
+impl<T: Interpolate + Clone> Default for Track<T> {
+    fn default() -> Track<T> {
+        Track::new()
+    }
+}
@@ -0,0 +1,180 @@
+//! Renders a scene file to a PNG or SVG image.
+//!
+//! ```text
+//! d2d-render <scene.json|scene.ron> -o <out.png|out.svg> [--size WxH] [--dpi N] [--format png|svg]
+//! ```
+//!
+//! The scene is drawn at its own size unless `--size` stretches it to
+//! another, in DIPs. PNGs are `size * dpi / 96` pixels big, and need
+//! Direct2D, so are only written on Windows.
+
+use direct2d::record::Recording;
+use direct2d::scene_file::SceneFile;
+use direct2d::svg::to_svg;
+
+use math2d::{Matrix3x2f, Sizef};
+
+use std::path::{Path, PathBuf};
+use std::process;
+
+const USAGE: &str =
+    "usage: d2d-render <scene> -o <output> [--size WxH] [--dpi N] [--format png|svg]";
+
+#[derive(Copy, Clone, PartialEq)]
+enum Format {
+    Png,
+    Svg,
+}
+
+struct Options {
+    scene: PathBuf,
+    output: PathBuf,
+    size: Option<Sizef>,
+    dpi: f32,
+    format: Format,
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("d2d-render: {}\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(message) = run(&options) {
+        eprintln!("d2d-render: {}", message);
+        process::exit(1);
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut scene = None;
+    let mut output = None;
+    let mut size = None;
+    let mut dpi = 96.0;
+    let mut format = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match &*arg {
+            "-o" | "--output" => output = Some(PathBuf::from(value(&arg)?)),
+            "--size" => {
+                let text = value(&arg)?;
+                size = Some(parse_size(&text).ok_or(format!("bad size `{}`", text))?);
+            }
+            "--dpi" => {
+                let text = value(&arg)?;
+                dpi = match text.parse::<f32>() {
+                    Ok(dpi) if dpi > 0.0 && dpi.is_finite() => dpi,
+                    _ => return Err(format!("bad DPI `{}`", text)),
+                };
+            }
+            "--format" => {
+                let text = value(&arg)?;
+                format = Some(parse_format(&text).ok_or(format!("unknown format `{}`", text))?);
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("unknown option `{}`", arg));
+            }
+            _ if scene.is_none() => scene = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+
+    let scene = scene.ok_or("no scene file given")?;
+    let output = output.ok_or("no output file given")?;
+    let format = match format {
+        Some(format) => format,
+        None => output
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(parse_format)
+            .ok_or("can't tell the format from the output file; use --format")?,
+    };
+    Ok(Options {
+        scene,
+        output,
+        size,
+        dpi,
+        format,
+    })
+}
+
+/// `WxH`, in DIPs.
+fn parse_size(text: &str) -> Option<Sizef> {
+    let (width, height) = text.split_once(['x', 'X'])?;
+    let (width, height) = (width.parse::<f32>().ok()?, height.parse::<f32>().ok()?);
+    if width > 0.0 && height > 0.0 && width.is_finite() && height.is_finite() {
+        Some(Sizef::new(width, height))
+    } else {
+        None
+    }
+}
+
+fn parse_format(text: &str) -> Option<Format> {
+    match &*text.to_ascii_lowercase() {
+        "png" => Some(Format::Png),
+        "svg" => Some(Format::Svg),
+        _ => None,
+    }
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let text = std::fs::read_to_string(&options.scene)
+        .map_err(|e| format!("{}: {}", options.scene.display(), e))?;
+    let scene =
+        SceneFile::parse(&text).map_err(|e| format!("{}: {}", options.scene.display(), e))?;
+
+    // Stretch the scene to the requested size.
+    let size = options.size.unwrap_or_else(|| scene.size());
+    let mut recording = Recording::new(size);
+    let scale = Matrix3x2f {
+        a: size.width / scene.size().width,
+        b: 0.0,
+        c: 0.0,
+        d: size.height / scene.size().height,
+        x: 0.0,
+        y: 0.0,
+    };
+    if scale != Matrix3x2f::IDENTITY {
+        recording.set_transform(&scale);
+    }
+    scene.record(&mut recording);
+
+    let bytes = match options.format {
+        Format::Svg => to_svg(&recording).into_bytes(),
+        Format::Png => render_png(&recording, options.dpi)?,
+    };
+    write(&options.output, &bytes)
+}
+
+#[cfg(windows)]
+fn render_png(recording: &Recording, dpi: f32) -> Result<Vec<u8>, String> {
+    use direct2d::render_target::Offscreen;
+    use math2d::Sizeu;
+
+    let pixels = |dips: f32| (dips * dpi / 96.0).ceil().max(1.0) as u32;
+    let size = recording.size();
+    let mut offscreen = Offscreen::new(Sizeu::new(pixels(size.width), pixels(size.height)), dpi)
+        .map_err(|e| format!("couldn't create a Direct2D target: {}", e))?;
+    let mut result = Ok(());
+    let bitmap = offscreen
+        .render(|context| result = recording.draw(context))
+        .map_err(|e| format!("rendering failed: {}", e))?;
+    result.map_err(|e| format!("rendering failed: {}", e))?;
+    Ok(bitmap.to_png())
+}
+
+#[cfg(not(windows))]
+fn render_png(_recording: &Recording, _dpi: f32) -> Result<Vec<u8>, String> {
+    Err("PNG output needs Direct2D, which is only on Windows; try --format svg".to_string())
+}
+
+fn write(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    std::fs::write(path, bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
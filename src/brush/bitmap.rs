@@ -1,5 +1,6 @@
 use crate::brush::IBrush;
 use crate::enums::{BitmapInterpolationMode, ExtendMode};
+use crate::image::{Bitmap, IBitmap};
 use crate::render_target::IRenderTarget;
 
 use checked_enum::UncheckedEnum;
@@ -23,17 +24,47 @@ impl BitmapBrush {
         BitmapBrushBuilder::new(context)
     }
 
+    /// Retrieves the bitmap being painted, if one has been set.
+    pub fn bitmap(&self) -> Option<Bitmap> {
+        unsafe {
+            let mut ptr = std::ptr::null_mut();
+            self.ptr.GetBitmap(&mut ptr);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(Bitmap::from_raw(ptr))
+            }
+        }
+    }
+
+    /// Replaces the bitmap being painted.
+    pub fn set_bitmap(&mut self, bitmap: &dyn IBitmap) {
+        unsafe { self.ptr.SetBitmap(bitmap.raw_bitmap() as *const _ as *mut _) }
+    }
+
     pub fn extend_mode_x(&self) -> UncheckedEnum<ExtendMode> {
         unsafe { self.ptr.GetExtendModeX().into() }
     }
 
+    pub fn set_extend_mode_x(&mut self, mode: ExtendMode) {
+        unsafe { self.ptr.SetExtendModeX(mode as u32) }
+    }
+
     pub fn extend_mode_y(&self) -> UncheckedEnum<ExtendMode> {
         unsafe { self.ptr.GetExtendModeY().into() }
     }
 
+    pub fn set_extend_mode_y(&mut self, mode: ExtendMode) {
+        unsafe { self.ptr.SetExtendModeY(mode as u32) }
+    }
+
     pub fn interpolation_mode(&self) -> UncheckedEnum<BitmapInterpolationMode> {
         unsafe { self.ptr.GetInterpolationMode().into() }
     }
+
+    pub fn set_interpolation_mode(&mut self, mode: BitmapInterpolationMode) {
+        unsafe { self.ptr.SetInterpolationMode(mode as u32) }
+    }
 }
 
 unsafe impl IBrush for BitmapBrush {
@@ -41,3 +72,5 @@ unsafe impl IBrush for BitmapBrush {
         &self.ptr
     }
 }
+
+unsafe impl super::BrushType for BitmapBrush {}
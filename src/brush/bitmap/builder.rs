@@ -69,6 +69,12 @@ impl<'a> BitmapBrushBuilder<'a> {
         self
     }
 
+    #[inline]
+    pub fn with_interpolation_mode(mut self, mode: BitmapInterpolationMode) -> Self {
+        self.b_properties.interpolationMode = mode as u32;
+        self
+    }
+
     #[inline]
     pub fn with_properties(mut self, properties: BrushProperties) -> Self {
         self.properties = properties;
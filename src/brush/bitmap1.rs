@@ -0,0 +1,84 @@
+use crate::brush::bitmap::BitmapBrush;
+use crate::brush::IBrush;
+use crate::device_context::IDeviceContext;
+use crate::enums::{ExtendMode, InterpolationMode};
+use crate::image::{Bitmap, IBitmap};
+
+use checked_enum::UncheckedEnum;
+use com_wrapper::ComWrapper;
+use winapi::um::d2d1::ID2D1Brush;
+use winapi::um::d2d1_1::ID2D1BitmapBrush1;
+use wio::com::ComPtr;
+
+pub use self::builder::*;
+
+pub mod builder;
+
+#[repr(transparent)]
+#[derive(ComWrapper, Clone)]
+#[com(send, sync, debug)]
+/// A bitmap brush which supports the full set of Direct2D 1.1 interpolation modes.
+pub struct BitmapBrush1 {
+    ptr: ComPtr<ID2D1BitmapBrush1>,
+}
+
+impl BitmapBrush1 {
+    pub fn create<'a>(context: &'a dyn IDeviceContext) -> BitmapBrushBuilder1<'a> {
+        BitmapBrushBuilder1::new(context)
+    }
+
+    /// Retrieves the bitmap being painted, if one has been set.
+    pub fn bitmap(&self) -> Option<Bitmap> {
+        unsafe {
+            let mut ptr = std::ptr::null_mut();
+            self.ptr.GetBitmap(&mut ptr);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(Bitmap::from_raw(ptr))
+            }
+        }
+    }
+
+    /// Replaces the bitmap being painted.
+    pub fn set_bitmap(&mut self, bitmap: &dyn IBitmap) {
+        unsafe { self.ptr.SetBitmap(bitmap.raw_bitmap() as *const _ as *mut _) }
+    }
+
+    pub fn extend_mode_x(&self) -> UncheckedEnum<ExtendMode> {
+        unsafe { self.ptr.GetExtendModeX().into() }
+    }
+
+    pub fn set_extend_mode_x(&mut self, mode: ExtendMode) {
+        unsafe { self.ptr.SetExtendModeX(mode as u32) }
+    }
+
+    pub fn extend_mode_y(&self) -> UncheckedEnum<ExtendMode> {
+        unsafe { self.ptr.GetExtendModeY().into() }
+    }
+
+    pub fn set_extend_mode_y(&mut self, mode: ExtendMode) {
+        unsafe { self.ptr.SetExtendModeY(mode as u32) }
+    }
+
+    pub fn interpolation_mode(&self) -> UncheckedEnum<InterpolationMode> {
+        unsafe { self.ptr.GetInterpolationMode1().into() }
+    }
+
+    pub fn set_interpolation_mode(&mut self, mode: InterpolationMode) {
+        unsafe { self.ptr.SetInterpolationMode1(mode as u32) }
+    }
+
+    /// Get this brush as a plain `BitmapBrush`.
+    pub fn as_bitmap_brush(&self) -> BitmapBrush {
+        unsafe { BitmapBrush::from_ptr(self.ptr.clone().up()) }
+    }
+}
+
+unsafe impl IBrush for BitmapBrush1 {
+    unsafe fn raw_brush(&self) -> &ID2D1Brush {
+        &self.ptr
+    }
+}
+
+unsafe impl super::BrushType for BitmapBrush1 {}
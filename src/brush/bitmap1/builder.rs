@@ -0,0 +1,89 @@
+use crate::brush::bitmap1::BitmapBrush1;
+use crate::device_context::IDeviceContext;
+use crate::enums::{ExtendMode, InterpolationMode};
+use crate::image::IBitmap;
+use crate::properties::BrushProperties;
+
+use std::ptr;
+
+use com_wrapper::ComWrapper;
+use dcommon::Error;
+use math2d::Matrix3x2f;
+use winapi::um::d2d1_1::D2D1_BITMAP_BRUSH_PROPERTIES1;
+
+pub struct BitmapBrushBuilder1<'a> {
+    context: &'a dyn IDeviceContext,
+    bitmap: Option<&'a dyn IBitmap>,
+    b_properties: D2D1_BITMAP_BRUSH_PROPERTIES1,
+    properties: BrushProperties,
+}
+
+impl<'a> BitmapBrushBuilder1<'a> {
+    pub fn new(context: &'a dyn IDeviceContext) -> Self {
+        BitmapBrushBuilder1 {
+            context,
+            bitmap: None,
+            b_properties: D2D1_BITMAP_BRUSH_PROPERTIES1 {
+                extendModeX: ExtendMode::Clamp as u32,
+                extendModeY: ExtendMode::Clamp as u32,
+                interpolationMode: InterpolationMode::Linear as u32,
+            },
+            properties: BrushProperties::new(1.0, &Matrix3x2f::IDENTITY),
+        }
+    }
+
+    pub fn build(self) -> Result<BitmapBrush1, Error> {
+        let bitmap = self.bitmap.expect("`bitmap` must be specified");
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let hr = self.context.raw_dc().CreateBitmapBrush(
+                bitmap.raw_bitmap() as *const _ as *mut _,
+                &self.b_properties,
+                (&self.properties) as *const _ as *const _,
+                &mut ptr,
+            );
+            Error::map_if(hr, || BitmapBrush1::from_raw(ptr))
+        }
+    }
+
+    pub fn with_bitmap(mut self, bitmap: &'a dyn IBitmap) -> Self {
+        self.bitmap = Some(bitmap);
+        self
+    }
+
+    #[inline]
+    pub fn with_extend_mode_x(mut self, mode: ExtendMode) -> Self {
+        self.b_properties.extendModeX = mode as u32;
+        self
+    }
+
+    #[inline]
+    pub fn with_extend_mode_y(mut self, mode: ExtendMode) -> Self {
+        self.b_properties.extendModeY = mode as u32;
+        self
+    }
+
+    #[inline]
+    pub fn with_interpolation_mode(mut self, mode: InterpolationMode) -> Self {
+        self.b_properties.interpolationMode = mode as u32;
+        self
+    }
+
+    #[inline]
+    pub fn with_properties(mut self, properties: BrushProperties) -> Self {
+        self.properties = properties;
+        self
+    }
+
+    #[inline]
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.properties.opacity = opacity;
+        self
+    }
+
+    #[inline]
+    pub fn with_transform(mut self, transform: Matrix3x2f) -> Self {
+        self.properties.transform = transform;
+        self
+    }
+}
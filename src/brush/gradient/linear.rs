@@ -36,6 +36,16 @@ impl LinearGradientBrush {
         unsafe { self.ptr.GetEndPoint().into() }
     }
 
+    /// Sets the coordinate where the gradient stop at position 0.0 applies in full.
+    pub fn set_start_point(&mut self, start: Point2f) {
+        unsafe { self.ptr.SetStartPoint(start.into()) }
+    }
+
+    /// Sets the coordinate where the gradient stop at position 1.0 applies in full.
+    pub fn set_end_point(&mut self, end: Point2f) {
+        unsafe { self.ptr.SetEndPoint(end.into()) }
+    }
+
     /// Retrieves the `GradientStopCollection` associated with this linear gradient brush.
     pub fn gradient_stop_collection(&self) -> GradientStopCollection {
         unsafe {
@@ -51,3 +61,5 @@ unsafe impl IBrush for LinearGradientBrush {
         &self.ptr
     }
 }
+
+unsafe impl crate::brush::BrushType for LinearGradientBrush {}
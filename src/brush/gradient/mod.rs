@@ -1,7 +1,9 @@
 pub use self::linear::LinearGradientBrush;
 pub use self::radial::RadialGradientBrush;
 pub use self::stops::GradientStopCollection;
+pub use self::stops1::GradientStopCollection1;
 
 pub mod linear;
 pub mod radial;
 pub mod stops;
+pub mod stops1;
@@ -16,7 +16,7 @@ pub mod builder;
 #[repr(transparent)]
 #[derive(ComWrapper, Clone)]
 #[com(send, sync, debug)]
-/// Paints an area with a linear gradient.
+/// Paints an area with a radial gradient.
 pub struct RadialGradientBrush {
     ptr: ComPtr<ID2D1RadialGradientBrush>,
 }
@@ -30,10 +30,20 @@ impl RadialGradientBrush {
         unsafe { self.ptr.GetCenter() }.into()
     }
 
+    /// Moves the center of the gradient ellipse.
+    pub fn set_center(&mut self, center: Point2f) {
+        unsafe { self.ptr.SetCenter(center.into()) }
+    }
+
     pub fn gradient_origin_offset(&self) -> Point2f {
         unsafe { self.ptr.GetGradientOriginOffset() }.into()
     }
 
+    /// Moves the origin of the gradient, relative to the center of the ellipse.
+    pub fn set_gradient_origin_offset(&mut self, offset: Point2f) {
+        unsafe { self.ptr.SetGradientOriginOffset(offset.into()) }
+    }
+
     /// Retrieves the `GradientStopCollection` associated with this linear gradient brush.
     pub fn gradient_stop_collection(&self) -> GradientStopCollection {
         unsafe {
@@ -50,6 +60,14 @@ impl RadialGradientBrush {
     pub fn radius_y(&self) -> f32 {
         unsafe { self.ptr.GetRadiusY() }
     }
+
+    pub fn set_radius_x(&mut self, radius_x: f32) {
+        unsafe { self.ptr.SetRadiusX(radius_x) }
+    }
+
+    pub fn set_radius_y(&mut self, radius_y: f32) {
+        unsafe { self.ptr.SetRadiusY(radius_y) }
+    }
 }
 
 unsafe impl IBrush for RadialGradientBrush {
@@ -57,3 +75,5 @@ unsafe impl IBrush for RadialGradientBrush {
         &self.ptr
     }
 }
+
+unsafe impl crate::brush::BrushType for RadialGradientBrush {}
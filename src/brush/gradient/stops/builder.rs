@@ -26,11 +26,7 @@ impl<'a> GradientStopBuilder<'a> {
     }
 
     pub fn build(self) -> Result<GradientStopCollection, Error> {
-        let slice: &[GradientStop] = match self.state {
-            GradientStopState::Empty => &[],
-            GradientStopState::Stops(ref vec) => vec,
-            GradientStopState::StopSlice(slice) => slice,
-        };
+        let slice = self.state.as_slice();
 
         unsafe {
             let mut ptr = std::ptr::null_mut();
@@ -63,8 +59,33 @@ impl<'a> GradientStopBuilder<'a> {
     where
         G: Into<GradientStop>,
     {
-        let stop = stop.into();
-        self.state = match self.state {
+        self.state = self.state.with_stop(stop.into());
+        self
+    }
+
+    pub fn with_stops(mut self, stops: &'a [GradientStop]) -> Self {
+        self.state = self.state.with_stops(stops);
+        self
+    }
+}
+
+pub(crate) enum GradientStopState<'a> {
+    Empty,
+    Stops(Vec<GradientStop>),
+    StopSlice(&'a [GradientStop]),
+}
+
+impl<'a> GradientStopState<'a> {
+    pub(crate) fn as_slice(&self) -> &[GradientStop] {
+        match *self {
+            GradientStopState::Empty => &[],
+            GradientStopState::Stops(ref vec) => vec,
+            GradientStopState::StopSlice(slice) => slice,
+        }
+    }
+
+    pub(crate) fn with_stop(self, stop: GradientStop) -> Self {
+        match self {
             GradientStopState::Empty => GradientStopState::Stops(vec![stop]),
             GradientStopState::Stops(mut vec) => {
                 vec.push(stop);
@@ -73,12 +94,11 @@ impl<'a> GradientStopBuilder<'a> {
             GradientStopState::StopSlice(slice) => {
                 GradientStopState::Stops(slice.iter().cloned().chain(Some(stop)).collect())
             }
-        };
-        self
+        }
     }
 
-    pub fn with_stops(mut self, stops: &'a [GradientStop]) -> Self {
-        self.state = match self.state {
+    pub(crate) fn with_stops(self, stops: &'a [GradientStop]) -> Self {
+        match self {
             GradientStopState::Empty => GradientStopState::StopSlice(stops),
             GradientStopState::Stops(mut vec) => {
                 vec.extend(stops);
@@ -87,13 +107,6 @@ impl<'a> GradientStopBuilder<'a> {
             GradientStopState::StopSlice(slice) => {
                 GradientStopState::Stops(slice.iter().chain(stops).cloned().collect())
             }
-        };
-        self
+        }
     }
 }
-
-enum GradientStopState<'a> {
-    Empty,
-    Stops(Vec<GradientStop>),
-    StopSlice(&'a [GradientStop]),
-}
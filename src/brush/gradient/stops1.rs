@@ -0,0 +1,81 @@
+use crate::brush::gradient::stops::GradientStopCollection;
+use crate::descriptions::GradientStop;
+use crate::device_context::IDeviceContext;
+use crate::enums::{BufferPrecision, ColorInterpolationMode, ColorSpace};
+
+use checked_enum::UncheckedEnum;
+use com_wrapper::ComWrapper;
+use winapi::um::d2d1::ID2D1Resource;
+use winapi::um::d2d1_1::ID2D1GradientStopCollection1;
+use wio::com::ComPtr;
+
+pub use self::builder::GradientStopBuilder1;
+
+pub mod builder;
+
+#[repr(transparent)]
+#[derive(ComWrapper, Clone)]
+#[com(send, sync, debug)]
+/// A Direct2D 1.1 collection of gradient stops, which can interpolate in a
+/// different color space than it is rendered in and be stored with higher
+/// precision than 8 bits per channel.
+pub struct GradientStopCollection1 {
+    ptr: ComPtr<ID2D1GradientStopCollection1>,
+}
+
+impl GradientStopCollection1 {
+    pub fn create<'a>(context: &'a dyn IDeviceContext) -> GradientStopBuilder1<'a> {
+        GradientStopBuilder1::new(context)
+    }
+
+    /// Get the number of stops in the collection
+    pub fn len(&self) -> u32 {
+        unsafe { self.ptr.GetGradientStopCount() }
+    }
+
+    /// Get the stops as they were specified when the collection was created,
+    /// with straight alpha and in the pre-interpolation color space.
+    pub fn stops(&self) -> Vec<GradientStop> {
+        unsafe {
+            let len = self.len();
+            let mut stops: Vec<GradientStop> = Vec::with_capacity(len as usize);
+            self.ptr.GetGradientStops1(stops.as_mut_ptr() as *mut _, len);
+            stops.set_len(len as usize);
+            stops
+        }
+    }
+
+    /// The color space the stops were specified in, which is where the
+    /// interpolation happens.
+    pub fn pre_interpolation_space(&self) -> UncheckedEnum<ColorSpace> {
+        unsafe { self.ptr.GetPreInterpolationSpace().into() }
+    }
+
+    /// The color space the interpolated colors are converted into before
+    /// being stored.
+    pub fn post_interpolation_space(&self) -> UncheckedEnum<ColorSpace> {
+        unsafe { self.ptr.GetPostInterpolationSpace().into() }
+    }
+
+    /// The precision of the texture the gradient is rendered into.
+    pub fn buffer_precision(&self) -> UncheckedEnum<BufferPrecision> {
+        unsafe { self.ptr.GetBufferPrecision().into() }
+    }
+
+    /// Whether the stops are interpolated with straight or premultiplied alpha.
+    pub fn color_interpolation_mode(&self) -> UncheckedEnum<ColorInterpolationMode> {
+        unsafe { self.ptr.GetColorInterpolationMode().into() }
+    }
+
+    /// Get this collection as a plain `GradientStopCollection` so it can be
+    /// given to the gradient brush builders.
+    pub fn as_collection(&self) -> GradientStopCollection {
+        unsafe { GradientStopCollection::from_ptr(self.ptr.clone().up()) }
+    }
+}
+
+unsafe impl crate::resource::IResource for GradientStopCollection1 {
+    unsafe fn raw_resource(&self) -> &ID2D1Resource {
+        &self.ptr
+    }
+}
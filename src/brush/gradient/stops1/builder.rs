@@ -0,0 +1,101 @@
+use crate::brush::gradient::stops::builder::GradientStopState;
+use crate::brush::gradient::stops1::GradientStopCollection1;
+use crate::descriptions::GradientStop;
+use crate::device_context::IDeviceContext;
+use crate::enums::*;
+
+use com_wrapper::ComWrapper;
+use dcommon::Error;
+
+pub struct GradientStopBuilder1<'a> {
+    context: &'a dyn IDeviceContext,
+    pre_interpolation_space: ColorSpace,
+    post_interpolation_space: ColorSpace,
+    buffer_precision: BufferPrecision,
+    extend_mode: ExtendMode,
+    color_interpolation_mode: ColorInterpolationMode,
+    state: GradientStopState<'a>,
+}
+
+impl<'a> GradientStopBuilder1<'a> {
+    /// Defaults match a `GradientStopBuilder` with `Gamma::_2_2`:
+    /// ColorSpace::Srgb before and after interpolation, BufferPrecision::_8BpcUnormSrgb,
+    /// ExtendMode::Clamp, ColorInterpolationMode::Premultiplied
+    pub fn new(context: &'a dyn IDeviceContext) -> Self {
+        GradientStopBuilder1 {
+            context,
+            pre_interpolation_space: ColorSpace::Srgb,
+            post_interpolation_space: ColorSpace::Srgb,
+            buffer_precision: BufferPrecision::_8BpcUnormSrgb,
+            extend_mode: ExtendMode::Clamp,
+            color_interpolation_mode: ColorInterpolationMode::Premultiplied,
+            state: GradientStopState::Empty,
+        }
+    }
+
+    pub fn build(self) -> Result<GradientStopCollection1, Error> {
+        let slice = self.state.as_slice();
+
+        unsafe {
+            let mut ptr = std::ptr::null_mut();
+            let hr = self.context.raw_dc().CreateGradientStopCollection(
+                slice.as_ptr() as *const _,
+                slice.len() as u32,
+                self.pre_interpolation_space as u32,
+                self.post_interpolation_space as u32,
+                self.buffer_precision as u32,
+                self.extend_mode as u32,
+                self.color_interpolation_mode as u32,
+                &mut ptr,
+            );
+            Error::map_if(hr, || GradientStopCollection1::from_raw(ptr))
+        }
+    }
+
+    /// The color space the stops are specified in and interpolated in. Use
+    /// `ColorSpace::Scrgb` for gradients between HDR colors.
+    pub fn with_pre_interpolation_space(mut self, space: ColorSpace) -> Self {
+        self.pre_interpolation_space = space;
+        self
+    }
+
+    /// The color space the interpolated colors are converted to. This should
+    /// generally match the space of the target being drawn to.
+    pub fn with_post_interpolation_space(mut self, space: ColorSpace) -> Self {
+        self.post_interpolation_space = space;
+        self
+    }
+
+    /// The precision of the intermediate texture. Higher precision avoids
+    /// banding in wide or HDR gradients.
+    pub fn with_buffer_precision(mut self, precision: BufferPrecision) -> Self {
+        self.buffer_precision = precision;
+        self
+    }
+
+    pub fn with_extend_mode(mut self, mode: ExtendMode) -> Self {
+        self.extend_mode = mode;
+        self
+    }
+
+    /// Whether the colors should be interpolated with straight or premultiplied alpha.
+    pub fn with_color_interpolation_mode(mut self, mode: ColorInterpolationMode) -> Self {
+        self.color_interpolation_mode = mode;
+        self
+    }
+
+    /// Appends a stop. Colors are given with straight alpha.
+    pub fn with_stop<G>(mut self, stop: G) -> Self
+    where
+        G: Into<GradientStop>,
+    {
+        self.state = self.state.with_stop(stop.into());
+        self
+    }
+
+    /// Appends a slice of stops. Colors are given with straight alpha.
+    pub fn with_stops(mut self, stops: &'a [GradientStop]) -> Self {
+        self.state = self.state.with_stops(stops);
+        self
+    }
+}
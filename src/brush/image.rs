@@ -0,0 +1,92 @@
+use crate::brush::IBrush;
+use crate::device_context::IDeviceContext;
+use crate::enums::{ExtendMode, InterpolationMode};
+use crate::image::{IImage, Image};
+
+use checked_enum::UncheckedEnum;
+use com_wrapper::ComWrapper;
+use math2d::Rectf;
+use winapi::um::d2d1::ID2D1Brush;
+use winapi::um::d2d1_1::ID2D1ImageBrush;
+use wio::com::ComPtr;
+
+pub use self::builder::*;
+
+pub mod builder;
+
+#[repr(transparent)]
+#[derive(ComWrapper, Clone)]
+#[com(send, sync, debug)]
+/// Paints an area with any image, including effect outputs and command lists.
+pub struct ImageBrush {
+    ptr: ComPtr<ID2D1ImageBrush>,
+}
+
+impl ImageBrush {
+    pub fn create<'a>(context: &'a dyn IDeviceContext) -> ImageBrushBuilder<'a> {
+        ImageBrushBuilder::new(context)
+    }
+
+    /// Retrieves the image being painted, if one has been set.
+    pub fn image(&self) -> Option<Image> {
+        unsafe {
+            let mut ptr = std::ptr::null_mut();
+            self.ptr.GetImage(&mut ptr);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(Image::from_raw(ptr))
+            }
+        }
+    }
+
+    /// Replaces the image being painted.
+    pub fn set_image(&mut self, image: &dyn IImage) {
+        unsafe { self.ptr.SetImage(image.raw_img() as *const _ as *mut _) }
+    }
+
+    /// The region of the image that is used as the source of the brush.
+    pub fn source_rect(&self) -> Rectf {
+        unsafe {
+            let mut rect = std::mem::zeroed();
+            self.ptr.GetSourceRectangle(&mut rect);
+            rect.into()
+        }
+    }
+
+    pub fn set_source_rect(&mut self, rect: Rectf) {
+        unsafe { self.ptr.SetSourceRectangle(&rect.into()) }
+    }
+
+    pub fn extend_mode_x(&self) -> UncheckedEnum<ExtendMode> {
+        unsafe { self.ptr.GetExtendModeX().into() }
+    }
+
+    pub fn set_extend_mode_x(&mut self, mode: ExtendMode) {
+        unsafe { self.ptr.SetExtendModeX(mode as u32) }
+    }
+
+    pub fn extend_mode_y(&self) -> UncheckedEnum<ExtendMode> {
+        unsafe { self.ptr.GetExtendModeY().into() }
+    }
+
+    pub fn set_extend_mode_y(&mut self, mode: ExtendMode) {
+        unsafe { self.ptr.SetExtendModeY(mode as u32) }
+    }
+
+    pub fn interpolation_mode(&self) -> UncheckedEnum<InterpolationMode> {
+        unsafe { self.ptr.GetInterpolationMode().into() }
+    }
+
+    pub fn set_interpolation_mode(&mut self, mode: InterpolationMode) {
+        unsafe { self.ptr.SetInterpolationMode(mode as u32) }
+    }
+}
+
+unsafe impl IBrush for ImageBrush {
+    unsafe fn raw_brush(&self) -> &ID2D1Brush {
+        &self.ptr
+    }
+}
+
+unsafe impl super::BrushType for ImageBrush {}
@@ -0,0 +1,108 @@
+use crate::brush::image::ImageBrush;
+use crate::device_context::IDeviceContext;
+use crate::enums::{ExtendMode, InterpolationMode};
+use crate::image::IImage;
+use crate::properties::BrushProperties;
+
+use std::ptr;
+
+use com_wrapper::ComWrapper;
+use dcommon::Error;
+use math2d::{Matrix3x2f, Rectf};
+use winapi::um::d2d1_1::D2D1_IMAGE_BRUSH_PROPERTIES;
+
+pub struct ImageBrushBuilder<'a> {
+    context: &'a dyn IDeviceContext,
+    image: Option<&'a dyn IImage>,
+    source_rect: Option<Rectf>,
+    extend_mode_x: ExtendMode,
+    extend_mode_y: ExtendMode,
+    interpolation_mode: InterpolationMode,
+    properties: BrushProperties,
+}
+
+impl<'a> ImageBrushBuilder<'a> {
+    /// Defaults: ExtendMode::Clamp on both axes, InterpolationMode::Linear.
+    /// Images don't necessarily have a size, so the source rectangle must be given.
+    pub fn new(context: &'a dyn IDeviceContext) -> Self {
+        ImageBrushBuilder {
+            context,
+            image: None,
+            source_rect: None,
+            extend_mode_x: ExtendMode::Clamp,
+            extend_mode_y: ExtendMode::Clamp,
+            interpolation_mode: InterpolationMode::Linear,
+            properties: BrushProperties::new(1.0, &Matrix3x2f::IDENTITY),
+        }
+    }
+
+    pub fn build(self) -> Result<ImageBrush, Error> {
+        let image = self.image.expect("`image` must be specified");
+        let source_rect = self.source_rect.expect("`source_rect` must be specified");
+        let i_properties = D2D1_IMAGE_BRUSH_PROPERTIES {
+            sourceRectangle: source_rect.into(),
+            extendModeX: self.extend_mode_x as u32,
+            extendModeY: self.extend_mode_y as u32,
+            interpolationMode: self.interpolation_mode as u32,
+        };
+
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let hr = self.context.raw_dc().CreateImageBrush(
+                image.raw_img() as *const _ as *mut _,
+                &i_properties,
+                (&self.properties) as *const _ as *const _,
+                &mut ptr,
+            );
+            Error::map_if(hr, || ImageBrush::from_raw(ptr))
+        }
+    }
+
+    pub fn with_image(mut self, image: &'a dyn IImage) -> Self {
+        self.image = Some(image);
+        self
+    }
+
+    /// The region of the image, in DIPs, which is painted by the brush and
+    /// repeated according to the extend modes.
+    pub fn with_source_rect(mut self, rect: Rectf) -> Self {
+        self.source_rect = Some(rect);
+        self
+    }
+
+    #[inline]
+    pub fn with_extend_mode_x(mut self, mode: ExtendMode) -> Self {
+        self.extend_mode_x = mode;
+        self
+    }
+
+    #[inline]
+    pub fn with_extend_mode_y(mut self, mode: ExtendMode) -> Self {
+        self.extend_mode_y = mode;
+        self
+    }
+
+    #[inline]
+    pub fn with_interpolation_mode(mut self, mode: InterpolationMode) -> Self {
+        self.interpolation_mode = mode;
+        self
+    }
+
+    #[inline]
+    pub fn with_properties(mut self, properties: BrushProperties) -> Self {
+        self.properties = properties;
+        self
+    }
+
+    #[inline]
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.properties.opacity = opacity;
+        self
+    }
+
+    #[inline]
+    pub fn with_transform(mut self, transform: Matrix3x2f) -> Self {
+        self.properties.transform = transform;
+        self
+    }
+}
@@ -1,19 +1,44 @@
+#[cfg(windows)]
 use math2d::*;
 
+#[cfg(windows)]
 use com_wrapper::ComWrapper;
+#[cfg(windows)]
 use winapi::um::d2d1::ID2D1Brush;
+#[cfg(windows)]
 use wio::com::ComPtr;
 
+#[cfg(windows)]
 pub use self::bitmap::BitmapBrush;
+#[cfg(windows)]
+pub use self::bitmap1::BitmapBrush1;
+#[cfg(windows)]
 pub use self::gradient::linear::LinearGradientBrush;
+#[cfg(windows)]
 pub use self::gradient::radial::RadialGradientBrush;
+#[cfg(windows)]
 pub use self::gradient::GradientStopCollection;
+#[cfg(windows)]
+pub use self::gradient::GradientStopCollection1;
+#[cfg(windows)]
+pub use self::image::ImageBrush;
+pub use self::palette::{Palette, PaletteKey, Theme};
+#[cfg(windows)]
 pub use self::solid_color::SolidColorBrush;
 
+#[cfg(windows)]
 pub mod bitmap;
+#[cfg(windows)]
+pub mod bitmap1;
+#[cfg(windows)]
 pub mod gradient;
+#[cfg(windows)]
+pub mod image;
+pub mod palette;
+#[cfg(windows)]
 pub mod solid_color;
 
+#[cfg(windows)]
 #[repr(transparent)]
 #[derive(ComWrapper, Clone)]
 #[com(send, sync, debug)]
@@ -21,11 +46,16 @@ pub struct Brush {
     ptr: ComPtr<ID2D1Brush>,
 }
 
+#[cfg(windows)]
 pub unsafe trait IBrush {
     fn opacity(&self) -> f32 {
         unsafe { self.raw_brush().GetOpacity() }
     }
 
+    fn set_opacity(&mut self, opacity: f32) {
+        unsafe { self.raw_brush().SetOpacity(opacity) }
+    }
+
     fn transform(&self) -> Matrix3x2f {
         unsafe {
             let mut mat: Matrix3x2f = std::mem::uninitialized();
@@ -35,11 +65,32 @@ pub unsafe trait IBrush {
         }
     }
 
+    fn set_transform(&mut self, transform: &Matrix3x2f) {
+        unsafe {
+            self.raw_brush()
+                .SetTransform(transform as *const _ as *const _)
+        }
+    }
+
     unsafe fn raw_brush(&self) -> &ID2D1Brush;
 }
 
+#[cfg(windows)]
 unsafe impl IBrush for Brush {
     unsafe fn raw_brush(&self) -> &ID2D1Brush {
         &self.ptr
     }
 }
+
+#[cfg(windows)]
+pub unsafe trait BrushType: ComWrapper + Clone {
+    fn to_generic(&self) -> Brush
+    where
+        Self: Sized,
+    {
+        unsafe { Brush::from_ptr(self.clone().into_ptr().cast().unwrap()) }
+    }
+}
+
+#[cfg(windows)]
+unsafe impl BrushType for Brush {}
@@ -0,0 +1,288 @@
+//! Named and color-keyed brushes which are created lazily and cached for the
+//! render target they were created on.
+
+#[cfg(windows)]
+use crate::brush::SolidColorBrush;
+#[cfg(windows)]
+use crate::render_target::IRenderTarget;
+#[cfg(windows)]
+use crate::resource::Resource;
+
+use std::collections::HashMap;
+
+#[cfg(windows)]
+use com_wrapper::ComWrapper;
+#[cfg(windows)]
+use dcommon::Error;
+use math2d::Color;
+
+/// Identifies the render target a set of cached brushes belongs to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TargetId(pub usize);
+
+#[cfg(windows)]
+impl TargetId {
+    /// The identity of a render target is the address of its COM object.
+    pub fn of(target: &dyn IRenderTarget) -> TargetId {
+        unsafe { TargetId(target.raw_rt() as *const _ as usize) }
+    }
+}
+
+/// A color which can be used as a hash key, compared bit-for-bit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ColorKey([u32; 4]);
+
+impl From<Color> for ColorKey {
+    fn from(c: Color) -> ColorKey {
+        ColorKey([c.r.to_bits(), c.g.to_bits(), c.b.to_bits(), c.a.to_bits()])
+    }
+}
+
+impl From<ColorKey> for Color {
+    fn from(key: ColorKey) -> Color {
+        let [r, g, b, a] = key.0;
+        Color {
+            r: f32::from_bits(r),
+            g: f32::from_bits(g),
+            b: f32::from_bits(b),
+            a: f32::from_bits(a),
+        }
+    }
+}
+
+/// How a brush in the palette is looked up.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PaletteKey {
+    /// A color from the current theme.
+    Named(String),
+    /// A literal color, independent of the theme.
+    Color(ColorKey),
+}
+
+impl<'a> From<&'a str> for PaletteKey {
+    fn from(name: &'a str) -> PaletteKey {
+        PaletteKey::Named(name.into())
+    }
+}
+
+impl From<String> for PaletteKey {
+    fn from(name: String) -> PaletteKey {
+        PaletteKey::Named(name)
+    }
+}
+
+impl From<Color> for PaletteKey {
+    fn from(color: Color) -> PaletteKey {
+        PaletteKey::Color(color.into())
+    }
+}
+
+/// A table of named colors. Swap the palette's theme to switch between e.g.
+/// light and dark mode.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Theme {
+    colors: HashMap<String, Color>,
+}
+
+impl Theme {
+    pub fn new() -> Theme {
+        Theme::default()
+    }
+
+    pub fn with_color(mut self, name: impl Into<String>, color: impl Into<Color>) -> Self {
+        self.set_color(name, color);
+        self
+    }
+
+    pub fn set_color(&mut self, name: impl Into<String>, color: impl Into<Color>) {
+        self.colors.insert(name.into(), color.into());
+    }
+
+    pub fn color(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).cloned()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.colors.keys().map(|name| &name[..])
+    }
+}
+
+/// Lazily creates brushes for a theme and caches them for one render target
+/// at a time. Asking for a brush on a different target (such as the one which
+/// replaced a target that returned `D2DERR_RECREATE_TARGET`) throws away
+/// everything that was cached for the old one.
+///
+/// `B` is the type of the cached brushes. On Windows, `Palette<SolidColorBrush>`
+/// creates them for you with `brush` and `color_brush`; any other type can be
+/// cached with `get_or_create`.
+pub struct Palette<B> {
+    theme: Theme,
+    target: Option<TargetId>,
+    brushes: HashMap<PaletteKey, B>,
+    // Holds a reference to the target the brushes were created on, so its
+    // address can't be reused by a new target while brushes are cached.
+    #[cfg(windows)]
+    keepalive: Option<Resource>,
+}
+
+impl<B> Palette<B> {
+    pub fn new(theme: Theme) -> Self {
+        Palette {
+            theme,
+            target: None,
+            brushes: HashMap::new(),
+            #[cfg(windows)]
+            keepalive: None,
+        }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Replaces the color table. Cached brushes for names whose color changed
+    /// or disappeared are dropped; everything else is kept.
+    pub fn set_theme(&mut self, theme: Theme) {
+        let old = std::mem::replace(&mut self.theme, theme);
+        let new = &self.theme;
+        self.brushes.retain(|key, _| match key {
+            PaletteKey::Named(name) => match (old.color(name), new.color(name)) {
+                (Some(a), Some(b)) => ColorKey::from(a) == ColorKey::from(b),
+                _ => false,
+            },
+            PaletteKey::Color(_) => true,
+        });
+    }
+
+    /// The color `key` currently refers to.
+    pub fn resolve(&self, key: &PaletteKey) -> Option<Color> {
+        match key {
+            PaletteKey::Named(name) => self.theme.color(name),
+            PaletteKey::Color(color) => Some((*color).into()),
+        }
+    }
+
+    /// Shorthand for looking up a named color in the current theme.
+    pub fn color(&self, name: &str) -> Option<Color> {
+        self.theme.color(name)
+    }
+
+    /// The target the cached brushes belong to, if any are cached.
+    pub fn target(&self) -> Option<TargetId> {
+        self.target
+    }
+
+    /// Whether a brush for `key` is cached for `target`.
+    pub fn is_cached(&self, target: TargetId, key: &PaletteKey) -> bool {
+        self.target == Some(target) && self.brushes.contains_key(key)
+    }
+
+    /// The number of cached brushes.
+    pub fn len(&self) -> usize {
+        self.brushes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.brushes.is_empty()
+    }
+
+    /// Drops every cached brush. Call this when the target is recreated if
+    /// you want the old brushes released before the next frame is drawn.
+    pub fn invalidate(&mut self) {
+        self.brushes.clear();
+        self.target = None;
+        #[cfg(windows)]
+        {
+            self.keepalive = None;
+        }
+    }
+
+    /// Retrieves the brush for `key` on `target`, calling `create` with the
+    /// resolved color if it isn't cached yet. Returns `Ok(None)` for names
+    /// which aren't in the theme.
+    pub fn get_or_create<E>(
+        &mut self,
+        target: TargetId,
+        key: PaletteKey,
+        create: impl FnOnce(Color) -> Result<B, E>,
+    ) -> Result<Option<&B>, E> {
+        if self.target != Some(target) {
+            self.invalidate();
+            self.target = Some(target);
+        }
+
+        let color = match self.resolve(&key) {
+            Some(color) => color,
+            None => return Ok(None),
+        };
+
+        if !self.brushes.contains_key(&key) {
+            let brush = create(color)?;
+            self.brushes.insert(key.clone(), brush);
+        }
+        Ok(self.brushes.get(&key))
+    }
+}
+
+#[cfg(windows)]
+impl Palette<SolidColorBrush> {
+    /// Retrieves the brush for a named theme color, creating it on `target`
+    /// if needed. Returns `Ok(None)` if the theme has no such color.
+    ///
+    /// ```no_run
+    /// # use direct2d::brush::{Palette, SolidColorBrush, Theme};
+    /// # use direct2d::render_target::{IRenderTarget, HwndRenderTarget};
+    /// # use math2d::Rectf;
+    /// fn paint(rt: &mut HwndRenderTarget, palette: &mut Palette<SolidColorBrush>) {
+    ///     rt.begin_draw();
+    ///     rt.clear(palette.color("background").unwrap());
+    ///     let accent = palette.brush(&*rt, "accent").unwrap().unwrap().clone();
+    ///     rt.fill_rectangle(Rectf::new(10.0, 10.0, 90.0, 40.0), &accent);
+    ///     rt.end_draw().unwrap();
+    /// }
+    /// # fn main() {
+    /// #     let _: Palette<SolidColorBrush> = Palette::new(
+    /// #         Theme::new()
+    /// #             .with_color("background", 0xFF_FF_FF)
+    /// #             .with_color("accent", 0x00_78_D7),
+    /// #     );
+    /// # }
+    /// ```
+    pub fn brush(
+        &mut self,
+        target: &dyn IRenderTarget,
+        name: &str,
+    ) -> Result<Option<&SolidColorBrush>, Error> {
+        self.solid_brush(target, PaletteKey::from(name))
+    }
+
+    /// Retrieves the brush for a literal color, creating it on `target` if needed.
+    pub fn color_brush(
+        &mut self,
+        target: &dyn IRenderTarget,
+        color: impl Into<Color>,
+    ) -> Result<&SolidColorBrush, Error> {
+        let key = PaletteKey::from(color.into());
+        self.solid_brush(target, key)
+            .map(|brush| brush.expect("literal colors always resolve"))
+    }
+
+    fn solid_brush(
+        &mut self,
+        target: &dyn IRenderTarget,
+        key: PaletteKey,
+    ) -> Result<Option<&SolidColorBrush>, Error> {
+        let id = TargetId::of(target);
+        if self.target != Some(id) {
+            self.invalidate();
+            self.target = Some(id);
+            self.keepalive = Some(unsafe {
+                let raw = target.raw_resource() as *const _ as *mut _;
+                (*raw).AddRef();
+                Resource::from_raw(raw)
+            });
+        }
+
+        self.get_or_create(id, key, |color| SolidColorBrush::new(target, color))
+    }
+}
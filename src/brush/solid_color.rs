@@ -36,6 +36,11 @@ impl SolidColorBrush {
     pub fn color(&self) -> Color {
         unsafe { self.ptr.GetColor().into() }
     }
+
+    #[inline]
+    pub fn set_color(&mut self, color: impl Into<Color>) {
+        unsafe { self.ptr.SetColor(&color.into().into()) }
+    }
 }
 
 unsafe impl IBrush for SolidColorBrush {
@@ -43,3 +48,5 @@ unsafe impl IBrush for SolidColorBrush {
         &self.ptr
     }
 }
+
+unsafe impl super::BrushType for SolidColorBrush {}
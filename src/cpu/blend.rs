@@ -0,0 +1,57 @@
+//! How each `PrimitiveBlend` combines a source pixel with the pixel already in
+//! the target. Colors here are premultiplied, as they are in the target.
+
+use crate::cpu::gradient::to_u8;
+use crate::enums::PrimitiveBlend;
+
+use math2d::Color;
+
+/// Combines a premultiplied `source` with a premultiplied `dest` the way
+/// Direct2D does for `mode`. Results aren't clamped, matching a floating
+/// point target; use `blend_bgra` for 8-bit targets.
+pub fn blend(mode: PrimitiveBlend, source: Color, dest: Color) -> Color {
+    match mode {
+        PrimitiveBlend::SourceOver => {
+            let inv = 1.0 - source.a;
+            zip(source, dest, |s, d| s + d * inv)
+        }
+        PrimitiveBlend::Copy => source,
+        PrimitiveBlend::Min => zip(source, dest, f32::min),
+        PrimitiveBlend::Add => zip(source, dest, |s, d| s + d),
+        PrimitiveBlend::Max => zip(source, dest, f32::max),
+    }
+}
+
+/// Blends every pixel of `source` onto `dest`, both premultiplied BGRA8 such
+/// as the output of `rasterize_bgra`. Results saturate at 255.
+///
+/// # Panics
+///
+/// If the buffers aren't the same length, or that length isn't a multiple of 4.
+pub fn blend_bgra(mode: PrimitiveBlend, source: &[u8], dest: &mut [u8]) {
+    assert_eq!(source.len(), dest.len(), "buffers must be the same size");
+    assert_eq!(source.len() % 4, 0, "buffers must hold whole BGRA pixels");
+
+    for (s, d) in source.chunks(4).zip(dest.chunks_mut(4)) {
+        let c = blend(mode, from_bgra(s), from_bgra(d));
+        d.copy_from_slice(&[to_u8(c.b), to_u8(c.g), to_u8(c.r), to_u8(c.a)]);
+    }
+}
+
+fn from_bgra(px: &[u8]) -> Color {
+    Color {
+        r: px[2] as f32 / 255.0,
+        g: px[1] as f32 / 255.0,
+        b: px[0] as f32 / 255.0,
+        a: px[3] as f32 / 255.0,
+    }
+}
+
+fn zip(a: Color, b: Color, f: impl Fn(f32, f32) -> f32) -> Color {
+    Color {
+        r: f(a.r, b.r),
+        g: f(a.g, b.g),
+        b: f(a.b, b.b),
+        a: f(a.a, b.a),
+    }
+}
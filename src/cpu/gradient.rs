@@ -0,0 +1,354 @@
+use crate::descriptions::GradientStop;
+use crate::enums::{ExtendMode, Gamma};
+use crate::properties::{
+    BrushProperties, LinearGradientBrushProperties, RadialGradientBrushProperties,
+};
+
+use math2d::{Color, Matrix3x2f, Point2f};
+
+/// Anything that can be asked for its color at a point in render target space.
+pub trait ColorSource {
+    /// The straight (non-premultiplied) color at `point`.
+    fn color_at(&self, point: Point2f) -> Color;
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "crate::serde_impls::GradientRampDef")
+)]
+/// The CPU equivalent of a `GradientStopCollection`: maps a position along the
+/// gradient to a color, honoring the gamma and extend mode.
+pub struct GradientRamp {
+    stops: Vec<GradientStop>,
+    gamma: Gamma,
+    extend_mode: ExtendMode,
+}
+
+impl GradientRamp {
+    /// Creates a ramp from the same values you would give a `GradientStopBuilder`.
+    /// The stops don't need to be sorted. Stops whose position is NaN have no
+    /// place on the gradient and are left out.
+    pub fn new(stops: &[GradientStop], gamma: Gamma, extend_mode: ExtendMode) -> Self {
+        let mut stops: Vec<_> = stops
+            .iter()
+            .filter(|stop| !stop.position.is_nan())
+            .cloned()
+            .collect();
+        // Stable, so coincident stops keep the order they were given in.
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        GradientRamp {
+            stops,
+            gamma,
+            extend_mode,
+        }
+    }
+
+    /// The stops, sorted by position.
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+
+    pub fn gamma(&self) -> Gamma {
+        self.gamma
+    }
+
+    pub fn extend_mode(&self) -> ExtendMode {
+        self.extend_mode
+    }
+
+    /// Retrieves the color at `position`, where 0.0 and 1.0 are the start and
+    /// end of the gradient. Positions outside of that range are folded back in
+    /// according to the extend mode.
+    pub fn color_at(&self, position: f32) -> Color {
+        let position = extend(self.extend_mode, position);
+
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return TRANSPARENT,
+        };
+
+        if position <= first.position {
+            return first.color;
+        }
+        if position >= last.position {
+            return last.color;
+        }
+
+        let i = self
+            .stops
+            .iter()
+            .position(|stop| stop.position > position)
+            .unwrap();
+        let (lo, hi) = (&self.stops[i - 1], &self.stops[i]);
+        let t = (position - lo.position) / (hi.position - lo.position);
+
+        let lo = premultiply(self.encode_for_interpolation(lo.color));
+        let hi = premultiply(self.encode_for_interpolation(hi.color));
+        let mixed = Color {
+            r: lerp(lo.r, hi.r, t),
+            g: lerp(lo.g, hi.g, t),
+            b: lerp(lo.b, hi.b, t),
+            a: lerp(lo.a, hi.a, t),
+        };
+        self.decode_after_interpolation(unpremultiply(mixed))
+    }
+
+    fn encode_for_interpolation(&self, color: Color) -> Color {
+        match self.gamma {
+            Gamma::_2_2 => color,
+            Gamma::_1_0 => map_rgb(color, srgb_to_linear),
+        }
+    }
+
+    fn decode_after_interpolation(&self, color: Color) -> Color {
+        match self.gamma {
+            Gamma::_2_2 => color,
+            Gamma::_1_0 => map_rgb(color, linear_to_srgb),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// The CPU equivalent of a `LinearGradientBrush`.
+pub struct LinearGradient {
+    pub ramp: GradientRamp,
+    pub properties: LinearGradientBrushProperties,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub brush: BrushProperties,
+}
+
+impl LinearGradient {
+    /// Creates a gradient with an opacity of 1.0 and the identity transform.
+    pub fn new(ramp: GradientRamp, properties: LinearGradientBrushProperties) -> Self {
+        LinearGradient {
+            ramp,
+            properties,
+            brush: BrushProperties::default(),
+        }
+    }
+
+    pub fn with_brush_properties(mut self, brush: BrushProperties) -> Self {
+        self.brush = brush;
+        self
+    }
+
+    /// The unextended gradient position of `point`. A degenerate gradient whose
+    /// start and end coincide always reports 0.0.
+    pub fn position_at(&self, point: Point2f) -> f32 {
+        let p = to_brush_space(&self.brush.transform, point);
+        let start = self.properties.start;
+        let end = self.properties.end;
+
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        let len_sq = dx * dx + dy * dy;
+        if len_sq == 0.0 {
+            return 0.0;
+        }
+
+        ((p.x - start.x) * dx + (p.y - start.y) * dy) / len_sq
+    }
+}
+
+impl ColorSource for LinearGradient {
+    fn color_at(&self, point: Point2f) -> Color {
+        let color = self.ramp.color_at(self.position_at(point));
+        with_opacity(color, self.brush.opacity)
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// The CPU equivalent of a `RadialGradientBrush`.
+pub struct RadialGradient {
+    pub ramp: GradientRamp,
+    pub properties: RadialGradientBrushProperties,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub brush: BrushProperties,
+}
+
+impl RadialGradient {
+    /// Creates a gradient with an opacity of 1.0 and the identity transform.
+    pub fn new(ramp: GradientRamp, properties: RadialGradientBrushProperties) -> Self {
+        RadialGradient {
+            ramp,
+            properties,
+            brush: BrushProperties::default(),
+        }
+    }
+
+    pub fn with_brush_properties(mut self, brush: BrushProperties) -> Self {
+        self.brush = brush;
+        self
+    }
+
+    /// The unextended gradient position of `point`: 0.0 at the gradient origin
+    /// (`center + origin_offset`) and 1.0 on the edge of the ellipse, measured
+    /// along the ray from the origin through `point`.
+    pub fn position_at(&self, point: Point2f) -> f32 {
+        let p = to_brush_space(&self.brush.transform, point);
+        let props = &self.properties;
+        if props.radius_x == 0.0 || props.radius_y == 0.0 {
+            return 0.0;
+        }
+
+        // Work in the space where the ellipse is the unit circle.
+        let (fx, fy) = (
+            props.origin_offset.x / props.radius_x,
+            props.origin_offset.y / props.radius_y,
+        );
+        let (qx, qy) = (
+            (p.x - props.center.x) / props.radius_x,
+            (p.y - props.center.y) / props.radius_y,
+        );
+        let (dx, dy) = (qx - fx, qy - fy);
+
+        let d_sq = dx * dx + dy * dy;
+        if d_sq == 0.0 {
+            return 0.0;
+        }
+
+        // Find s > 0 where `f + s * d` lies on the unit circle; the point itself
+        // is at s = 1, so its position is 1 / s. The discriminant only goes
+        // negative when the origin sits outside the ellipse, which Direct2D
+        // leaves undefined.
+        let f_dot_d = fx * dx + fy * dy;
+        let f_sq = fx * fx + fy * fy;
+        let disc = (f_dot_d * f_dot_d - d_sq * (f_sq - 1.0)).max(0.0);
+        let s = (-f_dot_d + disc.sqrt()) / d_sq;
+        if s <= 0.0 {
+            return f32::INFINITY;
+        }
+
+        1.0 / s
+    }
+}
+
+impl ColorSource for RadialGradient {
+    fn color_at(&self, point: Point2f) -> Color {
+        let color = self.ramp.color_at(self.position_at(point));
+        with_opacity(color, self.brush.opacity)
+    }
+}
+
+/// Folds an arbitrary gradient position into `[0, 1]` the way the extend mode
+/// describes.
+pub fn extend(mode: ExtendMode, position: f32) -> f32 {
+    if !position.is_finite() {
+        return match mode {
+            ExtendMode::Clamp if position < 0.0 => 0.0,
+            _ => 1.0,
+        };
+    }
+
+    match mode {
+        ExtendMode::Clamp => position.clamp(0.0, 1.0),
+        ExtendMode::Wrap => position - position.floor(),
+        ExtendMode::Mirror => {
+            let t = position.abs() % 2.0;
+            if t > 1.0 {
+                2.0 - t
+            } else {
+                t
+            }
+        }
+    }
+}
+
+/// Samples `source` at every pixel center and writes premultiplied BGRA8, the
+/// layout of a `B8G8R8A8Unorm` bitmap with `AlphaMode::Premultiplied`.
+/// `dpi` is used to map pixels back into DIPs before sampling.
+pub fn rasterize_bgra(source: &dyn ColorSource, width: u32, height: u32, dpi: f32) -> Vec<u8> {
+    let scale = 96.0 / dpi;
+    let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height {
+        for x in 0..width {
+            let point = Point2f {
+                x: (x as f32 + 0.5) * scale,
+                y: (y as f32 + 0.5) * scale,
+            };
+            let c = premultiply(source.color_at(point));
+            data.extend_from_slice(&[to_u8(c.b), to_u8(c.g), to_u8(c.r), to_u8(c.a)]);
+        }
+    }
+    data
+}
+
+/// Converts one sRGB-encoded channel to linear light.
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts one linear-light channel to sRGB encoding.
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+const TRANSPARENT: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.0,
+};
+
+pub(crate) fn premultiply(c: Color) -> Color {
+    Color {
+        r: c.r * c.a,
+        g: c.g * c.a,
+        b: c.b * c.a,
+        a: c.a,
+    }
+}
+
+pub(crate) fn unpremultiply(c: Color) -> Color {
+    if c.a == 0.0 {
+        return TRANSPARENT;
+    }
+    Color {
+        r: c.r / c.a,
+        g: c.g / c.a,
+        b: c.b / c.a,
+        a: c.a,
+    }
+}
+
+fn map_rgb(c: Color, f: fn(f32) -> f32) -> Color {
+    Color {
+        r: f(c.r),
+        g: f(c.g),
+        b: f(c.b),
+        a: c.a,
+    }
+}
+
+fn with_opacity(c: Color, opacity: f32) -> Color {
+    Color {
+        a: c.a * opacity,
+        ..c
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+pub(crate) fn to_u8(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+}
+
+/// Maps a render target point back through the brush transform. A singular
+/// transform collapses everything onto the brush origin.
+fn to_brush_space(m: &Matrix3x2f, p: Point2f) -> Point2f {
+    m.inverse()
+        .map_or(Point2f::ORIGIN, |inverse| inverse.transform_point(p))
+}
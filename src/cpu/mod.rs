@@ -0,0 +1,9 @@
+//! Portable reference implementations of Direct2D's pixel math. None of these
+//! need a device or even Windows, so they're useful for CPU fallbacks and as
+//! test oracles for what the GPU should have produced.
+
+pub use self::blend::{blend, blend_bgra};
+pub use self::gradient::{ColorSource, GradientRamp, LinearGradient, RadialGradient};
+
+pub mod blend;
+pub mod gradient;
@@ -1,9 +1,11 @@
 use math2d::Color;
 
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientStop {
     pub position: f32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::ColorDef"))]
     pub color: Color,
 }
 
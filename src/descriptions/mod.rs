@@ -1,7 +1,14 @@
 #[doc(inline)]
 pub use self::gradient_stop::*;
+#[cfg(windows)]
 #[doc(inline)]
 pub use self::pixel_format::*;
+#[cfg(windows)]
+#[doc(inline)]
+pub use self::rendering_controls::*;
 
 mod gradient_stop;
+#[cfg(windows)]
 mod pixel_format;
+#[cfg(windows)]
+mod rendering_controls;
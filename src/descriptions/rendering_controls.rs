@@ -0,0 +1,26 @@
+use crate::enums::BufferPrecision;
+
+use checked_enum::UncheckedEnum;
+use math2d::Sizeu;
+use winapi::um::d2d1_1::D2D1_RENDERING_CONTROLS;
+
+/// Controls the precision and tile size of the intermediate buffers a device
+/// context uses when rendering effects.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct RenderingControls {
+    pub buffer_precision: UncheckedEnum<BufferPrecision>,
+    pub tile_size: Sizeu,
+}
+
+impl From<RenderingControls> for D2D1_RENDERING_CONTROLS {
+    fn from(controls: RenderingControls) -> Self {
+        unsafe { std::mem::transmute(controls) }
+    }
+}
+
+impl From<D2D1_RENDERING_CONTROLS> for RenderingControls {
+    fn from(controls: D2D1_RENDERING_CONTROLS) -> Self {
+        unsafe { std::mem::transmute(controls) }
+    }
+}
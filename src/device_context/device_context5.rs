@@ -0,0 +1,126 @@
+use crate::device_context::{
+    IDeviceContext, IDeviceContext1, IDeviceContext2, IDeviceContext3, IDeviceContext4,
+};
+use crate::render_target::{Breadcrumbs, IRenderTarget, RTState};
+use crate::resource::IResource;
+
+use com_wrapper::ComWrapper;
+use winapi::um::d2d1::{ID2D1RenderTarget, ID2D1Resource};
+use winapi::um::d2d1_1::ID2D1DeviceContext;
+use winapi::um::d2d1_2::ID2D1DeviceContext1;
+use winapi::um::d2d1_3::{
+    ID2D1DeviceContext2, ID2D1DeviceContext3, ID2D1DeviceContext4, ID2D1DeviceContext5,
+};
+use wio::com::ComPtr;
+
+/// A device context created on a system which supports `ID2D1DeviceContext5`. Get one
+/// from an existing context with `IDeviceContext::cast`.
+#[repr(C)]
+pub struct DeviceContext5 {
+    ptr: ComPtr<ID2D1DeviceContext5>,
+    state: RTState,
+    breadcrumbs: Breadcrumbs,
+}
+
+pub unsafe trait IDeviceContext5: IDeviceContext4 {
+    unsafe fn raw_dc5(&self) -> &ID2D1DeviceContext5;
+}
+
+unsafe impl IResource for DeviceContext5 {
+    unsafe fn raw_resource(&self) -> &ID2D1Resource {
+        &self.ptr
+    }
+}
+
+unsafe impl IRenderTarget for DeviceContext5 {
+    unsafe fn raw_rt(&self) -> &ID2D1RenderTarget {
+        &self.ptr
+    }
+
+    fn draw_state(&self) -> RTState {
+        self.state
+    }
+
+    fn draw_state_mut(&mut self) -> &mut RTState {
+        &mut self.state
+    }
+
+    fn breadcrumbs(&self) -> &Breadcrumbs {
+        &self.breadcrumbs
+    }
+
+    fn breadcrumbs_mut(&mut self) -> &mut Breadcrumbs {
+        &mut self.breadcrumbs
+    }
+}
+
+unsafe impl IDeviceContext for DeviceContext5 {
+    unsafe fn raw_dc(&self) -> &ID2D1DeviceContext {
+        &self.ptr
+    }
+}
+
+unsafe impl IDeviceContext1 for DeviceContext5 {
+    unsafe fn raw_dc1(&self) -> &ID2D1DeviceContext1 {
+        &self.ptr
+    }
+}
+
+unsafe impl IDeviceContext2 for DeviceContext5 {
+    unsafe fn raw_dc2(&self) -> &ID2D1DeviceContext2 {
+        &self.ptr
+    }
+}
+
+unsafe impl IDeviceContext3 for DeviceContext5 {
+    unsafe fn raw_dc3(&self) -> &ID2D1DeviceContext3 {
+        &self.ptr
+    }
+}
+
+unsafe impl IDeviceContext4 for DeviceContext5 {
+    unsafe fn raw_dc4(&self) -> &ID2D1DeviceContext4 {
+        &self.ptr
+    }
+}
+
+unsafe impl IDeviceContext5 for DeviceContext5 {
+    unsafe fn raw_dc5(&self) -> &ID2D1DeviceContext5 {
+        &self.ptr
+    }
+}
+
+impl ComWrapper for DeviceContext5 {
+    type Interface = ID2D1DeviceContext5;
+    unsafe fn get_raw(&self) -> *mut Self::Interface {
+        self.ptr.as_raw()
+    }
+    unsafe fn into_raw(self) -> *mut Self::Interface {
+        self.ptr.into_raw()
+    }
+    unsafe fn from_raw(raw: *mut Self::Interface) -> Self {
+        Self::from_ptr(ComPtr::from_raw(raw))
+    }
+    unsafe fn from_ptr(ptr: ComPtr<Self::Interface>) -> Self {
+        DeviceContext5 {
+            ptr,
+            state: RTState::NOT_DRAWING | RTState::NO_TARGET_IMAGE,
+            breadcrumbs: Breadcrumbs::default(),
+        }
+    }
+    unsafe fn into_ptr(self) -> ComPtr<Self::Interface> {
+        self.ptr
+    }
+}
+
+unsafe impl Send for DeviceContext5 {}
+unsafe impl Sync for DeviceContext5 {}
+
+impl std::fmt::Debug for DeviceContext5 {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("DeviceContext5")
+            .field("ptr", &self.ptr.as_raw())
+            .field("state", &self.state)
+            .finish()
+    }
+}
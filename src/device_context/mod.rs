@@ -1,21 +1,42 @@
+use crate::descriptions::RenderingControls;
 use crate::device::IDevice;
-use crate::image::IImage;
-use crate::render_target::{IRenderTarget, RTState};
+use crate::enums::{BufferPrecision, CompositeMode, InterpolationMode, PrimitiveBlend, UnitMode};
+use crate::image::{IImage, Image};
+use crate::render_target::{Breadcrumbs, IRenderTarget, RTState};
 use crate::resource::IResource;
 
+use checked_enum::UncheckedEnum;
 use com_wrapper::ComWrapper;
 use dcommon::Error;
+use dxgi::enums::Format;
+use math2d::{Point2f, Rectf};
 use winapi::shared::winerror::SUCCEEDED;
-use winapi::um::d2d1::{ID2D1RenderTarget, ID2D1Resource};
+use winapi::um::d2d1::{ID2D1RenderTarget, ID2D1Resource, D2D1_RECT_F};
 use winapi::um::d2d1_1::{
     ID2D1DeviceContext, D2D1_DEVICE_CONTEXT_OPTIONS_ENABLE_MULTITHREADED_OPTIMIZATIONS,
 };
+use winapi::Interface;
 use wio::com::ComPtr;
 
+pub use self::device_context1::{DeviceContext1, IDeviceContext1};
+pub use self::device_context2::{DeviceContext2, IDeviceContext2};
+pub use self::device_context3::{DeviceContext3, IDeviceContext3};
+pub use self::device_context4::{DeviceContext4, IDeviceContext4};
+pub use self::device_context5::{DeviceContext5, IDeviceContext5};
+pub use self::device_context6::{DeviceContext6, IDeviceContext6};
+
+pub mod device_context1;
+pub mod device_context2;
+pub mod device_context3;
+pub mod device_context4;
+pub mod device_context5;
+pub mod device_context6;
+
 #[repr(C)]
 pub struct DeviceContext {
     ptr: ComPtr<ID2D1DeviceContext>,
     state: RTState,
+    breadcrumbs: Breadcrumbs,
 }
 
 impl DeviceContext {
@@ -44,6 +65,153 @@ pub unsafe trait IDeviceContext: IRenderTarget {
         }
     }
 
+    /// The image currently being drawn to, if one is set.
+    fn target(&self) -> Option<Image> {
+        unsafe {
+            let mut ptr = std::ptr::null_mut();
+            self.raw_dc().GetTarget(&mut ptr);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(Image::from_raw(ptr))
+            }
+        }
+    }
+
+    /// Chooses whether coordinates passed to this context are DIPs or physical
+    /// pixels. See `units` for converting between the two.
+    fn set_unit_mode(&mut self, mode: UnitMode) {
+        unsafe { self.raw_dc().SetUnitMode(mode as u32) }
+    }
+
+    fn unit_mode(&self) -> UncheckedEnum<UnitMode> {
+        unsafe { self.raw_dc().GetUnitMode().into() }
+    }
+
+    /// Changes how primitives, text and images are combined with the target.
+    /// `cpu::blend` shows what each mode does to a pixel.
+    fn set_primitive_blend(&mut self, blend: PrimitiveBlend) {
+        unsafe { self.raw_dc().SetPrimitiveBlend(blend as u32) }
+    }
+
+    fn primitive_blend(&self) -> UncheckedEnum<PrimitiveBlend> {
+        unsafe { self.raw_dc().GetPrimitiveBlend().into() }
+    }
+
+    fn set_rendering_controls(&mut self, controls: &RenderingControls) {
+        unsafe { self.raw_dc().SetRenderingControls(&(*controls).into()) }
+    }
+
+    fn rendering_controls(&self) -> RenderingControls {
+        unsafe {
+            let mut controls = std::mem::zeroed();
+            self.raw_dc().GetRenderingControls(&mut controls);
+            controls.into()
+        }
+    }
+
+    /// Draws `image` with its origin at `offset`. `image_rect` selects the
+    /// part of the image to draw, in image space; `None` draws all of it.
+    fn draw_image(
+        &mut self,
+        image: &dyn IImage,
+        offset: Point2f,
+        image_rect: Option<Rectf>,
+        interpolation: InterpolationMode,
+        composite: CompositeMode,
+    ) {
+        self.assert_can_draw("draw_image");
+
+        let image_rect: Option<D2D1_RECT_F> = image_rect.map(Into::into);
+        unsafe {
+            self.raw_dc().DrawImage(
+                image.raw_img() as *const _ as *mut _,
+                &offset.into(),
+                image_rect
+                    .as_ref()
+                    .map_or(std::ptr::null(), |r| r as *const _),
+                interpolation as u32,
+                composite as u32,
+            );
+        }
+    }
+
+    /// The bounds of `image` in DIPs, ignoring the current transform.
+    fn image_local_bounds(&self, image: &dyn IImage) -> Result<Rectf, Error> {
+        unsafe {
+            let mut bounds = std::mem::zeroed();
+            let hr = self
+                .raw_dc()
+                .GetImageLocalBounds(image.raw_img(), &mut bounds);
+            if SUCCEEDED(hr) {
+                Ok(bounds.into())
+            } else {
+                Err(hr.into())
+            }
+        }
+    }
+
+    /// The bounds of `image` in DIPs after applying the current transform.
+    fn image_world_bounds(&self, image: &dyn IImage) -> Result<Rectf, Error> {
+        unsafe {
+            let mut bounds = std::mem::zeroed();
+            let hr = self
+                .raw_dc()
+                .GetImageWorldBounds(image.raw_img(), &mut bounds);
+            if SUCCEEDED(hr) {
+                Ok(bounds.into())
+            } else {
+                Err(hr.into())
+            }
+        }
+    }
+
+    fn is_dxgi_format_supported(&self, format: Format) -> bool {
+        unsafe { self.raw_dc().IsDxgiFormatSupported(format as u32) != 0 }
+    }
+
+    fn is_buffer_precision_supported(&self, precision: BufferPrecision) -> bool {
+        unsafe { self.raw_dc().IsBufferPrecisionSupported(precision as u32) != 0 }
+    }
+
+    /// The largest width or height, in pixels, of a bitmap on this device.
+    fn maximum_bitmap_size(&self) -> u32 {
+        unsafe { self.raw_dc().GetMaximumBitmapSize() }
+    }
+
+    /// Queries the context for another device context interface, such as
+    /// `DeviceContext3`, returning `None` if this system doesn't support it.
+    /// Wrappers track drawing separately, so this also returns `None` between
+    /// `begin_draw` and `end_draw`.
+    fn cast<T>(&self) -> Option<T>
+    where
+        Self: Sized,
+        T: IDeviceContext + ComWrapper,
+        T::Interface: Interface,
+    {
+        if !self.draw_state().is_set(RTState::NOT_DRAWING) {
+            return None;
+        }
+
+        unsafe {
+            let mut ptr = std::ptr::null_mut();
+            let hr = self
+                .raw_dc()
+                .QueryInterface(&T::Interface::uuidof(), &mut ptr);
+            if !SUCCEEDED(hr) {
+                return None;
+            }
+
+            let mut context = T::from_raw(ptr as *mut T::Interface);
+            *context.draw_state_mut() = RTState::NOT_DRAWING;
+            // The target belongs to the underlying context, so is shared.
+            if self.draw_state().is_set(RTState::NO_TARGET_IMAGE) {
+                *context.draw_state_mut() |= RTState::NO_TARGET_IMAGE;
+            }
+            Some(context)
+        }
+    }
+
     unsafe fn raw_dc(&self) -> &ID2D1DeviceContext;
 }
 
@@ -65,6 +233,14 @@ unsafe impl IRenderTarget for DeviceContext {
     fn draw_state_mut(&mut self) -> &mut RTState {
         &mut self.state
     }
+
+    fn breadcrumbs(&self) -> &Breadcrumbs {
+        &self.breadcrumbs
+    }
+
+    fn breadcrumbs_mut(&mut self) -> &mut Breadcrumbs {
+        &mut self.breadcrumbs
+    }
 }
 
 unsafe impl IDeviceContext for DeviceContext {
@@ -88,6 +264,7 @@ impl ComWrapper for DeviceContext {
         DeviceContext {
             ptr,
             state: RTState::NOT_DRAWING | RTState::NO_TARGET_IMAGE,
+            breadcrumbs: Breadcrumbs::default(),
         }
     }
     unsafe fn into_ptr(self) -> ComPtr<Self::Interface> {
@@ -1,4 +1,6 @@
 #[auto_enum::auto_enum(u32, checked)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum BitmapInterpolationMode {
     NearestNeighbor = 0,
     Linear = 1,
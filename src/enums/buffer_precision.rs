@@ -0,0 +1,16 @@
+#[auto_enum::auto_enum(u32, checked)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BufferPrecision {
+    Unknown = 0,
+    #[cfg_attr(feature = "serde", serde(rename = "8bpc_unorm"))]
+    _8BpcUnorm = 1,
+    #[cfg_attr(feature = "serde", serde(rename = "8bpc_unorm_srgb"))]
+    _8BpcUnormSrgb = 2,
+    #[cfg_attr(feature = "serde", serde(rename = "16bpc_unorm"))]
+    _16BpcUnorm = 3,
+    #[cfg_attr(feature = "serde", serde(rename = "16bpc_float"))]
+    _16BpcFloat = 4,
+    #[cfg_attr(feature = "serde", serde(rename = "32bpc_float"))]
+    _32BpcFloat = 5,
+}
@@ -0,0 +1,8 @@
+#[auto_enum::auto_enum(u32, checked)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ColorSpace {
+    Custom = 0,
+    Srgb = 1,
+    Scrgb = 2,
+}
@@ -0,0 +1,18 @@
+#[auto_enum::auto_enum(u32, checked)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CompositeMode {
+    SourceOver = 0,
+    DestinationOver = 1,
+    SourceIn = 2,
+    DestinationIn = 3,
+    SourceOut = 4,
+    DestinationOut = 5,
+    SourceAtop = 6,
+    DestinationAtop = 7,
+    Xor = 8,
+    Plus = 9,
+    SourceCopy = 10,
+    BoundedSourceCopy = 11,
+    MaskInvert = 12,
+}
@@ -0,0 +1,9 @@
+#[auto_enum::auto_enum(u32, checked)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DebugLevel {
+    None = 0,
+    Error = 1,
+    Warning = 2,
+    Information = 3,
+}
@@ -0,0 +1,7 @@
+#[auto_enum::auto_enum(u32, checked)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FactoryType {
+    SingleThreaded = 0,
+    MultiThreaded = 1,
+}
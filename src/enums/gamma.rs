@@ -1,5 +1,8 @@
 #[auto_enum::auto_enum(u32, checked)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Gamma {
+    #[cfg_attr(feature = "serde", serde(rename = "2.2"))]
     _2_2 = 0,
+    #[cfg_attr(feature = "serde", serde(rename = "1.0"))]
     _1_0 = 1,
 }
@@ -0,0 +1,11 @@
+#[auto_enum::auto_enum(u32, checked)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum InterpolationMode {
+    NearestNeighbor = 0,
+    Linear = 1,
+    Cubic = 2,
+    MultiSampleLinear = 3,
+    Anisotropic = 4,
+    HighQualityCubic = 5,
+}
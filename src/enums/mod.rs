@@ -9,14 +9,26 @@ pub use self::bitmap_interpolation_mode::BitmapInterpolationMode;
 #[doc(inline)]
 pub use self::bitmap_options::BitmapOptions;
 #[doc(inline)]
+pub use self::buffer_precision::BufferPrecision;
+#[doc(inline)]
 pub use self::cap_style::CapStyle;
 #[doc(inline)]
+pub use self::color_interpolation_mode::ColorInterpolationMode;
+#[doc(inline)]
+pub use self::color_space::ColorSpace;
+#[doc(inline)]
+pub use self::composite_mode::CompositeMode;
+#[doc(inline)]
 pub use self::dash_style::DashStyle;
 #[doc(inline)]
+pub use self::debug_level::DebugLevel;
+#[doc(inline)]
 pub use self::draw_text_options::DrawTextOptions;
 #[doc(inline)]
 pub use self::extend_mode::ExtendMode;
 #[doc(inline)]
+pub use self::factory_type::FactoryType;
+#[doc(inline)]
 pub use self::feature_level::FeatureLevel;
 #[doc(inline)]
 pub use self::figure_begin::FigureBegin;
@@ -31,6 +43,8 @@ pub use self::geometry_relation::GeometryRelation;
 #[doc(inline)]
 pub use self::geometry_type::GeometryType;
 #[doc(inline)]
+pub use self::interpolation_mode::InterpolationMode;
+#[doc(inline)]
 pub use self::layer_options::LayerOptions;
 #[doc(inline)]
 pub use self::line_join::LineJoin;
@@ -39,6 +53,8 @@ pub use self::path_segment::PathSegment;
 #[doc(inline)]
 pub use self::present_options::PresentOptions;
 #[doc(inline)]
+pub use self::primitive_blend::PrimitiveBlend;
+#[doc(inline)]
 pub use self::render_target_type::RenderTargetType;
 #[doc(inline)]
 pub use self::render_target_usage::RenderTargetUsage;
@@ -47,6 +63,8 @@ pub use self::stroke_transform_type::StrokeTransformType;
 #[doc(inline)]
 pub use self::sweep_direction::SweepDirection;
 #[doc(inline)]
+pub use self::unit_mode::UnitMode;
+#[doc(inline)]
 pub use self::window_state::WindowState;
 
 mod alpha_mode;
@@ -54,10 +72,16 @@ mod antialias_mode;
 mod arc_size;
 mod bitmap_interpolation_mode;
 mod bitmap_options;
+mod buffer_precision;
 mod cap_style;
+mod color_interpolation_mode;
+mod color_space;
+mod composite_mode;
 mod dash_style;
+mod debug_level;
 mod draw_text_options;
 mod extend_mode;
+mod factory_type;
 mod feature_level;
 mod figure_begin;
 mod figure_end;
@@ -65,12 +89,15 @@ mod fill_mode;
 mod gamma;
 mod geometry_relation;
 mod geometry_type;
+mod interpolation_mode;
 mod layer_options;
 mod line_join;
 mod path_segment;
 mod present_options;
+mod primitive_blend;
 mod render_target_type;
 mod render_target_usage;
 mod stroke_transform_type;
 mod sweep_direction;
+mod unit_mode;
 mod window_state;
@@ -0,0 +1,10 @@
+#[auto_enum::auto_enum(u32, checked)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PrimitiveBlend {
+    SourceOver = 0,
+    Copy = 1,
+    Min = 2,
+    Add = 3,
+    Max = 4,
+}
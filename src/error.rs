@@ -0,0 +1,271 @@
+//! Typed versions of the `D2DERR_*` HRESULTs, so failures from `end_draw`,
+//! `flush` and friends can be matched on instead of compared to magic numbers.
+
+use std::fmt;
+
+#[cfg(windows)]
+use dcommon::Error;
+
+/// The HRESULT type used by COM, as it appears in `dcommon::Error`.
+pub type HRESULT = i32;
+
+/// `E_NOINTERFACE`: the object doesn't implement the requested interface.
+pub const E_NOINTERFACE: HRESULT = 0x8000_4002u32 as i32;
+/// `E_INVALIDARG`
+pub const E_INVALIDARG: HRESULT = 0x8007_0057u32 as i32;
+
+/// `DXGI_ERROR_DEVICE_REMOVED`
+pub const DXGI_ERROR_DEVICE_REMOVED: HRESULT = 0x887A_0005u32 as i32;
+/// `DXGI_ERROR_DEVICE_HUNG`
+pub const DXGI_ERROR_DEVICE_HUNG: HRESULT = 0x887A_0006u32 as i32;
+/// `DXGI_ERROR_DEVICE_RESET`
+pub const DXGI_ERROR_DEVICE_RESET: HRESULT = 0x887A_0007u32 as i32;
+/// `DXGI_ERROR_DRIVER_INTERNAL_ERROR`
+pub const DXGI_ERROR_DRIVER_INTERNAL_ERROR: HRESULT = 0x887A_0020u32 as i32;
+/// `DXGI_STATUS_OCCLUDED`: a success code from `Present` when the window
+/// can't be seen, so there's no point rendering until it can.
+pub const DXGI_STATUS_OCCLUDED: HRESULT = 0x087A_0001;
+
+macro_rules! d2d_errors {
+    ($($(#[$attr:meta])* $variant:ident = $const:ident = $hr:expr, $desc:expr;)*) => {
+        $(
+            $(#[$attr])*
+            pub const $const: HRESULT = $hr as u32 as i32;
+        )*
+
+        /// An error reported by Direct2D. HRESULTs that aren't `D2DERR_*` codes
+        /// are kept as `Other`.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum D2dError {
+            $(
+                $(#[$attr])*
+                $variant,
+            )*
+            /// Any HRESULT which isn't one of the `D2DERR_*` codes above.
+            Other(HRESULT),
+        }
+
+        impl D2dError {
+            /// Every `D2DERR_*` code known to this enum, in numeric order.
+            pub const ALL: &'static [D2dError] = &[$(D2dError::$variant,)*];
+
+            /// The HRESULT this error was created from.
+            pub fn code(self) -> HRESULT {
+                match self {
+                    $(D2dError::$variant => $const,)*
+                    D2dError::Other(hr) => hr,
+                }
+            }
+
+            /// A short, human readable explanation of the error.
+            pub fn description(self) -> &'static str {
+                match self {
+                    $(D2dError::$variant => $desc,)*
+                    D2dError::Other(_) => "Unrecognized HRESULT",
+                }
+            }
+        }
+
+        impl From<HRESULT> for D2dError {
+            fn from(hr: HRESULT) -> D2dError {
+                match hr {
+                    $($const => D2dError::$variant,)*
+                    hr => D2dError::Other(hr),
+                }
+            }
+        }
+    };
+}
+
+d2d_errors! {
+    /// `D2DERR_WRONG_STATE`
+    WrongState = D2DERR_WRONG_STATE = 0x8899_0001,
+        "The object was not in the correct state to process the method";
+    /// `D2DERR_NOT_INITIALIZED`
+    NotInitialized = D2DERR_NOT_INITIALIZED = 0x8899_0002,
+        "The object has not yet been initialized";
+    /// `D2DERR_UNSUPPORTED_OPERATION`
+    UnsupportedOperation = D2DERR_UNSUPPORTED_OPERATION = 0x8899_0003,
+        "The requested operation is not supported";
+    /// `D2DERR_SCANNER_FAILED`
+    ScannerFailed = D2DERR_SCANNER_FAILED = 0x8899_0004,
+        "The geometry scanner failed to process the data";
+    /// `D2DERR_SCREEN_ACCESS_DENIED`
+    ScreenAccessDenied = D2DERR_SCREEN_ACCESS_DENIED = 0x8899_0005,
+        "Direct2D could not access the screen";
+    /// `D2DERR_DISPLAY_STATE_INVALID`
+    DisplayStateInvalid = D2DERR_DISPLAY_STATE_INVALID = 0x8899_0006,
+        "A valid display state could not be determined";
+    /// `D2DERR_ZERO_VECTOR`
+    ZeroVector = D2DERR_ZERO_VECTOR = 0x8899_0007,
+        "The supplied vector is zero";
+    /// `D2DERR_INTERNAL_ERROR`
+    InternalError = D2DERR_INTERNAL_ERROR = 0x8899_0008,
+        "An internal error (Direct2D bug) occurred";
+    /// `D2DERR_DISPLAY_FORMAT_NOT_SUPPORTED`
+    DisplayFormatNotSupported = D2DERR_DISPLAY_FORMAT_NOT_SUPPORTED = 0x8899_0009,
+        "The display format Direct2D needs to render is not supported by the hardware device";
+    /// `D2DERR_INVALID_CALL`
+    InvalidCall = D2DERR_INVALID_CALL = 0x8899_000A,
+        "A call to this method is invalid";
+    /// `D2DERR_NO_HARDWARE_DEVICE`
+    NoHardwareDevice = D2DERR_NO_HARDWARE_DEVICE = 0x8899_000B,
+        "No hardware rendering device is available for this operation";
+    /// `D2DERR_RECREATE_TARGET`: the device was lost and the target, along
+    /// with everything created from it, must be recreated.
+    RecreateTarget = D2DERR_RECREATE_TARGET = 0x8899_000C,
+        "There has been a presentation error that may be recoverable. The caller needs to recreate, rerender the entire frame, and reattempt present";
+    /// `D2DERR_TOO_MANY_SHADER_ELEMENTS`
+    TooManyShaderElements = D2DERR_TOO_MANY_SHADER_ELEMENTS = 0x8899_000D,
+        "Shader construction failed because it was too complex";
+    /// `D2DERR_SHADER_COMPILE_FAILED`
+    ShaderCompileFailed = D2DERR_SHADER_COMPILE_FAILED = 0x8899_000E,
+        "Shader compilation failed";
+    /// `D2DERR_MAX_TEXTURE_SIZE_EXCEEDED`
+    MaxTextureSizeExceeded = D2DERR_MAX_TEXTURE_SIZE_EXCEEDED = 0x8899_000F,
+        "Requested DirectX surface size exceeded the maximum texture size";
+    /// `D2DERR_UNSUPPORTED_VERSION`
+    UnsupportedVersion = D2DERR_UNSUPPORTED_VERSION = 0x8899_0010,
+        "The requested Direct2D version is not supported";
+    /// `D2DERR_BAD_NUMBER`
+    BadNumber = D2DERR_BAD_NUMBER = 0x8899_0011,
+        "Invalid number";
+    /// `D2DERR_WRONG_FACTORY`
+    WrongFactory = D2DERR_WRONG_FACTORY = 0x8899_0012,
+        "Objects used together must be created from the same factory instance";
+    /// `D2DERR_LAYER_ALREADY_IN_USE`
+    LayerAlreadyInUse = D2DERR_LAYER_ALREADY_IN_USE = 0x8899_0013,
+        "A layer resource can only be in use once at any point in time";
+    /// `D2DERR_POP_CALL_DID_NOT_MATCH_PUSH`
+    PopCallDidNotMatchPush = D2DERR_POP_CALL_DID_NOT_MATCH_PUSH = 0x8899_0014,
+        "The pop call did not match the corresponding push call";
+    /// `D2DERR_WRONG_RESOURCE_DOMAIN`
+    WrongResourceDomain = D2DERR_WRONG_RESOURCE_DOMAIN = 0x8899_0015,
+        "The resource was realized on the wrong render target";
+    /// `D2DERR_PUSH_POP_UNBALANCED`
+    PushPopUnbalanced = D2DERR_PUSH_POP_UNBALANCED = 0x8899_0016,
+        "The push and pop calls were unbalanced";
+    /// `D2DERR_RENDER_TARGET_HAS_LAYER_OR_CLIPRECT`
+    RenderTargetHasLayerOrCliprect = D2DERR_RENDER_TARGET_HAS_LAYER_OR_CLIPRECT = 0x8899_0017,
+        "Attempt to copy from a render target while a layer or clip rect is applied";
+    /// `D2DERR_INCOMPATIBLE_BRUSH_TYPES`
+    IncompatibleBrushTypes = D2DERR_INCOMPATIBLE_BRUSH_TYPES = 0x8899_0018,
+        "The brush types are incompatible for the call";
+    /// `D2DERR_WIN32_ERROR`
+    Win32Error = D2DERR_WIN32_ERROR = 0x8899_0019,
+        "An unknown win32 failure occurred";
+    /// `D2DERR_TARGET_NOT_GDI_COMPATIBLE`
+    TargetNotGdiCompatible = D2DERR_TARGET_NOT_GDI_COMPATIBLE = 0x8899_001A,
+        "The render target is not compatible with GDI";
+    /// `D2DERR_TEXT_EFFECT_IS_WRONG_TYPE`
+    TextEffectIsWrongType = D2DERR_TEXT_EFFECT_IS_WRONG_TYPE = 0x8899_001B,
+        "A text client drawing effect object is of the wrong type";
+    /// `D2DERR_TEXT_RENDERER_NOT_RELEASED`
+    TextRendererNotReleased = D2DERR_TEXT_RENDERER_NOT_RELEASED = 0x8899_001C,
+        "The application is holding a reference to the IDWriteTextRenderer interface after the corresponding DrawText or DrawTextLayout call has returned";
+    /// `D2DERR_EXCEEDS_MAX_BITMAP_SIZE`
+    ExceedsMaxBitmapSize = D2DERR_EXCEEDS_MAX_BITMAP_SIZE = 0x8899_001D,
+        "The requested size is larger than the guaranteed supported texture size";
+    /// `D2DERR_INVALID_GRAPH_CONFIGURATION`
+    InvalidGraphConfiguration = D2DERR_INVALID_GRAPH_CONFIGURATION = 0x8899_001E,
+        "There was a configuration error in the graph";
+    /// `D2DERR_INVALID_INTERNAL_GRAPH_CONFIGURATION`
+    InvalidInternalGraphConfiguration = D2DERR_INVALID_INTERNAL_GRAPH_CONFIGURATION = 0x8899_001F,
+        "There was an internal configuration error in the graph";
+    /// `D2DERR_CYCLIC_GRAPH`
+    CyclicGraph = D2DERR_CYCLIC_GRAPH = 0x8899_0020,
+        "There was a cycle in the graph";
+    /// `D2DERR_BITMAP_CANNOT_DRAW`
+    BitmapCannotDraw = D2DERR_BITMAP_CANNOT_DRAW = 0x8899_0021,
+        "Cannot draw with a bitmap that has the D2D1_BITMAP_OPTIONS_CANNOT_DRAW option";
+    /// `D2DERR_OUTSTANDING_BITMAP_REFERENCES`
+    OutstandingBitmapReferences = D2DERR_OUTSTANDING_BITMAP_REFERENCES = 0x8899_0022,
+        "The operation cannot complete while there are outstanding references to the target bitmap";
+    /// `D2DERR_ORIGINAL_TARGET_NOT_BOUND`
+    OriginalTargetNotBound = D2DERR_ORIGINAL_TARGET_NOT_BOUND = 0x8899_0023,
+        "The operation failed because the original target is not currently bound as a target";
+    /// `D2DERR_INVALID_TARGET`
+    InvalidTarget = D2DERR_INVALID_TARGET = 0x8899_0024,
+        "Cannot set the image as a target because it is either an effect or is a bitmap that does not have the D2D1_BITMAP_OPTIONS_TARGET flag set";
+    /// `D2DERR_BITMAP_BOUND_AS_TARGET`
+    BitmapBoundAsTarget = D2DERR_BITMAP_BOUND_AS_TARGET = 0x8899_0025,
+        "Cannot draw with a bitmap that is currently bound as the target bitmap";
+    /// `D2DERR_INSUFFICIENT_DEVICE_CAPABILITIES`
+    InsufficientDeviceCapabilities = D2DERR_INSUFFICIENT_DEVICE_CAPABILITIES = 0x8899_0026,
+        "D3D Device does not have sufficient capabilities to perform the requested action";
+    /// `D2DERR_INTERMEDIATE_TOO_LARGE`
+    IntermediateTooLarge = D2DERR_INTERMEDIATE_TOO_LARGE = 0x8899_0027,
+        "The graph could not be rendered with the context's current tiling settings";
+    /// `D2DERR_EFFECT_IS_NOT_REGISTERED`
+    EffectIsNotRegistered = D2DERR_EFFECT_IS_NOT_REGISTERED = 0x8899_0028,
+        "The CLSID provided to Unregister did not correspond to a registered effect";
+    /// `D2DERR_INVALID_PROPERTY`
+    InvalidProperty = D2DERR_INVALID_PROPERTY = 0x8899_0029,
+        "The specified property does not exist";
+    /// `D2DERR_NO_SUBPROPERTIES`
+    NoSubproperties = D2DERR_NO_SUBPROPERTIES = 0x8899_002A,
+        "The specified sub-property does not exist";
+    /// `D2DERR_PRINT_JOB_CLOSED`
+    PrintJobClosed = D2DERR_PRINT_JOB_CLOSED = 0x8899_002B,
+        "AddPage or Close called after print job is already closed";
+    /// `D2DERR_PRINT_FORMAT_NOT_SUPPORTED`
+    PrintFormatNotSupported = D2DERR_PRINT_FORMAT_NOT_SUPPORTED = 0x8899_002C,
+        "Error during print control creation. Indicates that none of the package target types (representing printer formats) are supported by Direct2D print control";
+    /// `D2DERR_TOO_MANY_TRANSFORM_INPUTS`
+    TooManyTransformInputs = D2DERR_TOO_MANY_TRANSFORM_INPUTS = 0x8899_002D,
+        "An effect attempted to use a transform with too many inputs";
+    /// `D2DERR_INVALID_GLYPH_IMAGE`
+    InvalidGlyphImage = D2DERR_INVALID_GLYPH_IMAGE = 0x8899_002E,
+        "An error was encountered while rendering a glyph";
+}
+
+impl D2dError {
+    /// Whether drawing can continue after recreating the render target (or
+    /// the device underneath it) and everything created from it.
+    pub fn is_recoverable(self) -> bool {
+        match self {
+            D2dError::RecreateTarget => true,
+            D2dError::Other(hr) => is_device_lost(hr),
+            _ => false,
+        }
+    }
+}
+
+/// Whether `hr` is one of the DXGI codes for a removed, reset or hung device.
+pub fn is_device_lost(hr: HRESULT) -> bool {
+    matches!(
+        hr,
+        DXGI_ERROR_DEVICE_REMOVED
+            | DXGI_ERROR_DEVICE_HUNG
+            | DXGI_ERROR_DEVICE_RESET
+            | DXGI_ERROR_DRIVER_INTERNAL_ERROR
+    )
+}
+
+impl From<D2dError> for HRESULT {
+    fn from(error: D2dError) -> HRESULT {
+        error.code()
+    }
+}
+
+#[cfg(windows)]
+impl From<Error> for D2dError {
+    fn from(error: Error) -> D2dError {
+        error.0.into()
+    }
+}
+
+#[cfg(windows)]
+impl<'a> From<&'a Error> for D2dError {
+    fn from(error: &'a Error) -> D2dError {
+        error.0.into()
+    }
+}
+
+impl fmt::Display for D2dError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{} (0x{:08X})", self.description(), self.code() as u32)
+    }
+}
+
+impl std::error::Error for D2dError {}
@@ -0,0 +1,136 @@
+use crate::enums::{DebugLevel, FactoryType};
+use crate::factory::{Factory, FactoryOptions, FactoryVersion, IFactory};
+
+use com_wrapper::ComWrapper;
+use dcommon::Error;
+use winapi::ctypes::c_void;
+use winapi::shared::guiddef::GUID;
+use winapi::shared::winerror::SUCCEEDED;
+use winapi::um::d2d1::{D2D1CreateFactory, ID2D1Factory, D2D1_FACTORY_OPTIONS};
+use winapi::um::d2d1_1::ID2D1Factory1;
+use winapi::um::d2d1_2::ID2D1Factory2;
+use winapi::um::d2d1_3::{
+    ID2D1Factory3, ID2D1Factory4, ID2D1Factory5, ID2D1Factory6, ID2D1Factory7,
+};
+use winapi::Interface;
+
+/// Creates any version of the Direct2D factory with a chosen threading model
+/// and debug level.
+///
+/// ```no_run
+/// # use direct2d::enums::{DebugLevel, FactoryType};
+/// # use direct2d::factory::{Factory3, FactoryBuilder, FactoryVersion, IFactory};
+/// let factory: Factory3 = FactoryBuilder::new()
+///     .with_factory_type(FactoryType::SingleThreaded)
+///     .with_debug_level(DebugLevel::Information)
+///     .build()
+///     .unwrap();
+///
+/// // Or take the newest factory this system has, as long as it's at least Factory1.
+/// let (version, factory) = FactoryBuilder::new()
+///     .with_min_version(FactoryVersion::Factory1)
+///     .build_latest()
+///     .unwrap();
+/// assert_eq!(factory.version(), version);
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FactoryBuilder {
+    options: FactoryOptions,
+}
+
+impl FactoryBuilder {
+    /// Starts with a multithreaded factory with warnings enabled, the same
+    /// options `Factory::new` uses.
+    pub fn new() -> Self {
+        FactoryBuilder::default()
+    }
+
+    pub fn with_factory_type(mut self, factory_type: FactoryType) -> Self {
+        self.options.factory_type = factory_type;
+        self
+    }
+
+    /// Anything other than `DebugLevel::None` needs the Direct2D debug layer,
+    /// which is installed with the Windows SDK or the Graphics Tools feature.
+    pub fn with_debug_level(mut self, debug_level: DebugLevel) -> Self {
+        self.options.debug_level = debug_level;
+        self
+    }
+
+    /// The oldest version `build_latest` will settle for.
+    pub fn with_min_version(mut self, version: FactoryVersion) -> Self {
+        self.options.min_version = version;
+        self
+    }
+
+    /// The newest version `build_latest` will try.
+    pub fn with_max_version(mut self, version: FactoryVersion) -> Self {
+        self.options.max_version = version;
+        self
+    }
+
+    pub fn with_options(mut self, options: FactoryOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn options(&self) -> &FactoryOptions {
+        &self.options
+    }
+
+    /// Creates a factory of a specific version. Fails with `E_NOINTERFACE` if
+    /// the system doesn't support it.
+    pub fn build<F>(self) -> Result<F, Error>
+    where
+        F: IFactory + ComWrapper,
+        F::Interface: Interface,
+    {
+        self.options.validate()?;
+        unsafe {
+            let ptr = create_raw(&self.options, &F::Interface::uuidof())?;
+            Ok(F::from_raw(ptr as *mut F::Interface))
+        }
+    }
+
+    /// Creates the newest factory between the minimum and maximum versions,
+    /// falling back one version at a time until one is supported.
+    pub fn build_latest(self) -> Result<(FactoryVersion, Factory), Error> {
+        let (version, ptr) = self
+            .options
+            .negotiate(|version| unsafe { create_raw(&self.options, &iid(version)) })?;
+
+        // Every factory interface starts with ID2D1Factory's vtable.
+        unsafe { Ok((version, Factory::from_raw(ptr as *mut ID2D1Factory))) }
+    }
+}
+
+unsafe fn create_raw(options: &FactoryOptions, iid: &GUID) -> Result<*mut c_void, i32> {
+    let mut ptr: *mut c_void = std::ptr::null_mut();
+    let hr = D2D1CreateFactory(
+        options.factory_type as u32,
+        iid,
+        &D2D1_FACTORY_OPTIONS {
+            debugLevel: options.debug_level as u32,
+        },
+        &mut ptr,
+    );
+
+    if SUCCEEDED(hr) {
+        Ok(ptr)
+    } else {
+        Err(hr)
+    }
+}
+
+pub(crate) fn iid(version: FactoryVersion) -> GUID {
+    match version {
+        FactoryVersion::Factory => ID2D1Factory::uuidof(),
+        FactoryVersion::Factory1 => ID2D1Factory1::uuidof(),
+        FactoryVersion::Factory2 => ID2D1Factory2::uuidof(),
+        FactoryVersion::Factory3 => ID2D1Factory3::uuidof(),
+        FactoryVersion::Factory4 => ID2D1Factory4::uuidof(),
+        FactoryVersion::Factory5 => ID2D1Factory5::uuidof(),
+        FactoryVersion::Factory6 => ID2D1Factory6::uuidof(),
+        FactoryVersion::Factory7 => ID2D1Factory7::uuidof(),
+    }
+}
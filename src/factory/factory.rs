@@ -1,12 +1,10 @@
+use crate::factory::{FactoryBuilder, FactoryOptions, FactoryVersion};
+
 use com_wrapper::ComWrapper;
 use dcommon::Error;
-use winapi::ctypes::c_void;
 use winapi::shared::winerror::SUCCEEDED;
-use winapi::um::d2d1::{
-    D2D1CreateFactory, ID2D1Factory, D2D1_DEBUG_LEVEL_WARNING, D2D1_FACTORY_OPTIONS,
-    D2D1_FACTORY_TYPE_MULTI_THREADED,
-};
-use winapi::Interface;
+use winapi::um::d2d1::ID2D1Factory;
+use winapi::um::unknwnbase::IUnknown;
 use wio::com::ComPtr;
 
 #[derive(ComWrapper, Clone, PartialEq)]
@@ -16,24 +14,10 @@ pub struct Factory {
 }
 
 impl Factory {
+    /// Creates a multithreaded factory with warnings enabled. Use
+    /// `FactoryBuilder` for other options.
     pub fn new() -> Result<Factory, Error> {
-        unsafe {
-            let mut ptr: *mut ID2D1Factory = std::ptr::null_mut();
-            let hr = D2D1CreateFactory(
-                D2D1_FACTORY_TYPE_MULTI_THREADED,
-                &ID2D1Factory::uuidof(),
-                &D2D1_FACTORY_OPTIONS {
-                    debugLevel: D2D1_DEBUG_LEVEL_WARNING,
-                },
-                &mut ptr as *mut _ as *mut *mut c_void,
-            );
-
-            if SUCCEEDED(hr) {
-                Ok(Factory::from_raw(ptr))
-            } else {
-                Err(hr.into())
-            }
-        }
+        FactoryBuilder::new().build()
     }
 }
 
@@ -46,6 +30,28 @@ pub unsafe trait IFactory {
         }
     }
 
+    /// The newest factory interface this object implements. Falls back to
+    /// `FactoryVersion::Factory` if it can't be determined.
+    fn version(&self) -> FactoryVersion {
+        let query = |version| unsafe {
+            let mut ptr = std::ptr::null_mut();
+            let hr = self
+                .raw_f()
+                .QueryInterface(&crate::factory::builder::iid(version), &mut ptr);
+            if SUCCEEDED(hr) {
+                (*(ptr as *mut IUnknown)).Release();
+                Ok(())
+            } else {
+                Err(hr)
+            }
+        };
+
+        FactoryOptions::default()
+            .negotiate(query)
+            .map(|(version, ())| version)
+            .unwrap_or(FactoryVersion::Factory)
+    }
+
     unsafe fn raw_f(&self) -> &ID2D1Factory;
 }
 
@@ -0,0 +1,45 @@
+use crate::factory::{FactoryBuilder, IFactory, IFactory1};
+
+use com_wrapper::ComWrapper;
+use dcommon::Error;
+use winapi::um::d2d1::ID2D1Factory;
+use winapi::um::d2d1_1::ID2D1Factory1;
+use winapi::um::d2d1_2::ID2D1Factory2;
+use wio::com::ComPtr;
+
+#[derive(ComWrapper, Clone, PartialEq)]
+#[com(send, sync, debug)]
+pub struct Factory2 {
+    ptr: ComPtr<ID2D1Factory2>,
+}
+
+impl Factory2 {
+    /// Creates a multithreaded factory with warnings enabled. Use
+    /// `FactoryBuilder` for other options.
+    #[inline]
+    pub fn new() -> Result<Factory2, Error> {
+        FactoryBuilder::new().build()
+    }
+}
+
+pub unsafe trait IFactory2: IFactory1 {
+    unsafe fn raw_f2(&self) -> &ID2D1Factory2;
+}
+
+unsafe impl IFactory for Factory2 {
+    unsafe fn raw_f(&self) -> &ID2D1Factory {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory1 for Factory2 {
+    unsafe fn raw_f1(&self) -> &ID2D1Factory1 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory2 for Factory2 {
+    unsafe fn raw_f2(&self) -> &ID2D1Factory2 {
+        &self.ptr
+    }
+}
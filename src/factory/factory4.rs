@@ -0,0 +1,58 @@
+use crate::factory::{FactoryBuilder, IFactory, IFactory1, IFactory2, IFactory3};
+
+use com_wrapper::ComWrapper;
+use dcommon::Error;
+use winapi::um::d2d1::ID2D1Factory;
+use winapi::um::d2d1_1::ID2D1Factory1;
+use winapi::um::d2d1_2::ID2D1Factory2;
+use winapi::um::d2d1_3::{ID2D1Factory3, ID2D1Factory4};
+use wio::com::ComPtr;
+
+#[derive(ComWrapper, Clone, PartialEq)]
+#[com(send, sync, debug)]
+pub struct Factory4 {
+    ptr: ComPtr<ID2D1Factory4>,
+}
+
+impl Factory4 {
+    /// Creates a multithreaded factory with warnings enabled. Use
+    /// `FactoryBuilder` for other options.
+    #[inline]
+    pub fn new() -> Result<Factory4, Error> {
+        FactoryBuilder::new().build()
+    }
+}
+
+pub unsafe trait IFactory4: IFactory3 {
+    unsafe fn raw_f4(&self) -> &ID2D1Factory4;
+}
+
+unsafe impl IFactory for Factory4 {
+    unsafe fn raw_f(&self) -> &ID2D1Factory {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory1 for Factory4 {
+    unsafe fn raw_f1(&self) -> &ID2D1Factory1 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory2 for Factory4 {
+    unsafe fn raw_f2(&self) -> &ID2D1Factory2 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory3 for Factory4 {
+    unsafe fn raw_f3(&self) -> &ID2D1Factory3 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory4 for Factory4 {
+    unsafe fn raw_f4(&self) -> &ID2D1Factory4 {
+        &self.ptr
+    }
+}
@@ -0,0 +1,64 @@
+use crate::factory::{FactoryBuilder, IFactory, IFactory1, IFactory2, IFactory3, IFactory4};
+
+use com_wrapper::ComWrapper;
+use dcommon::Error;
+use winapi::um::d2d1::ID2D1Factory;
+use winapi::um::d2d1_1::ID2D1Factory1;
+use winapi::um::d2d1_2::ID2D1Factory2;
+use winapi::um::d2d1_3::{ID2D1Factory3, ID2D1Factory4, ID2D1Factory5};
+use wio::com::ComPtr;
+
+#[derive(ComWrapper, Clone, PartialEq)]
+#[com(send, sync, debug)]
+pub struct Factory5 {
+    ptr: ComPtr<ID2D1Factory5>,
+}
+
+impl Factory5 {
+    /// Creates a multithreaded factory with warnings enabled. Use
+    /// `FactoryBuilder` for other options.
+    #[inline]
+    pub fn new() -> Result<Factory5, Error> {
+        FactoryBuilder::new().build()
+    }
+}
+
+pub unsafe trait IFactory5: IFactory4 {
+    unsafe fn raw_f5(&self) -> &ID2D1Factory5;
+}
+
+unsafe impl IFactory for Factory5 {
+    unsafe fn raw_f(&self) -> &ID2D1Factory {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory1 for Factory5 {
+    unsafe fn raw_f1(&self) -> &ID2D1Factory1 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory2 for Factory5 {
+    unsafe fn raw_f2(&self) -> &ID2D1Factory2 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory3 for Factory5 {
+    unsafe fn raw_f3(&self) -> &ID2D1Factory3 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory4 for Factory5 {
+    unsafe fn raw_f4(&self) -> &ID2D1Factory4 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory5 for Factory5 {
+    unsafe fn raw_f5(&self) -> &ID2D1Factory5 {
+        &self.ptr
+    }
+}
@@ -0,0 +1,80 @@
+use crate::factory::{
+    FactoryBuilder, IFactory, IFactory1, IFactory2, IFactory3, IFactory4, IFactory5, IFactory6,
+};
+
+use com_wrapper::ComWrapper;
+use dcommon::Error;
+use winapi::um::d2d1::ID2D1Factory;
+use winapi::um::d2d1_1::ID2D1Factory1;
+use winapi::um::d2d1_2::ID2D1Factory2;
+use winapi::um::d2d1_3::{
+    ID2D1Factory3, ID2D1Factory4, ID2D1Factory5, ID2D1Factory6, ID2D1Factory7,
+};
+use wio::com::ComPtr;
+
+#[derive(ComWrapper, Clone, PartialEq)]
+#[com(send, sync, debug)]
+pub struct Factory7 {
+    ptr: ComPtr<ID2D1Factory7>,
+}
+
+impl Factory7 {
+    /// Creates a multithreaded factory with warnings enabled. Use
+    /// `FactoryBuilder` for other options.
+    #[inline]
+    pub fn new() -> Result<Factory7, Error> {
+        FactoryBuilder::new().build()
+    }
+}
+
+pub unsafe trait IFactory7: IFactory6 {
+    unsafe fn raw_f7(&self) -> &ID2D1Factory7;
+}
+
+unsafe impl IFactory for Factory7 {
+    unsafe fn raw_f(&self) -> &ID2D1Factory {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory1 for Factory7 {
+    unsafe fn raw_f1(&self) -> &ID2D1Factory1 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory2 for Factory7 {
+    unsafe fn raw_f2(&self) -> &ID2D1Factory2 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory3 for Factory7 {
+    unsafe fn raw_f3(&self) -> &ID2D1Factory3 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory4 for Factory7 {
+    unsafe fn raw_f4(&self) -> &ID2D1Factory4 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory5 for Factory7 {
+    unsafe fn raw_f5(&self) -> &ID2D1Factory5 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory6 for Factory7 {
+    unsafe fn raw_f6(&self) -> &ID2D1Factory6 {
+        &self.ptr
+    }
+}
+
+unsafe impl IFactory7 for Factory7 {
+    unsafe fn raw_f7(&self) -> &ID2D1Factory7 {
+        &self.ptr
+    }
+}
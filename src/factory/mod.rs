@@ -1,5 +1,39 @@
+#[cfg(windows)]
+pub use self::builder::FactoryBuilder;
+#[cfg(windows)]
 pub use self::factory::{Factory, IFactory};
+#[cfg(windows)]
 pub use self::factory1::{Factory1, IFactory1};
+#[cfg(windows)]
+pub use self::factory2::{Factory2, IFactory2};
+#[cfg(windows)]
+pub use self::factory3::{Factory3, IFactory3};
+#[cfg(windows)]
+pub use self::factory4::{Factory4, IFactory4};
+#[cfg(windows)]
+pub use self::factory5::{Factory5, IFactory5};
+#[cfg(windows)]
+pub use self::factory6::{Factory6, IFactory6};
+#[cfg(windows)]
+pub use self::factory7::{Factory7, IFactory7};
+pub use self::options::{FactoryOptions, FactoryVersion};
 
+#[cfg(windows)]
+pub mod builder;
+#[cfg(windows)]
 pub mod factory;
+#[cfg(windows)]
 pub mod factory1;
+#[cfg(windows)]
+pub mod factory2;
+#[cfg(windows)]
+pub mod factory3;
+#[cfg(windows)]
+pub mod factory4;
+#[cfg(windows)]
+pub mod factory5;
+#[cfg(windows)]
+pub mod factory6;
+#[cfg(windows)]
+pub mod factory7;
+pub mod options;
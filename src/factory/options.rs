@@ -0,0 +1,117 @@
+//! The parts of factory creation which don't need COM: the options and the
+//! choice of which factory interface to ask for.
+
+use crate::enums::{DebugLevel, FactoryType};
+use crate::error::{E_INVALIDARG, E_NOINTERFACE, HRESULT};
+
+/// The factory interfaces, from the Windows 7 `ID2D1Factory` up to
+/// `ID2D1Factory7`. Newer versions compare greater.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FactoryVersion {
+    Factory,
+    Factory1,
+    Factory2,
+    Factory3,
+    Factory4,
+    Factory5,
+    Factory6,
+    Factory7,
+}
+
+impl FactoryVersion {
+    /// Every version, oldest first.
+    pub const ALL: [FactoryVersion; 8] = [
+        FactoryVersion::Factory,
+        FactoryVersion::Factory1,
+        FactoryVersion::Factory2,
+        FactoryVersion::Factory3,
+        FactoryVersion::Factory4,
+        FactoryVersion::Factory5,
+        FactoryVersion::Factory6,
+        FactoryVersion::Factory7,
+    ];
+
+    pub const LATEST: FactoryVersion = FactoryVersion::Factory7;
+
+    /// The name of the COM interface, e.g. `ID2D1Factory3`.
+    pub fn interface_name(self) -> &'static str {
+        match self {
+            FactoryVersion::Factory => "ID2D1Factory",
+            FactoryVersion::Factory1 => "ID2D1Factory1",
+            FactoryVersion::Factory2 => "ID2D1Factory2",
+            FactoryVersion::Factory3 => "ID2D1Factory3",
+            FactoryVersion::Factory4 => "ID2D1Factory4",
+            FactoryVersion::Factory5 => "ID2D1Factory5",
+            FactoryVersion::Factory6 => "ID2D1Factory6",
+            FactoryVersion::Factory7 => "ID2D1Factory7",
+        }
+    }
+
+    /// The next older version, if there is one.
+    pub fn older(self) -> Option<FactoryVersion> {
+        (self as usize)
+            .checked_sub(1)
+            .map(|i| FactoryVersion::ALL[i])
+    }
+
+    /// The next newer version, if there is one.
+    pub fn newer(self) -> Option<FactoryVersion> {
+        FactoryVersion::ALL.get(self as usize + 1).cloned()
+    }
+}
+
+/// Everything that goes into creating a factory.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FactoryOptions {
+    pub factory_type: FactoryType,
+    pub debug_level: DebugLevel,
+    /// The oldest interface that's acceptable when negotiating.
+    pub min_version: FactoryVersion,
+    /// The newest interface to try when negotiating.
+    pub max_version: FactoryVersion,
+}
+
+impl Default for FactoryOptions {
+    /// A multithreaded factory with warnings enabled, accepting any version.
+    fn default() -> FactoryOptions {
+        FactoryOptions {
+            factory_type: FactoryType::MultiThreaded,
+            debug_level: DebugLevel::Warning,
+            min_version: FactoryVersion::Factory,
+            max_version: FactoryVersion::LATEST,
+        }
+    }
+}
+
+impl FactoryOptions {
+    /// Fails with `E_INVALIDARG` if the version range is empty.
+    pub fn validate(&self) -> Result<(), HRESULT> {
+        if self.min_version > self.max_version {
+            return Err(E_INVALIDARG);
+        }
+        Ok(())
+    }
+
+    /// Calls `create` with each version from `max_version` down to
+    /// `min_version`, returning the first one that succeeds. Only
+    /// `E_NOINTERFACE` moves on to the next older version; any other error is
+    /// returned immediately. If no version in range is available the result is
+    /// `E_NOINTERFACE`.
+    pub fn negotiate<T>(
+        &self,
+        mut create: impl FnMut(FactoryVersion) -> Result<T, HRESULT>,
+    ) -> Result<(FactoryVersion, T), HRESULT> {
+        self.validate()?;
+
+        let mut version = self.max_version;
+        loop {
+            match create(version) {
+                Ok(value) => return Ok((version, value)),
+                Err(E_NOINTERFACE) if version > self.min_version => {
+                    version = version.older().unwrap();
+                }
+                Err(hr) => return Err(hr),
+            }
+        }
+    }
+}
@@ -1,38 +1,68 @@
+#[cfg(windows)]
 use crate::enums::*;
+#[cfg(windows)]
 use crate::resource::IResource;
+#[cfg(windows)]
 use crate::stroke_style::StrokeStyle;
 
+#[cfg(windows)]
 use std::mem::MaybeUninit;
 
+#[cfg(windows)]
 use checked_enum::UncheckedEnum;
+#[cfg(windows)]
 use com_wrapper::ComWrapper;
+#[cfg(windows)]
 use dcommon::Error;
+#[cfg(windows)]
 use math2d::{Matrix3x2f, Point2f, Rectf, Vector2f};
+#[cfg(windows)]
 use winapi::shared::minwindef::*;
+#[cfg(windows)]
 use winapi::shared::winerror::*;
+#[cfg(windows)]
 use winapi::um::d2d1::*;
+#[cfg(windows)]
 use wio::com::ComPtr;
 
+#[cfg(windows)]
 pub use self::ellipse::EllipseGeometry;
+#[cfg(windows)]
 pub use self::group::GroupGeometry;
+pub use self::morph::PathMorph;
+#[cfg(windows)]
 pub use self::path::PathGeometry;
+pub use self::path_data::{CubicFigure, PathData, PathEvent};
+#[cfg(windows)]
 pub use self::rectangle::RectangleGeometry;
+#[cfg(windows)]
 pub use self::rounded_rectangle::RoundedRectangleGeometry;
+#[cfg(windows)]
 pub use self::transformed::TransformedGeometry;
 
+#[cfg(windows)]
 pub mod ellipse;
+#[cfg(windows)]
 pub mod group;
+pub mod morph;
+#[cfg(windows)]
 pub mod path;
+pub mod path_data;
+#[cfg(windows)]
 pub mod rectangle;
+#[cfg(windows)]
 pub mod rounded_rectangle;
+#[cfg(windows)]
 pub mod transformed;
 
+#[cfg(windows)]
 #[derive(ComWrapper, Clone)]
 #[com(send, sync, debug)]
 pub struct Geometry {
     ptr: ComPtr<ID2D1Geometry>,
 }
 
+#[cfg(windows)]
 pub unsafe trait IGeometry: IResource {
     /// Retrieve the bounds of the geometry, with an optional applied transform.
     ///
@@ -281,18 +311,21 @@ pub unsafe trait IGeometry: IResource {
     unsafe fn raw_geom(&self) -> &ID2D1Geometry;
 }
 
+#[cfg(windows)]
 unsafe impl IResource for Geometry {
     unsafe fn raw_resource(&self) -> &ID2D1Resource {
         &self.ptr
     }
 }
 
+#[cfg(windows)]
 unsafe impl IGeometry for Geometry {
     unsafe fn raw_geom(&self) -> &ID2D1Geometry {
         &self.ptr
     }
 }
 
+#[cfg(windows)]
 pub unsafe trait GeometryType: ComWrapper + Clone {
     fn to_generic(&self) -> Geometry
     where
@@ -302,4 +335,5 @@ pub unsafe trait GeometryType: ComWrapper + Clone {
     }
 }
 
+#[cfg(windows)]
 unsafe impl GeometryType for Geometry {}
@@ -0,0 +1,234 @@
+//! Blending one path into another, such as a play icon into a pause icon.
+
+use crate::enums::{FigureEnd, FillMode};
+use crate::geometry::path_data::{lerp, line_to_cubic, CubicFigure, PathData};
+
+use math2d::{BezierSegment, Point2f};
+
+/// Closed figures are split into at least this many segments, so there are
+/// enough places to start from when lining them up. With fewer, a square
+/// morphing into a circle has its corners paired with the circle's sides
+/// and pinches in on the way.
+const MIN_CLOSED_SEGMENTS: usize = 8;
+
+/// Two paths rearranged so every figure and segment in one has a partner in
+/// the other, ready to produce the shapes in between.
+///
+/// Both paths are converted to cubic beziers. Figures are paired in order,
+/// and a path with fewer figures gets extra ones collapsed to a point at the
+/// center of their partners, so they grow out of nothing. Within each pair
+/// the longest segments are split until both have the same number, and
+/// closed figures are split further and rotated to start at the points
+/// closest to each other so the shape doesn't twist or pinch on the way.
+/// Figures keep their direction, so the fill mode gives the same result at
+/// both ends as the original paths.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathMorph {
+    pairs: Vec<(CubicFigure, CubicFigure)>,
+    from_fill: FillMode,
+    to_fill: FillMode,
+}
+
+impl PathMorph {
+    pub fn new(from: &PathData, to: &PathData) -> PathMorph {
+        let mut a = figures(from);
+        let mut b = figures(to);
+        while a.len() < b.len() {
+            a.push(collapsed(&b[a.len()]));
+        }
+        while b.len() < a.len() {
+            b.push(collapsed(&a[b.len()]));
+        }
+
+        let pairs = a
+            .into_iter()
+            .zip(b)
+            .map(|(mut a, mut b)| {
+                let closed = a.is_closed() && b.is_closed();
+                let mut count = a.segments.len().max(b.segments.len());
+                if closed {
+                    count = count.max(MIN_CLOSED_SEGMENTS);
+                }
+                split_to(&mut a, count);
+                split_to(&mut b, count);
+                if closed {
+                    let shift = best_rotation(&a, &b);
+                    rotate(&mut b, shift);
+                }
+                (a, b)
+            })
+            .collect();
+
+        PathMorph {
+            pairs,
+            from_fill: from.fill_mode(),
+            to_fill: to.fill_mode(),
+        }
+    }
+
+    /// The number of figures in every in-between path.
+    pub fn figure_count(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// The path `t` of the way from the first path to the second. Values
+    /// outside `0.0..=1.0` extrapolate, so overshooting easing curves work.
+    ///
+    /// The fill mode and each figure's `FigureBegin` switch over halfway,
+    /// and figures are only closed if both partners were.
+    pub fn at(&self, t: f32) -> PathData {
+        let mut path = PathData::new().with_fill_mode(if t < 0.5 {
+            self.from_fill
+        } else {
+            self.to_fill
+        });
+
+        for (a, b) in &self.pairs {
+            let figure = CubicFigure {
+                start: lerp(a.start, b.start, t),
+                segments: a
+                    .segments
+                    .iter()
+                    .zip(&b.segments)
+                    .map(|(sa, sb)| BezierSegment {
+                        p1: lerp(sa.p1, sb.p1, t),
+                        p2: lerp(sa.p2, sb.p2, t),
+                        p3: lerp(sa.p3, sb.p3, t),
+                    })
+                    .collect(),
+                begin: if t < 0.5 { a.begin } else { b.begin },
+                end: if a.is_closed() && b.is_closed() {
+                    FigureEnd::Closed
+                } else {
+                    FigureEnd::Open
+                },
+            };
+            figure.append_to(&mut path);
+        }
+        path
+    }
+}
+
+/// The path's figures, each with at least one segment so it can be split.
+fn figures(path: &PathData) -> Vec<CubicFigure> {
+    let mut figures = path.to_cubics();
+    for figure in &mut figures {
+        if figure.segments.is_empty() {
+            figure
+                .segments
+                .push(line_to_cubic(figure.start, figure.start));
+        }
+    }
+    figures
+}
+
+/// A figure with a single segment squashed to the center of `partner`.
+fn collapsed(partner: &CubicFigure) -> CubicFigure {
+    let (mut min, mut max) = (partner.start, partner.start);
+    for segment in &partner.segments {
+        for p in &[segment.p1, segment.p2, segment.p3] {
+            min = Point2f {
+                x: min.x.min(p.x),
+                y: min.y.min(p.y),
+            };
+            max = Point2f {
+                x: max.x.max(p.x),
+                y: max.y.max(p.y),
+            };
+        }
+    }
+    let center = lerp(min, max, 0.5);
+    CubicFigure {
+        start: center,
+        segments: vec![line_to_cubic(center, center)],
+        begin: partner.begin,
+        end: partner.end,
+    }
+}
+
+/// Splits the longest segments of `figure` in half until it has `count`.
+fn split_to(figure: &mut CubicFigure, count: usize) {
+    while figure.segments.len() < count {
+        let starts: Vec<Point2f> = figure.points().collect();
+        let (index, _) = figure
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (i, hull_length(starts[i], s)))
+            .fold(
+                (0, -1.0),
+                |best, next| if next.1 > best.1 { next } else { best },
+            );
+        let (first, second) = split(starts[index], &figure.segments[index], 0.5);
+        figure.segments[index] = first;
+        figure.segments.insert(index + 1, second);
+    }
+}
+
+/// The length of the control polygon, which is never shorter than the curve.
+fn hull_length(start: Point2f, s: &BezierSegment) -> f32 {
+    distance(start, s.p1) + distance(s.p1, s.p2) + distance(s.p2, s.p3)
+}
+
+/// Splits a bezier starting at `start` into two at `t`.
+fn split(start: Point2f, s: &BezierSegment, t: f32) -> (BezierSegment, BezierSegment) {
+    let ab = lerp(start, s.p1, t);
+    let bc = lerp(s.p1, s.p2, t);
+    let cd = lerp(s.p2, s.p3, t);
+    let abc = lerp(ab, bc, t);
+    let bcd = lerp(bc, cd, t);
+    let mid = lerp(abc, bcd, t);
+    (
+        BezierSegment {
+            p1: ab,
+            p2: abc,
+            p3: mid,
+        },
+        BezierSegment {
+            p1: bcd,
+            p2: cd,
+            p3: s.p3,
+        },
+    )
+}
+
+/// How many segments to move the start of closed figure `b` forward by so
+/// its segments line up best with `a`'s.
+fn best_rotation(a: &CubicFigure, b: &CubicFigure) -> usize {
+    let count = a.segments.len();
+    let a_points: Vec<Point2f> = a.points().take(count).collect();
+    let b_points: Vec<Point2f> = b.points().take(count).collect();
+    (0..count)
+        .map(|shift| {
+            let cost: f32 = (0..count)
+                .map(|i| distance_sq(a_points[i], b_points[(i + shift) % count]))
+                .sum();
+            (shift, cost)
+        })
+        .fold((0, f32::INFINITY), |best, next| {
+            if next.1 < best.1 {
+                next
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+/// Starts closed figure `figure` at the end of its `shift`th segment. The
+/// figure has to end where it starts, which `to_cubics` makes sure of.
+fn rotate(figure: &mut CubicFigure, shift: usize) {
+    if shift == 0 {
+        return;
+    }
+    figure.start = figure.segments[shift - 1].p3;
+    figure.segments.rotate_left(shift);
+}
+
+fn distance(a: Point2f, b: Point2f) -> f32 {
+    distance_sq(a, b).sqrt()
+}
+
+fn distance_sq(a: Point2f, b: Point2f) -> f32 {
+    (a.x - b.x) * (a.x - b.x) + (a.y - b.y) * (a.y - b.y)
+}
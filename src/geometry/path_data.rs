@@ -0,0 +1,482 @@
+//! A path recorded as plain data, so it can be inspected, transformed and
+//! compared without a factory, then built into a `PathGeometry` when needed.
+
+use crate::enums::{FigureBegin, FigureEnd, FillMode};
+
+#[cfg(windows)]
+use crate::factory::IFactory;
+#[cfg(windows)]
+use crate::geometry::path::{PathBuilder, PathGeometry};
+#[cfg(windows)]
+use dcommon::Error;
+
+use math2d::{
+    ArcSegment, ArcSize, BezierSegment, Matrix3x2f, Point2f, QuadBezierSegment, Rectf,
+    SweepDirection,
+};
+
+use std::f32::consts::PI;
+
+/// One call to a `PathBuilder` or `FigureBuilder`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PathEvent {
+    Begin { start: Point2f, begin: FigureBegin },
+    Line(Point2f),
+    Quadratic(QuadBezierSegment),
+    Cubic(BezierSegment),
+    Arc(ArcSegment),
+    End(FigureEnd),
+}
+
+/// A sequence of figures, each a start point followed by segments, with the
+/// same meaning as the calls made to a `PathBuilder`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathData {
+    fill_mode: FillMode,
+    events: Vec<PathEvent>,
+    /// Whether a figure has been begun and not yet ended.
+    open: bool,
+}
+
+impl PathData {
+    pub fn new() -> PathData {
+        PathData {
+            fill_mode: FillMode::Alternate,
+            events: Vec::new(),
+            open: false,
+        }
+    }
+
+    pub fn fill_mode(&self) -> FillMode {
+        self.fill_mode
+    }
+
+    pub fn set_fill_mode(&mut self, fill_mode: FillMode) {
+        self.fill_mode = fill_mode;
+    }
+
+    pub fn with_fill_mode(mut self, fill_mode: FillMode) -> Self {
+        self.fill_mode = fill_mode;
+        self
+    }
+
+    /// Starts a figure, ending the previous one as open if it wasn't ended.
+    pub fn begin_figure(&mut self, start: impl Into<Point2f>, begin: FigureBegin) {
+        if self.open {
+            self.end_figure(FigureEnd::Open);
+        }
+        self.events.push(PathEvent::Begin {
+            start: start.into(),
+            begin,
+        });
+        self.open = true;
+    }
+
+    /// Ends the current figure. Does nothing if there isn't one.
+    pub fn end_figure(&mut self, end: FigureEnd) {
+        if self.open {
+            self.events.push(PathEvent::End(end));
+            self.open = false;
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If no figure has been begun.
+    pub fn add_line(&mut self, point: impl Into<Point2f>) {
+        self.push_segment(PathEvent::Line(point.into()));
+    }
+
+    pub fn add_lines(&mut self, points: &[Point2f]) {
+        for &point in points {
+            self.add_line(point);
+        }
+    }
+
+    pub fn add_bezier(&mut self, bezier: &BezierSegment) {
+        self.push_segment(PathEvent::Cubic(*bezier));
+    }
+
+    pub fn add_quadratic_bezier(&mut self, bezier: &QuadBezierSegment) {
+        self.push_segment(PathEvent::Quadratic(*bezier));
+    }
+
+    pub fn add_arc(&mut self, arc: &ArcSegment) {
+        self.push_segment(PathEvent::Arc(*arc));
+    }
+
+    /// Adds a closed, filled figure through `points`.
+    pub fn add_polygon(&mut self, points: &[Point2f]) {
+        if let Some((&first, rest)) = points.split_first() {
+            self.begin_figure(first, FigureBegin::Filled);
+            self.add_lines(rest);
+            self.end_figure(FigureEnd::Closed);
+        }
+    }
+
+    /// Appends the figures of `other`, keeping this path's fill mode. An
+    /// open figure in either path is ended as open first.
+    pub fn append(&mut self, other: &PathData) {
+        self.end_figure(FigureEnd::Open);
+        self.events.extend_from_slice(&other.events);
+        self.open = other.open;
+        self.end_figure(FigureEnd::Open);
+    }
+
+    /// The events recorded so far. A figure still being built has no `End`.
+    pub fn events(&self) -> &[PathEvent] {
+        &self.events
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The path with every segment converted to cubic beziers, one entry
+    /// per figure. Closed figures get an explicit closing segment if they
+    /// don't already end at their start.
+    pub fn to_cubics(&self) -> Vec<CubicFigure> {
+        let mut figures = Vec::new();
+        let mut current: Option<CubicFigure> = None;
+        let mut last = Point2f::ORIGIN;
+
+        for event in &self.events {
+            match *event {
+                PathEvent::Begin { start, begin } => {
+                    figures.extend(current.take());
+                    current = Some(CubicFigure {
+                        start,
+                        segments: Vec::new(),
+                        begin,
+                        end: FigureEnd::Open,
+                    });
+                    last = start;
+                }
+                PathEvent::End(end) => {
+                    if let Some(mut figure) = current.take() {
+                        figure.end = end;
+                        if end == FigureEnd::Closed && last != figure.start {
+                            figure.segments.push(line_to_cubic(last, figure.start));
+                        }
+                        figures.push(figure);
+                    }
+                }
+                segment => {
+                    let figure = match current.as_mut() {
+                        Some(figure) => figure,
+                        None => continue,
+                    };
+                    match segment {
+                        PathEvent::Line(p) => figure.segments.push(line_to_cubic(last, p)),
+                        PathEvent::Quadratic(q) => figure.segments.push(quad_to_cubic(last, &q)),
+                        PathEvent::Cubic(c) => figure.segments.push(c),
+                        PathEvent::Arc(arc) => figure.segments.extend(arc_to_beziers(last, &arc)),
+                        _ => unreachable!(),
+                    }
+                    last = figure.segments.last().map_or(last, |s| s.p3);
+                }
+            }
+        }
+        figures.extend(current);
+        figures
+    }
+
+    /// The smallest rectangle containing every figure, or `None` if there
+    /// are none.
+    pub fn bounds(&self) -> Option<Rectf> {
+        let mut bounds: Option<Rectf> = None;
+        let mut include = |p: Point2f| {
+            let r = bounds.get_or_insert(Rectf {
+                left: p.x,
+                top: p.y,
+                right: p.x,
+                bottom: p.y,
+            });
+            r.left = r.left.min(p.x);
+            r.top = r.top.min(p.y);
+            r.right = r.right.max(p.x);
+            r.bottom = r.bottom.max(p.y);
+        };
+        for figure in self.to_cubics() {
+            include(figure.start);
+            let mut last = figure.start;
+            for segment in &figure.segments {
+                include(segment.p3);
+                for t in cubic_extrema(last, segment) {
+                    include(cubic_point(last, segment, t));
+                }
+                last = segment.p3;
+            }
+        }
+        bounds
+    }
+
+    /// The path with every point passed through `transform`. Arcs become
+    /// beziers, since a transformed ellipse can't always be described by an
+    /// `ArcSegment`.
+    pub fn transformed(&self, transform: &Matrix3x2f) -> PathData {
+        let map = |p: Point2f| Point2f {
+            x: p.x * transform.a + p.y * transform.c + transform.x,
+            y: p.x * transform.b + p.y * transform.d + transform.y,
+        };
+        let mut path = PathData::new().with_fill_mode(self.fill_mode);
+        let mut last = Point2f::ORIGIN;
+        for event in &self.events {
+            match *event {
+                PathEvent::Begin { start, begin } => {
+                    path.begin_figure(map(start), begin);
+                    last = start;
+                }
+                PathEvent::Line(p) => {
+                    path.add_line(map(p));
+                    last = p;
+                }
+                PathEvent::Quadratic(q) => {
+                    path.add_quadratic_bezier(&QuadBezierSegment {
+                        p1: map(q.p1),
+                        p2: map(q.p2),
+                    });
+                    last = q.p2;
+                }
+                PathEvent::Cubic(c) => {
+                    path.add_bezier(&BezierSegment {
+                        p1: map(c.p1),
+                        p2: map(c.p2),
+                        p3: map(c.p3),
+                    });
+                    last = c.p3;
+                }
+                PathEvent::Arc(arc) => {
+                    for c in arc_to_beziers(last, &arc) {
+                        path.add_bezier(&BezierSegment {
+                            p1: map(c.p1),
+                            p2: map(c.p2),
+                            p3: map(c.p3),
+                        });
+                    }
+                    last = arc.point;
+                }
+                PathEvent::End(end) => path.end_figure(end),
+            }
+        }
+        path
+    }
+
+    fn push_segment(&mut self, event: PathEvent) {
+        assert!(self.open, "segments must be added inside a figure");
+        self.events.push(event);
+    }
+}
+
+#[cfg(windows)]
+impl PathData {
+    /// Creates a `PathGeometry` with the recorded figures.
+    pub fn build(&self, factory: &dyn IFactory) -> Result<PathGeometry, Error> {
+        self.replay(PathGeometry::create(factory)?).finish()
+    }
+
+    /// Makes the recorded calls on `builder`, including setting the fill
+    /// mode. A figure still being built is ended as open.
+    pub fn replay(&self, builder: PathBuilder) -> PathBuilder {
+        let mut builder = builder.fill_mode(self.fill_mode);
+        let mut events = self.events.iter().peekable();
+        while let Some(event) = events.next() {
+            let (start, begin) = match *event {
+                PathEvent::Begin { start, begin } => (start, begin),
+                _ => continue,
+            };
+
+            let mut figure = builder.begin_figure(start, begin);
+            let mut end = FigureEnd::Open;
+            while let Some(&&event) = events.peek() {
+                figure = match event {
+                    PathEvent::Begin { .. } => break,
+                    PathEvent::Line(p) => figure.add_line(p),
+                    PathEvent::Quadratic(q) => figure.add_quadratic_bezier(&q),
+                    PathEvent::Cubic(c) => figure.add_bezier(&c),
+                    PathEvent::Arc(a) => figure.add_arc(&a),
+                    PathEvent::End(e) => {
+                        end = e;
+                        events.next();
+                        break;
+                    }
+                };
+                events.next();
+            }
+            builder = figure.end(end);
+        }
+        builder
+    }
+}
+
+impl Default for PathData {
+    fn default() -> PathData {
+        PathData::new()
+    }
+}
+
+/// A figure made only of cubic beziers, as produced by `PathData::to_cubics`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CubicFigure {
+    pub start: Point2f,
+    pub segments: Vec<BezierSegment>,
+    pub begin: FigureBegin,
+    pub end: FigureEnd,
+}
+
+impl CubicFigure {
+    pub fn is_closed(&self) -> bool {
+        self.end == FigureEnd::Closed
+    }
+
+    /// Where each segment starts, followed by where the last one ends.
+    pub fn points(&self) -> impl Iterator<Item = Point2f> + '_ {
+        std::iter::once(self.start).chain(self.segments.iter().map(|s| s.p3))
+    }
+
+    /// Appends the figure to `path`.
+    pub fn append_to(&self, path: &mut PathData) {
+        path.begin_figure(self.start, self.begin);
+        for segment in &self.segments {
+            path.add_bezier(segment);
+        }
+        path.end_figure(self.end);
+    }
+}
+
+/// A straight line as a cubic bezier, with its control points a third of
+/// the way along from each end so it moves at an even speed.
+pub fn line_to_cubic(from: Point2f, to: Point2f) -> BezierSegment {
+    BezierSegment {
+        p1: lerp(from, to, 1.0 / 3.0),
+        p2: lerp(from, to, 2.0 / 3.0),
+        p3: to,
+    }
+}
+
+/// Raises a quadratic bezier to the cubic which traces the same curve.
+pub fn quad_to_cubic(from: Point2f, quad: &QuadBezierSegment) -> BezierSegment {
+    BezierSegment {
+        p1: lerp(from, quad.p1, 2.0 / 3.0),
+        p2: lerp(quad.p2, quad.p1, 2.0 / 3.0),
+        p3: quad.p2,
+    }
+}
+
+/// Approximates an arc starting at `from` with cubic beziers, one for every
+/// quarter turn or part of one. Radii too small to reach the end point are
+/// scaled up the way Direct2D does, and zero radii give a straight line.
+pub fn arc_to_beziers(from: Point2f, arc: &ArcSegment) -> Vec<BezierSegment> {
+    let to = arc.point;
+    if from == to {
+        return Vec::new();
+    }
+    let (mut rx, mut ry) = (arc.size.width.abs(), arc.size.height.abs());
+    if rx == 0.0 || ry == 0.0 {
+        return vec![line_to_cubic(from, to)];
+    }
+
+    // The center parameterization, as in the SVG implementation notes.
+    let (sin_phi, cos_phi) = arc.rotation_angle.to_radians().sin_cos();
+    let (hx, hy) = ((from.x - to.x) / 2.0, (from.y - to.y) / 2.0);
+    let x1 = cos_phi * hx + sin_phi * hy;
+    let y1 = -sin_phi * hx + cos_phi * hy;
+
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let clockwise = arc.sweep_direction == SweepDirection::Clockwise;
+    let large = arc.arc_size == ArcSize::Large;
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+    if large == clockwise {
+        coefficient = -coefficient;
+    }
+    let cx1 = coefficient * rx * y1 / ry;
+    let cy1 = -coefficient * ry * x1 / rx;
+    let cx = cos_phi * cx1 - sin_phi * cy1 + (from.x + to.x) / 2.0;
+    let cy = sin_phi * cx1 + cos_phi * cy1 + (from.y + to.y) / 2.0;
+
+    let start_angle = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+    let end_angle = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+    let mut sweep = end_angle - start_angle;
+    if clockwise && sweep < 0.0 {
+        sweep += 2.0 * PI;
+    } else if !clockwise && sweep > 0.0 {
+        sweep -= 2.0 * PI;
+    }
+
+    // Maps a point on the unit circle onto the ellipse.
+    let map = |ux: f32, uy: f32| Point2f {
+        x: cos_phi * rx * ux - sin_phi * ry * uy + cx,
+        y: sin_phi * rx * ux + cos_phi * ry * uy + cy,
+    };
+
+    let pieces = (sweep.abs() / (PI / 2.0) - 1e-4).ceil().max(1.0) as usize;
+    let step = sweep / pieces as f32;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+    let mut beziers = Vec::with_capacity(pieces);
+    for i in 0..pieces {
+        let (s0, c0) = (start_angle + step * i as f32).sin_cos();
+        let (s1, c1) = (start_angle + step * (i + 1) as f32).sin_cos();
+        beziers.push(BezierSegment {
+            p1: map(c0 - k * s0, s0 + k * c0),
+            p2: map(c1 + k * s1, s1 - k * c1),
+            p3: map(c1, s1),
+        });
+    }
+    // Land exactly on the requested end point.
+    if let Some(last) = beziers.last_mut() {
+        last.p3 = to;
+    }
+    beziers
+}
+
+fn cubic_point(p0: Point2f, s: &BezierSegment, t: f32) -> Point2f {
+    let u = 1.0 - t;
+    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+    Point2f {
+        x: a * p0.x + b * s.p1.x + c * s.p2.x + d * s.p3.x,
+        y: a * p0.y + b * s.p1.y + c * s.p2.y + d * s.p3.y,
+    }
+}
+
+/// The parameters strictly between 0 and 1 where the curve turns back in x
+/// or y.
+fn cubic_extrema(p0: Point2f, s: &BezierSegment) -> Vec<f32> {
+    let mut roots = Vec::new();
+    for &(v0, v1, v2, v3) in &[
+        (p0.x, s.p1.x, s.p2.x, s.p3.x),
+        (p0.y, s.p1.y, s.p2.y, s.p3.y),
+    ] {
+        // The derivative's coefficients, divided by 3.
+        let a = -v0 + 3.0 * v1 - 3.0 * v2 + v3;
+        let b = 2.0 * (v0 - 2.0 * v1 + v2);
+        let c = v1 - v0;
+        if a.abs() < 1e-6 {
+            if b.abs() > 1e-6 {
+                roots.push(-c / b);
+            }
+            continue;
+        }
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant >= 0.0 {
+            let root = discriminant.sqrt();
+            roots.push((-b + root) / (2.0 * a));
+            roots.push((-b - root) / (2.0 * a));
+        }
+    }
+    roots.retain(|&t| t > 0.0 && t < 1.0);
+    roots
+}
+
+pub(crate) fn lerp(a: Point2f, b: Point2f, t: f32) -> Point2f {
+    Point2f {
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+    }
+}
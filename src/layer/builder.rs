@@ -37,6 +37,33 @@ impl<'a, 'b> LayerBuilder<'a, 'b> {
         }
     }
 
+    /// Drawing outside `bounds`, in the current transform's coordinates, is
+    /// clipped away.
+    #[inline]
+    pub fn with_content_bounds(mut self, bounds: Rectf) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    /// Composites everything drawn in the layer at this opacity.
+    #[inline]
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    #[inline]
+    pub fn with_opacity_brush(mut self, brush: &'b dyn IBrush) -> Self {
+        self.opacity_brush = Some(brush);
+        self
+    }
+
+    #[inline]
+    pub fn with_options(mut self, options: LayerOptions) -> Self {
+        self.layer_opts = options;
+        self
+    }
+
     #[inline]
     pub fn with_mask(mut self, mask: &'b dyn IGeometry) -> Self {
         self.mask = Some(mask);
@@ -1,15 +1,32 @@
-#![cfg(windows)]
-
+pub mod animation;
 pub mod brush;
+pub mod cpu;
 pub mod descriptions;
+#[cfg(windows)]
 pub mod device;
+#[cfg(windows)]
 pub mod device_context;
 pub mod enums;
+pub mod error;
 pub mod factory;
 pub mod geometry;
+#[cfg(windows)]
 pub mod image;
+#[cfg(windows)]
 pub mod layer;
+pub mod pdf;
 pub mod properties;
+pub mod record;
 pub mod render_target;
 pub mod resource;
+pub mod scene;
+pub mod scene_file;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(windows)]
 pub mod stroke_style;
+pub mod svg;
+pub mod swap_chain;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod units;
@@ -0,0 +1,927 @@
+use crate::cpu::GradientRamp;
+use crate::enums::{
+    BitmapInterpolationMode, CapStyle, ExtendMode, FigureBegin, FigureEnd, FillMode, Gamma,
+    LineJoin,
+};
+use crate::geometry::path_data::{arc_to_beziers, quad_to_cubic};
+use crate::geometry::{PathData, PathEvent};
+use crate::pdf::document::{PdfDocument, POINTS_PER_DIP};
+use crate::record::{
+    BitmapData, BrushDesc, DrawOp, LayerDesc, Recording, Shape, StrokeStyleDesc, TextDesc,
+};
+use crate::render_target::dirty_region::transform_bounds;
+use crate::svg::export::num;
+
+use math2d::{BezierSegment, Color, Matrix3x2f, Point2f, Rectf, Sizef};
+
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Gradients which wrap or mirror are written out for at most this many
+/// periods either side of the first.
+const MAX_REPEATS: f32 = 64.0;
+
+/// Extra samples taken between gradient stops where PDF's interpolation
+/// differs from Direct2D's.
+const SUBDIVISIONS: usize = 8;
+
+/// Writes the content stream for `recording` and the resources it uses,
+/// returning the stream's object number. The resource dictionary goes in
+/// object `resources`, which has already been reserved.
+pub(crate) fn write_page(
+    document: &mut PdfDocument,
+    recording: &Recording,
+    resources: usize,
+) -> usize {
+    let size = recording.size();
+    // Drawing is in DIPs with y pointing down, where PDF starts out in
+    // points with y pointing up.
+    let base = Matrix3x2f {
+        a: POINTS_PER_DIP,
+        b: 0.0,
+        c: 0.0,
+        d: -POINTS_PER_DIP,
+        x: 0.0,
+        y: size.height * POINTS_PER_DIP,
+    };
+    let mut content = String::new();
+    write_cm(&mut content, &base);
+
+    let mut page = Page {
+        document,
+        resources_id: resources,
+        resources: Resources::default(),
+        streams: vec![Stream { content, base }],
+        pushed: Vec::new(),
+        transform: Matrix3x2f::IDENTITY,
+        size,
+    };
+    for op in recording.ops() {
+        page.op(op);
+    }
+    while !page.pushed.is_empty() {
+        page.close();
+    }
+
+    let content = page.streams.pop().unwrap().content;
+    let dict = page.resources.to_dict();
+    document.set(resources, dict.into_bytes());
+    document.add_stream("", content.as_bytes())
+}
+
+/// A content stream being written: the page's, or a layer's transparency
+/// group.
+struct Stream {
+    content: String,
+    /// From the stream's default space to the page's, which is the space
+    /// patterns are placed in.
+    base: Matrix3x2f,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Pushed {
+    Clip,
+    Layer,
+    /// A layer drawn into its own stream, composited with this opacity when
+    /// it's popped.
+    Group(f32),
+}
+
+/// Where a gradient's colors go: the shading type, its `/Coords` and
+/// `/Domain`, and the transform from its coordinates to user space.
+struct Shading {
+    kind: u8,
+    coords: String,
+    domain: (f32, f32),
+    to_user: Matrix3x2f,
+}
+
+/// How a fill or stroke is painted: the graphics state to set before the
+/// transform, and the color operators after it.
+struct Paint {
+    state: String,
+    color: String,
+}
+
+struct Page<'a> {
+    document: &'a mut PdfDocument,
+    resources_id: usize,
+    resources: Resources,
+    streams: Vec<Stream>,
+    pushed: Vec<Pushed>,
+    transform: Matrix3x2f,
+    size: Sizef,
+}
+
+impl<'a> Page<'a> {
+    fn out(&mut self) -> &mut String {
+        &mut self.streams.last_mut().unwrap().content
+    }
+
+    fn op(&mut self, op: &DrawOp) {
+        match op {
+            DrawOp::Clear(color) => {
+                let paint = self.solid(*color, 1.0, false);
+                let mut ops = format!("q\n{}{}", paint.state, paint.color);
+                write_rect(
+                    &mut ops,
+                    &Rectf {
+                        left: 0.0,
+                        top: 0.0,
+                        right: self.size.width,
+                        bottom: self.size.height,
+                    },
+                );
+                ops.push_str(" re f\nQ\n");
+                self.out().push_str(&ops);
+            }
+            DrawOp::SetTransform(transform) => self.transform = *transform,
+            DrawOp::Fill { shape, brush } => self.fill(shape, brush),
+            DrawOp::Stroke {
+                shape,
+                brush,
+                width,
+                style,
+            } => self.stroke(shape, brush, *width, style.as_ref()),
+            DrawOp::Bitmap {
+                bitmap,
+                dest,
+                opacity,
+                interpolation,
+                source,
+            } => self.bitmap(bitmap, dest, *opacity, *interpolation, source),
+            DrawOp::Text { text, brush } => self.text(text, brush),
+            DrawOp::PushAxisAlignedClip(rect) => {
+                // Direct2D clips to the bounds of the transformed rect.
+                let bounds = transform_bounds(rect, &self.transform);
+                let mut ops = String::from("q\n");
+                write_rect(&mut ops, &bounds);
+                ops.push_str(" re W n\n");
+                self.out().push_str(&ops);
+                self.pushed.push(Pushed::Clip);
+            }
+            DrawOp::PopAxisAlignedClip => self.pop(false),
+            DrawOp::PushLayer(layer) => self.push_layer(layer),
+            DrawOp::PopLayer => self.pop(true),
+        }
+    }
+
+    fn fill(&mut self, shape: &Shape, brush: &BrushDesc) {
+        if let Shape::Line(..) = shape {
+            return;
+        }
+        let path = shape.to_path();
+        let mut segments = String::new();
+        write_path(&mut segments, &path, true);
+        if segments.is_empty() {
+            return;
+        }
+        let paint = match self.paint(brush, false) {
+            Some(paint) => paint,
+            None => return,
+        };
+
+        let mut ops = format!("q\n{}", paint.state);
+        write_cm(&mut ops, &self.transform);
+        ops.push_str(&paint.color);
+        ops.push_str(&segments);
+        ops.push_str(match path.fill_mode() {
+            FillMode::Alternate => "f*\nQ\n",
+            FillMode::Winding => "f\nQ\n",
+        });
+        self.out().push_str(&ops);
+    }
+
+    fn stroke(
+        &mut self,
+        shape: &Shape,
+        brush: &BrushDesc,
+        width: f32,
+        style: Option<&StrokeStyleDesc>,
+    ) {
+        let mut segments = String::new();
+        write_path(&mut segments, &shape.to_path(), false);
+        if segments.is_empty() {
+            return;
+        }
+        let paint = match self.paint(brush, true) {
+            Some(paint) => paint,
+            None => return,
+        };
+
+        let mut ops = format!("q\n{}", paint.state);
+        write_cm(&mut ops, &self.transform);
+        ops.push_str(&paint.color);
+        write_stroke_style(&mut ops, width, style);
+        ops.push_str(&segments);
+        ops.push_str("S\nQ\n");
+        self.out().push_str(&ops);
+    }
+
+    fn bitmap(
+        &mut self,
+        bitmap: &Arc<BitmapData>,
+        dest: &Rectf,
+        opacity: f32,
+        interpolation: BitmapInterpolationMode,
+        source: &Rectf,
+    ) {
+        let (source_width, source_height) =
+            (source.right - source.left, source.bottom - source.top);
+        if source_width == 0.0
+            || source_height == 0.0
+            || bitmap.width() == 0
+            || bitmap.height() == 0
+        {
+            return;
+        }
+        let image = self
+            .document
+            .image(bitmap, interpolation == BitmapInterpolationMode::Linear);
+        let name = self.resources.x_object(image);
+
+        let mut ops = String::from("q\n");
+        if opacity < 1.0 {
+            let state = self.resources.alpha(opacity);
+            let _ = writeln!(ops, "/{} gs", state);
+        }
+        write_cm(&mut ops, &self.transform);
+        if *source != bitmap.rect() {
+            write_rect(&mut ops, dest);
+            ops.push_str(" re W n\n");
+        }
+        // Images fill the unit square with their first row at the top,
+        // which is y = 1. The whole bitmap is placed so that `source` lands
+        // on `dest`.
+        let scale_x = (dest.right - dest.left) / source_width;
+        let scale_y = (dest.bottom - dest.top) / source_height;
+        let height = bitmap.height() as f32 * scale_y;
+        let top = dest.top - source.top * scale_y;
+        write_cm(
+            &mut ops,
+            &Matrix3x2f {
+                a: bitmap.width() as f32 * scale_x,
+                b: 0.0,
+                c: 0.0,
+                d: -height,
+                x: dest.left - source.left * scale_x,
+                y: top + height,
+            },
+        );
+        let _ = write!(ops, "/{} Do\nQ\n", name);
+        self.out().push_str(&ops);
+    }
+
+    /// Text is set in whichever standard font is closest to its family, so
+    /// no fonts need embedding, and characters outside WinAnsi become `?`.
+    fn text(&mut self, text: &TextDesc, brush: &BrushDesc) {
+        if text.text.is_empty() {
+            return;
+        }
+        let paint = match self.paint(brush, false) {
+            Some(paint) => paint,
+            None => return,
+        };
+        let font = self.resources.font(standard_font(&text.font_family));
+
+        let mut ops = format!("q\n{}", paint.state);
+        write_cm(&mut ops, &self.transform);
+        ops.push_str(&paint.color);
+        // The text matrix flips y back, or the glyphs would be upside down.
+        let baseline = text.baseline_origin();
+        let _ = writeln!(
+            ops,
+            "BT\n/{} {} Tf\n1 0 0 -1 {} {} Tm\n({}) Tj\nET\nQ",
+            font,
+            num(text.font_size),
+            num(baseline.x),
+            num(baseline.y),
+            pdf_string(&text.text)
+        );
+        self.out().push_str(&ops);
+    }
+
+    fn push_layer(&mut self, layer: &LayerDesc) {
+        // Opacity applies to the layer as a whole, so it's drawn into a
+        // transparency group to be composited when it's popped.
+        let group = layer.opacity < 1.0;
+        if group {
+            self.streams.push(Stream {
+                content: String::new(),
+                base: Matrix3x2f::IDENTITY,
+            });
+        }
+
+        let mut ops = String::from("q\n");
+        if layer.content_bounds != Rectf::INFINITE {
+            let bounds = Shape::Rectangle(layer.content_bounds)
+                .to_path()
+                .transformed(&self.transform);
+            write_clip(&mut ops, &bounds);
+        }
+        if let Some(mask) = &layer.mask {
+            let transform = layer.mask_transform * self.transform;
+            write_clip(&mut ops, &mask.transformed(&transform));
+        }
+        self.out().push_str(&ops);
+        self.pushed.push(if group {
+            Pushed::Group(layer.opacity.max(0.0))
+        } else {
+            Pushed::Layer
+        });
+    }
+
+    /// Pops the innermost clip or layer if it's the kind asked for; a
+    /// mismatched pop would be a drawing error in Direct2D.
+    fn pop(&mut self, layer: bool) {
+        let matches = match self.pushed.last() {
+            Some(Pushed::Clip) => !layer,
+            Some(_) => layer,
+            None => false,
+        };
+        if matches {
+            self.close();
+        }
+    }
+
+    fn close(&mut self) {
+        match self.pushed.pop() {
+            Some(Pushed::Clip) | Some(Pushed::Layer) => self.out().push_str("Q\n"),
+            Some(Pushed::Group(opacity)) => {
+                let mut content = self.streams.pop().unwrap().content;
+                content.push_str("Q\n");
+                let dict = format!(
+                    "/Type /XObject /Subtype /Form /BBox [0 0 {} {}] \
+                     /Group << /S /Transparency /I true >> /Resources {} 0 R",
+                    num(self.size.width),
+                    num(self.size.height),
+                    self.resources_id
+                );
+                let form = self.document.add_stream(&dict, content.as_bytes());
+                let name = self.resources.x_object(form);
+                let state = self.resources.alpha(opacity);
+                let ops = format!("q\n/{} gs\n/{} Do\nQ\n", state, name);
+                self.out().push_str(&ops);
+            }
+            None => {}
+        }
+    }
+
+    fn paint(&mut self, brush: &BrushDesc, stroke: bool) -> Option<Paint> {
+        let opacity = brush.brush_properties().opacity.clamp(0.0, 1.0);
+        match brush {
+            BrushDesc::Solid { color, .. } => Some(self.solid(*color, opacity, stroke)),
+            BrushDesc::LinearGradient(gradient) => {
+                if gradient.ramp.stops().is_empty() {
+                    return None;
+                }
+                let (start, end) = (gradient.properties.start, gradient.properties.end);
+                if start == end {
+                    return Some(self.solid(gradient.ramp.color_at(0.0), opacity, stroke));
+                }
+                let (t0, t1) = self.positions(|p| gradient.position_at(p));
+                let (d0, d1) = domain(gradient.ramp.extend_mode(), t0, t1);
+                let at = |t: f32| {
+                    format!(
+                        "{} {}",
+                        num(start.x + (end.x - start.x) * t),
+                        num(start.y + (end.y - start.y) * t)
+                    )
+                };
+                let shading = Shading {
+                    kind: 2,
+                    coords: format!("{} {}", at(d0), at(d1)),
+                    domain: (d0, d1),
+                    to_user: gradient.brush.transform * self.transform,
+                };
+                Some(self.gradient(&shading, &gradient.ramp, opacity, stroke))
+            }
+            BrushDesc::RadialGradient(gradient) => {
+                if gradient.ramp.stops().is_empty() {
+                    return None;
+                }
+                let props = &gradient.properties;
+                if props.radius_x == 0.0 || props.radius_y == 0.0 {
+                    return Some(self.solid(gradient.ramp.color_at(0.0), opacity, stroke));
+                }
+                let (_, t1) = self.positions(|p| gradient.position_at(p));
+                let (d0, d1) = domain(gradient.ramp.extend_mode(), 0.0, t1);
+                // In the space where the ellipse is the unit circle, the
+                // gradient runs from a point at the origin to the circle,
+                // and then on to circles the same way.
+                let (fx, fy) = (
+                    props.origin_offset.x / props.radius_x,
+                    props.origin_offset.y / props.radius_y,
+                );
+                let unit = Matrix3x2f {
+                    a: props.radius_x,
+                    b: 0.0,
+                    c: 0.0,
+                    d: props.radius_y,
+                    x: props.center.x,
+                    y: props.center.y,
+                };
+                let shading = Shading {
+                    kind: 3,
+                    coords: format!(
+                        "{} {} 0 {} {} {}",
+                        num(fx),
+                        num(fy),
+                        num(fx * (1.0 - d1)),
+                        num(fy * (1.0 - d1)),
+                        num(d1)
+                    ),
+                    domain: (d0, d1),
+                    to_user: unit * gradient.brush.transform * self.transform,
+                };
+                Some(self.gradient(&shading, &gradient.ramp, opacity, stroke))
+            }
+        }
+    }
+
+    fn solid(&mut self, color: Color, opacity: f32, stroke: bool) -> Paint {
+        let alpha = (color.a * opacity).max(0.0);
+        let state = if alpha < 1.0 {
+            format!("/{} gs\n", self.resources.alpha(alpha))
+        } else {
+            String::new()
+        };
+        let channel = |c: f32| num(c.clamp(0.0, 1.0));
+        let color = format!(
+            "{} {} {} {}\n",
+            channel(color.r),
+            channel(color.g),
+            channel(color.b),
+            if stroke { "RG" } else { "rg" }
+        );
+        Paint { state, color }
+    }
+
+    /// Paints with a pattern of `ramp` laid out by `shading`. Alpha which
+    /// varies along the gradient becomes a soft mask of the same shading in
+    /// gray.
+    fn gradient(
+        &mut self,
+        shading: &Shading,
+        ramp: &GradientRamp,
+        opacity: f32,
+        stroke: bool,
+    ) -> Paint {
+        let Shading {
+            kind,
+            ref coords,
+            domain,
+            ref to_user,
+        } = *shading;
+        let samples = repeat(ramp, domain);
+        let shading = |space: &str, function: &str| {
+            format!(
+                "<< /ShadingType {} /ColorSpace /{} /Coords [{}] /Domain [{} {}] \
+                 /Function {} /Extend [true true] >>",
+                kind,
+                space,
+                coords,
+                num(domain.0),
+                num(domain.1),
+                function
+            )
+        };
+
+        let colors = function(&samples, domain, |c| {
+            let channel = |c: f32| num(c.clamp(0.0, 1.0));
+            format!("{} {} {}", channel(c.r), channel(c.g), channel(c.b))
+        });
+        let mut matrix = String::new();
+        write_matrix(&mut matrix, &(*to_user * self.streams.last().unwrap().base));
+        let pattern = format!(
+            "<< /Type /Pattern /PatternType 2 /Shading {} /Matrix [{}] >>",
+            shading("DeviceRGB", &colors),
+            matrix
+        );
+        let pattern = self.document.add(pattern.into_bytes());
+        let name = self.resources.pattern(pattern);
+
+        let mut state = String::new();
+        if samples.iter().any(|(_, c)| c.a < 1.0) {
+            let alphas = function(&samples, domain, |c| num(c.a.clamp(0.0, 1.0)));
+            // The mask is in the space current when it's set, which is the
+            // stream's DIPs.
+            let mut content = String::from("q\n");
+            write_cm(&mut content, to_user);
+            content.push_str("/Sh0 sh\nQ\n");
+            let dict = format!(
+                "/Type /XObject /Subtype /Form /BBox [0 0 {} {}] \
+                 /Group << /S /Transparency /CS /DeviceGray >> \
+                 /Resources << /Shading << /Sh0 {} >> >>",
+                num(self.size.width),
+                num(self.size.height),
+                shading("DeviceGray", &alphas)
+            );
+            let form = self.document.add_stream(&dict, content.as_bytes());
+            let mask = format!(
+                "/SMask << /Type /Mask /S /Luminosity /G {} 0 R >> /ca {o} /CA {o}",
+                form,
+                o = num(opacity)
+            );
+            state = format!("/{} gs\n", self.resources.state(mask));
+        } else if opacity < 1.0 {
+            state = format!("/{} gs\n", self.resources.alpha(opacity));
+        }
+
+        let color = if stroke {
+            format!("/Pattern CS /{} SCN\n", name)
+        } else {
+            format!("/Pattern cs /{} scn\n", name)
+        };
+        Paint { state, color }
+    }
+
+    /// The range of gradient positions across the visible page, given the
+    /// position of a point in user space.
+    fn positions(&self, position: impl Fn(Point2f) -> f32) -> (f32, f32) {
+        let inverse = match self.transform.inverse() {
+            Some(inverse) => inverse,
+            None => return (0.0, 1.0),
+        };
+        let (w, h) = (self.size.width, self.size.height);
+        [(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)]
+            .iter()
+            .map(|&(x, y)| position(inverse.transform_point((x, y))))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), t| {
+                (lo.min(t), hi.max(t))
+            })
+    }
+}
+
+/// Names for the resources a page uses, shared by its content stream and
+/// the streams of its layers.
+#[derive(Default)]
+struct Resources {
+    /// Graphics state dictionaries, written inline.
+    states: Vec<String>,
+    patterns: Vec<usize>,
+    x_objects: Vec<usize>,
+    /// Base names of standard fonts.
+    fonts: Vec<&'static str>,
+}
+
+impl Resources {
+    fn state(&mut self, dict: String) -> String {
+        let i = match self.states.iter().position(|s| *s == dict) {
+            Some(i) => i,
+            None => {
+                self.states.push(dict);
+                self.states.len() - 1
+            }
+        };
+        format!("GS{}", i)
+    }
+
+    /// A graphics state which sets the fill and stroke alpha.
+    fn alpha(&mut self, alpha: f32) -> String {
+        self.state(format!("/ca {a} /CA {a}", a = num(alpha)))
+    }
+
+    fn pattern(&mut self, id: usize) -> String {
+        self.patterns.push(id);
+        format!("P{}", self.patterns.len() - 1)
+    }
+
+    fn x_object(&mut self, id: usize) -> String {
+        let i = match self.x_objects.iter().position(|&x| x == id) {
+            Some(i) => i,
+            None => {
+                self.x_objects.push(id);
+                self.x_objects.len() - 1
+            }
+        };
+        format!("X{}", i)
+    }
+
+    fn font(&mut self, base_font: &'static str) -> String {
+        let i = match self.fonts.iter().position(|&f| f == base_font) {
+            Some(i) => i,
+            None => {
+                self.fonts.push(base_font);
+                self.fonts.len() - 1
+            }
+        };
+        format!("F{}", i)
+    }
+
+    fn to_dict(&self) -> String {
+        let mut dict = String::from("<<");
+        if !self.states.is_empty() {
+            dict.push_str(" /ExtGState <<");
+            for (i, state) in self.states.iter().enumerate() {
+                let _ = write!(dict, " /GS{} << /Type /ExtGState {} >>", i, state);
+            }
+            dict.push_str(" >>");
+        }
+        let mut references = |kind: &str, prefix: &str, ids: &[usize]| {
+            if !ids.is_empty() {
+                let _ = write!(dict, " /{} <<", kind);
+                for (i, id) in ids.iter().enumerate() {
+                    let _ = write!(dict, " /{}{} {} 0 R", prefix, i, id);
+                }
+                dict.push_str(" >>");
+            }
+        };
+        references("Pattern", "P", &self.patterns);
+        references("XObject", "X", &self.x_objects);
+        if !self.fonts.is_empty() {
+            dict.push_str(" /Font <<");
+            for (i, font) in self.fonts.iter().enumerate() {
+                let _ = write!(
+                    dict,
+                    " /F{} << /Type /Font /Subtype /Type1 /BaseFont /{} \
+                     /Encoding /WinAnsiEncoding >>",
+                    i, font
+                );
+            }
+            dict.push_str(" >>");
+        }
+        dict.push_str(" >>");
+        dict
+    }
+}
+
+/// The standard font standing in for `family`.
+fn standard_font(family: &str) -> &'static str {
+    let family = family.to_ascii_lowercase();
+    if family.contains("mono") || family.contains("courier") || family.contains("consol") {
+        "Courier"
+    } else if family.contains("times")
+        || family.contains("georgia")
+        || (family.contains("serif") && !family.contains("sans"))
+    {
+        "Times-Roman"
+    } else {
+        "Helvetica"
+    }
+}
+
+/// `text` as the contents of a literal string in WinAnsi encoding.
+fn pdf_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            // WinAnsi matches Latin-1 from here, but the stream is written
+            // as text, so these go in as octal escapes.
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(out, "\\{:03o}", c as u32);
+            }
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+/// The span of gradient positions to write out: the first period for a
+/// clamped gradient, which PDF extends by itself, and otherwise every
+/// period from `t0` to `t1`.
+fn domain(mode: ExtendMode, t0: f32, t1: f32) -> (f32, f32) {
+    if mode == ExtendMode::Clamp {
+        return (0.0, 1.0);
+    }
+    let lo = t0.min(0.0).floor().max(-MAX_REPEATS);
+    let hi = t1.max(1.0).ceil().min(MAX_REPEATS + 1.0);
+    (lo, hi)
+}
+
+/// Colors at increasing positions across `domain`, repeating or mirroring
+/// one period of the ramp.
+fn repeat(ramp: &GradientRamp, domain: (f32, f32)) -> Vec<(f32, Color)> {
+    let period = period(ramp);
+    if ramp.extend_mode() == ExtendMode::Clamp {
+        return period;
+    }
+    let mut samples = Vec::new();
+    for k in domain.0 as i32..domain.1 as i32 {
+        let offset = k as f32;
+        if ramp.extend_mode() == ExtendMode::Mirror && k.rem_euclid(2) == 1 {
+            samples.extend(period.iter().rev().map(|&(p, c)| (offset + 1.0 - p, c)));
+        } else {
+            samples.extend(period.iter().map(|&(p, c)| (offset + p, c)));
+        }
+    }
+    samples
+}
+
+/// Colors at increasing positions from 0 to 1. Coincident stops give two
+/// samples at the same position, for a hard edge.
+fn period(ramp: &GradientRamp) -> Vec<(f32, Color)> {
+    let clamped = GradientRamp::new(ramp.stops(), ramp.gamma(), ExtendMode::Clamp);
+    let mut stops: Vec<(f32, Color)> = ramp
+        .stops()
+        .iter()
+        .filter(|s| s.position >= 0.0 && s.position <= 1.0)
+        .map(|s| (s.position, s.color))
+        .collect();
+    if !matches!(stops.first(), Some(s) if s.0 <= 0.0) {
+        stops.insert(0, (0.0, clamped.color_at(0.0)));
+    }
+    if !matches!(stops.last(), Some(s) if s.0 >= 1.0) {
+        stops.push((1.0, clamped.color_at(1.0)));
+    }
+
+    let mut samples = vec![stops[0]];
+    for pair in stops.windows(2) {
+        let ((p0, c0), (p1, c1)) = (pair[0], pair[1]);
+        // PDF interpolates the components as they are, where Direct2D
+        // interpolates premultiplied colors, in linear light for a gamma of
+        // 1.0. Extra samples keep the difference small.
+        if p1 > p0 && (ramp.gamma() == Gamma::_1_0 || c0.a != c1.a) {
+            for i in 1..SUBDIVISIONS {
+                let p = p0 + (p1 - p0) * i as f32 / SUBDIVISIONS as f32;
+                samples.push((p, clamped.color_at(p)));
+            }
+        }
+        samples.push(pair[1]);
+    }
+    samples
+}
+
+/// A stitching function through `samples`, with each color written by
+/// `component`.
+fn function(
+    samples: &[(f32, Color)],
+    domain: (f32, f32),
+    component: impl Fn(&Color) -> String,
+) -> String {
+    let piece = |c0: &Color, c1: &Color| {
+        format!(
+            "<< /FunctionType 2 /Domain [0 1] /C0 [{}] /C1 [{}] /N 1 >>",
+            component(c0),
+            component(c1)
+        )
+    };
+    let mut pieces = Vec::new();
+    let mut bounds = Vec::new();
+    for pair in samples.windows(2) {
+        let ((p0, c0), (p1, c1)) = (pair[0], pair[1]);
+        // Narrower pieces than the written precision are hard edges.
+        if p1 - p0 < 0.001 {
+            continue;
+        }
+        if !pieces.is_empty() {
+            bounds.push(num(p0));
+        }
+        pieces.push(piece(&c0, &c1));
+    }
+    if pieces.is_empty() {
+        let (first, last) = (&samples[0].1, &samples[samples.len() - 1].1);
+        pieces.push(piece(first, last));
+    }
+    let encode = vec!["0 1"; pieces.len()];
+    format!(
+        "<< /FunctionType 3 /Domain [{} {}] /Functions [{}] /Bounds [{}] /Encode [{}] >>",
+        num(domain.0),
+        num(domain.1),
+        pieces.join(" "),
+        bounds.join(" "),
+        encode.join(" ")
+    )
+}
+
+fn write_stroke_style(ops: &mut String, width: f32, style: Option<&StrokeStyleDesc>) {
+    let default = StrokeStyleDesc::new();
+    let style = style.unwrap_or(&default);
+    let dashes: Vec<f32> = style.dash_pattern().iter().map(|d| d * width).collect();
+    let dashed = dashes.iter().any(|&d| d > 0.0);
+    // PDF has one cap for everything: the dash cap for dashed lines, and
+    // otherwise the start cap. It has no triangle caps; round is the closest.
+    let cap = match if dashed {
+        style.dash_cap
+    } else {
+        style.start_cap
+    } {
+        CapStyle::Flat => 0,
+        CapStyle::Round | CapStyle::Triangle => 1,
+        CapStyle::Square => 2,
+    };
+    let join = match style.line_join {
+        LineJoin::Miter | LineJoin::MiterOrBevel => 0,
+        LineJoin::Round => 1,
+        LineJoin::Bevel => 2,
+    };
+    let _ = writeln!(
+        ops,
+        "{} w {} J {} j {} M",
+        num(width),
+        cap,
+        join,
+        num(style.miter_limit.max(1.0))
+    );
+    if dashed {
+        let dashes: Vec<String> = dashes.iter().map(|&d| num(d)).collect();
+        let _ = writeln!(
+            ops,
+            "[{}] {} d",
+            dashes.join(" "),
+            num(style.dash_offset * width)
+        );
+    }
+}
+
+/// Sets `path` as the clip, without painting it.
+fn write_clip(ops: &mut String, path: &PathData) {
+    let start = ops.len();
+    write_path(ops, path, true);
+    if ops.len() == start {
+        // Nothing is inside an empty path.
+        ops.push_str("0 0 0 0 re\n");
+    }
+    ops.push_str(match path.fill_mode() {
+        FillMode::Alternate => "W* n\n",
+        FillMode::Winding => "W n\n",
+    });
+}
+
+/// Writes the path construction operators for `path`. With `filled_only`,
+/// hollow figures are skipped.
+fn write_path(ops: &mut String, path: &PathData, filled_only: bool) {
+    let mut last = Point2f::ORIGIN;
+    let mut skipping = false;
+    let curve = |ops: &mut String, c: &BezierSegment| {
+        let _ = writeln!(
+            ops,
+            "{} {} {} {} {} {} c",
+            num(c.p1.x),
+            num(c.p1.y),
+            num(c.p2.x),
+            num(c.p2.y),
+            num(c.p3.x),
+            num(c.p3.y)
+        );
+    };
+    for event in path.events() {
+        match *event {
+            PathEvent::Begin { start, begin } => {
+                skipping = filled_only && begin == FigureBegin::Hollow;
+                if !skipping {
+                    let _ = writeln!(ops, "{} {} m", num(start.x), num(start.y));
+                }
+                last = start;
+            }
+            _ if skipping => {}
+            PathEvent::Line(p) => {
+                let _ = writeln!(ops, "{} {} l", num(p.x), num(p.y));
+                last = p;
+            }
+            PathEvent::Quadratic(q) => {
+                curve(ops, &quad_to_cubic(last, &q));
+                last = q.p2;
+            }
+            PathEvent::Cubic(c) => {
+                curve(ops, &c);
+                last = c.p3;
+            }
+            PathEvent::Arc(arc) => {
+                for c in arc_to_beziers(last, &arc) {
+                    curve(ops, &c);
+                }
+                last = arc.point;
+            }
+            PathEvent::End(FigureEnd::Closed) => ops.push_str("h\n"),
+            PathEvent::End(FigureEnd::Open) => {}
+        }
+    }
+}
+
+fn write_rect(ops: &mut String, rect: &Rectf) {
+    let _ = write!(
+        ops,
+        "{} {} {} {}",
+        num(rect.left.min(rect.right)),
+        num(rect.top.min(rect.bottom)),
+        num((rect.right - rect.left).abs()),
+        num((rect.bottom - rect.top).abs())
+    );
+}
+
+/// Concatenates `m` with the current transform, unless it's the identity.
+fn write_cm(ops: &mut String, m: &Matrix3x2f) {
+    if *m != Matrix3x2f::IDENTITY {
+        write_matrix(ops, m);
+        ops.push_str(" cm\n");
+    }
+}
+
+fn write_matrix(ops: &mut String, m: &Matrix3x2f) {
+    let _ = write!(
+        ops,
+        "{} {} {} {} {} {}",
+        num(m.a),
+        num(m.b),
+        num(m.c),
+        num(m.d),
+        num(m.x),
+        num(m.y)
+    );
+}
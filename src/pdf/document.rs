@@ -0,0 +1,206 @@
+use crate::pdf::content;
+use crate::record::{BitmapData, Recording};
+use crate::svg::export::num;
+
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Object number of the catalog, which is reserved up front.
+const CATALOG: usize = 1;
+/// Object number of the page tree.
+const PAGES: usize = 2;
+
+/// A PDF document being assembled one page at a time.
+///
+/// Each page is drawn from a `Recording` and is the recording's size: a page
+/// recorded at 816 by 1056 DIPs comes out as US Letter. Bitmaps drawn more
+/// than once from the same `Arc` are only stored once.
+///
+/// ```
+/// # use direct2d::pdf::PdfDocument;
+/// # use direct2d::record::Recording;
+/// let mut cover = Recording::new((816.0, 1056.0));
+/// cover.clear(math2d::Color::new(1.0, 1.0, 1.0, 1.0));
+///
+/// let mut document = PdfDocument::new();
+/// document.add_page(&cover);
+/// let bytes = document.finish();
+/// assert!(bytes.starts_with(b"%PDF-1.4"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct PdfDocument {
+    /// Object bodies, without the `obj` and `endobj` around them. Object `n`
+    /// is at index `n - 1`.
+    objects: Vec<Vec<u8>>,
+    pages: Vec<usize>,
+    images: Vec<(Arc<BitmapData>, bool, usize)>,
+}
+
+impl PdfDocument {
+    pub fn new() -> PdfDocument {
+        let mut document = PdfDocument {
+            objects: Vec::new(),
+            pages: Vec::new(),
+            images: Vec::new(),
+        };
+        let catalog = document.reserve();
+        let pages = document.reserve();
+        debug_assert_eq!((catalog, pages), (CATALOG, PAGES));
+        document
+    }
+
+    /// Adds a page the size of `recording` showing what it draws.
+    pub fn add_page(&mut self, recording: &Recording) {
+        let page = self.reserve();
+        let resources = self.reserve();
+        let contents = content::write_page(self, recording, resources);
+
+        let size = recording.size();
+        let mut dict = String::new();
+        let _ = write!(
+            dict,
+            "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources {} 0 R /Contents {} 0 R >>",
+            PAGES,
+            num(size.width * POINTS_PER_DIP),
+            num(size.height * POINTS_PER_DIP),
+            resources,
+            contents,
+        );
+        self.set(page, dict.into_bytes());
+        self.pages.push(page);
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Writes out the document.
+    pub fn finish(mut self) -> Vec<u8> {
+        let catalog = format!("<< /Type /Catalog /Pages {} 0 R >>", PAGES);
+        self.set(CATALOG, catalog.into_bytes());
+        let kids: Vec<String> = self.pages.iter().map(|p| format!("{} 0 R", p)).collect();
+        let pages = format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            self.pages.len()
+        );
+        self.set(PAGES, pages.into_bytes());
+
+        // The binary comment marks the file as binary for transfer tools.
+        let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(self.objects.len());
+        for (i, body) in self.objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            pdf.extend_from_slice(body);
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref = pdf.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(table, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.objects.len() + 1,
+            CATALOG,
+            xref
+        );
+        pdf.extend_from_slice(table.as_bytes());
+        pdf
+    }
+
+    /// Allocates an object number, to be filled in by `set`.
+    pub(crate) fn reserve(&mut self) -> usize {
+        self.objects.push(Vec::new());
+        self.objects.len()
+    }
+
+    pub(crate) fn set(&mut self, id: usize, body: Vec<u8>) {
+        self.objects[id - 1] = body;
+    }
+
+    pub(crate) fn add(&mut self, body: Vec<u8>) -> usize {
+        self.objects.push(body);
+        self.objects.len()
+    }
+
+    /// Adds a stream object. `dict` is the contents of its dictionary
+    /// without the length.
+    pub(crate) fn add_stream(&mut self, dict: &str, data: &[u8]) -> usize {
+        let body = stream(dict, data);
+        self.add(body)
+    }
+
+    /// The image object for `bitmap`, adding it the first time it's seen.
+    pub(crate) fn image(&mut self, bitmap: &Arc<BitmapData>, interpolate: bool) -> usize {
+        let existing = self
+            .images
+            .iter()
+            .find(|(b, i, _)| Arc::ptr_eq(b, bitmap) && *i == interpolate);
+        if let Some(&(_, _, id)) = existing {
+            return id;
+        }
+
+        let rgba = bitmap.to_rgba();
+        let mut dict = format!(
+            "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+             /BitsPerComponent 8",
+            bitmap.width(),
+            bitmap.height()
+        );
+        if interpolate {
+            dict.push_str(" /Interpolate true");
+        }
+        if rgba.chunks_exact(4).any(|px| px[3] < 255) {
+            let alpha: Vec<u8> = rgba.chunks_exact(4).map(|px| px[3]).collect();
+            let mask = self.add_stream(
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} \
+                     /ColorSpace /DeviceGray /BitsPerComponent 8",
+                    bitmap.width(),
+                    bitmap.height()
+                ),
+                &alpha,
+            );
+            let _ = write!(dict, " /SMask {} 0 R", mask);
+        }
+        let rgb: Vec<u8> = rgba
+            .chunks_exact(4)
+            .flat_map(|px| px[..3].iter().cloned())
+            .collect();
+        let id = self.add_stream(&dict, &rgb);
+        self.images.push((bitmap.clone(), interpolate, id));
+        id
+    }
+}
+
+impl Default for PdfDocument {
+    fn default() -> PdfDocument {
+        PdfDocument::new()
+    }
+}
+
+/// Writes `recording` as a one-page PDF document.
+pub fn to_pdf(recording: &Recording) -> Vec<u8> {
+    let mut document = PdfDocument::new();
+    document.add_page(recording);
+    document.finish()
+}
+
+/// PDF's default unit is the point, 1/72 of an inch, where a DIP is 1/96.
+pub(crate) const POINTS_PER_DIP: f32 = 72.0 / 96.0;
+
+pub(crate) fn stream(dict: &str, data: &[u8]) -> Vec<u8> {
+    let mut body = if dict.is_empty() {
+        format!("<< /Length {} >>\nstream\n", data.len())
+    } else {
+        format!("<< {} /Length {} >>\nstream\n", dict, data.len())
+    }
+    .into_bytes();
+    body.extend_from_slice(data);
+    body.extend_from_slice(b"\nendstream");
+    body
+}
@@ -0,0 +1,16 @@
+//! Writing recordings as PDF documents, in pure Rust.
+//!
+//! Pages are drawn from `Recording`s at their size in DIPs, so what comes
+//! out matches what the recording draws on screen at 96 DPI. Paths, solid
+//! and gradient brushes, stroke styles, transforms, clips, layers and
+//! bitmaps are all supported.
+//!
+//! Some things are approximated: a stroke uses one cap for both ends and
+//! its dashes, triangle caps are drawn round, gradients which wrap or
+//! mirror are written out for a limited number of periods, and text is set
+//! in the closest of the standard Helvetica, Times and Courier fonts.
+
+pub use self::document::{to_pdf, PdfDocument};
+
+mod content;
+pub mod document;
@@ -1,22 +1,29 @@
+#[cfg(windows)]
 use crate::descriptions::PixelFormat;
+#[cfg(windows)]
 use crate::enums::BitmapOptions;
 
 use math2d::Matrix3x2f;
 use math2d::Point2f;
+#[cfg(windows)]
 use winapi::um::d2d1::{
     D2D1_BITMAP_PROPERTIES, D2D1_BRUSH_PROPERTIES, D2D1_LINEAR_GRADIENT_BRUSH_PROPERTIES,
     D2D1_RADIAL_GRADIENT_BRUSH_PROPERTIES,
 };
+#[cfg(windows)]
 use winapi::um::d2d1_1::D2D1_BITMAP_PROPERTIES1;
 
+#[cfg(windows)]
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BitmapProperties {
     pub pixel_format: PixelFormat,
     pub dpi_x: f32,
     pub dpi_y: f32,
 }
 
+#[cfg(windows)]
 impl From<BitmapProperties> for D2D1_BITMAP_PROPERTIES {
     #[inline]
     fn from(bp: BitmapProperties) -> Self {
@@ -28,8 +35,10 @@ impl From<BitmapProperties> for D2D1_BITMAP_PROPERTIES {
     }
 }
 
+#[cfg(windows)]
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BitmapProperties1 {
     pub pixel_format: PixelFormat,
     pub dpi_x: f32,
@@ -37,6 +46,7 @@ pub struct BitmapProperties1 {
     pub options: BitmapOptions,
 }
 
+#[cfg(windows)]
 impl From<BitmapProperties1> for D2D1_BITMAP_PROPERTIES1 {
     #[inline]
     fn from(bp: BitmapProperties1) -> Self {
@@ -52,8 +62,11 @@ impl From<BitmapProperties1> for D2D1_BITMAP_PROPERTIES1 {
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct BrushProperties {
     pub opacity: f32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::Matrix3x2fDef"))]
     pub transform: Matrix3x2f,
 }
 
@@ -67,6 +80,7 @@ impl BrushProperties {
     }
 }
 
+#[cfg(windows)]
 impl From<BrushProperties> for D2D1_BRUSH_PROPERTIES {
     #[inline]
     fn from(bp: BrushProperties) -> D2D1_BRUSH_PROPERTIES {
@@ -89,11 +103,15 @@ impl Default for BrushProperties {
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LinearGradientBrushProperties {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::Point2fDef"))]
     pub start: Point2f,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::Point2fDef"))]
     pub end: Point2f,
 }
 
+#[cfg(windows)]
 impl From<LinearGradientBrushProperties> for D2D1_LINEAR_GRADIENT_BRUSH_PROPERTIES {
     #[inline]
     fn from(bp: LinearGradientBrushProperties) -> D2D1_LINEAR_GRADIENT_BRUSH_PROPERTIES {
@@ -106,13 +124,20 @@ impl From<LinearGradientBrushProperties> for D2D1_LINEAR_GRADIENT_BRUSH_PROPERTI
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RadialGradientBrushProperties {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::Point2fDef"))]
     pub center: Point2f,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_impls::Point2fDef", default)
+    )]
     pub origin_offset: Point2f,
     pub radius_x: f32,
     pub radius_y: f32,
 }
 
+#[cfg(windows)]
 impl From<RadialGradientBrushProperties> for D2D1_RADIAL_GRADIENT_BRUSH_PROPERTIES {
     #[inline]
     fn from(bp: RadialGradientBrushProperties) -> D2D1_RADIAL_GRADIENT_BRUSH_PROPERTIES {
@@ -0,0 +1,371 @@
+use crate::record::inflate::zlib_decompress;
+
+use math2d::{Rectf, Sizeu};
+use std::fmt;
+
+/// The pixels of a recorded bitmap: premultiplied BGRA rows with no
+/// padding, the layout of a `B8G8R8A8Unorm` bitmap with
+/// `AlphaMode::Premultiplied`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitmapData {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl BitmapData {
+    /// Panics if `pixels` isn't exactly `width * height * 4` bytes.
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> BitmapData {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize * 4,
+            "bitmap data must be width * height * 4 bytes"
+        );
+        BitmapData {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn size(&self) -> Sizeu {
+        Sizeu::new(self.width, self.height)
+    }
+
+    /// The whole bitmap, as a source rectangle for `draw_bitmap`.
+    pub fn rect(&self) -> Rectf {
+        Rectf {
+            left: 0.0,
+            top: 0.0,
+            right: self.width as f32,
+            bottom: self.height as f32,
+        }
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The straight (non-premultiplied) RGBA value of every pixel, in the
+    /// same order.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(self.pixels.len());
+        for px in self.pixels.chunks_exact(4) {
+            let (b, g, r, a) = (px[0], px[1], px[2], px[3]);
+            let straight = |c: u8| match a {
+                0 => 0,
+                255 => c,
+                _ => ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8,
+            };
+            rgba.extend_from_slice(&[straight(r), straight(g), straight(b), a]);
+        }
+        rgba
+    }
+
+    /// Encodes the bitmap as an 8-bit RGBA PNG file.
+    ///
+    /// The image data is stored rather than compressed, which keeps the
+    /// encoder small at the cost of larger files.
+    pub fn to_png(&self) -> Vec<u8> {
+        let rgba = self.to_rgba();
+        let stride = self.width as usize * 4;
+        let mut raw = Vec::with_capacity((stride + 1) * self.height as usize);
+        for row in rgba.chunks_exact(stride.max(1)).take(self.height as usize) {
+            // Filter type 0: the row as it is.
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+        if stride == 0 {
+            raw.resize(self.height as usize, 0);
+        }
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        // 8 bits per channel, RGBA, deflate, adaptive filtering, no interlace.
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+        write_chunk(&mut png, b"IHDR", &header);
+        write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    /// Decodes an 8-bit, non-interlaced PNG file of any color type, such as
+    /// one written by `to_png`.
+    pub fn from_png(png: &[u8]) -> Result<BitmapData, PngError> {
+        if !png.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return Err(PngError::NotPng);
+        }
+
+        let mut header = None;
+        let mut palette: &[u8] = &[];
+        let mut transparency: &[u8] = &[];
+        let mut compressed = Vec::new();
+        let mut rest = &png[8..];
+        loop {
+            if rest.len() < 12 {
+                return Err(PngError::Malformed("truncated chunk"));
+            }
+            let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+            if rest.len() - 12 < len {
+                return Err(PngError::Malformed("truncated chunk"));
+            }
+            let kind = &rest[4..8];
+            let data = &rest[8..8 + len];
+            let crc = &rest[8 + len..12 + len];
+            if crc32(&rest[4..8 + len]).to_be_bytes() != crc {
+                return Err(PngError::Malformed("chunk checksum mismatch"));
+            }
+            rest = &rest[12 + len..];
+
+            match kind {
+                b"IHDR" => header = Some(Header::read(data)?),
+                b"PLTE" => palette = data,
+                b"tRNS" => transparency = data,
+                b"IDAT" => compressed.extend_from_slice(data),
+                b"IEND" => break,
+                _ => {}
+            }
+        }
+
+        let header = header.ok_or(PngError::Malformed("missing IHDR chunk"))?;
+        let raw = zlib_decompress(&compressed, header.raw_len()?).map_err(PngError::Malformed)?;
+        let rows = unfilter(&raw, &header)?;
+
+        let (width, height) = (header.width, header.height);
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for px in rows.chunks_exact(header.channels()) {
+            let [r, g, b, a] = match (header.color_type, px) {
+                (0, &[v]) => [v, v, v, key_alpha(transparency, &[v])],
+                (2, &[r, g, b]) => [r, g, b, key_alpha(transparency, &[r, g, b])],
+                (3, &[i]) => {
+                    let entry = palette
+                        .get(i as usize * 3..i as usize * 3 + 3)
+                        .ok_or(PngError::Malformed("palette index out of range"))?;
+                    let a = transparency.get(i as usize).cloned().unwrap_or(255);
+                    [entry[0], entry[1], entry[2], a]
+                }
+                (4, &[v, a]) => [v, v, v, a],
+                (_, &[r, g, b, a]) => [r, g, b, a],
+                _ => unreachable!("rows are split by channel count"),
+            };
+            let premultiplied = |c: u8| ((c as u32 * a as u32 + 127) / 255) as u8;
+            pixels.extend_from_slice(&[premultiplied(b), premultiplied(g), premultiplied(r), a]);
+        }
+        Ok(BitmapData::new(width, height, pixels))
+    }
+}
+
+/// Why a PNG file couldn't be decoded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PngError {
+    /// The data doesn't start with the PNG signature.
+    NotPng,
+    /// The file is a valid PNG, but uses something the decoder doesn't
+    /// handle, such as interlacing or 16-bit channels.
+    Unsupported(&'static str),
+    /// The file is damaged or truncated.
+    Malformed(&'static str),
+}
+
+impl fmt::Display for PngError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PngError::NotPng => fmt.write_str("not a PNG file"),
+            PngError::Unsupported(what) => write!(fmt, "unsupported PNG: {}", what),
+            PngError::Malformed(what) => write!(fmt, "malformed PNG: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for PngError {}
+
+struct Header {
+    width: u32,
+    height: u32,
+    color_type: u8,
+}
+
+impl Header {
+    fn read(data: &[u8]) -> Result<Header, PngError> {
+        if data.len() != 13 {
+            return Err(PngError::Malformed("bad IHDR chunk"));
+        }
+        let width = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+        let height = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+        let (depth, color_type) = (data[8], data[9]);
+        if !matches!(color_type, 0 | 2 | 3 | 4 | 6) || data[10] != 0 || data[11] != 0 {
+            return Err(PngError::Malformed("bad IHDR chunk"));
+        }
+        if depth != 8 {
+            return Err(PngError::Unsupported("bit depths other than 8"));
+        }
+        if data[12] != 0 {
+            return Err(PngError::Unsupported("interlaced images"));
+        }
+        Ok(Header {
+            width,
+            height,
+            color_type,
+        })
+    }
+
+    /// The length of the decompressed image data: each row with its filter
+    /// byte.
+    fn raw_len(&self) -> Result<usize, PngError> {
+        (self.width as usize)
+            .checked_mul(self.channels())
+            .and_then(|stride| (stride + 1).checked_mul(self.height as usize))
+            .ok_or(PngError::Malformed("image too large"))
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            0 | 3 => 1,
+            4 => 2,
+            2 => 3,
+            _ => 4,
+        }
+    }
+}
+
+/// Reverses the per-row filters, returning the rows without filter bytes.
+fn unfilter(raw: &[u8], header: &Header) -> Result<Vec<u8>, PngError> {
+    let bpp = header.channels();
+    let stride = (header.width as usize)
+        .checked_mul(bpp)
+        .ok_or(PngError::Malformed("image too large"))?;
+    let height = header.height as usize;
+    if (stride + 1).checked_mul(height) != Some(raw.len()) {
+        return Err(PngError::Malformed("image data is the wrong length"));
+    }
+
+    let mut rows = vec![0u8; stride * height];
+    for y in 0..height {
+        let filter = raw[y * (stride + 1)];
+        let line = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (done, current) = rows.split_at_mut(y * stride);
+        let above = if y == 0 {
+            None
+        } else {
+            Some(&done[(y - 1) * stride..])
+        };
+        let current = &mut current[..stride];
+        for x in 0..stride {
+            let a = if x >= bpp { current[x - bpp] } else { 0 };
+            let b = above.map_or(0, |row| row[x]);
+            let c = match above {
+                Some(row) if x >= bpp => row[x - bpp],
+                _ => 0,
+            };
+            let prediction = match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(PngError::Malformed("bad filter type")),
+            };
+            current[x] = line[x].wrapping_add(prediction);
+        }
+    }
+    Ok(rows)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// The alpha of a gray or RGB pixel given a `tRNS` color key, which holds
+/// 16-bit samples.
+fn key_alpha(transparency: &[u8], color: &[u8]) -> u8 {
+    let matches = transparency.len() == color.len() * 2
+        && color
+            .iter()
+            .enumerate()
+            .all(|(i, &c)| transparency[i * 2] == 0 && transparency[i * 2 + 1] == c);
+    if matches {
+        0
+    } else {
+        255
+    }
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xffff;
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 11);
+    out.extend_from_slice(&[0x78, 0x01]);
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+pub(crate) fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,239 @@
+use crate::brush::{IBrush, LinearGradientBrush, RadialGradientBrush, SolidColorBrush};
+use crate::enums::{AntialiasMode, DashStyle, DrawTextOptions};
+use crate::factory::IFactory;
+use crate::image::Bitmap;
+use crate::layer::Layer;
+use crate::record::{BrushDesc, DrawOp, Recording, Shape, StrokeStyleDesc};
+use crate::render_target::IRenderTarget;
+use crate::resource::IResource;
+use crate::stroke_style::StrokeStyle;
+
+use dcommon::Error;
+use directwrite::TextFormat;
+use dxgi::enums::Format;
+use math2d::Rectf;
+
+/// Text is laid out in a box this big, so it never wraps.
+const UNBOUNDED: f32 = 1.0e7;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Pushed {
+    Clip,
+    Layer,
+}
+
+impl Recording {
+    /// Plays the recording back on `target`, creating the brushes, stroke
+    /// styles, geometries and layers it needs as it goes.
+    ///
+    /// Recorded transforms are applied on top of the target's transform,
+    /// which is restored afterwards. A DirectWrite factory is created for
+    /// the first text drawn. Clips and layers left pushed are popped
+    /// at the end.
+    pub fn draw(&self, target: &mut dyn IRenderTarget) -> Result<(), Error> {
+        let factory = target.factory();
+        let base = target.transform();
+        let mut pushed = Vec::new();
+        let mut dwrite = None;
+
+        for op in self.ops() {
+            match op {
+                DrawOp::Clear(color) => target.clear(*color),
+                DrawOp::SetTransform(transform) => {
+                    target.set_transform(&(*transform * base));
+                }
+                DrawOp::Fill { shape, brush } => {
+                    let brush = match brush.build(target)? {
+                        Some(brush) => brush,
+                        None => continue,
+                    };
+                    let brush = &*brush;
+                    match shape {
+                        Shape::Rectangle(rect) => target.fill_rectangle(*rect, brush),
+                        Shape::RoundedRectangle(rect) => {
+                            target.fill_rounded_rectangle(*rect, brush)
+                        }
+                        Shape::Ellipse(ellipse) => target.fill_ellipse(*ellipse, brush),
+                        Shape::Line(..) => {}
+                        Shape::Path(path) => {
+                            let geometry = path.build(&factory)?;
+                            target.fill_geometry(&geometry, brush);
+                        }
+                    }
+                }
+                DrawOp::Stroke {
+                    shape,
+                    brush,
+                    width,
+                    style,
+                } => {
+                    let brush = match brush.build(target)? {
+                        Some(brush) => brush,
+                        None => continue,
+                    };
+                    let brush = &*brush;
+                    let style = match style {
+                        Some(style) => Some(style.build(&factory)?),
+                        None => None,
+                    };
+                    let style = style.as_ref();
+                    match shape {
+                        Shape::Rectangle(rect) => {
+                            target.draw_rectangle(*rect, brush, *width, style)
+                        }
+                        Shape::RoundedRectangle(rect) => {
+                            target.draw_rounded_rectangle(*rect, brush, *width, style)
+                        }
+                        Shape::Ellipse(ellipse) => {
+                            target.draw_ellipse(*ellipse, brush, *width, style)
+                        }
+                        Shape::Line(p0, p1) => target.draw_line(*p0, *p1, brush, *width, style),
+                        Shape::Path(path) => {
+                            let geometry = path.build(&factory)?;
+                            target.draw_geometry(&geometry, brush, *width, style);
+                        }
+                    }
+                }
+                DrawOp::Bitmap {
+                    bitmap,
+                    dest,
+                    opacity,
+                    interpolation,
+                    source,
+                } => {
+                    let created = Bitmap::create(target)
+                        .with_raw_data(bitmap.size(), bitmap.pixels(), bitmap.width() * 4)
+                        .with_format(Format::B8G8R8A8Unorm)
+                        .build()?;
+                    target.draw_bitmap(&created, *dest, *opacity, *interpolation, *source);
+                }
+                DrawOp::Text { text, brush } => {
+                    let brush = match brush.build(target)? {
+                        Some(brush) => brush,
+                        None => continue,
+                    };
+                    if dwrite.is_none() {
+                        dwrite = Some(directwrite::Factory::new()?);
+                    }
+                    let format = TextFormat::create(dwrite.as_ref().unwrap())
+                        .with_family(&text.font_family)
+                        .with_size(text.font_size)
+                        .build()?;
+                    let layout = Rectf {
+                        left: text.origin.x,
+                        top: text.origin.y,
+                        right: text.origin.x + UNBOUNDED,
+                        bottom: text.origin.y + UNBOUNDED,
+                    };
+                    target.draw_text(&text.text, &format, layout, &*brush, DrawTextOptions::NONE);
+                }
+                DrawOp::PushAxisAlignedClip(clip) => {
+                    target.push_axis_aligned_clip(*clip, AntialiasMode::PerPrimitive);
+                    pushed.push(Pushed::Clip);
+                }
+                DrawOp::PopAxisAlignedClip => {
+                    if pushed.last() == Some(&Pushed::Clip) {
+                        pushed.pop();
+                        target.pop_axis_aligned_clip();
+                    }
+                }
+                DrawOp::PushLayer(desc) => {
+                    let layer = Layer::create(target, None)?;
+                    let mask = match &desc.mask {
+                        Some(mask) => Some(mask.build(&factory)?),
+                        None => None,
+                    };
+                    let mut builder = target
+                        .push_layer_dyn(&layer)
+                        .with_content_bounds(desc.content_bounds)
+                        .with_mask_transform(desc.mask_transform)
+                        .with_opacity(desc.opacity);
+                    if let Some(mask) = &mask {
+                        builder = builder.with_mask(mask);
+                    }
+                    builder.push();
+                    pushed.push(Pushed::Layer);
+                }
+                DrawOp::PopLayer => {
+                    if pushed.last() == Some(&Pushed::Layer) {
+                        pushed.pop();
+                        target.pop_layer();
+                    }
+                }
+            }
+        }
+
+        while let Some(kind) = pushed.pop() {
+            match kind {
+                Pushed::Clip => target.pop_axis_aligned_clip(),
+                Pushed::Layer => target.pop_layer(),
+            }
+        }
+        target.set_transform(&base);
+        Ok(())
+    }
+}
+
+impl BrushDesc {
+    /// Creates the brush on `target`, or returns `None` for a gradient
+    /// without stops, which paints nothing.
+    pub fn build(&self, target: &dyn IRenderTarget) -> Result<Option<Box<dyn IBrush>>, Error> {
+        Ok(Some(match self {
+            BrushDesc::Solid { color, brush } => Box::new(
+                SolidColorBrush::create(target)
+                    .with_color(*color)
+                    .with_properties(*brush)
+                    .build()?,
+            ),
+            BrushDesc::LinearGradient(gradient) => {
+                if gradient.ramp.stops().is_empty() {
+                    return Ok(None);
+                }
+                Box::new(
+                    LinearGradientBrush::create(target)
+                        .with_properties(gradient.brush)
+                        .with_start(gradient.properties.start)
+                        .with_end(gradient.properties.end)
+                        .with_extend_mode(gradient.ramp.extend_mode())
+                        .with_gamma(gradient.ramp.gamma())
+                        .with_stops(gradient.ramp.stops())
+                        .build()?,
+                )
+            }
+            BrushDesc::RadialGradient(gradient) => {
+                if gradient.ramp.stops().is_empty() {
+                    return Ok(None);
+                }
+                Box::new(
+                    RadialGradientBrush::create(target)
+                        .with_properties(gradient.brush)
+                        .with_center(gradient.properties.center)
+                        .with_origin_offset(gradient.properties.origin_offset)
+                        .with_radius(gradient.properties.radius_x, gradient.properties.radius_y)
+                        .with_extend_mode(gradient.ramp.extend_mode())
+                        .with_gamma(gradient.ramp.gamma())
+                        .with_stops(gradient.ramp.stops())
+                        .build()?,
+                )
+            }
+        }))
+    }
+}
+
+impl StrokeStyleDesc {
+    /// Creates the stroke style with `factory`.
+    pub fn build(&self, factory: &dyn IFactory) -> Result<StrokeStyle, Error> {
+        let mut builder = StrokeStyle::create(factory)
+            .with_start_cap(self.start_cap)
+            .with_end_cap(self.end_cap)
+            .with_dash_cap(self.dash_cap)
+            .with_line_join(self.line_join)
+            .with_miter_limit(self.miter_limit)
+            .with_dash_style(self.dash_style)
+            .with_dash_offset(self.dash_offset);
+        if self.dash_style == DashStyle::Custom {
+            builder = builder.with_dashes(&self.dashes);
+        }
+        builder.build()
+    }
+}
@@ -0,0 +1,284 @@
+//! Just enough zlib to read PNG files back: stored, fixed and dynamic
+//! Huffman deflate blocks, without preset dictionaries.
+
+use crate::record::bitmap::adler32;
+
+/// Base lengths for length symbols 257 to 285, and their extra bits.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distances for distance symbols 0 to 29, and their extra bits.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const TOO_LONG: &str = "zlib stream decompresses to more data than expected";
+/// The order code length code lengths are sent in.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Decompresses a zlib stream, checking its checksum. Streams that would
+/// decompress to more than `limit` bytes are rejected as soon as they do.
+pub(crate) fn zlib_decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, &'static str> {
+    if data.len() < 6 {
+        return Err("truncated zlib stream");
+    }
+    let (cmf, flg) = (data[0], data[1]);
+    if cmf & 0x0f != 8 || u16::from_be_bytes([cmf, flg]) % 31 != 0 {
+        return Err("bad zlib header");
+    }
+    if flg & 0x20 != 0 {
+        return Err("zlib preset dictionaries aren't supported");
+    }
+
+    let mut bits = Bits {
+        data: &data[2..],
+        pos: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut out = Vec::new();
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => stored(&mut bits, &mut out, limit)?,
+            1 => {
+                let (lengths, distances) = fixed_codes();
+                codes(&mut bits, &mut out, limit, &lengths, &distances)?;
+            }
+            2 => {
+                let (lengths, distances) = dynamic_codes(&mut bits)?;
+                codes(&mut bits, &mut out, limit, &lengths, &distances)?;
+            }
+            _ => return Err("bad deflate block type"),
+        }
+        if last {
+            break;
+        }
+    }
+
+    bits.align();
+    let checksum = bits.bytes(4)?;
+    if u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) != adler32(&out) {
+        return Err("zlib checksum mismatch");
+    }
+    Ok(out)
+}
+
+/// Reads deflate's least-significant-bit-first bit stream.
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl<'a> Bits<'a> {
+    fn read(&mut self, n: u32) -> Result<u32, &'static str> {
+        while self.count < n {
+            let byte = *self.data.get(self.pos).ok_or("truncated deflate stream")?;
+            self.pos += 1;
+            self.buffer |= (byte as u32) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1 << n) - 1);
+        self.buffer >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+
+    /// Drops what's left of the current byte.
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+
+    /// Whole bytes, which must come after `align`.
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], &'static str> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + n)
+            .ok_or("truncated deflate stream")?;
+        self.pos += n;
+        Ok(bytes)
+    }
+}
+
+/// A canonical Huffman code, as the count of codes of each length and the
+/// symbols ordered by code.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Huffman, &'static str> {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err("over-subscribed Huffman code");
+            }
+        }
+
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, &'static str> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bits.read(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("bad Huffman code")
+    }
+}
+
+fn stored(bits: &mut Bits, out: &mut Vec<u8>, limit: usize) -> Result<(), &'static str> {
+    bits.align();
+    let header = bits.bytes(4)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    if !len != u16::from_le_bytes([header[2], header[3]]) {
+        return Err("stored block length mismatch");
+    }
+    if limit - out.len() < len as usize {
+        return Err(TOO_LONG);
+    }
+    out.extend_from_slice(bits.bytes(len as usize)?);
+    Ok(())
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    for (symbol, len) in lengths.iter_mut().enumerate() {
+        *len = match symbol {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    let lengths = Huffman::new(&lengths).expect("the fixed code is complete");
+    let distances = Huffman::new(&[5; 30]).expect("the fixed code is complete");
+    (lengths, distances)
+}
+
+fn dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman), &'static str> {
+    let literals = bits.read(5)? as usize + 257;
+    let distances = bits.read(5)? as usize + 1;
+    let code_lengths = bits.read(4)? as usize + 4;
+    if literals > 286 || distances > 30 {
+        return Err("too many codes in a dynamic block");
+    }
+
+    let mut lengths = [0u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..code_lengths] {
+        lengths[i] = bits.read(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&lengths)?;
+
+    let mut lengths = vec![0u8; literals + distances];
+    let mut i = 0;
+    while i < lengths.len() {
+        let symbol = code_length_code.decode(bits)?;
+        let (len, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 if i == 0 => return Err("repeat with no previous length"),
+            16 => (lengths[i - 1], 3 + bits.read(2)? as usize),
+            17 => (0, 3 + bits.read(3)? as usize),
+            _ => (0, 11 + bits.read(7)? as usize),
+        };
+        if i + repeat > lengths.len() {
+            return Err("too many lengths in a dynamic block");
+        }
+        for len_slot in &mut lengths[i..i + repeat] {
+            *len_slot = len;
+        }
+        i += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err("dynamic block has no end code");
+    }
+
+    let (literal_lengths, distance_lengths) = lengths.split_at(literals);
+    Ok((
+        Huffman::new(literal_lengths)?,
+        Huffman::new(distance_lengths)?,
+    ))
+}
+
+fn codes(
+    bits: &mut Bits,
+    out: &mut Vec<u8>,
+    limit: usize,
+    lengths: &Huffman,
+    distances: &Huffman,
+) -> Result<(), &'static str> {
+    loop {
+        let symbol = lengths.decode(bits)? as usize;
+        if symbol < 256 {
+            if out.len() == limit {
+                return Err(TOO_LONG);
+            }
+            out.push(symbol as u8);
+            continue;
+        } else if symbol == 256 {
+            return Ok(());
+        }
+
+        let symbol = symbol - 257;
+        if symbol >= LENGTH_BASE.len() {
+            return Err("bad length symbol");
+        }
+        let len = LENGTH_BASE[symbol] as usize + bits.read(LENGTH_EXTRA[symbol] as u32)? as usize;
+        let symbol = distances.decode(bits)? as usize;
+        if symbol >= DISTANCE_BASE.len() {
+            return Err("bad distance symbol");
+        }
+        let distance =
+            DISTANCE_BASE[symbol] as usize + bits.read(DISTANCE_EXTRA[symbol] as u32)? as usize;
+        if distance > out.len() {
+            return Err("distance reaches before the start of the output");
+        }
+        if limit - out.len() < len {
+            return Err(TOO_LONG);
+        }
+        let start = out.len() - distance;
+        for i in 0..len {
+            let byte = out[start + i];
+            out.push(byte);
+        }
+    }
+}
@@ -0,0 +1,17 @@
+//! Drawing captured as data instead of sent to a render target.
+//!
+//! A `Recording` takes the same calls as `IRenderTarget`, with brushes,
+//! stroke styles and geometry described by plain values rather than device
+//! resources. Exporters such as `svg::to_svg` and `pdf::to_pdf` walk the
+//! recorded `DrawOp`s.
+
+pub use self::bitmap::{BitmapData, PngError};
+pub use self::ops::{BrushDesc, DrawOp, LayerDesc, Shape, StrokeStyleDesc, TextDesc};
+pub use self::recording::Recording;
+
+pub mod bitmap;
+#[cfg(windows)]
+pub mod draw;
+mod inflate;
+pub mod ops;
+pub mod recording;
@@ -0,0 +1,422 @@
+use crate::cpu::{LinearGradient, RadialGradient};
+use crate::enums::{
+    BitmapInterpolationMode, CapStyle, DashStyle, FigureBegin, FigureEnd, LineJoin,
+};
+use crate::geometry::PathData;
+use crate::properties::BrushProperties;
+use crate::record::BitmapData;
+
+use math2d::{
+    ArcSegment, ArcSize, Color, Ellipse, Matrix3x2f, Point2f, Rectf, RoundedRect, Sizef,
+    SweepDirection,
+};
+
+use std::sync::Arc;
+
+/// A brush, described by the values it would be created with: a recipe
+/// that `build` turns into a brush on a render target. With the `serde`
+/// feature it can be kept in config files.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BrushDesc {
+    Solid {
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::ColorDef"))]
+        color: Color,
+        #[cfg_attr(feature = "serde", serde(default))]
+        brush: BrushProperties,
+    },
+    LinearGradient(LinearGradient),
+    RadialGradient(RadialGradient),
+}
+
+impl BrushDesc {
+    pub fn solid(color: impl Into<Color>) -> BrushDesc {
+        BrushDesc::Solid {
+            color: color.into(),
+            brush: BrushProperties::default(),
+        }
+    }
+
+    /// The opacity and transform every kind of brush has.
+    pub fn brush_properties(&self) -> &BrushProperties {
+        match self {
+            BrushDesc::Solid { brush, .. } => brush,
+            BrushDesc::LinearGradient(gradient) => &gradient.brush,
+            BrushDesc::RadialGradient(gradient) => &gradient.brush,
+        }
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.brush_properties_mut().opacity = opacity;
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Matrix3x2f) -> Self {
+        self.brush_properties_mut().transform = transform;
+        self
+    }
+
+    fn brush_properties_mut(&mut self) -> &mut BrushProperties {
+        match self {
+            BrushDesc::Solid { brush, .. } => brush,
+            BrushDesc::LinearGradient(gradient) => &mut gradient.brush,
+            BrushDesc::RadialGradient(gradient) => &mut gradient.brush,
+        }
+    }
+}
+
+impl From<Color> for BrushDesc {
+    fn from(color: Color) -> BrushDesc {
+        BrushDesc::solid(color)
+    }
+}
+
+impl From<LinearGradient> for BrushDesc {
+    fn from(gradient: LinearGradient) -> BrushDesc {
+        BrushDesc::LinearGradient(gradient)
+    }
+}
+
+impl From<RadialGradient> for BrushDesc {
+    fn from(gradient: RadialGradient) -> BrushDesc {
+        BrushDesc::RadialGradient(gradient)
+    }
+}
+
+/// A stroke style, described by the values a `StrokeStyleBuilder` takes:
+/// a recipe that `build` turns into a stroke style with a factory. With the
+/// `serde` feature, fields left out are deserialized as their defaults.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct StrokeStyleDesc {
+    pub start_cap: CapStyle,
+    pub end_cap: CapStyle,
+    pub dash_cap: CapStyle,
+    pub line_join: LineJoin,
+    pub miter_limit: f32,
+    pub dash_style: DashStyle,
+    /// In multiples of the stroke width.
+    pub dash_offset: f32,
+    /// Used when `dash_style` is `Custom`, in multiples of the stroke width.
+    pub dashes: Vec<f32>,
+}
+
+impl StrokeStyleDesc {
+    pub fn new() -> StrokeStyleDesc {
+        // The same defaults as StrokeStyleBuilder.
+        StrokeStyleDesc {
+            start_cap: CapStyle::Flat,
+            end_cap: CapStyle::Flat,
+            dash_cap: CapStyle::Flat,
+            line_join: LineJoin::Miter,
+            miter_limit: 10.0,
+            dash_style: DashStyle::Solid,
+            dash_offset: 0.0,
+            dashes: Vec::new(),
+        }
+    }
+
+    /// Sets the start, end and dash caps together.
+    pub fn with_caps(mut self, cap: CapStyle) -> Self {
+        self.start_cap = cap;
+        self.end_cap = cap;
+        self.dash_cap = cap;
+        self
+    }
+
+    pub fn with_start_cap(mut self, start_cap: CapStyle) -> Self {
+        self.start_cap = start_cap;
+        self
+    }
+
+    pub fn with_end_cap(mut self, end_cap: CapStyle) -> Self {
+        self.end_cap = end_cap;
+        self
+    }
+
+    pub fn with_dash_cap(mut self, dash_cap: CapStyle) -> Self {
+        self.dash_cap = dash_cap;
+        self
+    }
+
+    pub fn with_line_join(mut self, line_join: LineJoin) -> Self {
+        self.line_join = line_join;
+        self
+    }
+
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    pub fn with_dash_style(mut self, dash_style: DashStyle) -> Self {
+        self.dash_style = dash_style;
+        self
+    }
+
+    pub fn with_dash_offset(mut self, dash_offset: f32) -> Self {
+        self.dash_offset = dash_offset;
+        self
+    }
+
+    pub fn with_dashes(mut self, dashes: &[f32]) -> Self {
+        self.dash_style = DashStyle::Custom;
+        self.dashes = dashes.to_vec();
+        self
+    }
+
+    /// Alternating dash and gap lengths in multiples of the stroke width, as
+    /// Direct2D defines the built-in styles. Empty for solid lines.
+    pub fn dash_pattern(&self) -> Vec<f32> {
+        match self.dash_style {
+            DashStyle::Solid => Vec::new(),
+            DashStyle::Dash => vec![2.0, 2.0],
+            DashStyle::Dot => vec![0.0, 2.0],
+            DashStyle::DashDot => vec![2.0, 2.0, 0.0, 2.0],
+            DashStyle::DashDotDot => vec![2.0, 2.0, 0.0, 2.0, 0.0, 2.0],
+            DashStyle::Custom => self.dashes.clone(),
+        }
+    }
+}
+
+impl Default for StrokeStyleDesc {
+    fn default() -> StrokeStyleDesc {
+        StrokeStyleDesc::new()
+    }
+}
+
+/// The things which can be filled or stroked.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Rectangle(Rectf),
+    RoundedRectangle(RoundedRect),
+    Ellipse(Ellipse),
+    /// Only drawn by strokes.
+    Line(Point2f, Point2f),
+    Path(PathData),
+}
+
+impl Shape {
+    /// The same outline as a path. Rectangles run clockwise from their
+    /// top-left corner and ellipses from their rightmost point; lines become
+    /// a hollow, open figure.
+    pub fn to_path(&self) -> PathData {
+        let mut path = PathData::new();
+        match self {
+            Shape::Rectangle(rect) => {
+                let r = normalize(rect);
+                path.add_polygon(&[
+                    point(r.left, r.top),
+                    point(r.right, r.top),
+                    point(r.right, r.bottom),
+                    point(r.left, r.bottom),
+                ]);
+            }
+            Shape::RoundedRectangle(rounded) => {
+                let r = normalize(&rounded.rect);
+                let rx = rounded.radius_x.abs().min((r.right - r.left) / 2.0);
+                let ry = rounded.radius_y.abs().min((r.bottom - r.top) / 2.0);
+                if rx == 0.0 || ry == 0.0 {
+                    return Shape::Rectangle(r).to_path();
+                }
+                let corner = |x, y| ArcSegment {
+                    point: point(x, y),
+                    size: Sizef::new(rx, ry),
+                    rotation_angle: 0.0,
+                    sweep_direction: SweepDirection::Clockwise,
+                    arc_size: ArcSize::Small,
+                };
+                path.begin_figure(point(r.left + rx, r.top), FigureBegin::Filled);
+                path.add_line(point(r.right - rx, r.top));
+                path.add_arc(&corner(r.right, r.top + ry));
+                path.add_line(point(r.right, r.bottom - ry));
+                path.add_arc(&corner(r.right - rx, r.bottom));
+                path.add_line(point(r.left + rx, r.bottom));
+                path.add_arc(&corner(r.left, r.bottom - ry));
+                path.add_line(point(r.left, r.top + ry));
+                path.add_arc(&corner(r.left + rx, r.top));
+                path.end_figure(FigureEnd::Closed);
+            }
+            Shape::Ellipse(e) => {
+                let (rx, ry) = (e.radius_x.abs(), e.radius_y.abs());
+                let half = |x, y| ArcSegment {
+                    point: point(x, y),
+                    size: Sizef::new(rx, ry),
+                    rotation_angle: 0.0,
+                    sweep_direction: SweepDirection::Clockwise,
+                    arc_size: ArcSize::Small,
+                };
+                let (cx, cy) = (e.center.x, e.center.y);
+                path.begin_figure(point(cx + rx, cy), FigureBegin::Filled);
+                path.add_arc(&half(cx - rx, cy));
+                path.add_arc(&half(cx + rx, cy));
+                path.end_figure(FigureEnd::Closed);
+            }
+            Shape::Line(p0, p1) => {
+                path.begin_figure(*p0, FigureBegin::Hollow);
+                path.add_line(*p1);
+                path.end_figure(FigureEnd::Open);
+            }
+            Shape::Path(p) => return p.clone(),
+        }
+        path
+    }
+
+    /// The smallest rectangle containing the shape, not counting any stroke.
+    /// `None` for a path with no figures.
+    pub fn bounds(&self) -> Option<Rectf> {
+        match self {
+            Shape::Rectangle(rect) => Some(normalize(rect)),
+            Shape::RoundedRectangle(rounded) => Some(normalize(&rounded.rect)),
+            Shape::Ellipse(e) => Some(Rectf {
+                left: e.center.x - e.radius_x.abs(),
+                top: e.center.y - e.radius_y.abs(),
+                right: e.center.x + e.radius_x.abs(),
+                bottom: e.center.y + e.radius_y.abs(),
+            }),
+            Shape::Line(p0, p1) => Some(Rectf {
+                left: p0.x.min(p1.x),
+                top: p0.y.min(p1.y),
+                right: p0.x.max(p1.x),
+                bottom: p0.y.max(p1.y),
+            }),
+            Shape::Path(path) => path.bounds(),
+        }
+    }
+}
+
+fn normalize(r: &Rectf) -> Rectf {
+    Rectf {
+        left: r.left.min(r.right),
+        top: r.top.min(r.bottom),
+        right: r.left.max(r.right),
+        bottom: r.top.max(r.bottom),
+    }
+}
+
+fn point(x: f32, y: f32) -> Point2f {
+    Point2f { x, y }
+}
+
+/// A line of text in one font, as it would be given to `draw_text`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextDesc {
+    pub text: String,
+    pub font_family: String,
+    /// The font size in DIPs.
+    pub font_size: f32,
+    /// The top-left corner of the layout box.
+    pub origin: Point2f,
+}
+
+impl TextDesc {
+    /// The share of the font size from the top of a line to its baseline,
+    /// used where the font's own metrics aren't available. It's close to
+    /// the ascent of the common UI fonts.
+    pub const ASCENT: f32 = 0.9;
+
+    pub fn new(text: impl Into<String>, font_family: impl Into<String>, font_size: f32) -> Self {
+        TextDesc {
+            text: text.into(),
+            font_family: font_family.into(),
+            font_size,
+            origin: Point2f { x: 0.0, y: 0.0 },
+        }
+    }
+
+    pub fn with_origin(mut self, origin: impl Into<Point2f>) -> Self {
+        self.origin = origin.into();
+        self
+    }
+
+    /// Where the baseline of the text starts, estimated with `ASCENT`.
+    pub fn baseline_origin(&self) -> Point2f {
+        point(self.origin.x, self.origin.y + self.font_size * Self::ASCENT)
+    }
+}
+
+/// The parameters of a layer, as given to a `LayerBuilder`.
+#[derive(Clone, Debug)]
+pub struct LayerDesc {
+    /// Drawing outside these bounds is clipped, in the coordinates of the
+    /// transform when the layer is pushed.
+    pub content_bounds: Rectf,
+    /// Drawing outside this geometry is clipped.
+    pub mask: Option<PathData>,
+    /// Applied to the mask before the transform.
+    pub mask_transform: Matrix3x2f,
+    pub opacity: f32,
+}
+
+impl LayerDesc {
+    pub fn new() -> LayerDesc {
+        LayerDesc {
+            content_bounds: Rectf::INFINITE,
+            mask: None,
+            mask_transform: Matrix3x2f::IDENTITY,
+            opacity: 1.0,
+        }
+    }
+
+    pub fn with_content_bounds(mut self, bounds: Rectf) -> Self {
+        self.content_bounds = bounds;
+        self
+    }
+
+    pub fn with_mask(mut self, mask: PathData) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    pub fn with_mask_transform(mut self, transform: Matrix3x2f) -> Self {
+        self.mask_transform = transform;
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+}
+
+impl Default for LayerDesc {
+    fn default() -> LayerDesc {
+        LayerDesc::new()
+    }
+}
+
+/// One recorded call.
+#[derive(Clone, Debug)]
+pub enum DrawOp {
+    /// Fills the whole target, ignoring the transform but not clips.
+    Clear(Color),
+    SetTransform(Matrix3x2f),
+    Fill {
+        shape: Shape,
+        brush: BrushDesc,
+    },
+    Stroke {
+        shape: Shape,
+        brush: BrushDesc,
+        width: f32,
+        style: Option<StrokeStyleDesc>,
+    },
+    /// Draws the `source` part of `bitmap`, in pixels, stretched over `dest`.
+    Bitmap {
+        bitmap: Arc<BitmapData>,
+        dest: Rectf,
+        opacity: f32,
+        interpolation: BitmapInterpolationMode,
+        source: Rectf,
+    },
+    Text {
+        text: TextDesc,
+        brush: BrushDesc,
+    },
+    PushAxisAlignedClip(Rectf),
+    PopAxisAlignedClip,
+    PushLayer(LayerDesc),
+    PopLayer,
+}
@@ -0,0 +1,188 @@
+use crate::enums::BitmapInterpolationMode;
+use crate::geometry::PathData;
+use crate::record::{BitmapData, BrushDesc, DrawOp, LayerDesc, Shape, StrokeStyleDesc, TextDesc};
+
+use math2d::{Color, Ellipse, Matrix3x2f, Point2f, Rectf, RoundedRect, Sizef};
+
+use std::sync::Arc;
+
+/// A list of drawing operations on a target of a given size, in DIPs.
+///
+/// The methods mirror `IRenderTarget`, so drawing code can be pointed at a
+/// recording with few changes.
+#[derive(Clone, Debug)]
+pub struct Recording {
+    size: Sizef,
+    ops: Vec<DrawOp>,
+    transform: Matrix3x2f,
+}
+
+impl Recording {
+    pub fn new(size: impl Into<Sizef>) -> Recording {
+        Recording {
+            size: size.into(),
+            ops: Vec::new(),
+            transform: Matrix3x2f::IDENTITY,
+        }
+    }
+
+    pub fn size(&self) -> Sizef {
+        self.size
+    }
+
+    pub fn ops(&self) -> &[DrawOp] {
+        &self.ops
+    }
+
+    /// Appends an operation as-is.
+    pub fn push(&mut self, op: DrawOp) {
+        if let DrawOp::SetTransform(transform) = op {
+            self.transform = transform;
+        }
+        self.ops.push(op);
+    }
+
+    pub fn clear(&mut self, color: impl Into<Color>) {
+        self.push(DrawOp::Clear(color.into()));
+    }
+
+    pub fn set_transform(&mut self, transform: &Matrix3x2f) {
+        self.push(DrawOp::SetTransform(*transform));
+    }
+
+    /// The transform set most recently.
+    pub fn transform(&self) -> Matrix3x2f {
+        self.transform
+    }
+
+    pub fn fill_rectangle(&mut self, rect: Rectf, brush: impl Into<BrushDesc>) {
+        self.fill(Shape::Rectangle(rect), brush);
+    }
+
+    pub fn draw_rectangle(
+        &mut self,
+        rect: Rectf,
+        brush: impl Into<BrushDesc>,
+        width: f32,
+        style: Option<&StrokeStyleDesc>,
+    ) {
+        self.stroke(Shape::Rectangle(rect), brush, width, style);
+    }
+
+    pub fn fill_rounded_rectangle(&mut self, rect: RoundedRect, brush: impl Into<BrushDesc>) {
+        self.fill(Shape::RoundedRectangle(rect), brush);
+    }
+
+    pub fn draw_rounded_rectangle(
+        &mut self,
+        rect: RoundedRect,
+        brush: impl Into<BrushDesc>,
+        width: f32,
+        style: Option<&StrokeStyleDesc>,
+    ) {
+        self.stroke(Shape::RoundedRectangle(rect), brush, width, style);
+    }
+
+    pub fn fill_ellipse(&mut self, ellipse: Ellipse, brush: impl Into<BrushDesc>) {
+        self.fill(Shape::Ellipse(ellipse), brush);
+    }
+
+    pub fn draw_ellipse(
+        &mut self,
+        ellipse: Ellipse,
+        brush: impl Into<BrushDesc>,
+        width: f32,
+        style: Option<&StrokeStyleDesc>,
+    ) {
+        self.stroke(Shape::Ellipse(ellipse), brush, width, style);
+    }
+
+    pub fn draw_line(
+        &mut self,
+        p0: impl Into<Point2f>,
+        p1: impl Into<Point2f>,
+        brush: impl Into<BrushDesc>,
+        width: f32,
+        style: Option<&StrokeStyleDesc>,
+    ) {
+        self.stroke(Shape::Line(p0.into(), p1.into()), brush, width, style);
+    }
+
+    pub fn fill_geometry(&mut self, path: &PathData, brush: impl Into<BrushDesc>) {
+        self.fill(Shape::Path(path.clone()), brush);
+    }
+
+    pub fn draw_geometry(
+        &mut self,
+        path: &PathData,
+        brush: impl Into<BrushDesc>,
+        width: f32,
+        style: Option<&StrokeStyleDesc>,
+    ) {
+        self.stroke(Shape::Path(path.clone()), brush, width, style);
+    }
+
+    /// Draws the `src_rect` part of `bitmap`, in pixels, stretched over
+    /// `dest_rect`. `bitmap.rect()` draws all of it.
+    pub fn draw_bitmap(
+        &mut self,
+        bitmap: &Arc<BitmapData>,
+        dest_rect: Rectf,
+        opacity: f32,
+        interpolation: BitmapInterpolationMode,
+        src_rect: Rectf,
+    ) {
+        self.push(DrawOp::Bitmap {
+            bitmap: bitmap.clone(),
+            dest: dest_rect,
+            opacity,
+            interpolation,
+            source: src_rect,
+        });
+    }
+
+    pub fn draw_text(&mut self, text: TextDesc, brush: impl Into<BrushDesc>) {
+        self.push(DrawOp::Text {
+            text,
+            brush: brush.into(),
+        });
+    }
+
+    pub fn push_axis_aligned_clip(&mut self, clip: Rectf) {
+        self.push(DrawOp::PushAxisAlignedClip(clip));
+    }
+
+    pub fn pop_axis_aligned_clip(&mut self) {
+        self.push(DrawOp::PopAxisAlignedClip);
+    }
+
+    pub fn push_layer(&mut self, layer: LayerDesc) {
+        self.push(DrawOp::PushLayer(layer));
+    }
+
+    pub fn pop_layer(&mut self) {
+        self.push(DrawOp::PopLayer);
+    }
+
+    fn fill(&mut self, shape: Shape, brush: impl Into<BrushDesc>) {
+        self.push(DrawOp::Fill {
+            shape,
+            brush: brush.into(),
+        });
+    }
+
+    fn stroke(
+        &mut self,
+        shape: Shape,
+        brush: impl Into<BrushDesc>,
+        width: f32,
+        style: Option<&StrokeStyleDesc>,
+    ) {
+        self.push(DrawOp::Stroke {
+            shape,
+            brush: brush.into(),
+            width,
+            style: style.cloned(),
+        });
+    }
+}
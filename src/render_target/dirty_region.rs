@@ -0,0 +1,285 @@
+//! Collecting the parts of a window that changed, so a frame only has to
+//! redraw and present those.
+
+use crate::render_target::WindowRect;
+use crate::units::{Dip, Dpi};
+
+#[cfg(windows)]
+use crate::enums::AntialiasMode;
+#[cfg(windows)]
+use crate::render_target::IRenderTarget;
+
+use math2d::{Matrix3x2f, Rectf, Sizeu};
+
+/// Two rects which don't overlap are still merged if their union wastes no
+/// more than this fraction of its area on space neither covered.
+const MERGE_WASTE: f32 = 0.25;
+
+/// A set of rects in DIPs which need to be redrawn, kept small by merging
+/// rects that overlap or sit close together.
+///
+/// Rects are in the same coordinates as the drawing calls they cover, before
+/// the render target's transform is applied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirtyRegion {
+    rects: Vec<Rectf>,
+    max_rects: usize,
+}
+
+impl DirtyRegion {
+    /// The default limit on the number of separate rects.
+    pub const DEFAULT_MAX_RECTS: usize = 8;
+
+    pub fn new() -> DirtyRegion {
+        DirtyRegion::with_max_rects(DirtyRegion::DEFAULT_MAX_RECTS)
+    }
+
+    /// Once there would be more than `max_rects` rects, the pair whose union
+    /// wastes the least area is merged. A limit of 1 tracks just the bounds.
+    pub fn with_max_rects(max_rects: usize) -> DirtyRegion {
+        DirtyRegion {
+            rects: Vec::new(),
+            max_rects: max_rects.max(1),
+        }
+    }
+
+    pub fn max_rects(&self) -> usize {
+        self.max_rects
+    }
+
+    /// Marks `rect` as needing a redraw. Empty and NaN rects are ignored.
+    pub fn add(&mut self, rect: Rectf) {
+        if is_empty(&rect) {
+            return;
+        }
+        self.insert(rect);
+
+        while self.rects.len() > self.max_rects {
+            let (i, j) = self.cheapest_pair();
+            let merged = union(&self.rects[i], &self.rects[j]);
+            // j > i, so removing j first leaves i where it was.
+            self.rects.swap_remove(j);
+            self.rects.swap_remove(i);
+            self.insert(merged);
+        }
+    }
+
+    /// Adds every rect in `other`.
+    pub fn add_region(&mut self, other: &DirtyRegion) {
+        for &rect in &other.rects {
+            self.add(rect);
+        }
+    }
+
+    /// Marks everything inside `bounds` as dirty, replacing what was there.
+    pub fn invalidate_all(&mut self, bounds: Rectf) {
+        self.rects.clear();
+        self.add(bounds);
+    }
+
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rects.len()
+    }
+
+    pub fn rects(&self) -> &[Rectf] {
+        &self.rects
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Rectf> {
+        self.rects.iter()
+    }
+
+    /// The smallest rect containing the whole region.
+    pub fn bounds(&self) -> Option<Rectf> {
+        let mut iter = self.rects.iter();
+        let first = *iter.next()?;
+        Some(iter.fold(first, |acc, r| union(&acc, r)))
+    }
+
+    /// Whether any part of `rect` is dirty, meaning whatever's drawn there
+    /// has to be redrawn.
+    pub fn intersects(&self, rect: &Rectf) -> bool {
+        self.rects.iter().any(|r| overlaps(r, rect))
+    }
+
+    /// The region in whole device pixels: each rect is transformed, scaled by
+    /// `dpi`, rounded outwards and clipped to a target of `size`. The result
+    /// can be passed straight to `PresentParams::with_dirty_rects`.
+    pub fn to_pixel_rects(&self, transform: &Matrix3x2f, dpi: Dpi, size: Sizeu) -> Vec<WindowRect> {
+        let target = Rectf {
+            left: 0.0,
+            top: 0.0,
+            right: size.width as f32,
+            bottom: size.height as f32,
+        };
+
+        // Rounding can make neighbours overlap, so coalesce again in pixels.
+        let mut pixels = DirtyRegion::with_max_rects(self.max_rects);
+        for rect in &self.rects {
+            let px = Dip(transform_bounds(rect, transform))
+                .to_px(dpi)
+                .round_out()
+                .0;
+            if let Some(clipped) = intersection(&px, &target) {
+                pixels.add(clipped);
+            }
+        }
+
+        pixels
+            .rects
+            .iter()
+            .map(|r| WindowRect::new(r.left as i32, r.top as i32, r.right as i32, r.bottom as i32))
+            .collect()
+    }
+
+    /// Merges `rect` with anything it should be merged with, then stores it.
+    fn insert(&mut self, mut rect: Rectf) {
+        while let Some(i) = self.rects.iter().position(|r| should_merge(r, &rect)) {
+            rect = union(&self.rects.swap_remove(i), &rect);
+        }
+        self.rects.push(rect);
+    }
+
+    fn cheapest_pair(&self) -> (usize, usize) {
+        let mut best = (0, 1, f32::INFINITY);
+        for i in 0..self.rects.len() {
+            for j in i + 1..self.rects.len() {
+                let waste = waste(&self.rects[i], &self.rects[j]);
+                if waste < best.2 {
+                    best = (i, j, waste);
+                }
+            }
+        }
+        (best.0, best.1)
+    }
+}
+
+#[cfg(windows)]
+impl DirtyRegion {
+    /// Clips drawing to the bounds of the region. Returns whether a clip was
+    /// pushed; if so, pop it with `pop_axis_aligned_clip` when done.
+    pub fn push_clip<T: IRenderTarget + ?Sized>(&self, target: &mut T) -> bool {
+        match self.bounds() {
+            Some(bounds) => {
+                target.push_axis_aligned_clip(bounds, AntialiasMode::Aliased);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Calls `draw` once for each rect in the region, with drawing clipped to
+    /// that rect.
+    pub fn draw_clipped<T, F>(&self, target: &mut T, mut draw: F)
+    where
+        T: IRenderTarget + ?Sized,
+        F: FnMut(&mut T, Rectf),
+    {
+        for &rect in &self.rects {
+            target.push_axis_aligned_clip(rect, AntialiasMode::Aliased);
+            draw(target, rect);
+            target.pop_axis_aligned_clip();
+        }
+    }
+}
+
+impl Default for DirtyRegion {
+    fn default() -> DirtyRegion {
+        DirtyRegion::new()
+    }
+}
+
+impl<'a> IntoIterator for &'a DirtyRegion {
+    type Item = &'a Rectf;
+    type IntoIter = std::slice::Iter<'a, Rectf>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rects.iter()
+    }
+}
+
+impl Extend<Rectf> for DirtyRegion {
+    fn extend<I: IntoIterator<Item = Rectf>>(&mut self, iter: I) {
+        for rect in iter {
+            self.add(rect);
+        }
+    }
+}
+
+pub(crate) fn is_empty(r: &Rectf) -> bool {
+    // Written so NaN counts as empty.
+    !(r.right > r.left && r.bottom > r.top)
+}
+
+fn area(r: &Rectf) -> f32 {
+    (r.right - r.left) * (r.bottom - r.top)
+}
+
+pub(crate) fn union(a: &Rectf, b: &Rectf) -> Rectf {
+    Rectf {
+        left: a.left.min(b.left),
+        top: a.top.min(b.top),
+        right: a.right.max(b.right),
+        bottom: a.bottom.max(b.bottom),
+    }
+}
+
+pub(crate) fn intersection(a: &Rectf, b: &Rectf) -> Option<Rectf> {
+    let r = Rectf {
+        left: a.left.max(b.left),
+        top: a.top.max(b.top),
+        right: a.right.min(b.right),
+        bottom: a.bottom.min(b.bottom),
+    };
+    if is_empty(&r) {
+        None
+    } else {
+        Some(r)
+    }
+}
+
+fn overlaps(a: &Rectf, b: &Rectf) -> bool {
+    intersection(a, b).is_some()
+}
+
+/// The area of the union of `a` and `b` which neither of them covers.
+fn waste(a: &Rectf, b: &Rectf) -> f32 {
+    let covered = area(a) + area(b) - intersection(a, b).map_or(0.0, |i| area(&i));
+    area(&union(a, b)) - covered
+}
+
+fn should_merge(a: &Rectf, b: &Rectf) -> bool {
+    overlaps(a, b) || waste(a, b) <= MERGE_WASTE * area(&union(a, b))
+}
+
+/// The axis-aligned bounds of `r` after `m` is applied to it.
+pub(crate) fn transform_bounds(r: &Rectf, m: &Matrix3x2f) -> Rectf {
+    let corners = [
+        (r.left, r.top),
+        (r.right, r.top),
+        (r.left, r.bottom),
+        (r.right, r.bottom),
+    ];
+    let mut out = Rectf {
+        left: f32::INFINITY,
+        top: f32::INFINITY,
+        right: -f32::INFINITY,
+        bottom: -f32::INFINITY,
+    };
+    for &(x, y) in &corners {
+        let p = m.transform_point((x, y));
+        out.left = out.left.min(p.x);
+        out.top = out.top.min(p.y);
+        out.right = out.right.max(p.x);
+        out.bottom = out.bottom.max(p.y);
+    }
+    out
+}
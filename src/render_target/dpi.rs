@@ -0,0 +1,120 @@
+//! Responding to `WM_DPICHANGED` when a window moves between monitors with
+//! different scale factors.
+
+use crate::units::Dpi;
+
+use math2d::Sizeu;
+
+#[cfg(windows)]
+use winapi::shared::windef::{HWND, RECT};
+
+/// A window rectangle in screen pixels, laid out like a Win32 `RECT`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct WindowRect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl WindowRect {
+    pub fn new(left: i32, top: i32, right: i32, bottom: i32) -> WindowRect {
+        WindowRect {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+}
+
+#[cfg(windows)]
+impl From<RECT> for WindowRect {
+    fn from(rect: RECT) -> WindowRect {
+        WindowRect::new(rect.left, rect.top, rect.right, rect.bottom)
+    }
+}
+
+#[cfg(windows)]
+impl From<WindowRect> for RECT {
+    fn from(rect: WindowRect) -> RECT {
+        RECT {
+            left: rect.left,
+            top: rect.top,
+            right: rect.right,
+            bottom: rect.bottom,
+        }
+    }
+}
+
+/// Unpacks the new DPI from the `wParam` of `WM_DPICHANGED`: the low word is
+/// the horizontal DPI and the high word the vertical one.
+pub fn dpi_from_wparam(wparam: usize) -> Dpi {
+    Dpi::new((wparam & 0xFFFF) as f32, ((wparam >> 16) & 0xFFFF) as f32)
+}
+
+/// Scales a pixel size from one DPI to another, rounding to the nearest pixel.
+/// Anything that was at least one pixel stays at least one pixel.
+pub fn scale_pixel_size(size: Sizeu, from: Dpi, to: Dpi) -> Sizeu {
+    let scale = |len: u32, from: f32, to: f32| {
+        if len == 0 {
+            0
+        } else {
+            ((len as f32 * to / from).round() as u32).max(1)
+        }
+    };
+    Sizeu {
+        width: scale(size.width, from.x, to.x),
+        height: scale(size.height, from.y, to.y),
+    }
+}
+
+/// Scales a window rect from one DPI to another, keeping its top left corner
+/// in place. Use this when there's no suggested rect from Windows, such as
+/// when a window is first shown on a scaled monitor.
+pub fn scale_window_rect(rect: WindowRect, from: Dpi, to: Dpi) -> WindowRect {
+    let scale = |len: i32, from: f32, to: f32| (len as f32 * to / from).round() as i32;
+    WindowRect {
+        left: rect.left,
+        top: rect.top,
+        right: rect.left + scale(rect.width(), from.x, to.x),
+        bottom: rect.top + scale(rect.height(), from.y, to.y),
+    }
+}
+
+/// The DPI of the monitor `hwnd` is on. Returns `None` before Windows 10
+/// version 1607, where that can't be queried per window.
+#[cfg(windows)]
+pub fn window_dpi(hwnd: HWND) -> Option<Dpi> {
+    use winapi::um::libloaderapi::{GetModuleHandleW, GetProcAddress};
+    use wio::wide::ToWide;
+
+    type GetDpiForWindow = unsafe extern "system" fn(HWND) -> u32;
+
+    unsafe {
+        let user32 = GetModuleHandleW("user32.dll".to_wide_null().as_ptr());
+        if user32.is_null() {
+            return None;
+        }
+        let proc = GetProcAddress(user32, b"GetDpiForWindow\0".as_ptr() as *const _);
+        if proc.is_null() {
+            return None;
+        }
+
+        let get_dpi: GetDpiForWindow = std::mem::transmute(proc);
+        match get_dpi(hwnd) {
+            // Returned for invalid windows.
+            0 => None,
+            dpi => Some(Dpi::uniform(dpi as f32)),
+        }
+    }
+}
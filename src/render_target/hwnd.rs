@@ -1,13 +1,16 @@
 use crate::enums::WindowState;
 use crate::factory::IFactory;
-use crate::render_target::{IRenderTarget, RTState};
+use crate::render_target::dpi::{self, WindowRect};
+use crate::render_target::{Breadcrumbs, IRenderTarget, RTState};
 use crate::resource::IResource;
+use crate::units::Dpi;
 
 use checked_enum::UncheckedEnum;
 use com_wrapper::ComWrapper;
 use dcommon::Error;
 use math2d::Sizeu;
-use winapi::shared::windef::HWND;
+use winapi::shared::minwindef::{LPARAM, WPARAM};
+use winapi::shared::windef::{HWND, RECT};
 use winapi::shared::winerror::SUCCEEDED;
 use winapi::um::d2d1::{ID2D1HwndRenderTarget, ID2D1RenderTarget, ID2D1Resource};
 use wio::com::ComPtr;
@@ -20,6 +23,7 @@ pub mod builder;
 pub struct HwndRenderTarget {
     ptr: ComPtr<ID2D1HwndRenderTarget>,
     state: RTState,
+    breadcrumbs: Breadcrumbs,
 }
 
 impl HwndRenderTarget {
@@ -45,6 +49,40 @@ impl HwndRenderTarget {
     pub fn hwnd(&self) -> HWND {
         unsafe { self.ptr.GetHwnd() }
     }
+
+    /// The DPI of the monitor the window is currently on, if the OS can
+    /// report it per window.
+    pub fn window_dpi(&self) -> Option<Dpi> {
+        dpi::window_dpi(self.hwnd())
+    }
+
+    /// Switches to a new DPI, scaling the pixel size to match so the content
+    /// keeps the same size in DIPs. If resizing fails the DPI is left alone.
+    pub fn apply_dpi(&mut self, new_dpi: Dpi) -> Result<Sizeu, Error> {
+        let old_dpi = Dpi::from(self.dpi());
+        let pixel_size = dpi::scale_pixel_size(self.pixel_size(), old_dpi, new_dpi);
+        self.resize(pixel_size)?;
+        self.set_dpi(new_dpi.x, new_dpi.y);
+        Ok(pixel_size)
+    }
+
+    /// Handles `WM_DPICHANGED` by applying the new DPI from `wparam` and
+    /// returning the window rect Windows suggests in `lparam`. Pass that to
+    /// `SetWindowPos` to finish the move.
+    ///
+    /// # Safety
+    ///
+    /// `lparam` must be the `lParam` of a `WM_DPICHANGED` message, which
+    /// points to a `RECT`.
+    pub unsafe fn handle_dpi_changed(
+        &mut self,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> Result<WindowRect, Error> {
+        let suggested = *(lparam as *const RECT);
+        self.apply_dpi(dpi::dpi_from_wparam(wparam))?;
+        Ok(suggested.into())
+    }
 }
 
 unsafe impl IResource for HwndRenderTarget {
@@ -65,6 +103,14 @@ unsafe impl IRenderTarget for HwndRenderTarget {
     fn draw_state_mut(&mut self) -> &mut RTState {
         &mut self.state
     }
+
+    fn breadcrumbs(&self) -> &Breadcrumbs {
+        &self.breadcrumbs
+    }
+
+    fn breadcrumbs_mut(&mut self) -> &mut Breadcrumbs {
+        &mut self.breadcrumbs
+    }
 }
 
 impl ComWrapper for HwndRenderTarget {
@@ -82,6 +128,7 @@ impl ComWrapper for HwndRenderTarget {
         HwndRenderTarget {
             ptr,
             state: RTState::NOT_DRAWING,
+            breadcrumbs: Breadcrumbs::default(),
         }
     }
     unsafe fn into_ptr(self) -> ComPtr<Self::Interface> {
@@ -1,10 +1,15 @@
 use crate::enums::{AlphaMode, FeatureLevel, PresentOptions, RenderTargetType, RenderTargetUsage};
 use crate::factory::IFactory;
+use crate::render_target::dpi;
 use crate::render_target::hwnd::HwndRenderTarget;
+#[cfg(feature = "raw-window-handle")]
+use crate::render_target::window::{self, WindowHandleError};
 
 use com_wrapper::ComWrapper;
 use dcommon::Error;
 use dxgi::enums::Format;
+#[cfg(feature = "raw-window-handle")]
+use raw_window_handle::HasRawWindowHandle;
 use winapi::shared::windef::HWND;
 use winapi::shared::winerror::SUCCEEDED;
 use winapi::um::d2d1::D2D1_HWND_RENDER_TARGET_PROPERTIES;
@@ -16,6 +21,7 @@ pub struct HwndRenderTargetBuilder<'a> {
     factory: &'a dyn IFactory,
     rt_props: D2D1_RENDER_TARGET_PROPERTIES,
     hwnd_props: D2D1_HWND_RENDER_TARGET_PROPERTIES,
+    window_dpi: bool,
 }
 
 const DEFAULT_PROPS: D2D1_RENDER_TARGET_PROPERTIES = D2D1_RENDER_TARGET_PROPERTIES {
@@ -45,10 +51,18 @@ impl<'a> HwndRenderTargetBuilder<'a> {
             factory,
             rt_props: DEFAULT_PROPS,
             hwnd_props: DEFAULT_HWND_PROPS,
+            window_dpi: false,
         }
     }
 
-    pub fn build(self) -> Result<HwndRenderTarget, Error> {
+    pub fn build(mut self) -> Result<HwndRenderTarget, Error> {
+        if self.window_dpi {
+            let dpi = dpi::window_dpi(self.hwnd_props.hwnd)
+                .unwrap_or_else(|| self.factory.desktop_dpi().into());
+            self.rt_props.dpiX = dpi.x;
+            self.rt_props.dpiY = dpi.y;
+        }
+
         unsafe {
             let mut ptr = std::ptr::null_mut();
             let hr = self.factory.raw_f().CreateHwndRenderTarget(
@@ -83,6 +97,15 @@ impl<'a> HwndRenderTargetBuilder<'a> {
     pub fn with_dpi(mut self, dpi_x: f32, dpi_y: f32) -> Self {
         self.rt_props.dpiX = dpi_x;
         self.rt_props.dpiY = dpi_y;
+        self.window_dpi = false;
+        self
+    }
+
+    /// Uses the DPI of the monitor the window is on rather than a fixed value,
+    /// falling back to `IFactory::desktop_dpi` where the OS can't report it.
+    /// Keep it in sync afterwards with `HwndRenderTarget::handle_dpi_changed`.
+    pub fn with_window_dpi(mut self) -> Self {
+        self.window_dpi = true;
         self
     }
 
@@ -101,6 +124,24 @@ impl<'a> HwndRenderTargetBuilder<'a> {
         self
     }
 
+    /// Targets the window behind a `raw_window_handle` implementation, such as
+    /// a winit or SDL window, and sizes the target to its client area.
+    ///
+    /// Handles from other platforms and null or destroyed windows are
+    /// rejected here, rather than by `build` with an opaque HRESULT.
+    #[cfg(feature = "raw-window-handle")]
+    pub fn with_window(
+        mut self,
+        window: &impl HasRawWindowHandle,
+    ) -> Result<Self, WindowHandleError> {
+        let hwnd = window::hwnd_from_handle(window.raw_window_handle())?;
+        self.hwnd_props.hwnd = hwnd;
+        if let Some(size) = window::client_pixel_size(hwnd) {
+            self.hwnd_props.pixelSize = size.into();
+        }
+        Ok(self)
+    }
+
     pub fn with_pixel_size(mut self, width: u32, height: u32) -> Self {
         self.hwnd_props.pixelSize.width = width;
         self.hwnd_props.pixelSize.height = height;
@@ -1,34 +1,68 @@
+#[cfg(windows)]
 use crate::brush::IBrush;
+#[cfg(windows)]
 use crate::enums::{AntialiasMode, BitmapInterpolationMode, DrawTextOptions};
+#[cfg(windows)]
 use crate::geometry::IGeometry;
+#[cfg(windows)]
 use crate::image::IBitmap;
+#[cfg(windows)]
 use crate::layer::{Layer, LayerBuilder};
+#[cfg(windows)]
 use crate::resource::IResource;
+#[cfg(windows)]
 use crate::stroke_style::StrokeStyle;
 
+#[cfg(windows)]
 use checked_enum::UncheckedEnum;
+#[cfg(windows)]
 use com_wrapper::ComWrapper;
+#[cfg(windows)]
 use dcommon::error::Error;
+#[cfg(windows)]
 use directwrite::{TextFormat, TextLayout};
+#[cfg(windows)]
 use math2d::*;
+#[cfg(windows)]
 use winapi::shared::winerror::SUCCEEDED;
+#[cfg(windows)]
 use winapi::um::d2d1::{ID2D1RenderTarget, ID2D1Resource, D2D1_TAG};
+#[cfg(windows)]
 use winapi::um::dcommon::DWRITE_MEASURING_MODE_NATURAL;
+#[cfg(windows)]
 use wio::com::ComPtr;
+#[cfg(windows)]
 use wio::wide::ToWide;
 
+pub use self::dirty_region::DirtyRegion;
+pub use self::dpi::WindowRect;
+#[cfg(windows)]
 pub use self::hwnd::HwndRenderTarget;
-pub use self::render_tag::RenderTag;
-
+#[cfg(windows)]
+pub use self::offscreen::Offscreen;
+pub use self::render_tag::{Breadcrumbs, RenderTag, TagId};
+#[cfg(feature = "raw-window-handle")]
+pub use self::window::WindowHandleError;
+
+pub mod dirty_region;
+pub mod dpi;
+#[cfg(windows)]
 pub mod hwnd;
+#[cfg(windows)]
+pub mod offscreen;
 pub mod render_tag;
+#[cfg(feature = "raw-window-handle")]
+pub mod window;
 
+#[cfg(windows)]
 #[repr(C)]
 pub struct RenderTarget {
     ptr: ComPtr<ID2D1RenderTarget>,
     state: RTState,
+    breadcrumbs: Breadcrumbs,
 }
 
+#[cfg(windows)]
 pub unsafe trait IRenderTarget: IResource {
     fn size(&self) -> Sizef {
         unsafe { self.raw_rt().GetSize().into() }
@@ -47,6 +81,7 @@ pub unsafe trait IRenderTarget: IResource {
             self.raw_rt().BeginDraw();
             self.draw_state_mut().clear(RTState::NOT_DRAWING);
         }
+        self.breadcrumbs_mut().clear();
     }
 
     fn end_draw(&mut self) -> Result<(), (Error, Option<RenderTag>)> {
@@ -67,7 +102,19 @@ pub unsafe trait IRenderTarget: IResource {
         }
     }
 
+    /// Like `end_draw`, but on failure also returns the breadcrumbs recorded
+    /// since `begin_draw`.
+    fn end_draw_report(&mut self) -> Result<(), DrawFailure> {
+        self.end_draw()
+            .map_err(|(error, tag)| DrawFailure::new(error, tag, self.breadcrumbs()))
+    }
+
+    /// Sets the tag reported if a following draw call fails, and records it
+    /// as a breadcrumb if those are enabled.
     fn set_tag(&mut self, tag: Option<RenderTag>) {
+        if let Some(tag) = tag {
+            self.breadcrumbs_mut().push(tag);
+        }
         unsafe {
             if let Some(tag) = tag {
                 let (tag1, tag2) = tag.to_raw();
@@ -104,6 +151,20 @@ pub unsafe trait IRenderTarget: IResource {
         }
     }
 
+    /// Like `flush`, but on failure also returns the breadcrumbs recorded
+    /// since `begin_draw`.
+    fn flush_report(&mut self) -> Result<(), DrawFailure> {
+        self.flush()
+            .map_err(|(error, tag)| DrawFailure::new(error, tag, self.breadcrumbs()))
+    }
+
+    /// Starts keeping the last `capacity` tags set during each frame, so a
+    /// failed `end_draw_report` shows the path that led up to the failure.
+    /// A capacity of 0 turns it back off.
+    fn enable_breadcrumbs(&mut self, capacity: usize) {
+        self.breadcrumbs_mut().set_capacity(capacity);
+    }
+
     fn clear(&mut self, color: Color) {
         self.assert_can_draw("clear");
 
@@ -420,6 +481,8 @@ pub unsafe trait IRenderTarget: IResource {
     unsafe fn raw_rt(&self) -> &ID2D1RenderTarget;
     fn draw_state(&self) -> RTState;
     fn draw_state_mut(&mut self) -> &mut RTState;
+    fn breadcrumbs(&self) -> &Breadcrumbs;
+    fn breadcrumbs_mut(&mut self) -> &mut Breadcrumbs;
 
     #[doc(hidden)]
     fn assert_can_draw(&self, fname: &str) {
@@ -437,18 +500,21 @@ pub unsafe trait IRenderTarget: IResource {
     }
 }
 
+#[cfg(windows)]
 impl dyn IRenderTarget + '_ {
     pub fn push_layer_dyn<'a, 'b>(&'a mut self, layer: &'b Layer) -> LayerBuilder<'a, 'b> {
         LayerBuilder::create(self, layer)
     }
 }
 
+#[cfg(windows)]
 unsafe impl IResource for RenderTarget {
     unsafe fn raw_resource(&self) -> &ID2D1Resource {
         &self.ptr
     }
 }
 
+#[cfg(windows)]
 unsafe impl IRenderTarget for RenderTarget {
     unsafe fn raw_rt(&self) -> &ID2D1RenderTarget {
         &self.ptr
@@ -461,8 +527,17 @@ unsafe impl IRenderTarget for RenderTarget {
     fn draw_state_mut(&mut self) -> &mut RTState {
         &mut self.state
     }
+
+    fn breadcrumbs(&self) -> &Breadcrumbs {
+        &self.breadcrumbs
+    }
+
+    fn breadcrumbs_mut(&mut self) -> &mut Breadcrumbs {
+        &mut self.breadcrumbs
+    }
 }
 
+#[cfg(windows)]
 impl ComWrapper for RenderTarget {
     type Interface = ID2D1RenderTarget;
     unsafe fn get_raw(&self) -> *mut Self::Interface {
@@ -478,24 +553,59 @@ impl ComWrapper for RenderTarget {
         RenderTarget {
             ptr,
             state: RTState::NOT_DRAWING,
+            breadcrumbs: Breadcrumbs::default(),
         }
     }
     unsafe fn into_ptr(self) -> ComPtr<Self::Interface> {
         self.ptr
     }
 }
-pub(crate) unsafe fn make_tag(tag1: D2D1_TAG, tag2: D2D1_TAG) -> Option<RenderTag> {
-    if tag1 == 0 {
-        None
-    } else {
-        let tag = RenderTag::from_raw(tag1, tag2);
-        Some(tag)
+#[cfg(windows)]
+pub(crate) fn make_tag(tag1: D2D1_TAG, tag2: D2D1_TAG) -> Option<RenderTag> {
+    RenderTag::from_raw(tag1, tag2)
+}
+
+#[cfg(windows)]
+/// A failed `end_draw` or `flush`, along with the tags that led up to it.
+#[derive(Debug)]
+pub struct DrawFailure {
+    pub error: Error,
+    /// The tag which was set when the failing call was made.
+    pub tag: Option<RenderTag>,
+    /// The breadcrumbs recorded since `begin_draw`, oldest first. Empty unless
+    /// breadcrumbs were enabled on the target.
+    pub breadcrumbs: Vec<RenderTag>,
+}
+
+#[cfg(windows)]
+impl DrawFailure {
+    fn new(error: Error, tag: Option<RenderTag>, breadcrumbs: &Breadcrumbs) -> DrawFailure {
+        DrawFailure {
+            error,
+            tag,
+            breadcrumbs: breadcrumbs.to_vec(),
+        }
+    }
+}
+
+#[cfg(windows)]
+impl std::fmt::Display for DrawFailure {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "{} at {}", self.error, render_tag::fmt_tag(self.tag))?;
+        if !self.breadcrumbs.is_empty() {
+            fmt.write_str("\nBreadcrumbs (oldest first):\n")?;
+            render_tag::fmt_trail(fmt, self.breadcrumbs.iter())?;
+        }
+        Ok(())
     }
 }
 
+#[cfg(windows)]
 unsafe impl Send for RenderTarget {}
+#[cfg(windows)]
 unsafe impl Sync for RenderTarget {}
 
+#[cfg(windows)]
 impl std::fmt::Debug for RenderTarget {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
         fmt.debug_struct("RenderTarget")
@@ -505,6 +615,7 @@ impl std::fmt::Debug for RenderTarget {
     }
 }
 
+#[cfg(windows)]
 #[auto_enum::enum_flags]
 /// Flags that act as blockers to a target being able to be drawn to.
 pub enum RTState {
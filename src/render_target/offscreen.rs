@@ -0,0 +1,121 @@
+use crate::device::Device;
+use crate::device_context::{DeviceContext, IDeviceContext};
+use crate::enums::BitmapOptions;
+use crate::factory::Factory1;
+use crate::image::{Bitmap1, IBitmap, IBitmap1};
+use crate::record::BitmapData;
+use crate::render_target::IRenderTarget;
+
+use dcommon::Error;
+use direct3d11::enums::CreateDeviceFlags;
+use dxgi::enums::Format;
+use math2d::{Color, Sizeu};
+use winapi::shared::winerror::SUCCEEDED;
+use winapi::um::d2d1_1::{D2D1_MAPPED_RECT, D2D1_MAP_OPTIONS_READ};
+
+/// A device context drawing into a bitmap that can be read back, for
+/// rendering images without a window.
+///
+/// ```no_run
+/// # use direct2d::brush::SolidColorBrush;
+/// # use direct2d::render_target::{IRenderTarget, Offscreen};
+/// # use math2d::Rectf;
+/// let mut offscreen = Offscreen::new((64, 64), 96.0).unwrap();
+/// let pixels = offscreen
+///     .render(|context| {
+///         let brush = SolidColorBrush::create(&*context)
+///             .with_color(0xFF_00_00)
+///             .build()
+///             .unwrap();
+///         context.fill_rectangle(Rectf::new(8.0, 8.0, 56.0, 56.0), &brush);
+///     })
+///     .unwrap();
+/// std::fs::write("square.png", pixels.to_png()).unwrap();
+/// ```
+pub struct Offscreen {
+    context: DeviceContext,
+    target: Bitmap1,
+    readback: Bitmap1,
+    size: Sizeu,
+}
+
+impl Offscreen {
+    /// Creates a device and a premultiplied BGRA target `size` pixels big,
+    /// drawn to at `dpi`.
+    pub fn new(size: impl Into<Sizeu>, dpi: f32) -> Result<Offscreen, Error> {
+        let size = size.into();
+        let factory = Factory1::new()?;
+        let (_, d3d, _) = direct3d11::device::Device::create()
+            .with_flags(CreateDeviceFlags::BGRA_SUPPORT)
+            .build()?;
+        let device = Device::create(&factory, &d3d.as_dxgi())?;
+        let mut context = DeviceContext::create(&device)?;
+
+        let blank = vec![0; size.width as usize * size.height as usize * 4];
+        let target = Bitmap1::create(&context)
+            .with_format(Format::B8G8R8A8Unorm)
+            .with_image_data(size, &blank, size.width * 4)
+            .with_dpi(dpi, dpi)
+            .with_options(BitmapOptions::TARGET)
+            .build()?;
+        let readback = Bitmap1::create(&context)
+            .with_format(Format::B8G8R8A8Unorm)
+            .with_image_data(size, &blank, size.width * 4)
+            .with_dpi(dpi, dpi)
+            .with_options(BitmapOptions::CPU_READ | BitmapOptions::CANNOT_DRAW)
+            .build()?;
+
+        context.set_dpi(dpi, dpi);
+        context.set_target(&target);
+        Ok(Offscreen {
+            context,
+            target,
+            readback,
+            size,
+        })
+    }
+
+    pub fn context(&self) -> &DeviceContext {
+        &self.context
+    }
+
+    /// Clears the target to transparent, lets `draw` draw on it between
+    /// `begin_draw` and `end_draw`, and reads back the result.
+    pub fn render(&mut self, draw: impl FnOnce(&mut DeviceContext)) -> Result<BitmapData, Error> {
+        self.context.begin_draw();
+        self.context.clear(Color::new(0.0, 0.0, 0.0, 0.0));
+        draw(&mut self.context);
+        self.context.end_draw().map_err(|(error, _)| error)?;
+        self.read_back()
+    }
+
+    fn read_back(&mut self) -> Result<BitmapData, Error> {
+        let row = self.size.width as usize * 4;
+        let mut pixels = Vec::with_capacity(row * self.size.height as usize);
+        unsafe {
+            let hr = self.readback.raw_bitmap().CopyFromBitmap(
+                std::ptr::null(),
+                self.target.raw_bitmap() as *const _ as *mut _,
+                std::ptr::null(),
+            );
+            if !SUCCEEDED(hr) {
+                return Err(hr.into());
+            }
+
+            let mut mapped: D2D1_MAPPED_RECT = std::mem::zeroed();
+            let hr = self
+                .readback
+                .raw_bitmap1()
+                .Map(D2D1_MAP_OPTIONS_READ, &mut mapped);
+            if !SUCCEEDED(hr) {
+                return Err(hr.into());
+            }
+            for y in 0..self.size.height as usize {
+                let start = mapped.bits.add(y * mapped.pitch as usize);
+                pixels.extend_from_slice(std::slice::from_raw_parts(start, row));
+            }
+            self.readback.raw_bitmap1().Unmap();
+        }
+        Ok(BitmapData::new(self.size.width, self.size.height, pixels))
+    }
+}
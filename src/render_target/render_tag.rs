@@ -1,36 +1,219 @@
-#[derive(Copy, Clone, Debug)]
+//! Tags which identify the draw call that made `end_draw` or `flush` fail.
+//!
+//! Direct2D only stores two `u64`s per tag, so the locations are kept in a
+//! process-wide table and the tag holds an index into it. Tags set by other
+//! components sharing the target don't point into the table, and are reported
+//! as no tag at all.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct RenderTag {
     pub loc: &'static str,
+    /// Extra context for the draw call, such as a frame number or widget id.
+    pub payload: Option<u64>,
 }
 
 impl std::fmt::Display for RenderTag {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(fmt, "'{}'", self.loc)
+        write!(fmt, "'{}'", self.loc)?;
+        if let Some(payload) = self.payload {
+            write!(fmt, " ({})", payload)?;
+        }
+        Ok(())
     }
 }
 
 impl RenderTag {
+    pub const fn new(loc: &'static str) -> RenderTag {
+        RenderTag { loc, payload: None }
+    }
+
+    pub fn with_payload(mut self, payload: u64) -> RenderTag {
+        self.payload = Some(payload);
+        self
+    }
+
+    /// The tag's entry in the location table, registering it if needed.
+    pub fn id(&self) -> TagId {
+        TagId::register(self.loc)
+    }
+
+    /// Encodes the tag as the two values passed to `SetTags`.
     pub fn to_raw(&self) -> (u64, u64) {
-        let ptr = self.loc.as_ptr() as usize;
-        let len = self.loc.len();
-        (ptr as u64, len as u64)
+        let mut tag1 = TAG_MAGIC << 32 | (self.id().0 as u64 + 1);
+        if self.payload.is_some() {
+            tag1 |= HAS_PAYLOAD;
+        }
+        (tag1, self.payload.unwrap_or(0))
     }
 
-    pub unsafe fn from_raw(tag1: u64, tag2: u64) -> RenderTag {
-        let ptr = tag1 as usize as *const u8;
-        let len = tag2 as usize;
-        RenderTag {
-            loc: std::str::from_utf8_unchecked(std::slice::from_raw_parts(ptr, len)),
+    /// Decodes values returned from `GetTags`, `EndDraw` or `Flush`. Returns
+    /// `None` for values which weren't produced by `to_raw`.
+    pub fn from_raw(tag1: u64, tag2: u64) -> Option<RenderTag> {
+        if tag1 >> 32 != TAG_MAGIC {
+            return None;
         }
+        let index = (tag1 & INDEX_MASK).checked_sub(1)? as u32;
+        let loc = table().locs.get(index as usize).cloned()?;
+        Some(RenderTag {
+            loc,
+            payload: if tag1 & HAS_PAYLOAD != 0 {
+                Some(tag2)
+            } else {
+                None
+            },
+        })
+    }
+}
+
+const TAG_MAGIC: u64 = 0x6432_6474;
+const HAS_PAYLOAD: u64 = 1 << 31;
+const INDEX_MASK: u64 = HAS_PAYLOAD - 1;
+
+/// A safe index into the table of registered tag locations.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TagId(u32);
+
+impl TagId {
+    /// Adds `loc` to the table. Registering the same location again returns
+    /// the same id.
+    pub fn register(loc: &'static str) -> TagId {
+        let mut table = table();
+        let TagTable { locs, ids } = &mut *table;
+        let ids = ids.get_or_insert_with(HashMap::new);
+        let id = *ids.entry(loc).or_insert_with(|| {
+            assert!((locs.len() as u64) < INDEX_MASK, "too many render tags");
+            locs.push(loc);
+            locs.len() as u32 - 1
+        });
+        TagId(id)
+    }
+
+    pub fn index(self) -> u32 {
+        self.0
+    }
+
+    pub fn loc(self) -> &'static str {
+        table().locs[self.0 as usize]
     }
 }
 
+struct TagTable {
+    locs: Vec<&'static str>,
+    ids: Option<HashMap<&'static str, u32>>,
+}
+
+static TABLE: Mutex<TagTable> = Mutex::new(TagTable {
+    locs: Vec::new(),
+    ids: None,
+});
+
+fn table() -> MutexGuard<'static, TagTable> {
+    // The table is only ever appended to, so it's still usable after a panic.
+    TABLE.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// A ring buffer of the most recent tags set on a render target during the
+/// current frame. It's disabled (a capacity of 0) until enabled with
+/// `IRenderTarget::enable_breadcrumbs`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Breadcrumbs {
+    capacity: usize,
+    tags: VecDeque<RenderTag>,
+}
+
+impl Breadcrumbs {
+    pub fn new(capacity: usize) -> Breadcrumbs {
+        Breadcrumbs {
+            capacity,
+            tags: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity != 0
+    }
+
+    /// Changes how many tags are kept, dropping the oldest if there are too
+    /// many. A capacity of 0 disables recording.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.tags.len() > capacity {
+            self.tags.pop_front();
+        }
+    }
+
+    /// Records a tag, dropping the oldest one if the buffer is full.
+    pub fn push(&mut self, tag: RenderTag) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.tags.len() == self.capacity {
+            self.tags.pop_front();
+        }
+        self.tags.push_back(tag);
+    }
+
+    pub fn clear(&mut self) {
+        self.tags.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// The most recently recorded tag.
+    pub fn last(&self) -> Option<&RenderTag> {
+        self.tags.back()
+    }
+
+    /// The recorded tags, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &RenderTag> {
+        self.tags.iter()
+    }
+
+    pub fn to_vec(&self) -> Vec<RenderTag> {
+        self.tags.iter().cloned().collect()
+    }
+}
+
+impl fmt::Display for Breadcrumbs {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt_trail(fmt, self.tags.iter())
+    }
+}
+
+pub(crate) fn fmt_trail<'a>(
+    fmt: &mut fmt::Formatter,
+    tags: impl Iterator<Item = &'a RenderTag>,
+) -> fmt::Result {
+    for (i, tag) in tags.enumerate() {
+        if i != 0 {
+            fmt.write_str("\n")?;
+        }
+        write!(fmt, "{:>4}: {}", i, tag)?;
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
 pub(crate) fn fmt_tag(tag: Option<RenderTag>) -> impl std::fmt::Display {
     struct DRT(Option<RenderTag>);
     impl std::fmt::Display for DRT {
         fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
             match self.0 {
-                Some(tag) => write!(fmt, "'{}'", tag.loc),
+                Some(tag) => write!(fmt, "{}", tag),
                 None => fmt.write_str("None"),
             }
         }
@@ -42,15 +225,15 @@ pub(crate) fn fmt_tag(tag: Option<RenderTag>) -> impl std::fmt::Display {
 #[doc(hidden)]
 macro_rules! make_render_tag {
     () => {
-        $crate::render_target::RenderTag {
-            loc: concat!(file!(), ':', line!()),
-        }
+        $crate::render_target::RenderTag::new(concat!(file!(), ':', line!()))
     };
 }
 
+#[cfg(windows)]
 #[macro_export]
 /// Use this to set a checkpoint that will be returned if flush() or end_draw() returns an
-/// error to help debug which part of the drawing code is causing the error.
+/// error to help debug which part of the drawing code is causing the error. A second
+/// argument attaches a `u64` payload to the tag, such as a frame number or widget id.
 ///
 /// ```
 /// # use direct2d::set_render_tag;
@@ -72,13 +255,13 @@ macro_rules! make_render_tag {
 ///     set_render_tag!(context);
 ///     context.draw_line((10.0, 10.0).into(), (20.0, 20.0).into(), &brush, 2.0, None);
 ///
-///     set_render_tag!(context);
+///     set_render_tag!(context, 42);
 ///     context.draw_line((10.0, 20.0).into(), (20.0, 10.0).into(), &brush, 2.0, None);
 ///
 ///     match context.end_draw() {
 ///         Ok(_) => {/* cool */},
 ///         Err((err, Some(tag))) => {
-///             panic!("Uh oh, rendering failed at {}: {}", tag.loc, err);
+///             panic!("Uh oh, rendering failed at {}: {}", tag, err);
 ///         }
 ///         Err((err, None)) => {
 ///             panic!("Uh oh, rendering failed at an unknown location: {}", err);
@@ -116,4 +299,10 @@ macro_rules! set_render_tag {
     ($rt:expr) => {
         $crate::render_target::IRenderTarget::set_tag($rt, Some($crate::make_render_tag!()));
     };
+    ($rt:expr, $payload:expr) => {
+        $crate::render_target::IRenderTarget::set_tag(
+            $rt,
+            Some($crate::make_render_tag!().with_payload($payload)),
+        );
+    };
 }
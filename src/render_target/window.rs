@@ -0,0 +1,119 @@
+//! Getting an `HWND` out of windowing libraries which implement
+//! `raw_window_handle::HasRawWindowHandle`, such as winit and SDL.
+
+use crate::error::HRESULT;
+
+use std::fmt;
+use std::os::raw::c_void;
+
+use raw_window_handle::RawWindowHandle;
+
+/// Why a window handle can't be used to create an `HwndRenderTarget`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WindowHandleError {
+    /// The window belongs to another platform; the name of its handle kind is
+    /// included.
+    NotWin32(&'static str),
+    /// The handle's `HWND` is null.
+    NullHandle,
+    /// The `HWND` doesn't refer to a live window, most likely because it has
+    /// already been destroyed.
+    InvalidWindow,
+}
+
+impl WindowHandleError {
+    /// An HRESULT for callers that report errors as one. Null and destroyed
+    /// windows map to the same code Direct2D itself returns for a bad `HWND`.
+    pub fn hresult(self) -> HRESULT {
+        match self {
+            // HRESULT_FROM_WIN32(ERROR_NOT_SUPPORTED)
+            WindowHandleError::NotWin32(_) => 0x8007_0032u32 as i32,
+            // HRESULT_FROM_WIN32(ERROR_INVALID_WINDOW_HANDLE)
+            WindowHandleError::NullHandle | WindowHandleError::InvalidWindow => {
+                0x8007_0578u32 as i32
+            }
+        }
+    }
+}
+
+impl fmt::Display for WindowHandleError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WindowHandleError::NotWin32(kind) => {
+                write!(fmt, "{} windows can't be drawn to by Direct2D", kind)
+            }
+            WindowHandleError::NullHandle => fmt.write_str("the window handle is null"),
+            WindowHandleError::InvalidWindow => {
+                fmt.write_str("the window handle doesn't refer to an existing window")
+            }
+        }
+    }
+}
+
+impl std::error::Error for WindowHandleError {}
+
+/// A readable name for the platform a handle comes from.
+pub fn handle_kind(handle: &RawWindowHandle) -> &'static str {
+    match handle {
+        RawWindowHandle::Win32(_) => "Win32",
+        RawWindowHandle::WinRt(_) => "WinRT",
+        RawWindowHandle::Xlib(_) => "Xlib",
+        RawWindowHandle::Xcb(_) => "Xcb",
+        RawWindowHandle::Wayland(_) => "Wayland",
+        RawWindowHandle::AppKit(_) => "AppKit",
+        RawWindowHandle::UiKit(_) => "UIKit",
+        RawWindowHandle::Orbital(_) => "Orbital",
+        RawWindowHandle::Web(_) => "Web",
+        RawWindowHandle::AndroidNdk(_) => "Android",
+        _ => "Unknown",
+    }
+}
+
+/// Pulls the `HWND` out of `handle`, checking that it's a Win32 handle, isn't
+/// null and that `is_window` accepts it. On Windows `is_window` should be
+/// `IsWindow`; `hwnd_from_handle` does that for you.
+pub fn validate_handle(
+    handle: RawWindowHandle,
+    is_window: impl FnOnce(*mut c_void) -> bool,
+) -> Result<*mut c_void, WindowHandleError> {
+    let hwnd = match handle {
+        RawWindowHandle::Win32(handle) => handle.hwnd,
+        other => return Err(WindowHandleError::NotWin32(handle_kind(&other))),
+    };
+
+    if hwnd.is_null() {
+        Err(WindowHandleError::NullHandle)
+    } else if !is_window(hwnd) {
+        Err(WindowHandleError::InvalidWindow)
+    } else {
+        Ok(hwnd)
+    }
+}
+
+/// Pulls a live `HWND` out of `handle`.
+#[cfg(windows)]
+pub fn hwnd_from_handle(
+    handle: RawWindowHandle,
+) -> Result<winapi::shared::windef::HWND, WindowHandleError> {
+    use winapi::um::winuser::IsWindow;
+
+    validate_handle(handle, |hwnd| unsafe { IsWindow(hwnd as _) != 0 }).map(|hwnd| hwnd as _)
+}
+
+/// The size of the window's client area in pixels, which is what a render
+/// target for it should start out as.
+#[cfg(windows)]
+pub fn client_pixel_size(hwnd: winapi::shared::windef::HWND) -> Option<math2d::Sizeu> {
+    use winapi::um::winuser::GetClientRect;
+
+    unsafe {
+        let mut rect = std::mem::zeroed();
+        if GetClientRect(hwnd, &mut rect) == 0 {
+            return None;
+        }
+        Some(math2d::Sizeu {
+            width: (rect.right - rect.left).max(0) as u32,
+            height: (rect.bottom - rect.top).max(0) as u32,
+        })
+    }
+}
@@ -0,0 +1,265 @@
+//! Keeps device-dependent resources alive across device loss.
+//!
+//! Everything created from a render target (brushes, bitmaps, layers, gradient
+//! stop collections) becomes useless once `end_draw` or `flush` reports that the
+//! target has to be recreated. A `ResourceManager` holds a recipe for each
+//! resource instead of the resource itself, so it can throw them all away and
+//! build them again on the replacement target. Callers hold on to `Handle`s,
+//! which stay valid across rebuilds.
+
+use crate::error::D2dError;
+#[cfg(windows)]
+use crate::render_target::IRenderTarget;
+
+use std::any::Any;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+#[cfg(windows)]
+use dcommon::Error;
+
+pub use crate::error::{
+    D2DERR_RECREATE_TARGET, DXGI_ERROR_DEVICE_HUNG, DXGI_ERROR_DEVICE_REMOVED,
+    DXGI_ERROR_DEVICE_RESET, DXGI_ERROR_DRIVER_INTERNAL_ERROR,
+};
+
+/// What to do about a failed `end_draw` or `flush`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Recovery {
+    /// Recreate the render target and rebuild its resources.
+    RecreateTarget,
+    /// The underlying D3D device is gone. Recreate it, then the Direct2D
+    /// device and target, and rebuild their resources.
+    RecreateDevice,
+    /// Not something that recreating resources will fix.
+    Fatal,
+}
+
+impl Recovery {
+    /// Whether the error is recovered from by rebuilding resources.
+    pub fn is_recoverable(self) -> bool {
+        self != Recovery::Fatal
+    }
+}
+
+/// Decides how to recover from the HRESULT returned by `end_draw` or `flush`.
+pub fn classify(hr: i32) -> Recovery {
+    match D2dError::from(hr) {
+        D2dError::RecreateTarget => Recovery::RecreateTarget,
+        error if error.is_recoverable() => Recovery::RecreateDevice,
+        _ => Recovery::Fatal,
+    }
+}
+
+/// A stable reference to a resource owned by a `ResourceManager`.
+pub struct Handle<R> {
+    index: usize,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> Handle<R> {
+    fn new(index: usize) -> Self {
+        Handle {
+            index,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R> Clone for Handle<R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R> Copy for Handle<R> {}
+
+impl<R> PartialEq for Handle<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<R> Eq for Handle<R> {}
+
+impl<R> Hash for Handle<R> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state)
+    }
+}
+
+impl<R> fmt::Debug for Handle<R> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("Handle").field(&self.index).finish()
+    }
+}
+
+type Recipe<T, E> = Box<dyn Fn(&T) -> Result<Box<dyn Any>, E>>;
+
+struct Slot<T: ?Sized, E> {
+    recipe: Recipe<T, E>,
+    resource: Option<Box<dyn Any>>,
+}
+
+/// Owns recipes for resources created from a `T` (usually a render target),
+/// builds them on demand, and rebuilds them after the target is recreated.
+pub struct ResourceManager<T: ?Sized, E> {
+    slots: Vec<Option<Slot<T, E>>>,
+    generation: u64,
+}
+
+/// A `ResourceManager` for resources created from any render target.
+///
+/// ```no_run
+/// # use direct2d::brush::SolidColorBrush;
+/// # use direct2d::render_target::{IRenderTarget, HwndRenderTarget};
+/// # use direct2d::resource::manager::{Handle, Recovery, TargetResources};
+/// # use math2d::Rectf;
+/// fn paint(
+///     rt: &mut HwndRenderTarget,
+///     resources: &mut TargetResources,
+///     accent: Handle<SolidColorBrush>,
+/// ) -> Result<(), Recovery> {
+///     resources.ensure(&*rt).map_err(|err| resources.handle_error(&err))?;
+///
+///     rt.begin_draw();
+///     rt.fill_rectangle(Rectf::new(0.0, 0.0, 10.0, 10.0), resources.get(accent).unwrap());
+///     // On failure the resources are already invalidated; recreate `rt` if the
+///     // error says so and they'll be rebuilt on the next frame.
+///     rt.end_draw().map_err(|(err, _)| resources.handle_error(&err))
+/// }
+/// # fn main() {
+/// #     let mut resources = TargetResources::new();
+/// #     let _accent = resources.register(|rt| SolidColorBrush::new(rt, 0x00_78_D7));
+/// # }
+/// ```
+#[cfg(windows)]
+pub type TargetResources = ResourceManager<dyn IRenderTarget, Error>;
+
+impl<T: ?Sized, E> ResourceManager<T, E> {
+    pub fn new() -> Self {
+        ResourceManager {
+            slots: Vec::new(),
+            generation: 0,
+        }
+    }
+
+    /// Adds a recipe. The resource isn't built until the next `ensure`.
+    pub fn register<R, F>(&mut self, recipe: F) -> Handle<R>
+    where
+        R: 'static,
+        F: Fn(&T) -> Result<R, E> + 'static,
+    {
+        let recipe: Recipe<T, E> =
+            Box::new(move |target| recipe(target).map(|r| Box::new(r) as Box<dyn Any>));
+        self.slots.push(Some(Slot {
+            recipe,
+            resource: None,
+        }));
+        Handle::new(self.slots.len() - 1)
+    }
+
+    /// Removes a recipe, returning the resource if it was built. The handle
+    /// is never reused.
+    pub fn unregister<R: 'static>(&mut self, handle: Handle<R>) -> Option<R> {
+        let slot = self.slots.get_mut(handle.index)?.take()?;
+        slot.resource
+            .and_then(|r| r.downcast::<R>().ok())
+            .map(|r| *r)
+    }
+
+    /// The resource for `handle`, if it has been built since the last invalidation.
+    pub fn get<R: 'static>(&self, handle: Handle<R>) -> Option<&R> {
+        self.slot(handle.index)?
+            .resource
+            .as_ref()?
+            .downcast_ref::<R>()
+    }
+
+    /// The resource for `handle`, if it has been built since the last invalidation.
+    pub fn get_mut<R: 'static>(&mut self, handle: Handle<R>) -> Option<&mut R> {
+        self.slots
+            .get_mut(handle.index)?
+            .as_mut()?
+            .resource
+            .as_mut()?
+            .downcast_mut::<R>()
+    }
+
+    /// Whether `handle` refers to a registered recipe.
+    pub fn contains<R>(&self, handle: Handle<R>) -> bool {
+        self.slot(handle.index).is_some()
+    }
+
+    /// Whether the resource for `handle` is currently built.
+    pub fn is_built<R>(&self, handle: Handle<R>) -> bool {
+        self.slot(handle.index)
+            .map(|slot| slot.resource.is_some())
+            .unwrap_or(false)
+    }
+
+    /// The number of registered recipes.
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Incremented every time the resources are invalidated, so callers can
+    /// tell whether anything they derived from them is stale.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Builds every resource which isn't built yet. Resources built before an
+    /// error are kept, so calling this again only retries what's missing.
+    pub fn ensure(&mut self, target: &T) -> Result<(), E> {
+        for slot in self.slots.iter_mut().flatten() {
+            if slot.resource.is_none() {
+                slot.resource = Some((slot.recipe)(target)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops every built resource, keeping the recipes. Call this when the
+    /// target or device is recreated, then `ensure` with the new target.
+    pub fn invalidate(&mut self) {
+        for slot in self.slots.iter_mut().flatten() {
+            slot.resource = None;
+        }
+        self.generation += 1;
+    }
+
+    /// Classifies a failed `end_draw` or `flush`, and invalidates the
+    /// resources if the target has to be recreated.
+    pub fn handle_hresult(&mut self, hr: i32) -> Recovery {
+        let recovery = classify(hr);
+        if recovery.is_recoverable() {
+            self.invalidate();
+        }
+        recovery
+    }
+
+    fn slot(&self, index: usize) -> Option<&Slot<T, E>> {
+        self.slots.get(index)?.as_ref()
+    }
+}
+
+#[cfg(windows)]
+impl<T: ?Sized> ResourceManager<T, Error> {
+    /// Classifies the error from a failed `end_draw` or `flush`, and
+    /// invalidates the resources if the target has to be recreated.
+    pub fn handle_error(&mut self, error: &Error) -> Recovery {
+        self.handle_hresult(error.0)
+    }
+}
+
+impl<T: ?Sized, E> Default for ResourceManager<T, E> {
+    fn default() -> Self {
+        ResourceManager::new()
+    }
+}
@@ -1,9 +1,18 @@
+#[cfg(windows)]
 use crate::factory::Factory;
 
+#[cfg(windows)]
 use com_wrapper::ComWrapper;
+#[cfg(windows)]
 use winapi::um::d2d1::ID2D1Resource;
+#[cfg(windows)]
 use wio::com::ComPtr;
 
+pub use self::manager::{Handle, Recovery, ResourceManager};
+
+pub mod manager;
+
+#[cfg(windows)]
 #[repr(transparent)]
 #[derive(ComWrapper)]
 #[com(send, sync, debug)]
@@ -11,6 +20,7 @@ pub struct Resource {
     ptr: ComPtr<ID2D1Resource>,
 }
 
+#[cfg(windows)]
 pub unsafe trait IResource {
     fn factory(&self) -> Factory {
         unsafe {
@@ -23,6 +33,7 @@ pub unsafe trait IResource {
     unsafe fn raw_resource(&self) -> &ID2D1Resource;
 }
 
+#[cfg(windows)]
 unsafe impl IResource for Resource {
     unsafe fn raw_resource(&self) -> &ID2D1Resource {
         &self.ptr
@@ -0,0 +1,196 @@
+use crate::brush::{Brush, BrushType};
+use crate::enums::{BitmapInterpolationMode, DrawTextOptions};
+use crate::geometry::{Geometry, GeometryType, IGeometry};
+use crate::image::{Bitmap, IBitmap};
+use crate::render_target::dirty_region::union;
+use crate::render_target::IRenderTarget;
+use crate::scene::{DrawContent, SceneContent};
+use crate::stroke_style::StrokeStyle;
+
+use com_wrapper::ComWrapper;
+use directwrite::TextLayout;
+use math2d::{Point2f, Rectf};
+use winapi::shared::winerror::SUCCEEDED;
+
+/// The outline of a `Content::Geometry`.
+#[derive(Clone)]
+pub struct Stroke {
+    pub brush: Brush,
+    pub width: f32,
+    pub style: Option<StrokeStyle>,
+}
+
+/// The things a scene node can draw with Direct2D.
+pub enum Content {
+    Geometry {
+        geometry: Geometry,
+        fill: Option<Brush>,
+        stroke: Option<Stroke>,
+    },
+    Bitmap {
+        bitmap: Bitmap,
+        dest: Rectf,
+        opacity: f32,
+        interpolation: BitmapInterpolationMode,
+    },
+    Text {
+        layout: TextLayout,
+        origin: Point2f,
+        brush: Brush,
+        options: DrawTextOptions,
+    },
+}
+
+impl Content {
+    pub fn fill(geometry: &impl GeometryType, brush: &impl BrushType) -> Content {
+        Content::Geometry {
+            geometry: geometry.to_generic(),
+            fill: Some(brush.to_generic()),
+            stroke: None,
+        }
+    }
+
+    pub fn stroke(geometry: &impl GeometryType, brush: &impl BrushType, width: f32) -> Content {
+        Content::Geometry {
+            geometry: geometry.to_generic(),
+            fill: None,
+            stroke: Some(Stroke {
+                brush: brush.to_generic(),
+                width,
+                style: None,
+            }),
+        }
+    }
+
+    /// Draws the whole of `bitmap` into `dest`.
+    pub fn bitmap(bitmap: Bitmap, dest: Rectf) -> Content {
+        Content::Bitmap {
+            bitmap,
+            dest,
+            opacity: 1.0,
+            interpolation: BitmapInterpolationMode::Linear,
+        }
+    }
+
+    pub fn text(layout: &TextLayout, origin: Point2f, brush: &impl BrushType) -> Content {
+        Content::Text {
+            layout: layout.clone(),
+            origin,
+            brush: brush.to_generic(),
+            options: DrawTextOptions::NONE,
+        }
+    }
+}
+
+impl SceneContent for Content {
+    fn bounds(&self) -> Option<Rectf> {
+        match self {
+            Content::Geometry {
+                geometry,
+                fill,
+                stroke,
+            } => {
+                let filled = fill.as_ref().and_then(|_| geometry.bounds(None).ok());
+                let stroked = stroke.as_ref().and_then(|s| {
+                    geometry
+                        .widened_bounds(s.width, s.style.as_ref(), None)
+                        .ok()
+                });
+                match (filled, stroked) {
+                    (Some(a), Some(b)) => Some(union(&a, &b)),
+                    (a, b) => a.or(b),
+                }
+            }
+            Content::Bitmap { dest, .. } => Some(*dest),
+            Content::Text { layout, origin, .. } => unsafe {
+                let layout = &*layout.get_raw();
+                let mut metrics = std::mem::zeroed();
+                if !SUCCEEDED(layout.GetMetrics(&mut metrics)) {
+                    return None;
+                }
+                // Overhangs are how far the ink reaches out of the layout box.
+                let mut overhang = std::mem::zeroed();
+                if !SUCCEEDED(layout.GetOverhangMetrics(&mut overhang)) {
+                    return None;
+                }
+                Some(Rectf {
+                    left: origin.x - overhang.left,
+                    top: origin.y - overhang.top,
+                    right: origin.x + metrics.layoutWidth + overhang.right,
+                    bottom: origin.y + metrics.layoutHeight + overhang.bottom,
+                })
+            },
+        }
+    }
+
+    fn contains(&self, point: Point2f) -> bool {
+        match self {
+            Content::Geometry {
+                geometry,
+                fill,
+                stroke,
+            } => {
+                let in_fill =
+                    fill.is_some() && geometry.fill_contains_point(point, None).unwrap_or(false);
+                let in_stroke = match stroke {
+                    Some(s) => geometry
+                        .stroke_contains_point(point, s.width, s.style.as_ref(), None)
+                        .unwrap_or(false),
+                    None => false,
+                };
+                in_fill || in_stroke
+            }
+            _ => match self.bounds() {
+                Some(b) => {
+                    point.x >= b.left && point.x < b.right && point.y >= b.top && point.y < b.bottom
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+impl DrawContent for Content {
+    fn draw(&self, target: &mut dyn IRenderTarget) {
+        match self {
+            Content::Geometry {
+                geometry,
+                fill,
+                stroke,
+            } => {
+                if let Some(fill) = fill {
+                    target.fill_geometry(geometry, fill);
+                }
+                if let Some(stroke) = stroke {
+                    target.draw_geometry(
+                        geometry,
+                        &stroke.brush,
+                        stroke.width,
+                        stroke.style.as_ref(),
+                    );
+                }
+            }
+            Content::Bitmap {
+                bitmap,
+                dest,
+                opacity,
+                interpolation,
+            } => {
+                let size = bitmap.size();
+                let src = Rectf {
+                    left: 0.0,
+                    top: 0.0,
+                    right: size.width,
+                    bottom: size.height,
+                };
+                target.draw_bitmap(bitmap, *dest, *opacity, *interpolation, src);
+            }
+            Content::Text {
+                layout,
+                origin,
+                brush,
+                options,
+            } => target.draw_text_layout(*origin, layout, brush, *options),
+        }
+    }
+}
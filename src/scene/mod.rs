@@ -0,0 +1,19 @@
+//! A retained tree of things to draw.
+//!
+//! Each node has a transform, opacity, clip and optional content, and draws
+//! its content before its children. The tree itself (world transforms,
+//! bounds, hit-testing and dirty tracking) works with any `SceneContent`, so
+//! it can be used and tested without a render target; `Content` and
+//! `SceneRenderer` draw it with Direct2D.
+
+#[cfg(windows)]
+pub use self::content::{Content, Stroke};
+#[cfg(windows)]
+pub use self::render::{DrawContent, SceneRenderer};
+pub use self::tree::{Node, NodeId, Scene, SceneContent};
+
+#[cfg(windows)]
+pub mod content;
+#[cfg(windows)]
+pub mod render;
+pub mod tree;
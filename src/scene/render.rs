@@ -0,0 +1,91 @@
+use crate::enums::AntialiasMode;
+use crate::layer::{Layer, LayerBuilder};
+use crate::render_target::IRenderTarget;
+use crate::scene::tree::is_axis_aligned;
+use crate::scene::{NodeId, Scene, SceneContent};
+
+use dcommon::Error;
+use math2d::{Matrix3x2f, Rectf};
+
+/// Content that can draw itself, in its node's coordinates.
+pub trait DrawContent: SceneContent {
+    fn draw(&self, target: &mut dyn IRenderTarget);
+}
+
+/// Draws scenes, keeping hold of the layers used for group opacity and
+/// rotated clips between frames. Layers belong to the target they were made
+/// for, so use one renderer per target.
+#[derive(Default)]
+pub struct SceneRenderer {
+    layers: Vec<Layer>,
+}
+
+impl SceneRenderer {
+    pub fn new() -> SceneRenderer {
+        SceneRenderer { layers: Vec::new() }
+    }
+
+    /// Draws the whole scene on top of the target's current transform, which
+    /// is restored afterwards. Must be called between `begin_draw` and
+    /// `end_draw`.
+    pub fn render<C: DrawContent>(
+        &mut self,
+        scene: &Scene<C>,
+        target: &mut dyn IRenderTarget,
+    ) -> Result<(), Error> {
+        let base = target.transform();
+        let result = self.render_node(scene, scene.root(), &base, 0, target);
+        target.set_transform(&base);
+        result
+    }
+
+    fn render_node<C: DrawContent>(
+        &mut self,
+        scene: &Scene<C>,
+        id: NodeId,
+        parent: &Matrix3x2f,
+        depth: usize,
+        target: &mut dyn IRenderTarget,
+    ) -> Result<(), Error> {
+        let node = match scene.node(id) {
+            Some(node) if node.is_drawn() => node,
+            _ => return Ok(()),
+        };
+        let world = node.transform * *parent;
+        target.set_transform(&world);
+
+        // Axis-aligned clips are much cheaper than layers, so they're only
+        // used when the clip is rotated or there's opacity to apply.
+        let layered = node.opacity < 1.0 || (node.clip.is_some() && !is_axis_aligned(&world));
+        if layered {
+            while self.layers.len() <= depth {
+                self.layers.push(Layer::create(&*target, None)?);
+            }
+            LayerBuilder::create(target, &self.layers[depth])
+                .with_content_bounds(node.clip.unwrap_or(Rectf::INFINITE))
+                .with_opacity(node.opacity)
+                .push();
+        } else if let Some(clip) = node.clip {
+            target.push_axis_aligned_clip(clip, AntialiasMode::PerPrimitive);
+        }
+
+        if let Some(content) = &node.content {
+            content.draw(target);
+        }
+        let mut result = Ok(());
+        for &child in scene.children(id) {
+            result = self.render_node(scene, child, &world, depth + 1, target);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        // Popped even after an error so the target's stacks stay balanced.
+        if layered {
+            target.pop_layer();
+        } else if node.clip.is_some() {
+            target.pop_axis_aligned_clip();
+        }
+        result
+    }
+}
@@ -0,0 +1,342 @@
+use crate::render_target::dirty_region::{intersection, transform_bounds, union};
+use crate::render_target::DirtyRegion;
+
+use math2d::{Matrix3x2f, Point2f, Rectf};
+
+/// What a node draws. Implemented by `Content` for real drawing, and by
+/// anything else that can report its bounds and be hit-tested, so the tree
+/// logic can run without a render target.
+pub trait SceneContent {
+    /// The area drawn, in the node's local coordinates, or `None` if nothing
+    /// is drawn.
+    fn bounds(&self) -> Option<Rectf>;
+
+    /// Whether `point`, in the node's local coordinates, lands on the content.
+    fn contains(&self, point: Point2f) -> bool;
+}
+
+/// Identifies a node in a `Scene`. Ids aren't reused after a node is removed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+/// A node's own properties. Its content is drawn first, then its children
+/// in order, all with its transform, opacity and clip applied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node<C> {
+    /// Maps the node's coordinates to its parent's.
+    pub transform: Matrix3x2f,
+    /// Applied to the node and its children as a group.
+    pub opacity: f32,
+    /// Clips the node and its children, in the node's coordinates.
+    pub clip: Option<Rectf>,
+    pub visible: bool,
+    pub content: Option<C>,
+}
+
+impl<C> Node<C> {
+    pub fn new() -> Node<C> {
+        Node {
+            transform: Matrix3x2f::IDENTITY,
+            opacity: 1.0,
+            clip: None,
+            visible: true,
+            content: None,
+        }
+    }
+
+    pub fn with_content(mut self, content: C) -> Self {
+        self.content = Some(content);
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Matrix3x2f) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn with_clip(mut self, clip: Rectf) -> Self {
+        self.clip = Some(clip);
+        self
+    }
+
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    /// Whether the node would draw anything at all.
+    pub fn is_drawn(&self) -> bool {
+        self.visible && self.opacity > 0.0
+    }
+}
+
+impl<C> Default for Node<C> {
+    fn default() -> Node<C> {
+        Node::new()
+    }
+}
+
+struct Slot<C> {
+    node: Node<C>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    changed: bool,
+    /// The node's content bounds when `take_dirty` last ran.
+    drawn_bounds: Option<Rectf>,
+}
+
+/// A tree of nodes, rooted at an empty node which is always present.
+///
+/// Changes made through `node_mut`, `set_transform`, `add` and `remove` are
+/// remembered so `take_dirty` can report the area that has to be redrawn.
+pub struct Scene<C> {
+    slots: Vec<Option<Slot<C>>>,
+    removed: DirtyRegion,
+}
+
+impl<C: SceneContent> Scene<C> {
+    pub fn new() -> Scene<C> {
+        Scene {
+            slots: vec![Some(Slot {
+                node: Node::new(),
+                parent: None,
+                children: Vec::new(),
+                changed: true,
+                drawn_bounds: None,
+            })],
+            removed: DirtyRegion::new(),
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// Adds `node` as the last (topmost) child of `parent`.
+    ///
+    /// # Panics
+    ///
+    /// If `parent` isn't in the scene.
+    pub fn add(&mut self, parent: NodeId, node: Node<C>) -> NodeId {
+        assert!(self.contains(parent), "{:?} isn't in the scene", parent);
+
+        let id = NodeId(self.slots.len());
+        self.slots.push(Some(Slot {
+            node,
+            parent: Some(parent),
+            children: Vec::new(),
+            changed: true,
+            drawn_bounds: None,
+        }));
+        self.slot_mut(parent).unwrap().children.push(id);
+        id
+    }
+
+    /// Removes a node along with everything under it, returning the node.
+    /// The root can't be removed.
+    pub fn remove(&mut self, id: NodeId) -> Option<Node<C>> {
+        let parent = self.slot(id)?.parent?;
+        self.slot_mut(parent).unwrap().children.retain(|&c| c != id);
+
+        let mut stack = vec![id];
+        let mut removed = None;
+        while let Some(next) = stack.pop() {
+            let slot = self.slots[next.0].take().unwrap();
+            if let Some(bounds) = slot.drawn_bounds {
+                self.removed.add(bounds);
+            }
+            stack.extend(slot.children);
+            if next == id {
+                removed = Some(slot.node);
+            }
+        }
+        removed
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.slot(id).is_some()
+    }
+
+    /// The number of nodes, including the root.
+    pub fn node_count(&self) -> usize {
+        self.slots.iter().filter(|s| s.is_some()).count()
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node<C>> {
+        self.slot(id).map(|s| &s.node)
+    }
+
+    /// Gives access to a node's properties, marking it as changed.
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node<C>> {
+        let slot = self.slot_mut(id)?;
+        slot.changed = true;
+        Some(&mut slot.node)
+    }
+
+    pub fn set_transform(&mut self, id: NodeId, transform: Matrix3x2f) {
+        if let Some(node) = self.node_mut(id) {
+            node.transform = transform;
+        }
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.slot(id)?.parent
+    }
+
+    /// The node's children, bottom to top.
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        self.slot(id).map_or(&[], |s| &s.children)
+    }
+
+    /// Maps the node's coordinates to the scene's.
+    pub fn world_transform(&self, id: NodeId) -> Option<Matrix3x2f> {
+        let mut transform = self.node(id)?.transform;
+        let mut next = self.parent(id);
+        while let Some(ancestor) = next {
+            transform = transform * self.slots[ancestor.0].as_ref()?.node.transform;
+            next = self.parent(ancestor);
+        }
+        Some(transform)
+    }
+
+    /// Whether the node and all of its ancestors are drawn.
+    pub fn is_drawn(&self, id: NodeId) -> bool {
+        let mut next = Some(id);
+        while let Some(current) = next {
+            match self.slot(current) {
+                Some(slot) if slot.node.is_drawn() => next = slot.parent,
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// The bounds of the node's own content in scene coordinates, clipped by
+    /// its clip and its ancestors'. `None` if it draws nothing.
+    pub fn content_bounds(&self, id: NodeId) -> Option<Rectf> {
+        if !self.is_drawn(id) {
+            return None;
+        }
+        let local = self.node(id)?.content.as_ref()?.bounds()?;
+        let world = transform_bounds(&local, &self.world_transform(id)?);
+        match self.world_clip(id) {
+            Some(clip) => intersection(&world, &clip),
+            None => Some(world),
+        }
+    }
+
+    /// The bounds of everything the node and its descendants draw, in scene
+    /// coordinates.
+    pub fn world_bounds(&self, id: NodeId) -> Option<Rectf> {
+        let own = self.content_bounds(id);
+        self.children(id)
+            .iter()
+            .filter_map(|&child| self.world_bounds(child))
+            .fold(own, |acc, b| Some(acc.map_or(b, |a| union(&a, &b))))
+    }
+
+    /// The topmost node whose content contains `point`, in scene coordinates.
+    pub fn hit_test(&self, point: Point2f) -> Option<NodeId> {
+        self.hit_node(self.root(), point)
+    }
+
+    /// The area that has to be redrawn because of changes since the last call:
+    /// where changed nodes and their descendants used to be and where they
+    /// are now, plus wherever removed nodes were.
+    pub fn take_dirty(&mut self) -> DirtyRegion {
+        let mut region = std::mem::take(&mut self.removed);
+
+        let mut stack = vec![(self.root(), false)];
+        while let Some((id, parent_changed)) = stack.pop() {
+            let changed = parent_changed || self.slot(id).unwrap().changed;
+            if changed {
+                let bounds = self.content_bounds(id);
+                let slot = self.slot_mut(id).unwrap();
+                let old = std::mem::replace(&mut slot.drawn_bounds, bounds);
+                slot.changed = false;
+                // Even with the same bounds, what's drawn there may differ.
+                region.extend(old);
+                region.extend(bounds);
+            }
+            stack.extend(self.children(id).iter().map(|&c| (c, changed)));
+        }
+        region
+    }
+
+    fn hit_node(&self, id: NodeId, point: Point2f) -> Option<NodeId> {
+        let node = self.node(id)?;
+        if !node.is_drawn() {
+            return None;
+        }
+        let local = self.world_transform(id)?.inverse()?.transform_point(point);
+        if let Some(clip) = node.clip {
+            if !rect_contains(&clip, local) {
+                return None;
+            }
+        }
+
+        for &child in self.children(id).iter().rev() {
+            if let Some(hit) = self.hit_node(child, point) {
+                return Some(hit);
+            }
+        }
+        match node.content {
+            Some(ref content) if content.contains(local) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// The intersection of the node's clip and its ancestors' clips, in scene
+    /// coordinates.
+    fn world_clip(&self, id: NodeId) -> Option<Rectf> {
+        let mut clip: Option<Rectf> = None;
+        let mut next = Some(id);
+        while let Some(current) = next {
+            let slot = self.slot(current)?;
+            if let Some(local) = slot.node.clip {
+                let world = transform_bounds(&local, &self.world_transform(current)?);
+                clip = Some(match clip {
+                    // An empty intersection clips everything away.
+                    Some(c) => intersection(&c, &world).unwrap_or(Rectf {
+                        left: 0.0,
+                        top: 0.0,
+                        right: 0.0,
+                        bottom: 0.0,
+                    }),
+                    None => world,
+                });
+            }
+            next = slot.parent;
+        }
+        clip
+    }
+
+    fn slot(&self, id: NodeId) -> Option<&Slot<C>> {
+        self.slots.get(id.0)?.as_ref()
+    }
+
+    fn slot_mut(&mut self, id: NodeId) -> Option<&mut Slot<C>> {
+        self.slots.get_mut(id.0)?.as_mut()
+    }
+}
+
+impl<C: SceneContent> Default for Scene<C> {
+    fn default() -> Scene<C> {
+        Scene::new()
+    }
+}
+
+/// Whether `m` keeps rects axis-aligned, so clipping can skip a layer.
+#[cfg(windows)]
+pub(crate) fn is_axis_aligned(m: &Matrix3x2f) -> bool {
+    (m.b == 0.0 && m.c == 0.0) || (m.a == 0.0 && m.d == 0.0)
+}
+
+fn rect_contains(r: &Rectf, p: Point2f) -> bool {
+    p.x >= r.left && p.x < r.right && p.y >= r.top && p.y < r.bottom
+}
@@ -0,0 +1,32 @@
+use std::fmt;
+
+/// Why a scene file couldn't be loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SceneError {
+    /// The text isn't valid JSON or RON. The line and column, counting from
+    /// 1, and a description of the problem are included.
+    Syntax {
+        line: usize,
+        column: usize,
+        message: &'static str,
+    },
+    /// The file parsed, but doesn't describe a valid scene. `at` is the
+    /// path to the offending value, such as `items[2].fill.stops`.
+    Invalid { at: String, message: String },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Syntax {
+                line,
+                column,
+                message,
+            } => write!(fmt, "{} at line {}, column {}", message, line, column),
+            SceneError::Invalid { at, message } if at.is_empty() => fmt.write_str(message),
+            SceneError::Invalid { at, message } => write!(fmt, "{}: {}", at, message),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
@@ -0,0 +1,685 @@
+//! Turns parsed values into a `SceneFile`, checking everything on the way.
+
+use crate::cpu::{GradientRamp, LinearGradient, RadialGradient};
+use crate::descriptions::GradientStop;
+use crate::enums::{CapStyle, DashStyle, ExtendMode, FillMode, Gamma, LineJoin};
+use crate::geometry::PathData;
+use crate::properties::{
+    BrushProperties, LinearGradientBrushProperties, RadialGradientBrushProperties,
+};
+use crate::record::{BrushDesc, Shape, StrokeStyleDesc, TextDesc};
+use crate::scene_file::{Group, Item, SceneError, SceneFile, ShapeItem, TextItem, Value};
+use crate::svg::values::{parse_color, parse_transform};
+use crate::svg::{parse_path, Stroke};
+
+use math2d::{Color, Ellipse, Matrix3x2f, Point2f, Rectf, RoundedRect, Sizef};
+
+/// Used for text that doesn't name a font.
+const DEFAULT_FONT: &str = "Segoe UI";
+const DEFAULT_FONT_SIZE: f32 = 16.0;
+
+pub(crate) fn scene(value: &Value) -> Result<SceneFile, SceneError> {
+    let mut object = Object::new(value, String::new())?;
+    if let Some(name) = object.name {
+        if keyword(name) != "scene" {
+            return Err(invalid("", format!("expected a scene, not `{}`", name)));
+        }
+    }
+
+    let size = match object.get("size") {
+        Some((value, at)) => {
+            let [width, height] = numbers(value, &at)?;
+            if width <= 0.0 || height <= 0.0 {
+                return Err(invalid(&at, "the size must be positive"));
+            }
+            Sizef::new(width, height)
+        }
+        None => return Err(invalid("", "missing `size`")),
+    };
+    let background = match object.get("background") {
+        Some((Value::Null, _)) | None => None,
+        Some((value, at)) => Some(color(value, &at)?),
+    };
+    let items = match object.get("items") {
+        Some((value, at)) => items(value, &at)?,
+        None => Vec::new(),
+    };
+    object.finish()?;
+
+    Ok(SceneFile {
+        size,
+        background,
+        items,
+    })
+}
+
+fn items(value: &Value, at: &str) -> Result<Vec<Item>, SceneError> {
+    list(value, at)?
+        .iter()
+        .enumerate()
+        .map(|(i, value)| item(value, format!("{}[{}]", at, i)))
+        .collect()
+}
+
+fn item(value: &Value, at: String) -> Result<Item, SceneError> {
+    let mut object = Object::new(value, at)?;
+    let kind = object.kind()?;
+    let transform = match object.get("transform") {
+        Some((value, at)) => transform(value, &at)?,
+        None => Matrix3x2f::IDENTITY,
+    };
+
+    let item = match &*kind {
+        "group" => {
+            let opacity = match object.get("opacity") {
+                Some((value, at)) => unit(value, &at)?,
+                None => 1.0,
+            };
+            let clip = match object.get("clip") {
+                Some((Value::String(data), at)) => Some(path(data, &at)?),
+                Some((value, at)) => {
+                    let rect = rect(value, &at)?;
+                    let mut clip = PathData::new();
+                    clip.add_polygon(&[
+                        Point2f::new(rect.left, rect.top),
+                        Point2f::new(rect.right, rect.top),
+                        Point2f::new(rect.right, rect.bottom),
+                        Point2f::new(rect.left, rect.bottom),
+                    ]);
+                    Some(clip)
+                }
+                None => None,
+            };
+            let items = match object.get("items") {
+                Some((value, at)) => items(value, &at)?,
+                None => Vec::new(),
+            };
+            Item::Group(Group {
+                transform,
+                opacity,
+                clip,
+                items,
+            })
+        }
+        "text" => {
+            let text = match object.get("text") {
+                Some((Value::String(text), _)) => text.clone(),
+                Some((value, at)) => return Err(expected(&at, "a string", value)),
+                None => return Err(object.missing("text")),
+            };
+            let position = match object.get("position") {
+                Some((value, at)) => point(value, &at)?,
+                None => Point2f::new(0.0, 0.0),
+            };
+            let font = match object.get("font") {
+                Some((Value::String(font), _)) => font.clone(),
+                Some((value, at)) => return Err(expected(&at, "a font family", value)),
+                None => DEFAULT_FONT.to_string(),
+            };
+            let font_size = match object.get("font_size") {
+                Some((value, at)) => match number(value, &at)? {
+                    size if size > 0.0 => size,
+                    _ => return Err(invalid(&at, "the font size must be positive")),
+                },
+                None => DEFAULT_FONT_SIZE,
+            };
+            let fill = match object.get("fill") {
+                Some((value, at)) => brush(value, &at)?,
+                None => BrushDesc::solid(Color::new(0.0, 0.0, 0.0, 1.0)),
+            };
+            Item::Text(Box::new(TextItem {
+                text: TextDesc::new(text, font, font_size).with_origin(position),
+                transform,
+                fill,
+            }))
+        }
+        _ => {
+            let shape = shape(&kind, &mut object)?;
+            let fill = match object.get("fill") {
+                Some((Value::Null, _)) | None => None,
+                Some((_, at)) if matches!(shape, Shape::Line(..)) => {
+                    return Err(invalid(&at, "lines can't be filled"));
+                }
+                Some((value, at)) => Some(brush(value, &at)?),
+            };
+            let stroke = stroke(&mut object)?;
+            if fill.is_none() && stroke.is_none() {
+                return Err(invalid(&object.at, "needs a `fill` or a `stroke`"));
+            }
+            Item::Shape(Box::new(ShapeItem {
+                shape,
+                transform,
+                fill,
+                stroke,
+            }))
+        }
+    };
+    object.finish()?;
+    Ok(item)
+}
+
+fn shape(kind: &str, object: &mut Object) -> Result<Shape, SceneError> {
+    match kind {
+        "rect" | "rectangle" => {
+            let rect = match object.get("rect") {
+                Some((value, at)) => rect(value, &at)?,
+                None => return Err(object.missing("rect")),
+            };
+            match object.get("radius") {
+                Some((value, at)) => {
+                    let (radius_x, radius_y) = radii(value, &at)?;
+                    Ok(Shape::RoundedRectangle(RoundedRect {
+                        rect,
+                        radius_x,
+                        radius_y,
+                    }))
+                }
+                None => Ok(Shape::Rectangle(rect)),
+            }
+        }
+        "ellipse" | "circle" => {
+            let center = match object.get("center") {
+                Some((value, at)) => point(value, &at)?,
+                None => return Err(object.missing("center")),
+            };
+            let (radius_x, radius_y) = match object.get("radius") {
+                Some((value, at)) => radii(value, &at)?,
+                None => return Err(object.missing("radius")),
+            };
+            Ok(Shape::Ellipse(Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            }))
+        }
+        "line" => {
+            let from = match object.get("from") {
+                Some((value, at)) => point(value, &at)?,
+                None => return Err(object.missing("from")),
+            };
+            let to = match object.get("to") {
+                Some((value, at)) => point(value, &at)?,
+                None => return Err(object.missing("to")),
+            };
+            Ok(Shape::Line(from, to))
+        }
+        "path" => {
+            let data = match object.get("data") {
+                Some((Value::String(data), at)) => path(data, &at)?,
+                Some((value, at)) => return Err(expected(&at, "SVG path data", value)),
+                None => return Err(object.missing("data")),
+            };
+            let fill_mode = match object.get("fill_rule") {
+                Some((value, at)) => match &*keyword_value(value, &at)? {
+                    "nonzero" => FillMode::Winding,
+                    "evenodd" => FillMode::Alternate,
+                    _ => return Err(invalid(&at, "expected `nonzero` or `evenodd`")),
+                },
+                None => FillMode::Winding,
+            };
+            Ok(Shape::Path(data.with_fill_mode(fill_mode)))
+        }
+        _ => Err(invalid(
+            &object.kind_at(),
+            format!("unknown item type `{}`", kind),
+        )),
+    }
+}
+
+fn stroke(object: &mut Object) -> Result<Option<Stroke>, SceneError> {
+    let brush = match object.get("stroke") {
+        Some((Value::Null, _)) | None => None,
+        Some((value, at)) => Some(brush(value, &at)?),
+    };
+    let width = match object.get("stroke_width") {
+        Some((value, at)) => match number(value, &at)? {
+            width if width >= 0.0 => Some(width),
+            _ => return Err(invalid(&at, "the stroke width can't be negative")),
+        },
+        None => None,
+    };
+    let style = match object.get("stroke_style") {
+        Some((value, at)) => Some(stroke_style(value, at)?),
+        None => None,
+    };
+
+    match brush {
+        Some(brush) => Ok(Some(Stroke {
+            brush,
+            width: width.unwrap_or(1.0),
+            style: style.unwrap_or_else(StrokeStyleDesc::new),
+        })),
+        None if width.is_some() || style.is_some() => Err(invalid(
+            &object.at,
+            "a stroke width or style needs a `stroke`",
+        )),
+        None => Ok(None),
+    }
+}
+
+fn stroke_style(value: &Value, at: String) -> Result<StrokeStyleDesc, SceneError> {
+    let mut object = Object::new(value, at)?;
+    let mut style = StrokeStyleDesc::new();
+    if let Some((value, at)) = object.get("cap") {
+        style = style.with_caps(cap(value, &at)?);
+    }
+    if let Some((value, at)) = object.get("start_cap") {
+        style = style.with_start_cap(cap(value, &at)?);
+    }
+    if let Some((value, at)) = object.get("end_cap") {
+        style = style.with_end_cap(cap(value, &at)?);
+    }
+    if let Some((value, at)) = object.get("dash_cap") {
+        style = style.with_dash_cap(cap(value, &at)?);
+    }
+    if let Some((value, at)) = object.get("join") {
+        style = style.with_line_join(match &*keyword_value(value, &at)? {
+            "miter" => LineJoin::Miter,
+            "bevel" => LineJoin::Bevel,
+            "round" => LineJoin::Round,
+            "miterorbevel" => LineJoin::MiterOrBevel,
+            _ => return Err(invalid(&at, "unknown line join")),
+        });
+    }
+    if let Some((value, at)) = object.get("miter_limit") {
+        match number(value, &at)? {
+            limit if limit >= 1.0 => style = style.with_miter_limit(limit),
+            _ => return Err(invalid(&at, "the miter limit must be at least 1")),
+        }
+    }
+    if let Some((value, at)) = object.get("dash") {
+        style = match value {
+            Value::List(_) => {
+                let dashes = list(value, &at)?
+                    .iter()
+                    .enumerate()
+                    .map(|(i, dash)| match number(dash, &format!("{}[{}]", at, i))? {
+                        dash if dash >= 0.0 => Ok(dash),
+                        _ => Err(invalid(
+                            &format!("{}[{}]", at, i),
+                            "dashes can't be negative",
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if dashes.is_empty() {
+                    return Err(invalid(&at, "a dash pattern needs at least one length"));
+                }
+                style.with_dashes(&dashes)
+            }
+            _ => style.with_dash_style(match &*keyword_value(value, &at)? {
+                "solid" => DashStyle::Solid,
+                "dash" => DashStyle::Dash,
+                "dot" => DashStyle::Dot,
+                "dashdot" => DashStyle::DashDot,
+                "dashdotdot" => DashStyle::DashDotDot,
+                _ => return Err(invalid(&at, "unknown dash style")),
+            }),
+        };
+    }
+    if let Some((value, at)) = object.get("dash_offset") {
+        style = style.with_dash_offset(number(value, &at)?);
+    }
+    object.finish()?;
+    Ok(style)
+}
+
+fn cap(value: &Value, at: &str) -> Result<CapStyle, SceneError> {
+    match &*keyword_value(value, at)? {
+        "flat" | "butt" => Ok(CapStyle::Flat),
+        "square" => Ok(CapStyle::Square),
+        "round" => Ok(CapStyle::Round),
+        "triangle" => Ok(CapStyle::Triangle),
+        _ => Err(invalid(at, "unknown cap style")),
+    }
+}
+
+/// A color on its own is a solid brush; anything else is an object with a
+/// `type` of `solid`, `linear` or `radial`.
+fn brush(value: &Value, at: &str) -> Result<BrushDesc, SceneError> {
+    if let Value::String(_) | Value::List(_) = value {
+        return Ok(BrushDesc::solid(color(value, at)?));
+    }
+
+    let mut object = Object::new(value, at.to_string())?;
+    let kind = object.kind()?;
+    let opacity = match object.get("opacity") {
+        Some((value, at)) => unit(value, &at)?,
+        None => 1.0,
+    };
+    let transform = match object.get("transform") {
+        Some((value, at)) => transform(value, &at)?,
+        None => Matrix3x2f::IDENTITY,
+    };
+    let properties = BrushProperties::new(opacity, &transform);
+
+    let brush = match &*kind {
+        "solid" | "color" => {
+            let color = match object.get("color") {
+                Some((value, at)) => color(value, &at)?,
+                None => return Err(object.missing("color")),
+            };
+            BrushDesc::Solid {
+                color,
+                brush: properties,
+            }
+        }
+        "linear" | "lineargradient" => {
+            let ramp = ramp(&mut object)?;
+            let start = match object.get("start") {
+                Some((value, at)) => point(value, &at)?,
+                None => return Err(object.missing("start")),
+            };
+            let end = match object.get("end") {
+                Some((value, at)) => point(value, &at)?,
+                None => return Err(object.missing("end")),
+            };
+            let gradient = LinearGradient::new(ramp, LinearGradientBrushProperties { start, end });
+            BrushDesc::LinearGradient(gradient.with_brush_properties(properties))
+        }
+        "radial" | "radialgradient" => {
+            let ramp = ramp(&mut object)?;
+            let center = match object.get("center") {
+                Some((value, at)) => point(value, &at)?,
+                None => return Err(object.missing("center")),
+            };
+            let (radius_x, radius_y) = match object.get("radius") {
+                Some((value, at)) => radii(value, &at)?,
+                None => return Err(object.missing("radius")),
+            };
+            let origin_offset = match object.get("origin_offset") {
+                Some((value, at)) => point(value, &at)?,
+                None => Point2f::new(0.0, 0.0),
+            };
+            let gradient = RadialGradient::new(
+                ramp,
+                RadialGradientBrushProperties {
+                    center,
+                    origin_offset,
+                    radius_x,
+                    radius_y,
+                },
+            );
+            BrushDesc::RadialGradient(gradient.with_brush_properties(properties))
+        }
+        _ => {
+            return Err(invalid(
+                &object.kind_at(),
+                format!("unknown brush type `{}`", kind),
+            ))
+        }
+    };
+    object.finish()?;
+    Ok(brush)
+}
+
+/// The `stops`, `extend` and `gamma` of a gradient.
+fn ramp(object: &mut Object) -> Result<GradientRamp, SceneError> {
+    let stops = match object.get("stops") {
+        Some((value, at)) => {
+            let values = list(value, &at)?;
+            if values.is_empty() {
+                return Err(invalid(&at, "a gradient needs at least one stop"));
+            }
+            let mut stops = Vec::with_capacity(values.len());
+            for (i, value) in values.iter().enumerate() {
+                stops.push(stop(value, format!("{}[{}]", at, i))?);
+            }
+            stops
+        }
+        None => return Err(object.missing("stops")),
+    };
+    let extend_mode = match object.get("extend") {
+        Some((value, at)) => match &*keyword_value(value, &at)? {
+            "clamp" | "pad" => ExtendMode::Clamp,
+            "wrap" | "repeat" => ExtendMode::Wrap,
+            "mirror" | "reflect" => ExtendMode::Mirror,
+            _ => return Err(invalid(&at, "unknown extend mode")),
+        },
+        None => ExtendMode::Clamp,
+    };
+    let gamma = match object.get("gamma") {
+        Some((value, at)) => match value {
+            Value::Number(n) if *n == 2.2 => Gamma::_2_2,
+            Value::Number(n) if *n == 1.0 => Gamma::_1_0,
+            // The names the enum is serialized with.
+            Value::String(text) if text == "2.2" => Gamma::_2_2,
+            Value::String(text) if text == "1.0" => Gamma::_1_0,
+            _ => return Err(invalid(&at, "expected a gamma of `2.2` or `1.0`")),
+        },
+        None => Gamma::_2_2,
+    };
+    Ok(GradientRamp::new(&stops, gamma, extend_mode))
+}
+
+/// `[offset, color]` or `{ offset, color }`.
+fn stop(value: &Value, at: String) -> Result<GradientStop, SceneError> {
+    let (position, color) = match value {
+        Value::List(values) if values.len() == 2 => (
+            unit(&values[0], &format!("{}[0]", at))?,
+            color(&values[1], &format!("{}[1]", at))?,
+        ),
+        Value::List(_) => return Err(invalid(&at, "expected `[offset, color]`")),
+        _ => {
+            let mut object = Object::new(value, at)?;
+            let position = match object.get("offset") {
+                Some((value, at)) => unit(value, &at)?,
+                None => return Err(object.missing("offset")),
+            };
+            let color = match object.get("color") {
+                Some((value, at)) => color(value, &at)?,
+                None => return Err(object.missing("color")),
+            };
+            object.finish()?;
+            (position, color)
+        }
+    };
+    Ok(GradientStop { position, color })
+}
+
+/// A CSS color string, or 3 or 4 components from 0 to 1.
+fn color(value: &Value, at: &str) -> Result<Color, SceneError> {
+    match value {
+        Value::String(text) => parse_color(text).ok_or_else(|| invalid(at, "unknown color")),
+        Value::List(values) if values.len() == 3 || values.len() == 4 => {
+            let mut components = [1.0; 4];
+            for (i, value) in values.iter().enumerate() {
+                components[i] = unit(value, &format!("{}[{}]", at, i))?;
+            }
+            let [r, g, b, a] = components;
+            Ok(Color::new(r, g, b, a))
+        }
+        _ => Err(expected(at, "a color", value)),
+    }
+}
+
+/// An SVG transform list, or the six numbers `[a, b, c, d, x, y]`.
+fn transform(value: &Value, at: &str) -> Result<Matrix3x2f, SceneError> {
+    match value {
+        Value::String(text) => {
+            parse_transform(text).ok_or_else(|| invalid(at, "malformed transform"))
+        }
+        Value::List(_) => {
+            let [a, b, c, d, x, y] = numbers(value, at)?;
+            Ok(Matrix3x2f { a, b, c, d, x, y })
+        }
+        _ => Err(expected(at, "a transform", value)),
+    }
+}
+
+fn path(data: &str, at: &str) -> Result<PathData, SceneError> {
+    parse_path(data).map_err(|error| invalid(at, error.to_string()))
+}
+
+/// `[x, y, width, height]`.
+fn rect(value: &Value, at: &str) -> Result<Rectf, SceneError> {
+    let [x, y, width, height] = numbers(value, at)?;
+    if width < 0.0 || height < 0.0 {
+        return Err(invalid(at, "the size can't be negative"));
+    }
+    Ok(Rectf::new(x, y, x + width, y + height))
+}
+
+/// One radius for both axes, or `[x, y]`.
+fn radii(value: &Value, at: &str) -> Result<(f32, f32), SceneError> {
+    let (x, y) = match value {
+        Value::Number(_) => {
+            let radius = number(value, at)?;
+            (radius, radius)
+        }
+        _ => {
+            let [x, y] = numbers(value, at)?;
+            (x, y)
+        }
+    };
+    if x < 0.0 || y < 0.0 {
+        return Err(invalid(at, "a radius can't be negative"));
+    }
+    Ok((x, y))
+}
+
+fn point(value: &Value, at: &str) -> Result<Point2f, SceneError> {
+    let [x, y] = numbers(value, at)?;
+    Ok(Point2f::new(x, y))
+}
+
+fn numbers<const N: usize>(value: &Value, at: &str) -> Result<[f32; N], SceneError> {
+    match value {
+        Value::List(values) if values.len() == N => {
+            let mut numbers = [0.0; N];
+            for (i, value) in values.iter().enumerate() {
+                numbers[i] = number(value, &format!("{}[{}]", at, i))?;
+            }
+            Ok(numbers)
+        }
+        _ => Err(invalid(
+            at,
+            format!("expected a list of {} numbers, found {}", N, value.kind()),
+        )),
+    }
+}
+
+fn number(value: &Value, at: &str) -> Result<f32, SceneError> {
+    match value {
+        Value::Number(n) if n.abs() <= f32::MAX as f64 => Ok(*n as f32),
+        Value::Number(_) => Err(invalid(at, "number out of range")),
+        _ => Err(expected(at, "a number", value)),
+    }
+}
+
+/// A number from 0 to 1.
+fn unit(value: &Value, at: &str) -> Result<f32, SceneError> {
+    match number(value, at)? {
+        n if (0.0..=1.0).contains(&n) => Ok(n),
+        _ => Err(invalid(at, "expected a number from 0 to 1")),
+    }
+}
+
+fn list<'a>(value: &'a Value, at: &str) -> Result<&'a [Value], SceneError> {
+    match value {
+        Value::List(values) => Ok(values),
+        _ => Err(expected(at, "a list", value)),
+    }
+}
+
+fn keyword_value(value: &Value, at: &str) -> Result<String, SceneError> {
+    match value {
+        Value::String(text) => Ok(keyword(text)),
+        _ => Err(expected(at, "a name", value)),
+    }
+}
+
+/// Names compare case-insensitively and ignoring separators, so
+/// `miter_or_bevel`, `miter-or-bevel` and RON's `MiterOrBevel` are the same.
+fn keyword(text: &str) -> String {
+    text.chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' ' | '.'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn invalid(at: &str, message: impl Into<String>) -> SceneError {
+    SceneError::Invalid {
+        at: at.to_string(),
+        message: message.into(),
+    }
+}
+
+fn expected(at: &str, what: &str, found: &Value) -> SceneError {
+    invalid(at, format!("expected {}, found {}", what, found.kind()))
+}
+
+/// The fields of an object, keeping track of which have been read so that
+/// any left over can be reported.
+struct Object<'a> {
+    at: String,
+    name: Option<&'a str>,
+    fields: &'a [(String, Value)],
+    used: Vec<bool>,
+}
+
+impl<'a> Object<'a> {
+    fn new(value: &'a Value, at: String) -> Result<Object<'a>, SceneError> {
+        match value {
+            Value::Object { name, fields } => Ok(Object {
+                at,
+                name: name.as_deref(),
+                fields,
+                used: vec![false; fields.len()],
+            }),
+            _ => Err(expected(&at, "an object", value)),
+        }
+    }
+
+    /// The value of a field and the path to it.
+    fn get(&mut self, key: &str) -> Option<(&'a Value, String)> {
+        let i = self.fields.iter().position(|(k, _)| k == key)?;
+        self.used[i] = true;
+        Some((&self.fields[i].1, self.field_at(key)))
+    }
+
+    /// The `type` field, or the struct name in RON.
+    fn kind(&mut self) -> Result<String, SceneError> {
+        match self.get("type") {
+            Some((Value::String(kind), _)) => Ok(keyword(kind)),
+            Some((value, at)) => Err(expected(&at, "a type name", value)),
+            None => match self.name {
+                Some(name) => Ok(keyword(name)),
+                None => Err(self.missing("type")),
+            },
+        }
+    }
+
+    fn kind_at(&self) -> String {
+        if self.fields.iter().any(|(k, _)| k == "type") {
+            self.field_at("type")
+        } else {
+            self.at.clone()
+        }
+    }
+
+    fn field_at(&self, key: &str) -> String {
+        if self.at.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.at, key)
+        }
+    }
+
+    fn missing(&self, key: &str) -> SceneError {
+        invalid(&self.at, format!("missing `{}`", key))
+    }
+
+    /// Fails if any field wasn't read.
+    fn finish(self) -> Result<(), SceneError> {
+        match self.used.iter().position(|used| !used) {
+            Some(i) => {
+                let key = &self.fields[i].0;
+                Err(invalid(
+                    &self.field_at(key),
+                    format!("unknown field `{}`", key),
+                ))
+            }
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Declarative scenes, read from JSON or RON files.
+//!
+//! A scene has a `size` in DIPs, an optional `background` color and a list
+//! of `items`. Each item has a `type`, or in RON a struct name: `rect`,
+//! `ellipse`, `line`, `path`, `text` or `group`. Any item can have a
+//! `transform`, written in SVG syntax or as `[a, b, c, d, x, y]`.
+//!
+//! ```json
+//! {
+//!     "size": [200, 100],
+//!     "background": "white",
+//!     "items": [
+//!         { "type": "rect", "rect": [10, 10, 80, 80], "radius": 8,
+//!           "fill": { "type": "linear", "start": [10, 10], "end": [90, 90],
+//!                     "stops": [[0, "#3060c0"], [1, "#80c0ff"]] } },
+//!         { "type": "path", "data": "M 110 90 L 150 10 L 190 90 Z",
+//!           "stroke": "black", "stroke_width": 4,
+//!           "stroke_style": { "join": "round", "dash": [2, 1] } },
+//!         { "type": "text", "text": "Hello", "position": [110, 60],
+//!           "font": "Segoe UI", "font_size": 20, "fill": "navy" }
+//!     ]
+//! }
+//! ```
+//!
+//! - `rect` takes `rect: [x, y, width, height]` and an optional `radius`.
+//! - `ellipse` takes a `center` and a `radius`. A radius is one number, or
+//!   `[x, y]`.
+//! - `line` takes `from` and `to`, and can only be stroked.
+//! - `path` takes SVG path `data` and a `fill_rule` of `nonzero` (the
+//!   default) or `evenodd`.
+//! - `text` takes `text`, the top left `position`, `font`, `font_size` and
+//!   a `fill`, which defaults to black.
+//! - `group` takes `items`, an `opacity` and a `clip`, which is either a
+//!   rectangle or SVG path data.
+//!
+//! Shapes need a `fill`, a `stroke` or both. A stroke can have a
+//! `stroke_width` and a `stroke_style` with `cap`, `start_cap`, `end_cap`,
+//! `dash_cap`, `join`, `miter_limit`, `dash` (a style name or a list of
+//! lengths) and `dash_offset`.
+//!
+//! A brush is a color or an object with a `type` of `solid` (with a
+//! `color`), `linear` (with `start` and `end`) or `radial` (with `center`,
+//! `radius` and `origin_offset`). Gradients take `stops` as
+//! `[offset, color]` pairs, an `extend` of `clamp`, `wrap` or `mirror`, and
+//! a `gamma` of `2.2` or `1.0`, as a number or a string. Every brush can
+//! have an `opacity` and a `transform`. Colors are CSS colors or lists of 3
+//! or 4 numbers from 0 to 1.
+//!
+//! Anything unrecognized is an error, reported with the path to it.
+
+pub use self::error::SceneError;
+pub use self::scene::{Group, Item, SceneFile, ShapeItem, TextItem};
+pub use self::value::Value;
+
+pub mod error;
+mod load;
+pub mod scene;
+pub mod value;
@@ -0,0 +1,154 @@
+use crate::geometry::PathData;
+use crate::record::{BrushDesc, DrawOp, LayerDesc, Recording, Shape, TextDesc};
+use crate::scene_file::{load, value, SceneError};
+use crate::svg::Stroke;
+
+#[cfg(windows)]
+use crate::render_target::IRenderTarget;
+#[cfg(windows)]
+use dcommon::Error;
+
+use math2d::{Color, Matrix3x2f, Sizef};
+
+/// A loaded scene file, ready to be drawn.
+#[derive(Clone, Debug)]
+pub struct SceneFile {
+    pub(crate) size: Sizef,
+    pub(crate) background: Option<Color>,
+    pub(crate) items: Vec<Item>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Item {
+    Group(Group),
+    Shape(Box<ShapeItem>),
+    Text(Box<TextItem>),
+}
+
+/// Items drawn together, composited with an opacity and clip as a layer.
+#[derive(Clone, Debug)]
+pub struct Group {
+    /// From the group's coordinates to its parent's.
+    pub transform: Matrix3x2f,
+    pub opacity: f32,
+    /// In the group's coordinates.
+    pub clip: Option<PathData>,
+    pub items: Vec<Item>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ShapeItem {
+    pub shape: Shape,
+    /// From the shape's coordinates to its parent's.
+    pub transform: Matrix3x2f,
+    pub fill: Option<BrushDesc>,
+    pub stroke: Option<Stroke>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TextItem {
+    pub text: TextDesc,
+    /// From the text's coordinates to its parent's.
+    pub transform: Matrix3x2f,
+    pub fill: BrushDesc,
+}
+
+impl SceneFile {
+    /// Parses and validates a scene written in JSON or RON.
+    pub fn parse(text: &str) -> Result<SceneFile, SceneError> {
+        load::scene(&value::parse(text)?)
+    }
+
+    /// The size of the scene, in DIPs.
+    pub fn size(&self) -> Sizef {
+        self.size
+    }
+
+    /// The color the scene is cleared to before anything is drawn, if any.
+    pub fn background(&self) -> Option<Color> {
+        self.background
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Records the scene on a recording of its size.
+    pub fn to_recording(&self) -> Recording {
+        let mut recording = Recording::new(self.size);
+        self.record(&mut recording);
+        recording
+    }
+
+    /// Records the scene at the recording's current transform, which is
+    /// restored afterwards. The background, if there is one, is recorded as
+    /// a `Clear`.
+    pub fn record(&self, recording: &mut Recording) {
+        if let Some(background) = self.background {
+            recording.clear(background);
+        }
+        let base = recording.transform();
+        record_items(&self.items, &base, recording);
+        if recording.transform() != base {
+            recording.set_transform(&base);
+        }
+    }
+}
+
+#[cfg(windows)]
+impl SceneFile {
+    /// Draws the scene at the target's current transform.
+    pub fn draw(&self, target: &mut dyn IRenderTarget) -> Result<(), Error> {
+        self.to_recording().draw(target)
+    }
+}
+
+fn record_items(items: &[Item], parent: &Matrix3x2f, recording: &mut Recording) {
+    for item in items {
+        match item {
+            Item::Group(group) => {
+                let world = group.transform * *parent;
+                let layer = group.opacity < 1.0 || group.clip.is_some();
+                if layer {
+                    set_transform(recording, &world);
+                    let mut desc = LayerDesc::new().with_opacity(group.opacity);
+                    if let Some(clip) = &group.clip {
+                        desc = desc.with_mask(clip.clone());
+                    }
+                    recording.push_layer(desc);
+                }
+                record_items(&group.items, &world, recording);
+                if layer {
+                    recording.pop_layer();
+                }
+            }
+            Item::Shape(item) => {
+                set_transform(recording, &(item.transform * *parent));
+                if let Some(fill) = &item.fill {
+                    recording.push(DrawOp::Fill {
+                        shape: item.shape.clone(),
+                        brush: fill.clone(),
+                    });
+                }
+                if let Some(stroke) = &item.stroke {
+                    recording.push(DrawOp::Stroke {
+                        shape: item.shape.clone(),
+                        brush: stroke.brush.clone(),
+                        width: stroke.width,
+                        style: Some(stroke.style.clone()),
+                    });
+                }
+            }
+            Item::Text(item) => {
+                set_transform(recording, &(item.transform * *parent));
+                recording.draw_text(item.text.clone(), item.fill.clone());
+            }
+        }
+    }
+}
+
+fn set_transform(recording: &mut Recording, transform: &Matrix3x2f) {
+    if recording.transform() != *transform {
+        recording.set_transform(transform);
+    }
+}
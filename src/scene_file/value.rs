@@ -0,0 +1,352 @@
+//! Reads JSON and RON into one tree of values.
+//!
+//! A single reader accepts both: JSON objects alongside RON structs, maps,
+//! tuples and lists, with RON's comments, trailing commas, bare identifiers,
+//! `Some(..)` and `None`.
+
+use crate::scene_file::SceneError;
+
+/// Deeper nesting than this is rejected rather than risking the stack.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// JSON's `null` or RON's `None`.
+    Null,
+    Bool(bool),
+    Number(f64),
+    /// A string, or a bare identifier such as a RON enum variant.
+    String(String),
+    /// A JSON array, RON list or RON tuple.
+    List(Vec<Value>),
+    /// A JSON object, RON map or RON struct. A struct's name is kept if it
+    /// was written, as in `Rect(..)`.
+    Object {
+        name: Option<String>,
+        fields: Vec<(String, Value)>,
+    },
+}
+
+impl Value {
+    /// A short description of the kind of value, for error messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::List(_) => "a list",
+            Value::Object { .. } => "an object",
+        }
+    }
+}
+
+/// Parses a whole document, which holds one value.
+pub fn parse(text: &str) -> Result<Value, SceneError> {
+    let mut parser = Parser { text, pos: 0 };
+    parser.skip_whitespace()?;
+    parser.skip_attributes()?;
+    let value = parser.value(0)?;
+    parser.skip_whitespace()?;
+    if parser.pos < text.len() {
+        return Err(parser.error("content after the value"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn error(&self, message: &'static str) -> SceneError {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, pos: usize, message: &'static str) -> SceneError {
+        let before = &self.text[..pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |i| i + 1)..]
+            .chars()
+            .count()
+            + 1;
+        SceneError::Syntax {
+            line,
+            column,
+            message,
+        }
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_whitespace(&mut self) -> Result<(), SceneError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if trimmed.starts_with("/*") {
+                match trimmed.find("*/") {
+                    Some(end) => self.pos += end + 2,
+                    None => return Err(self.error("unterminated comment")),
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Skips RON's inner attributes, such as `#![enable(implicit_some)]`.
+    fn skip_attributes(&mut self) -> Result<(), SceneError> {
+        while self.rest().starts_with("#!") {
+            match self.rest().find(']') {
+                Some(end) => self.pos += end + 1,
+                None => return Err(self.error("unterminated attribute")),
+            }
+            self.skip_whitespace()?;
+        }
+        Ok(())
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.rest().starts_with(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, SceneError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("values nested too deeply"));
+        }
+        let c = match self.rest().chars().next() {
+            Some(c) => c,
+            None => return Err(self.error("expected a value")),
+        };
+        match c {
+            '{' => {
+                self.pos += 1;
+                let fields = self.fields('}', depth)?;
+                Ok(Value::Object { name: None, fields })
+            }
+            '[' => {
+                self.pos += 1;
+                Ok(Value::List(self.list(']', depth)?))
+            }
+            '(' => {
+                self.pos += 1;
+                self.parenthesized(None, depth)
+            }
+            '"' => Ok(Value::String(self.string()?)),
+            '-' | '+' | '.' | '0'..='9' => self.number(),
+            c if is_ident_start(c) => {
+                let ident = self.ident();
+                self.skip_whitespace()?;
+                if self.eat('(') {
+                    if ident == "Some" {
+                        self.skip_whitespace()?;
+                        let value = self.value(depth + 1)?;
+                        self.skip_whitespace()?;
+                        self.eat(',');
+                        self.skip_whitespace()?;
+                        if !self.eat(')') {
+                            return Err(self.error("expected ')'"));
+                        }
+                        return Ok(value);
+                    }
+                    return self.parenthesized(Some(ident.to_string()), depth);
+                }
+                Ok(match ident {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" | "None" => Value::Null,
+                    _ => Value::String(ident.to_string()),
+                })
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    /// The contents of `(..)`: struct fields if the first thing is a name
+    /// followed by a colon, otherwise a tuple.
+    fn parenthesized(&mut self, name: Option<String>, depth: usize) -> Result<Value, SceneError> {
+        self.skip_whitespace()?;
+        let start = self.pos;
+        let is_struct = match self.rest().chars().next() {
+            Some(c) if is_ident_start(c) => {
+                self.ident();
+                self.skip_whitespace()?;
+                let colon = self.rest().starts_with(':');
+                self.pos = start;
+                colon
+            }
+            Some(')') => true,
+            _ => false,
+        };
+        if is_struct {
+            let fields = self.fields(')', depth)?;
+            Ok(Value::Object { name, fields })
+        } else if name.is_some() {
+            Err(self.error("expected field names"))
+        } else {
+            Ok(Value::List(self.list(')', depth)?))
+        }
+    }
+
+    /// Comma-separated `key: value` pairs up to `close`. Keys are strings
+    /// or identifiers.
+    fn fields(&mut self, close: char, depth: usize) -> Result<Vec<(String, Value)>, SceneError> {
+        let mut fields: Vec<(String, Value)> = Vec::new();
+        loop {
+            self.skip_whitespace()?;
+            if self.eat(close) {
+                return Ok(fields);
+            }
+            let at = self.pos;
+            let key = match self.rest().chars().next() {
+                Some('"') => self.string()?,
+                Some(c) if is_ident_start(c) => self.ident().to_string(),
+                _ => return Err(self.error("expected a field name")),
+            };
+            if fields.iter().any(|(k, _)| *k == key) {
+                return Err(self.error_at(at, "duplicate field"));
+            }
+            self.skip_whitespace()?;
+            if !self.eat(':') {
+                return Err(self.error("expected ':'"));
+            }
+            self.skip_whitespace()?;
+            let value = self.value(depth + 1)?;
+            fields.push((key, value));
+            self.skip_whitespace()?;
+            if !self.eat(',') {
+                self.skip_whitespace()?;
+                if self.eat(close) {
+                    return Ok(fields);
+                }
+                return Err(self.error("expected ',' or the end of the object"));
+            }
+        }
+    }
+
+    /// Comma-separated values up to `close`.
+    fn list(&mut self, close: char, depth: usize) -> Result<Vec<Value>, SceneError> {
+        let mut values = Vec::new();
+        loop {
+            self.skip_whitespace()?;
+            if self.eat(close) {
+                return Ok(values);
+            }
+            values.push(self.value(depth + 1)?);
+            self.skip_whitespace()?;
+            if !self.eat(',') {
+                self.skip_whitespace()?;
+                if self.eat(close) {
+                    return Ok(values);
+                }
+                return Err(self.error("expected ',' or the end of the list"));
+            }
+        }
+    }
+
+    fn ident(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn number(&mut self) -> Result<Value, SceneError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+            .unwrap_or(rest.len());
+        let text = &rest[..len];
+        match text.trim_start_matches('+').parse::<f64>() {
+            Ok(n) if n.is_finite() => {
+                self.pos += len;
+                Ok(Value::Number(n))
+            }
+            _ => Err(self.error("malformed number")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, SceneError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let rest = self.rest();
+            let end = match rest.find(['"', '\\']) {
+                Some(end) => end,
+                None => return Err(self.error_at(start, "unterminated string")),
+            };
+            out.push_str(&rest[..end]);
+            self.pos += end + 1;
+            if rest[end..].starts_with('"') {
+                return Ok(out);
+            }
+
+            let escape_at = self.pos - 1;
+            let c = match self.rest().chars().next() {
+                Some(c) => c,
+                None => return Err(self.error_at(start, "unterminated string")),
+            };
+            self.pos += c.len_utf8();
+            out.push(match c {
+                '"' => '"',
+                '\\' => '\\',
+                '/' => '/',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => self.unicode_escape(escape_at)?,
+                _ => return Err(self.error_at(escape_at, "unknown escape")),
+            });
+        }
+    }
+
+    /// The rest of a `\u` escape, including a following low surrogate if
+    /// this is a high one.
+    fn unicode_escape(&mut self, escape_at: usize) -> Result<char, SceneError> {
+        let first = self.hex4(escape_at)?;
+        let code = if (0xd800..0xdc00).contains(&first) && self.rest().starts_with("\\u") {
+            self.pos += 2;
+            let second = self.hex4(escape_at)?;
+            if !(0xdc00..0xe000).contains(&second) {
+                return Err(self.error_at(escape_at, "bad surrogate pair"));
+            }
+            0x10000 + ((first - 0xd800) << 10) + (second - 0xdc00)
+        } else {
+            first
+        };
+        std::char::from_u32(code).ok_or_else(|| self.error_at(escape_at, "bad unicode escape"))
+    }
+
+    fn hex4(&mut self, escape_at: usize) -> Result<u32, SceneError> {
+        let digits = self
+            .rest()
+            .get(..4)
+            .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error_at(escape_at, "bad unicode escape"))?;
+        let code = u32::from_str_radix(digits, 16)
+            .map_err(|_| self.error_at(escape_at, "bad unicode escape"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
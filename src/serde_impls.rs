@@ -0,0 +1,269 @@
+//! Serialization for the types that can't derive it: math2d's types,
+//! enum flags and pixel formats.
+//!
+//! Names are snake_case throughout. Flags are lists of the names of the
+//! flags set, and values without a name are written as numbers so that
+//! nothing is lost.
+
+use crate::cpu::GradientRamp;
+use crate::descriptions::GradientStop;
+use crate::enums::{
+    BitmapOptions, DrawTextOptions, ExtendMode, Gamma, PresentOptions, RenderTargetUsage,
+};
+
+use math2d::{Color, Matrix3x2f, Point2f};
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Color")]
+pub(crate) struct ColorDef {
+    r: f32,
+    g: f32,
+    b: f32,
+    #[serde(default = "opaque")]
+    a: f32,
+}
+
+fn opaque() -> f32 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Point2f")]
+pub(crate) struct Point2fDef {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Matrix3x2f")]
+pub(crate) struct Matrix3x2fDef {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    x: f32,
+    y: f32,
+}
+
+/// What a `GradientRamp` is deserialized from, so that its stops are
+/// sorted the same way `GradientRamp::new` sorts them. Stops that can't be
+/// placed on the gradient are an error rather than being left out.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct GradientRampDef {
+    stops: Vec<GradientStop>,
+    #[serde(default = "default_gamma")]
+    gamma: Gamma,
+    #[serde(default = "default_extend_mode")]
+    extend_mode: ExtendMode,
+}
+
+fn default_gamma() -> Gamma {
+    Gamma::_2_2
+}
+
+fn default_extend_mode() -> ExtendMode {
+    ExtendMode::Clamp
+}
+
+impl TryFrom<GradientRampDef> for GradientRamp {
+    type Error = String;
+
+    fn try_from(def: GradientRampDef) -> Result<GradientRamp, String> {
+        if let Some(stop) = def.stops.iter().find(|stop| !stop.position.is_finite()) {
+            return Err(format!(
+                "gradient stop position {} isn't finite",
+                stop.position
+            ));
+        }
+        Ok(GradientRamp::new(&def.stops, def.gamma, def.extend_mode))
+    }
+}
+
+/// A value written by name if it has one, or as a number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NameOrNumber {
+    Name(String),
+    Number(u32),
+}
+
+impl NameOrNumber {
+    fn value<E: de::Error>(self, names: &[(&str, u32)], what: &str) -> Result<u32, E> {
+        match self {
+            NameOrNumber::Number(value) => Ok(value),
+            NameOrNumber::Name(name) => names
+                .iter()
+                .find(|&&(n, _)| n == name)
+                .map(|&(_, value)| value)
+                .ok_or_else(|| E::custom(format_args!("unknown {} `{}`", what, name))),
+        }
+    }
+}
+
+fn serialize_flags<S: Serializer>(
+    bits: u32,
+    names: &[(&str, u32)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(None)?;
+    let mut rest = bits;
+    for &(name, flag) in names {
+        if bits & flag == flag {
+            seq.serialize_element(name)?;
+            rest &= !flag;
+        }
+    }
+    if rest != 0 {
+        seq.serialize_element(&rest)?;
+    }
+    seq.end()
+}
+
+struct FlagsVisitor {
+    names: &'static [(&'static str, u32)],
+    what: &'static str,
+}
+
+impl<'de> Visitor<'de> for FlagsVisitor {
+    type Value = u32;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "a list of {} flags", self.what)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<u32, A::Error> {
+        let mut bits = 0;
+        while let Some(flag) = seq.next_element::<NameOrNumber>()? {
+            bits |= flag.value(self.names, self.what)?;
+        }
+        Ok(bits)
+    }
+}
+
+macro_rules! flags {
+    ($flags:ident, $what:expr, { $($flag:ident => $name:expr,)* }) => {
+        impl $flags {
+            const NAMES: &'static [(&'static str, u32)] = &[$(($name, $flags::$flag.0),)*];
+        }
+
+        impl Serialize for $flags {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serialize_flags(self.0, $flags::NAMES, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $flags {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let visitor = FlagsVisitor {
+                    names: $flags::NAMES,
+                    what: $what,
+                };
+                deserializer.deserialize_seq(visitor).map($flags)
+            }
+        }
+    };
+}
+
+flags!(BitmapOptions, "bitmap option", {
+    TARGET => "target",
+    CANNOT_DRAW => "cannot_draw",
+    CPU_READ => "cpu_read",
+    GDI_COMPATIBLE => "gdi_compatible",
+});
+
+flags!(DrawTextOptions, "draw text option", {
+    NO_SNAP => "no_snap",
+    CLIP => "clip",
+    ENABLE_COLOR_FONT => "enable_color_font",
+});
+
+flags!(PresentOptions, "present option", {
+    RETAIN_CONTENTS => "retain_contents",
+    IMMEDIATELY => "immediately",
+});
+
+flags!(RenderTargetUsage, "render target usage", {
+    FORCE_BITMAP_REMOTING => "force_bitmap_remoting",
+    GDI_COMPATIBLE => "gdi_compatible",
+});
+
+#[cfg(windows)]
+mod pixel_format {
+    use super::NameOrNumber;
+    use crate::descriptions::PixelFormat;
+
+    use serde::ser::{SerializeStruct, Serializer};
+    use serde::{Deserialize, Deserializer, Serialize};
+    use winapi::shared::dxgiformat::*;
+    use winapi::um::dcommon::D2D1_PIXEL_FORMAT;
+
+    /// The formats Direct2D can draw to or from. Others are written as
+    /// their `DXGI_FORMAT` value.
+    const FORMATS: &[(&str, u32)] = &[
+        ("unknown", DXGI_FORMAT_UNKNOWN),
+        ("r32g32b32a32_float", DXGI_FORMAT_R32G32B32A32_FLOAT),
+        ("r16g16b16a16_float", DXGI_FORMAT_R16G16B16A16_FLOAT),
+        ("r16g16b16a16_unorm", DXGI_FORMAT_R16G16B16A16_UNORM),
+        ("r10g10b10a2_unorm", DXGI_FORMAT_R10G10B10A2_UNORM),
+        ("r8g8b8a8_unorm", DXGI_FORMAT_R8G8B8A8_UNORM),
+        ("r8g8b8a8_unorm_srgb", DXGI_FORMAT_R8G8B8A8_UNORM_SRGB),
+        ("a8_unorm", DXGI_FORMAT_A8_UNORM),
+        ("b8g8r8a8_unorm", DXGI_FORMAT_B8G8R8A8_UNORM),
+        ("b8g8r8x8_unorm", DXGI_FORMAT_B8G8R8X8_UNORM),
+        ("b8g8r8a8_unorm_srgb", DXGI_FORMAT_B8G8R8A8_UNORM_SRGB),
+        ("b8g8r8x8_unorm_srgb", DXGI_FORMAT_B8G8R8X8_UNORM_SRGB),
+    ];
+    /// The same names `AlphaMode` is serialized with.
+    const ALPHA_MODES: &[(&str, u32)] = &[
+        ("unknown", 0),
+        ("premultiplied", 1),
+        ("straight", 2),
+        ("ignore", 3),
+    ];
+
+    struct Named(u32, &'static [(&'static str, u32)]);
+
+    impl Serialize for Named {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self.1.iter().find(|&&(_, value)| value == self.0) {
+                Some(&(name, _)) => serializer.serialize_str(name),
+                None => serializer.serialize_u32(self.0),
+            }
+        }
+    }
+
+    impl Serialize for PixelFormat {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let raw = D2D1_PIXEL_FORMAT::from(*self);
+            let mut state = serializer.serialize_struct("PixelFormat", 2)?;
+            state.serialize_field("format", &Named(raw.format, FORMATS))?;
+            state.serialize_field("alpha_mode", &Named(raw.alphaMode, ALPHA_MODES))?;
+            state.end()
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Fields {
+        format: NameOrNumber,
+        alpha_mode: NameOrNumber,
+    }
+
+    impl<'de> Deserialize<'de> for PixelFormat {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let fields = Fields::deserialize(deserializer)?;
+            Ok(D2D1_PIXEL_FORMAT {
+                format: fields.format.value(FORMATS, "pixel format")?,
+                alphaMode: fields.alpha_mode.value(ALPHA_MODES, "alpha mode")?,
+            }
+            .into())
+        }
+    }
+}
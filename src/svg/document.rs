@@ -0,0 +1,929 @@
+use crate::cpu::{GradientRamp, LinearGradient, RadialGradient};
+use crate::descriptions::GradientStop;
+use crate::enums::{CapStyle, ExtendMode, FigureBegin, FigureEnd, FillMode, Gamma, LineJoin};
+use crate::geometry::PathData;
+use crate::properties::{
+    BrushProperties, LinearGradientBrushProperties, RadialGradientBrushProperties,
+};
+use crate::record::{BrushDesc, DrawOp, LayerDesc, Recording, Shape, StrokeStyleDesc};
+use crate::render_target::dirty_region::{transform_bounds, union};
+use crate::svg::path::parse_path_partial;
+use crate::svg::values::{
+    parse_color, parse_length, parse_numbers, parse_paint, parse_transform, parse_url, Length,
+    Paint,
+};
+use crate::svg::xml::{self, Element};
+use crate::svg::SvgError;
+
+#[cfg(windows)]
+use crate::render_target::IRenderTarget;
+#[cfg(windows)]
+use dcommon::Error;
+
+use math2d::{Color, Ellipse, Matrix3x2f, Point2f, Rectf, RoundedRect, Sizef};
+
+use std::collections::HashMap;
+
+/// Gradients referring to each other through `href` are followed this far.
+const MAX_HREF_DEPTH: usize = 16;
+
+/// A parsed SVG document, ready to be drawn.
+///
+/// The supported subset is static SVG 1.1: `path`, `rect`, `circle`,
+/// `ellipse`, `line`, `polyline` and `polygon`, grouped by `g` and `a` with
+/// transforms; fill and stroke painted with colors or linear and radial
+/// gradients; stroke styles; `opacity`; and `clipPath`. Style properties are
+/// read from presentation attributes and `style` attributes, but not from
+/// style sheets. Anything else is ignored.
+#[derive(Clone, Debug)]
+pub struct Document {
+    size: Sizef,
+    root: Group,
+}
+
+/// A group of nodes drawn together, composited with an opacity and clip.
+#[derive(Clone, Debug)]
+pub struct Group {
+    /// From the group's coordinates to its parent's.
+    pub transform: Matrix3x2f,
+    pub opacity: f32,
+    /// In the group's coordinates. An empty path clips everything away.
+    pub clip: Option<PathData>,
+    pub children: Vec<Node>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Node {
+    Group(Group),
+    Shape(Box<ShapeNode>),
+}
+
+/// A shape with its fill and stroke resolved to brushes.
+#[derive(Clone, Debug)]
+pub struct ShapeNode {
+    pub shape: Shape,
+    /// From the shape's coordinates to its parent's.
+    pub transform: Matrix3x2f,
+    pub fill: Option<BrushDesc>,
+    pub stroke: Option<Stroke>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Stroke {
+    pub brush: BrushDesc,
+    pub width: f32,
+    pub style: StrokeStyleDesc,
+}
+
+impl Document {
+    pub fn parse(text: &str) -> Result<Document, SvgError> {
+        let root = xml::parse(text)?;
+        if root.name != "svg" {
+            return Err(SvgError::NotSvg);
+        }
+
+        let view_box = root
+            .attribute("viewBox")
+            .and_then(|v| match *parse_numbers(v) {
+                [x, y, w, h] if w > 0.0 && h > 0.0 => Some(Rectf {
+                    left: x,
+                    top: y,
+                    right: x + w,
+                    bottom: y + h,
+                }),
+                _ => None,
+            });
+        let length = |name, from_view_box: fn(&Rectf) -> f32| match root
+            .attribute(name)
+            .and_then(parse_length)
+        {
+            Some(Length::Number(n)) if n > 0.0 => n,
+            _ => view_box.as_ref().map_or(100.0, from_view_box),
+        };
+        let size = Sizef::new(
+            length("width", |r| r.right - r.left),
+            length("height", |r| r.bottom - r.top),
+        );
+
+        let mut ids = HashMap::new();
+        root.walk(&mut |element| {
+            if let Some(id) = element.attribute("id") {
+                ids.entry(id).or_insert(element);
+            }
+        });
+        let viewport = view_box.map_or(size, |r| Sizef::new(r.right - r.left, r.bottom - r.top));
+        let builder = Builder { ids, viewport };
+
+        let style = builder.cascade(&Style::default(), &root);
+        let mut children = Vec::new();
+        builder.children(&root, &style, &mut children);
+        let mut root_group = Group {
+            transform: Matrix3x2f::IDENTITY,
+            opacity: 1.0,
+            clip: None,
+            children,
+        };
+        if let Some(view_box) = view_box {
+            let (transform, slice) = view_box_transform(
+                &view_box,
+                size,
+                root.attribute("preserveAspectRatio").unwrap_or(""),
+            );
+            root_group.transform = transform;
+            if slice {
+                // Parts of the view box outside the viewport are hidden.
+                let mut clip = PathData::new();
+                clip.add_polygon(&[
+                    Point2f { x: 0.0, y: 0.0 },
+                    Point2f {
+                        x: size.width,
+                        y: 0.0,
+                    },
+                    Point2f {
+                        x: size.width,
+                        y: size.height,
+                    },
+                    Point2f {
+                        x: 0.0,
+                        y: size.height,
+                    },
+                ]);
+                root_group = Group {
+                    transform: Matrix3x2f::IDENTITY,
+                    opacity: 1.0,
+                    clip: Some(clip),
+                    children: vec![Node::Group(root_group)],
+                };
+            }
+        }
+
+        Ok(Document {
+            size,
+            root: root_group,
+        })
+    }
+
+    /// The size of the viewport, in DIPs.
+    pub fn size(&self) -> Sizef {
+        self.size
+    }
+
+    pub fn root(&self) -> &Group {
+        &self.root
+    }
+
+    /// Records the document on a recording of its size.
+    pub fn to_recording(&self) -> Recording {
+        let mut recording = Recording::new(self.size);
+        self.record(&mut recording);
+        recording
+    }
+
+    /// Records the document at the recording's current transform, which is
+    /// restored afterwards.
+    pub fn record(&self, recording: &mut Recording) {
+        let base = recording.transform();
+        record_group(&self.root, &base, recording);
+        if recording.transform() != base {
+            recording.set_transform(&base);
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Document {
+    /// Draws the document at the target's current transform.
+    pub fn draw(&self, target: &mut dyn IRenderTarget) -> Result<(), Error> {
+        self.to_recording().draw(target)
+    }
+}
+
+fn record_group(group: &Group, parent: &Matrix3x2f, recording: &mut Recording) {
+    let world = group.transform * *parent;
+    let layer = group.opacity < 1.0 || group.clip.is_some();
+    if layer {
+        set_transform(recording, &world);
+        let mut desc = LayerDesc::new().with_opacity(group.opacity);
+        if let Some(clip) = &group.clip {
+            desc = desc.with_mask(clip.clone());
+        }
+        recording.push_layer(desc);
+    }
+    for child in &group.children {
+        match child {
+            Node::Group(group) => record_group(group, &world, recording),
+            Node::Shape(node) => {
+                set_transform(recording, &(node.transform * world));
+                if let Some(fill) = &node.fill {
+                    recording.push(DrawOp::Fill {
+                        shape: node.shape.clone(),
+                        brush: fill.clone(),
+                    });
+                }
+                if let Some(stroke) = &node.stroke {
+                    recording.push(DrawOp::Stroke {
+                        shape: node.shape.clone(),
+                        brush: stroke.brush.clone(),
+                        width: stroke.width,
+                        style: Some(stroke.style.clone()),
+                    });
+                }
+            }
+        }
+    }
+    if layer {
+        recording.pop_layer();
+    }
+}
+
+fn set_transform(recording: &mut Recording, transform: &Matrix3x2f) {
+    if recording.transform() != *transform {
+        recording.set_transform(transform);
+    }
+}
+
+/// Maps the view box onto a viewport of `size` following a
+/// `preserveAspectRatio` value. Also returns whether the view box may
+/// overflow the viewport.
+fn view_box_transform(view_box: &Rectf, size: Sizef, aspect: &str) -> (Matrix3x2f, bool) {
+    let (vw, vh) = (
+        view_box.right - view_box.left,
+        view_box.bottom - view_box.top,
+    );
+    let (mut sx, mut sy) = (size.width / vw, size.height / vh);
+    let mut words = aspect.split_whitespace().filter(|&w| w != "defer");
+    let align = words.next().unwrap_or("xMidYMid");
+    let slice = words.next() == Some("slice");
+    let (mut tx, mut ty) = (0.0, 0.0);
+    if align != "none" {
+        let s = if slice { sx.max(sy) } else { sx.min(sy) };
+        sx = s;
+        sy = s;
+        let (extra_x, extra_y) = (size.width - vw * s, size.height - vh * s);
+        if align.starts_with("xMid") {
+            tx = extra_x / 2.0;
+        } else if align.starts_with("xMax") {
+            tx = extra_x;
+        }
+        if align.ends_with("YMid") {
+            ty = extra_y / 2.0;
+        } else if align.ends_with("YMax") {
+            ty = extra_y;
+        }
+    }
+    let transform = Matrix3x2f {
+        a: sx,
+        b: 0.0,
+        c: 0.0,
+        d: sy,
+        x: tx - view_box.left * sx,
+        y: ty - view_box.top * sy,
+    };
+    (transform, slice && align != "none")
+}
+
+/// The inherited style properties.
+#[derive(Clone, Debug)]
+struct Style {
+    color: Color,
+    fill: Paint,
+    fill_opacity: f32,
+    fill_rule: FillMode,
+    stroke: Paint,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    line_cap: CapStyle,
+    line_join: LineJoin,
+    miter_limit: f32,
+    dash_array: Vec<f32>,
+    dash_offset: f32,
+    clip_rule: FillMode,
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style {
+            color: Color::new(0.0, 0.0, 0.0, 1.0),
+            fill: Paint::Color(Color::new(0.0, 0.0, 0.0, 1.0)),
+            fill_opacity: 1.0,
+            fill_rule: FillMode::Winding,
+            stroke: Paint::None,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            line_cap: CapStyle::Flat,
+            line_join: LineJoin::MiterOrBevel,
+            miter_limit: 4.0,
+            dash_array: Vec::new(),
+            dash_offset: 0.0,
+            clip_rule: FillMode::Winding,
+        }
+    }
+}
+
+/// A style property of an element, from its `style` attribute or else its
+/// presentation attribute. `inherit` counts as not being set.
+fn property<'a>(element: &'a Element, name: &str) -> Option<&'a str> {
+    let declared = element.attribute("style").and_then(|style| {
+        style
+            .split(';')
+            .filter_map(|declaration| {
+                let colon = declaration.find(':')?;
+                let key = declaration[..colon].trim();
+                Some((key, declaration[colon + 1..].trim()))
+            })
+            .filter(|&(key, _)| key == name)
+            .map(|(_, value)| value.trim_end_matches("!important").trim())
+            .next_back()
+    });
+    declared
+        .or_else(|| element.attribute(name).map(str::trim))
+        .filter(|&value| value != "inherit")
+}
+
+fn parse_opacity(text: &str) -> Option<f32> {
+    match parse_length(text)? {
+        Length::Number(n) => Some(n.clamp(0.0, 1.0)),
+        Length::Percent(p) => Some((p / 100.0).clamp(0.0, 1.0)),
+    }
+}
+
+fn parse_fill_rule(text: &str) -> Option<FillMode> {
+    match text {
+        "nonzero" => Some(FillMode::Winding),
+        "evenodd" => Some(FillMode::Alternate),
+        _ => None,
+    }
+}
+
+struct Builder<'a> {
+    ids: HashMap<&'a str, &'a Element>,
+    /// The size percentages are taken of.
+    viewport: Sizef,
+}
+
+impl<'a> Builder<'a> {
+    fn cascade(&self, parent: &Style, element: &Element) -> Style {
+        let mut style = parent.clone();
+        let get = |name| property(element, name);
+        if let Some(color) = get("color").and_then(parse_color) {
+            style.color = color;
+        }
+        if let Some(paint) = get("fill").and_then(parse_paint) {
+            style.fill = paint;
+        }
+        if let Some(opacity) = get("fill-opacity").and_then(parse_opacity) {
+            style.fill_opacity = opacity;
+        }
+        if let Some(rule) = get("fill-rule").and_then(parse_fill_rule) {
+            style.fill_rule = rule;
+        }
+        if let Some(paint) = get("stroke").and_then(parse_paint) {
+            style.stroke = paint;
+        }
+        if let Some(opacity) = get("stroke-opacity").and_then(parse_opacity) {
+            style.stroke_opacity = opacity;
+        }
+        if let Some(width) = get("stroke-width").and_then(|w| self.length(w, Axis::Diagonal)) {
+            if width >= 0.0 {
+                style.stroke_width = width;
+            }
+        }
+        match get("stroke-linecap") {
+            Some("butt") => style.line_cap = CapStyle::Flat,
+            Some("round") => style.line_cap = CapStyle::Round,
+            Some("square") => style.line_cap = CapStyle::Square,
+            _ => {}
+        }
+        match get("stroke-linejoin") {
+            // SVG bevels miters past the limit rather than clipping them.
+            Some("miter") | Some("miter-clip") => style.line_join = LineJoin::MiterOrBevel,
+            Some("round") => style.line_join = LineJoin::Round,
+            Some("bevel") => style.line_join = LineJoin::Bevel,
+            _ => {}
+        }
+        if let Some(limit) = get("stroke-miterlimit").and_then(|m| m.parse::<f32>().ok()) {
+            if limit >= 1.0 {
+                style.miter_limit = limit;
+            }
+        }
+        if let Some(dashes) = get("stroke-dasharray") {
+            style.dash_array = if dashes == "none" {
+                Vec::new()
+            } else {
+                dashes
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|s| !s.is_empty())
+                    .map(|d| self.length(d, Axis::Diagonal))
+                    .collect::<Option<Vec<_>>>()
+                    .unwrap_or_default()
+            };
+        }
+        if let Some(offset) = get("stroke-dashoffset").and_then(|o| self.length(o, Axis::Diagonal))
+        {
+            style.dash_offset = offset;
+        }
+        if let Some(rule) = get("clip-rule").and_then(parse_fill_rule) {
+            style.clip_rule = rule;
+        }
+        style
+    }
+
+    /// A length in user units, with percentages taken of the viewport.
+    fn length(&self, text: &str, axis: Axis) -> Option<f32> {
+        let reference = match axis {
+            Axis::X => self.viewport.width,
+            Axis::Y => self.viewport.height,
+            Axis::Diagonal => {
+                let (w, h) = (self.viewport.width, self.viewport.height);
+                ((w * w + h * h) / 2.0).sqrt()
+            }
+        };
+        parse_length(text).map(|l| l.resolve(reference))
+    }
+
+    fn attribute_length(&self, element: &Element, name: &str, axis: Axis) -> f32 {
+        element
+            .attribute(name)
+            .and_then(|v| self.length(v, axis))
+            .unwrap_or(0.0)
+    }
+
+    fn children(&self, element: &Element, style: &Style, out: &mut Vec<Node>) {
+        for child in &element.children {
+            if let Some(node) = self.node(child, style) {
+                out.push(node);
+            }
+        }
+    }
+
+    fn node(&self, element: &Element, parent: &Style) -> Option<Node> {
+        if property(element, "display") == Some("none") {
+            return None;
+        }
+        let style = self.cascade(parent, element);
+        let transform = element
+            .attribute("transform")
+            .and_then(parse_transform)
+            .unwrap_or(Matrix3x2f::IDENTITY);
+        let opacity = property(element, "opacity")
+            .and_then(parse_opacity)
+            .unwrap_or(1.0);
+
+        let (node, bounds) = match element.name.as_str() {
+            "g" | "a" => {
+                let mut children = Vec::new();
+                self.children(element, &style, &mut children);
+                let bounds = children
+                    .iter()
+                    .filter_map(node_bounds)
+                    .fold(None, |a, b| Some(a.map_or(b, |a| union(&a, &b))));
+                let group = Group {
+                    transform,
+                    opacity,
+                    clip: None,
+                    children,
+                };
+                (Node::Group(group), bounds)
+            }
+            _ => {
+                let shape = self.shape(element, &style)?;
+                let bounds = shape.bounds();
+                let fill = match shape {
+                    Shape::Line(..) => None,
+                    _ => self.brush(&style.fill, &style, style.fill_opacity, bounds),
+                };
+                let stroke = self.stroke(&style, bounds);
+                if fill.is_none() && stroke.is_none() {
+                    return None;
+                }
+                let node = ShapeNode {
+                    shape,
+                    transform,
+                    fill,
+                    stroke,
+                };
+                (Node::Shape(Box::new(node)), bounds)
+            }
+        };
+
+        let clip = property(element, "clip-path")
+            .and_then(parse_url)
+            .and_then(|id| self.clip(&id, bounds));
+        match node {
+            Node::Group(mut group) => {
+                group.clip = clip;
+                Some(Node::Group(group))
+            }
+            Node::Shape(node) if opacity < 1.0 || clip.is_some() => {
+                // The clip is in the shape's coordinates, so the group takes
+                // on its transform.
+                Some(Node::Group(Group {
+                    transform: node.transform,
+                    opacity,
+                    clip,
+                    children: vec![Node::Shape(Box::new(ShapeNode {
+                        transform: Matrix3x2f::IDENTITY,
+                        ..*node
+                    }))],
+                }))
+            }
+            node => Some(node),
+        }
+    }
+
+    /// The geometry of a shape element, or `None` if it isn't one or it
+    /// isn't drawn.
+    fn shape(&self, element: &Element, style: &Style) -> Option<Shape> {
+        let length = |name, axis| self.attribute_length(element, name, axis);
+        let shape = match element.name.as_str() {
+            "path" => {
+                let (path, _) = parse_path_partial(element.attribute("d")?);
+                Shape::Path(path.with_fill_mode(style.fill_rule))
+            }
+            "rect" => {
+                let (x, y) = (length("x", Axis::X), length("y", Axis::Y));
+                let (w, h) = (length("width", Axis::X), length("height", Axis::Y));
+                if w <= 0.0 || h <= 0.0 {
+                    return None;
+                }
+                let rect = Rectf {
+                    left: x,
+                    top: y,
+                    right: x + w,
+                    bottom: y + h,
+                };
+                let radius = |name, axis| {
+                    element
+                        .attribute(name)
+                        .and_then(|v| self.length(v, axis))
+                        .filter(|&r| r > 0.0)
+                };
+                let (rx, ry) = match (radius("rx", Axis::X), radius("ry", Axis::Y)) {
+                    (Some(rx), Some(ry)) => (rx, ry),
+                    (Some(r), None) | (None, Some(r)) => (r, r),
+                    (None, None) => return Some(Shape::Rectangle(rect)),
+                };
+                Shape::RoundedRectangle(RoundedRect {
+                    rect,
+                    radius_x: rx.min(w / 2.0),
+                    radius_y: ry.min(h / 2.0),
+                })
+            }
+            "circle" => {
+                let r = length("r", Axis::Diagonal);
+                if r <= 0.0 {
+                    return None;
+                }
+                Shape::Ellipse(Ellipse {
+                    center: Point2f {
+                        x: length("cx", Axis::X),
+                        y: length("cy", Axis::Y),
+                    },
+                    radius_x: r,
+                    radius_y: r,
+                })
+            }
+            "ellipse" => {
+                let (rx, ry) = (length("rx", Axis::X), length("ry", Axis::Y));
+                if rx <= 0.0 || ry <= 0.0 {
+                    return None;
+                }
+                Shape::Ellipse(Ellipse {
+                    center: Point2f {
+                        x: length("cx", Axis::X),
+                        y: length("cy", Axis::Y),
+                    },
+                    radius_x: rx,
+                    radius_y: ry,
+                })
+            }
+            "line" => Shape::Line(
+                Point2f {
+                    x: length("x1", Axis::X),
+                    y: length("y1", Axis::Y),
+                },
+                Point2f {
+                    x: length("x2", Axis::X),
+                    y: length("y2", Axis::Y),
+                },
+            ),
+            "polyline" | "polygon" => {
+                let numbers = parse_numbers(element.attribute("points")?);
+                let points: Vec<Point2f> = numbers
+                    .chunks_exact(2)
+                    .map(|p| Point2f { x: p[0], y: p[1] })
+                    .collect();
+                if points.len() < 2 {
+                    return None;
+                }
+                let mut path = PathData::new().with_fill_mode(style.fill_rule);
+                if element.name == "polygon" {
+                    path.add_polygon(&points);
+                } else {
+                    path.begin_figure(points[0], FigureBegin::Filled);
+                    path.add_lines(&points[1..]);
+                    path.end_figure(FigureEnd::Open);
+                }
+                Shape::Path(path)
+            }
+            _ => return None,
+        };
+        Some(shape)
+    }
+
+    fn stroke(&self, style: &Style, bounds: Option<Rectf>) -> Option<Stroke> {
+        let width = style.stroke_width;
+        if width <= 0.0 {
+            return None;
+        }
+        let brush = self.brush(&style.stroke, style, style.stroke_opacity, bounds)?;
+        let mut desc = StrokeStyleDesc::new()
+            .with_caps(style.line_cap)
+            .with_line_join(style.line_join)
+            .with_miter_limit(style.miter_limit);
+
+        let dashes = &style.dash_array;
+        if dashes.iter().all(|&d| d >= 0.0) && dashes.iter().sum::<f32>() > 0.0 {
+            // An odd number of dashes is repeated to make an even number.
+            let mut pattern: Vec<f32> = dashes.iter().map(|d| d / width).collect();
+            if pattern.len() % 2 == 1 {
+                pattern.extend_from_slice(&pattern.clone());
+            }
+            desc = desc
+                .with_dashes(&pattern)
+                .with_dash_offset(style.dash_offset / width);
+        }
+        Some(Stroke {
+            brush,
+            width,
+            style: desc,
+        })
+    }
+
+    /// Resolves a paint to a brush, or `None` if nothing should be drawn.
+    /// `bounds` is the shape's bounding box, which gradients may be relative
+    /// to.
+    fn brush(
+        &self,
+        paint: &Paint,
+        style: &Style,
+        opacity: f32,
+        bounds: Option<Rectf>,
+    ) -> Option<BrushDesc> {
+        match paint {
+            Paint::None => None,
+            Paint::Color(color) => Some(BrushDesc::solid(*color).with_opacity(opacity)),
+            Paint::CurrentColor => Some(BrushDesc::solid(style.color).with_opacity(opacity)),
+            Paint::Url(id, fallback) => match self.ids.get(id.as_str()) {
+                Some(element)
+                    if element.name == "linearGradient" || element.name == "radialGradient" =>
+                {
+                    self.gradient(element, style, opacity, bounds)
+                }
+                _ => fallback
+                    .as_ref()
+                    .and_then(|paint| self.brush(paint, style, opacity, bounds)),
+            },
+        }
+    }
+
+    fn gradient(
+        &self,
+        element: &Element,
+        style: &Style,
+        opacity: f32,
+        bounds: Option<Rectf>,
+    ) -> Option<BrushDesc> {
+        // Attributes and stops missing from a gradient are taken from the
+        // one it refers to.
+        let mut chain = vec![element];
+        while chain.len() < MAX_HREF_DEPTH {
+            let next = chain
+                .last()
+                .and_then(|e| e.attribute("href"))
+                .and_then(|href| href.strip_prefix('#'))
+                .and_then(|id| self.ids.get(id));
+            match next {
+                Some(&next) if !chain.iter().any(|&e| std::ptr::eq(e, next)) => chain.push(next),
+                _ => break,
+            }
+        }
+        let attribute = |name| chain.iter().find_map(|e| e.attribute(name));
+
+        let stops = chain
+            .iter()
+            .find(|e| e.children.iter().any(|c| c.name == "stop"))
+            .map_or_else(Vec::new, |e| self.stops(e, style));
+        match stops.len() {
+            0 => return None,
+            1 => return Some(BrushDesc::solid(stops[0].color).with_opacity(opacity)),
+            _ => {}
+        }
+        let extend_mode = match attribute("spreadMethod") {
+            Some("reflect") => ExtendMode::Mirror,
+            Some("repeat") => ExtendMode::Wrap,
+            _ => ExtendMode::Clamp,
+        };
+        let gamma = match chain
+            .iter()
+            .find_map(|e| property(e, "color-interpolation"))
+        {
+            Some("linearRGB") => Gamma::_1_0,
+            _ => Gamma::_2_2,
+        };
+        let ramp = GradientRamp::new(&stops, gamma, extend_mode);
+
+        let user_space = attribute("gradientUnits") == Some("userSpaceOnUse");
+        let mut transform = attribute("gradientTransform")
+            .and_then(parse_transform)
+            .unwrap_or(Matrix3x2f::IDENTITY);
+        if !user_space {
+            let b = bounds?;
+            let (w, h) = (b.right - b.left, b.bottom - b.top);
+            if w <= 0.0 || h <= 0.0 {
+                return None;
+            }
+            let bbox = Matrix3x2f {
+                a: w,
+                b: 0.0,
+                c: 0.0,
+                d: h,
+                x: b.left,
+                y: b.top,
+            };
+            transform = transform * bbox;
+        }
+        let brush = BrushProperties::new(opacity, &transform);
+
+        // Coordinates are fractions of the bounding box, or user units.
+        let coordinate = |name, default: f32, axis| {
+            match attribute(name) {
+                Some(value) if user_space => self.length(value, axis),
+                Some(value) => parse_length(value).map(|l| l.resolve(1.0)),
+                None => None,
+            }
+            .unwrap_or(if user_space {
+                Length::Percent(default * 100.0).resolve(match axis {
+                    Axis::X => self.viewport.width,
+                    _ => self.viewport.height,
+                })
+            } else {
+                default
+            })
+        };
+
+        if element.name == "linearGradient" {
+            let properties = LinearGradientBrushProperties {
+                start: Point2f {
+                    x: coordinate("x1", 0.0, Axis::X),
+                    y: coordinate("y1", 0.0, Axis::Y),
+                },
+                end: Point2f {
+                    x: coordinate("x2", 1.0, Axis::X),
+                    y: coordinate("y2", 0.0, Axis::Y),
+                },
+            };
+            let gradient = LinearGradient::new(ramp, properties).with_brush_properties(brush);
+            Some(BrushDesc::LinearGradient(gradient))
+        } else {
+            let (cx, cy) = (
+                coordinate("cx", 0.5, Axis::X),
+                coordinate("cy", 0.5, Axis::Y),
+            );
+            let r = match attribute("r") {
+                Some(value) if user_space => self.length(value, Axis::Diagonal),
+                Some(value) => parse_length(value).map(|l| l.resolve(1.0)),
+                None => None,
+            }
+            .unwrap_or_else(|| {
+                if user_space {
+                    self.length("50%", Axis::Diagonal).unwrap_or(0.0)
+                } else {
+                    0.5
+                }
+            });
+            if r <= 0.0 {
+                return Some(BrushDesc::solid(stops[stops.len() - 1].color).with_opacity(opacity));
+            }
+            let fx = attribute("fx").map_or(cx, |_| coordinate("fx", 0.5, Axis::X));
+            let fy = attribute("fy").map_or(cy, |_| coordinate("fy", 0.5, Axis::Y));
+            let properties = RadialGradientBrushProperties {
+                center: Point2f { x: cx, y: cy },
+                origin_offset: Point2f {
+                    x: fx - cx,
+                    y: fy - cy,
+                },
+                radius_x: r,
+                radius_y: r,
+            };
+            let gradient = RadialGradient::new(ramp, properties).with_brush_properties(brush);
+            Some(BrushDesc::RadialGradient(gradient))
+        }
+    }
+
+    fn stops(&self, gradient: &Element, style: &Style) -> Vec<GradientStop> {
+        let mut stops: Vec<GradientStop> = Vec::new();
+        for stop in gradient.children.iter().filter(|c| c.name == "stop") {
+            let offset = stop
+                .attribute("offset")
+                .and_then(parse_opacity)
+                .unwrap_or(0.0);
+            // Offsets can't go backwards.
+            let offset = stops.last().map_or(offset, |s| offset.max(s.position));
+            let mut color = match property(stop, "stop-color") {
+                Some("currentColor") => style.color,
+                Some(value) => parse_color(value).unwrap_or(Color::new(0.0, 0.0, 0.0, 1.0)),
+                None => Color::new(0.0, 0.0, 0.0, 1.0),
+            };
+            color.a *= property(stop, "stop-opacity")
+                .and_then(parse_opacity)
+                .unwrap_or(1.0);
+            stops.push(GradientStop {
+                position: offset,
+                color,
+            });
+        }
+        stops
+    }
+
+    /// The outline of the clip path `id` in the coordinates of an element
+    /// with `bounds`, or `None` if there's no such clip path.
+    fn clip(&self, id: &str, bounds: Option<Rectf>) -> Option<PathData> {
+        let element = *self.ids.get(id)?;
+        if element.name != "clipPath" {
+            return None;
+        }
+        let style = self.cascade(&Style::default(), element);
+        let mut clip = PathData::new();
+        let mut rules = Vec::new();
+        for child in &element.children {
+            if property(child, "display") == Some("none") {
+                continue;
+            }
+            let child_style = self.cascade(&style, child);
+            let shape = match self.shape(child, &child_style) {
+                Some(shape) => shape,
+                None => continue,
+            };
+            let transform = child
+                .attribute("transform")
+                .and_then(parse_transform)
+                .unwrap_or(Matrix3x2f::IDENTITY);
+            clip.append(&shape.to_path().transformed(&transform));
+            rules.push(child_style.clip_rule);
+        }
+        // Children with different rules can't be combined exactly; nonzero
+        // is the closer fit when their outlines don't overlap.
+        let rule = match rules.first() {
+            Some(&rule) if rules.iter().all(|&r| r == rule) => rule,
+            _ => FillMode::Winding,
+        };
+        clip.set_fill_mode(rule);
+
+        let mut transform = element
+            .attribute("transform")
+            .and_then(parse_transform)
+            .unwrap_or(Matrix3x2f::IDENTITY);
+        if element.attribute("clipPathUnits") == Some("objectBoundingBox") {
+            let b = match bounds {
+                Some(b) => b,
+                None => return Some(PathData::new()),
+            };
+            let bbox = Matrix3x2f {
+                a: b.right - b.left,
+                b: 0.0,
+                c: 0.0,
+                d: b.bottom - b.top,
+                x: b.left,
+                y: b.top,
+            };
+            transform = transform * bbox;
+        }
+        Some(clip.transformed(&transform))
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Axis {
+    X,
+    Y,
+    Diagonal,
+}
+
+/// The bounds of a node in its parent's coordinates, ignoring clips.
+fn node_bounds(node: &Node) -> Option<Rectf> {
+    let (bounds, transform) = match node {
+        Node::Shape(shape) => (shape.shape.bounds()?, shape.transform),
+        Node::Group(group) => {
+            let bounds = group
+                .children
+                .iter()
+                .filter_map(node_bounds)
+                .fold(None, |a: Option<Rectf>, b| {
+                    Some(a.map_or(b, |a| union(&a, &b)))
+                })?;
+            (bounds, group.transform)
+        }
+    };
+    Some(transform_bounds(&bounds, &transform))
+}
@@ -0,0 +1,25 @@
+use std::fmt;
+
+/// Why a document or path couldn't be read.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SvgError {
+    /// The text isn't well-formed XML. The byte offset of the problem and a
+    /// description of it are included.
+    Xml(usize, &'static str),
+    /// The root element isn't `<svg>`.
+    NotSvg,
+    /// Path data is malformed at this byte offset.
+    PathData(usize),
+}
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SvgError::Xml(offset, message) => write!(fmt, "{} at byte {}", message, offset),
+            SvgError::NotSvg => fmt.write_str("the root element isn't <svg>"),
+            SvgError::PathData(offset) => write!(fmt, "bad path data at byte {}", offset),
+        }
+    }
+}
+
+impl std::error::Error for SvgError {}
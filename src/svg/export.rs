@@ -0,0 +1,672 @@
+use crate::cpu::gradient::to_u8;
+use crate::cpu::GradientRamp;
+use crate::enums::{
+    BitmapInterpolationMode, CapStyle, ExtendMode, FigureBegin, FigureEnd, FillMode, Gamma,
+    LineJoin,
+};
+use crate::geometry::{PathData, PathEvent};
+use crate::record::{
+    BitmapData, BrushDesc, DrawOp, LayerDesc, Recording, Shape, StrokeStyleDesc, TextDesc,
+};
+use crate::render_target::dirty_region::transform_bounds;
+
+use math2d::{ArcSize, Color, Matrix3x2f, Rectf, SweepDirection};
+
+use std::fmt::Write;
+
+/// Writes `recording` as a standalone SVG 1.1 document the size of the
+/// recording, in DIPs.
+///
+/// The output only depends on the recording, so it can be compared against
+/// a stored copy. Numbers are written with at most three decimal places,
+/// and bitmaps are embedded as PNG data.
+///
+/// SVG can't express everything Direct2D does, so some things are
+/// approximated: strokes use the start cap for every cap, `Miter` joins fall
+/// back to bevels past the miter limit as `MiterOrBevel` does, `Clear`
+/// paints over what's below it rather than replacing it, and text is placed
+/// on a baseline estimated from its font size.
+pub fn to_svg(recording: &Recording) -> String {
+    let mut exporter = Exporter {
+        defs: String::new(),
+        body: String::new(),
+        next_id: 1,
+        groups: Vec::new(),
+        transform: Matrix3x2f::IDENTITY,
+        size: (recording.size().width, recording.size().height),
+    };
+    for op in recording.ops() {
+        exporter.op(op);
+    }
+    while !exporter.groups.is_empty() {
+        exporter.close_group();
+    }
+
+    let (width, height) = exporter.size;
+    let mut svg = String::new();
+    svg.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" \
+         width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+        w = num(width),
+        h = num(height),
+    );
+    if !exporter.defs.is_empty() {
+        svg.push_str("  <defs>\n");
+        svg.push_str(&exporter.defs);
+        svg.push_str("  </defs>\n");
+    }
+    svg.push_str(&exporter.body);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum GroupKind {
+    Clip,
+    Layer,
+}
+
+struct Exporter {
+    defs: String,
+    body: String,
+    next_id: usize,
+    /// Open `<g>`s: what pushed them and how many elements to close.
+    groups: Vec<(GroupKind, usize)>,
+    transform: Matrix3x2f,
+    size: (f32, f32),
+}
+
+impl Exporter {
+    fn op(&mut self, op: &DrawOp) {
+        match op {
+            DrawOp::Clear(color) => {
+                let (width, height) = self.size;
+                let mut element =
+                    format!("<rect width=\"{}\" height=\"{}\"", num(width), num(height));
+                write_color(&mut element, "fill", *color, 1.0);
+                self.element(&element);
+            }
+            DrawOp::SetTransform(transform) => self.transform = *transform,
+            DrawOp::Fill { shape, brush } => self.fill(shape, brush),
+            DrawOp::Stroke {
+                shape,
+                brush,
+                width,
+                style,
+            } => self.stroke(shape, brush, *width, style.as_ref()),
+            DrawOp::Bitmap {
+                bitmap,
+                dest,
+                opacity,
+                interpolation,
+                source,
+            } => self.bitmap(bitmap, dest, *opacity, *interpolation, source),
+            DrawOp::Text { text, brush } => self.text(text, brush),
+            DrawOp::PushAxisAlignedClip(rect) => {
+                // Direct2D clips to the bounds of the transformed rect.
+                let bounds = transform_bounds(rect, &self.transform);
+                let clip = self.clip_path(&rect_element(&bounds), None);
+                self.open_group(GroupKind::Clip, &[clip]);
+            }
+            DrawOp::PopAxisAlignedClip => self.pop(GroupKind::Clip),
+            DrawOp::PushLayer(layer) => self.push_layer(layer),
+            DrawOp::PopLayer => self.pop(GroupKind::Layer),
+        }
+    }
+
+    fn fill(&mut self, shape: &Shape, brush: &BrushDesc) {
+        let mut element = match shape_element(shape, true) {
+            Some(element) => element,
+            None => return,
+        };
+        if let Shape::Path(path) = shape {
+            if path.fill_mode() == FillMode::Alternate {
+                element.push_str(" fill-rule=\"evenodd\"");
+            }
+        }
+        self.write_transform(&mut element);
+        self.write_paint(&mut element, "fill", brush);
+        self.element(&element);
+    }
+
+    fn stroke(
+        &mut self,
+        shape: &Shape,
+        brush: &BrushDesc,
+        width: f32,
+        style: Option<&StrokeStyleDesc>,
+    ) {
+        let mut element = match shape_element(shape, false) {
+            Some(element) => element,
+            None => return,
+        };
+        self.write_transform(&mut element);
+        element.push_str(" fill=\"none\"");
+        self.write_paint(&mut element, "stroke", brush);
+        let _ = write!(element, " stroke-width=\"{}\"", num(width));
+
+        let default = StrokeStyleDesc::new();
+        let style = style.unwrap_or(&default);
+        match style.start_cap {
+            CapStyle::Flat => {}
+            CapStyle::Square => element.push_str(" stroke-linecap=\"square\""),
+            // SVG has no triangle caps; round is the closest.
+            CapStyle::Round | CapStyle::Triangle => element.push_str(" stroke-linecap=\"round\""),
+        }
+        match style.line_join {
+            LineJoin::Miter | LineJoin::MiterOrBevel => {
+                if style.miter_limit != 4.0 {
+                    let _ = write!(
+                        element,
+                        " stroke-miterlimit=\"{}\"",
+                        num(style.miter_limit.max(1.0))
+                    );
+                }
+            }
+            LineJoin::Bevel => element.push_str(" stroke-linejoin=\"bevel\""),
+            LineJoin::Round => element.push_str(" stroke-linejoin=\"round\""),
+        }
+
+        let pattern = style.dash_pattern();
+        if !pattern.is_empty() {
+            let dashes: Vec<_> = pattern.iter().map(|d| num(d * width)).collect();
+            let _ = write!(element, " stroke-dasharray=\"{}\"", dashes.join(" "));
+            if style.dash_offset != 0.0 {
+                let _ = write!(
+                    element,
+                    " stroke-dashoffset=\"{}\"",
+                    num(style.dash_offset * width)
+                );
+            }
+        }
+        self.element(&element);
+    }
+
+    fn bitmap(
+        &mut self,
+        bitmap: &BitmapData,
+        dest: &Rectf,
+        opacity: f32,
+        interpolation: BitmapInterpolationMode,
+        source: &Rectf,
+    ) {
+        let (source_width, source_height) =
+            (source.right - source.left, source.bottom - source.top);
+        if source_width == 0.0
+            || source_height == 0.0
+            || bitmap.width() == 0
+            || bitmap.height() == 0
+        {
+            return;
+        }
+        // The whole bitmap is placed so that `source` lands on `dest`, then
+        // clipped to `dest` if that isn't all of it.
+        let scale_x = (dest.right - dest.left) / source_width;
+        let scale_y = (dest.bottom - dest.top) / source_height;
+        let placed = Rectf {
+            left: dest.left - source.left * scale_x,
+            top: dest.top - source.top * scale_y,
+            right: dest.left + (bitmap.width() as f32 - source.left) * scale_x,
+            bottom: dest.top + (bitmap.height() as f32 - source.top) * scale_y,
+        };
+        let cropped = *source != bitmap.rect();
+        if cropped {
+            let clip = self.clip_path(&rect_element(dest), Some(self.transform));
+            self.open_group(GroupKind::Clip, &[clip]);
+        }
+
+        let mut element = rect_element(&placed).replacen("<rect", "<image", 1);
+        element.push_str(" preserveAspectRatio=\"none\"");
+        self.write_transform(&mut element);
+        if opacity < 1.0 {
+            let _ = write!(element, " opacity=\"{}\"", num(opacity.max(0.0)));
+        }
+        if interpolation == BitmapInterpolationMode::NearestNeighbor {
+            element.push_str(" image-rendering=\"optimizeSpeed\"");
+        }
+        let _ = write!(
+            element,
+            " xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             xlink:href=\"data:image/png;base64,{}\"",
+            base64(&bitmap.to_png())
+        );
+        self.element(&element);
+
+        if cropped {
+            self.close_group();
+        }
+    }
+
+    fn text(&mut self, text: &TextDesc, brush: &BrushDesc) {
+        if text.text.is_empty() {
+            return;
+        }
+        let baseline = text.baseline_origin();
+        let mut element = format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\"",
+            num(baseline.x),
+            num(baseline.y),
+            escape(&text.font_family),
+            num(text.font_size)
+        );
+        self.write_transform(&mut element);
+        self.write_paint(&mut element, "fill", brush);
+        let indent = " ".repeat(self.depth());
+        let _ = writeln!(
+            self.body,
+            "{}{} xml:space=\"preserve\">{}</text>",
+            indent,
+            element,
+            escape(&text.text)
+        );
+    }
+
+    fn push_layer(&mut self, layer: &LayerDesc) {
+        let mut attributes = Vec::new();
+        if layer.opacity < 1.0 {
+            attributes.push(format!("opacity=\"{}\"", num(layer.opacity.max(0.0))));
+        }
+
+        let mut clips = Vec::new();
+        if layer.content_bounds != Rectf::INFINITE {
+            clips.push(self.clip_path(&rect_element(&layer.content_bounds), Some(self.transform)));
+        }
+        if let Some(mask) = &layer.mask {
+            let mut element = format!("<path d=\"{}\"", path_data(mask, false));
+            if mask.fill_mode() == FillMode::Alternate {
+                element.push_str(" clip-rule=\"evenodd\"");
+            }
+            let transform = layer.mask_transform * self.transform;
+            clips.push(self.clip_path(&element, Some(transform)));
+        }
+
+        // Each clip needs its own group, since a group can only be clipped
+        // by one clip path.
+        let mut groups = Vec::new();
+        if clips.is_empty() {
+            groups.push(attributes.join(" "));
+        }
+        for (i, clip) in clips.into_iter().enumerate() {
+            if i == 0 && !attributes.is_empty() {
+                groups.push(format!("{} {}", attributes.join(" "), clip));
+            } else {
+                groups.push(clip);
+            }
+        }
+        self.open_group(GroupKind::Layer, &groups);
+    }
+
+    /// Adds a clip path holding `element` to the defs and returns the
+    /// attribute which applies it.
+    fn clip_path(&mut self, element: &str, transform: Option<Matrix3x2f>) -> String {
+        let id = self.id("clip");
+        let mut element = element.to_string();
+        if let Some(transform) = transform.filter(|&t| t != Matrix3x2f::IDENTITY) {
+            write_matrix(&mut element, "transform", &transform);
+        }
+        let _ = writeln!(self.defs, "    <clipPath id=\"{}\">", id);
+        let _ = writeln!(self.defs, "      {}/>", element);
+        self.defs.push_str("    </clipPath>\n");
+        format!("clip-path=\"url(#{})\"", id)
+    }
+
+    fn open_group(&mut self, kind: GroupKind, groups: &[String]) {
+        for (i, attributes) in groups.iter().enumerate() {
+            let indent = " ".repeat(self.depth() + i * 2);
+            if attributes.is_empty() {
+                let _ = writeln!(self.body, "{}<g>", indent);
+            } else {
+                let _ = writeln!(self.body, "{}<g {}>", indent, attributes);
+            }
+        }
+        // Popping closes the whole set at once.
+        self.groups.push((kind, groups.len()));
+    }
+
+    /// Closes the innermost group if it was opened by the same kind of push;
+    /// a mismatched pop would be a drawing error in Direct2D.
+    fn pop(&mut self, kind: GroupKind) {
+        if let Some(&(top, _)) = self.groups.last() {
+            if top == kind {
+                self.close_group();
+            }
+        }
+    }
+
+    fn close_group(&mut self) {
+        if let Some((_, count)) = self.groups.pop() {
+            for i in (0..count).rev() {
+                let indent = " ".repeat(self.depth() + i * 2);
+                let _ = writeln!(self.body, "{}</g>", indent);
+            }
+        }
+    }
+
+    /// The indentation of the next element in the body.
+    fn depth(&self) -> usize {
+        let open: usize = self.groups.iter().map(|&(_, count)| count).sum();
+        2 + open * 2
+    }
+
+    fn element(&mut self, element: &str) {
+        let indent = " ".repeat(self.depth());
+        let _ = writeln!(self.body, "{}{}/>", indent, element);
+    }
+
+    fn write_transform(&self, element: &mut String) {
+        if self.transform != Matrix3x2f::IDENTITY {
+            write_matrix(element, "transform", &self.transform);
+        }
+    }
+
+    /// Writes `fill` or `stroke` and its opacity for `brush`, adding a
+    /// gradient to the defs if needed.
+    fn write_paint(&mut self, element: &mut String, attribute: &str, brush: &BrushDesc) {
+        let opacity = brush.brush_properties().opacity;
+        match brush {
+            BrushDesc::LinearGradient(gradient) if gradient.ramp.stops().is_empty() => {
+                let _ = write!(element, " {}=\"none\"", attribute);
+            }
+            BrushDesc::RadialGradient(gradient) if gradient.ramp.stops().is_empty() => {
+                let _ = write!(element, " {}=\"none\"", attribute);
+            }
+            BrushDesc::Solid { color, .. } => write_color(element, attribute, *color, opacity),
+            BrushDesc::LinearGradient(gradient) => {
+                let id = self.id("gradient");
+                let p = &gradient.properties;
+                let _ = write!(
+                    self.defs,
+                    "    <linearGradient id=\"{}\" gradientUnits=\"userSpaceOnUse\" \
+                     x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"",
+                    id,
+                    num(p.start.x),
+                    num(p.start.y),
+                    num(p.end.x),
+                    num(p.end.y)
+                );
+                self.write_gradient(&gradient.ramp, &gradient.brush.transform, "linearGradient");
+                write_gradient_paint(element, attribute, &id, opacity);
+            }
+            BrushDesc::RadialGradient(gradient) => {
+                let id = self.id("gradient");
+                let p = &gradient.properties;
+                // SVG gradients are circles, so squash one of radius_x into
+                // the ellipse with the gradient transform.
+                let squash = if p.radius_x != 0.0 {
+                    p.radius_y / p.radius_x
+                } else {
+                    1.0
+                };
+                let ellipse = Matrix3x2f {
+                    d: squash,
+                    y: p.center.y * (1.0 - squash),
+                    ..Matrix3x2f::IDENTITY
+                };
+                let focus_y = if squash != 0.0 {
+                    p.origin_offset.y / squash
+                } else {
+                    0.0
+                };
+                let _ = write!(
+                    self.defs,
+                    "    <radialGradient id=\"{}\" gradientUnits=\"userSpaceOnUse\" \
+                     cx=\"{}\" cy=\"{}\" r=\"{}\" fx=\"{}\" fy=\"{}\"",
+                    id,
+                    num(p.center.x),
+                    num(p.center.y),
+                    num(p.radius_x.abs()),
+                    num(p.center.x + p.origin_offset.x),
+                    num(p.center.y + focus_y)
+                );
+                let transform = ellipse * gradient.brush.transform;
+                self.write_gradient(&gradient.ramp, &transform, "radialGradient");
+                write_gradient_paint(element, attribute, &id, opacity);
+            }
+        }
+    }
+
+    /// Finishes a gradient whose opening tag has been started in the defs.
+    fn write_gradient(&mut self, ramp: &GradientRamp, transform: &Matrix3x2f, tag: &str) {
+        match ramp.extend_mode() {
+            ExtendMode::Clamp => {}
+            ExtendMode::Wrap => self.defs.push_str(" spreadMethod=\"repeat\""),
+            ExtendMode::Mirror => self.defs.push_str(" spreadMethod=\"reflect\""),
+        }
+        if *transform != Matrix3x2f::IDENTITY {
+            write_matrix(&mut self.defs, "gradientTransform", transform);
+        }
+        if ramp.gamma() == Gamma::_1_0 {
+            self.defs.push_str(" color-interpolation=\"linearRGB\"");
+        }
+        self.defs.push_str(">\n");
+        for stop in ramp.stops() {
+            let mut element = format!("<stop offset=\"{}\"", num(stop.position.clamp(0.0, 1.0)));
+            write_color_as(&mut element, "stop-color", "stop-opacity", stop.color, 1.0);
+            let _ = writeln!(self.defs, "      {}/>", element);
+        }
+        let _ = writeln!(self.defs, "    </{}>", tag);
+    }
+
+    fn id(&mut self, prefix: &str) -> String {
+        let id = format!("{}{}", prefix, self.next_id);
+        self.next_id += 1;
+        id
+    }
+}
+
+/// The opening of the element for `shape`, or `None` if it draws nothing.
+/// Fills leave out lines and hollow figures, which Direct2D doesn't fill.
+fn shape_element(shape: &Shape, fill: bool) -> Option<String> {
+    Some(match shape {
+        Shape::Rectangle(rect) => rect_element(rect),
+        Shape::RoundedRectangle(rounded) => {
+            let mut element = rect_element(&rounded.rect);
+            let _ = write!(
+                element,
+                " rx=\"{}\" ry=\"{}\"",
+                num(rounded.radius_x.abs()),
+                num(rounded.radius_y.abs())
+            );
+            element
+        }
+        Shape::Ellipse(ellipse) => format!(
+            "<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\"",
+            num(ellipse.center.x),
+            num(ellipse.center.y),
+            num(ellipse.radius_x.abs()),
+            num(ellipse.radius_y.abs())
+        ),
+        Shape::Line(p0, p1) if !fill => format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"",
+            num(p0.x),
+            num(p0.y),
+            num(p1.x),
+            num(p1.y)
+        ),
+        Shape::Line(..) => return None,
+        Shape::Path(path) => {
+            let d = path_data(path, fill);
+            if d.is_empty() {
+                return None;
+            }
+            format!("<path d=\"{}\"", d)
+        }
+    })
+}
+
+fn rect_element(rect: &Rectf) -> String {
+    format!(
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"",
+        num(rect.left.min(rect.right)),
+        num(rect.top.min(rect.bottom)),
+        num((rect.right - rect.left).abs()),
+        num((rect.bottom - rect.top).abs())
+    )
+}
+
+/// The path's `d` attribute. With `filled_only`, hollow figures are skipped.
+pub(crate) fn path_data(path: &PathData, filled_only: bool) -> String {
+    let mut d = Vec::new();
+    let mut skipping = false;
+    for event in path.events() {
+        match *event {
+            PathEvent::Begin { start, begin } => {
+                skipping = filled_only && begin == FigureBegin::Hollow;
+                if !skipping {
+                    d.push(format!("M {} {}", num(start.x), num(start.y)));
+                }
+            }
+            _ if skipping => {}
+            PathEvent::Line(p) => d.push(format!("L {} {}", num(p.x), num(p.y))),
+            PathEvent::Quadratic(q) => d.push(format!(
+                "Q {} {} {} {}",
+                num(q.p1.x),
+                num(q.p1.y),
+                num(q.p2.x),
+                num(q.p2.y)
+            )),
+            PathEvent::Cubic(c) => d.push(format!(
+                "C {} {} {} {} {} {}",
+                num(c.p1.x),
+                num(c.p1.y),
+                num(c.p2.x),
+                num(c.p2.y),
+                num(c.p3.x),
+                num(c.p3.y)
+            )),
+            PathEvent::Arc(a) => d.push(format!(
+                "A {} {} {} {} {} {} {}",
+                num(a.size.width.abs()),
+                num(a.size.height.abs()),
+                num(a.rotation_angle),
+                (a.arc_size == ArcSize::Large) as u8,
+                (a.sweep_direction == SweepDirection::Clockwise) as u8,
+                num(a.point.x),
+                num(a.point.y)
+            )),
+            PathEvent::End(FigureEnd::Closed) => d.push("Z".into()),
+            PathEvent::End(FigureEnd::Open) => {}
+        }
+    }
+    d.join(" ")
+}
+
+fn write_color(element: &mut String, attribute: &str, color: Color, opacity: f32) {
+    write_color_as(
+        element,
+        attribute,
+        &format!("{}-opacity", attribute),
+        color,
+        opacity,
+    );
+}
+
+fn write_color_as(
+    element: &mut String,
+    attribute: &str,
+    opacity_attribute: &str,
+    color: Color,
+    opacity: f32,
+) {
+    let _ = write!(
+        element,
+        " {}=\"#{:02x}{:02x}{:02x}\"",
+        attribute,
+        to_u8(color.r),
+        to_u8(color.g),
+        to_u8(color.b)
+    );
+    let alpha = color.a * opacity;
+    if alpha < 1.0 {
+        let _ = write!(
+            element,
+            " {}=\"{}\"",
+            opacity_attribute,
+            num(alpha.max(0.0))
+        );
+    }
+}
+
+fn write_gradient_paint(element: &mut String, attribute: &str, id: &str, opacity: f32) {
+    let _ = write!(element, " {}=\"url(#{})\"", attribute, id);
+    if opacity < 1.0 {
+        let _ = write!(
+            element,
+            " {}-opacity=\"{}\"",
+            attribute,
+            num(opacity.max(0.0))
+        );
+    }
+}
+
+/// Escapes text for use in element content or a quoted attribute.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.chunks(3).len() * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - i * 6)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn write_matrix(element: &mut String, attribute: &str, m: &Matrix3x2f) {
+    let _ = write!(
+        element,
+        " {}=\"matrix({} {} {} {} {} {})\"",
+        attribute,
+        num(m.a),
+        num(m.b),
+        num(m.c),
+        num(m.d),
+        num(m.x),
+        num(m.y)
+    );
+}
+
+/// Formats `value` with at most three decimal places and no trailing zeros.
+pub(crate) fn num(value: f32) -> String {
+    if !value.is_finite() {
+        return "0".into();
+    }
+    let mut s = format!("{:.3}", value);
+    if s.contains('.') {
+        while s.ends_with('0') {
+            s.pop();
+        }
+        if s.ends_with('.') {
+            s.pop();
+        }
+    }
+    if s == "-0" {
+        s = "0".into();
+    }
+    s
+}
@@ -0,0 +1,17 @@
+//! Scalable Vector Graphics.
+//!
+//! `to_svg` writes a `Recording` out as an SVG 1.1 document, and `Document`
+//! reads a static subset of SVG into a tree of shapes which can be recorded
+//! or drawn to a render target.
+
+pub use self::document::{Document, Group, Node, ShapeNode, Stroke};
+pub use self::error::SvgError;
+pub use self::export::to_svg;
+pub use self::path::parse_path;
+
+pub mod document;
+pub mod error;
+pub mod export;
+pub mod path;
+pub mod values;
+mod xml;
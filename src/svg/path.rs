@@ -0,0 +1,232 @@
+//! The SVG path data syntax, as used by the `d` attribute.
+
+use crate::enums::{FigureBegin, FigureEnd};
+use crate::geometry::PathData;
+use crate::svg::values::Scanner;
+use crate::svg::SvgError;
+
+use math2d::{
+    ArcSegment, ArcSize, BezierSegment, Point2f, QuadBezierSegment, Sizef, SweepDirection,
+};
+
+/// Parses path data such as `M 0 0 L 10 10 Z` into a path with the default
+/// fill mode.
+pub fn parse_path(d: &str) -> Result<PathData, SvgError> {
+    match parse_path_partial(d) {
+        (path, None) => Ok(path),
+        (_, Some(error)) => Err(error),
+    }
+}
+
+/// Parses as much of `d` as is valid, which is how documents render a bad
+/// path, along with the error that stopped it.
+pub fn parse_path_partial(d: &str) -> (PathData, Option<SvgError>) {
+    let mut parser = PathParser {
+        path: PathData::new(),
+        open: false,
+        current: Point2f::ORIGIN,
+        start: Point2f::ORIGIN,
+        last_control: None,
+    };
+    let mut scanner = Scanner::new(d);
+    let error = parser.parse(&mut scanner).err();
+    parser.path.end_figure(FigureEnd::Open);
+    (parser.path, error)
+}
+
+struct PathParser {
+    path: PathData,
+    /// Whether a figure has been begun for the current subpath. Figures are
+    /// only begun once they get a segment.
+    open: bool,
+    current: Point2f,
+    start: Point2f,
+    /// The command and control point which `S` and `T` reflect.
+    last_control: Option<(u8, Point2f)>,
+}
+
+impl PathParser {
+    fn parse(&mut self, s: &mut Scanner) -> Result<(), SvgError> {
+        let mut previous: Option<u8> = None;
+        while !s.is_done() {
+            let at = s.pos;
+            let command = match s.peek() {
+                Some(c) if c.is_ascii_alphabetic() => {
+                    s.pos += 1;
+                    c
+                }
+                // Coordinates after a command repeat it, and after a move
+                // they're lines.
+                _ => match previous {
+                    Some(b'M') => b'L',
+                    Some(b'm') => b'l',
+                    Some(c) if c != b'Z' && c != b'z' => c,
+                    _ => return Err(SvgError::PathData(s.pos)),
+                },
+            };
+            if previous.is_none() && command != b'M' && command != b'm' {
+                return Err(SvgError::PathData(at));
+            }
+            self.command(s, command)?;
+            previous = Some(command);
+        }
+        Ok(())
+    }
+
+    fn command(&mut self, s: &mut Scanner, command: u8) -> Result<(), SvgError> {
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative {
+            self.current
+        } else {
+            Point2f::ORIGIN
+        };
+        let start = s.pos;
+        let number = |s: &mut Scanner| s.number().ok_or(SvgError::PathData(start));
+        let point = |s: &mut Scanner| -> Result<Point2f, SvgError> {
+            let x = number(s)?;
+            let y = number(s)?;
+            Ok(Point2f {
+                x: origin.x + x,
+                y: origin.y + y,
+            })
+        };
+
+        let mut control = None;
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                let p = point(s)?;
+                self.path.end_figure(FigureEnd::Open);
+                self.open = false;
+                self.current = p;
+                self.start = p;
+            }
+            b'L' => {
+                let p = point(s)?;
+                self.line(p);
+            }
+            b'H' => {
+                let x = number(s)?;
+                let p = Point2f {
+                    x: origin.x + x,
+                    y: self.current.y,
+                };
+                self.line(p);
+            }
+            b'V' => {
+                let y = number(s)?;
+                let p = Point2f {
+                    x: self.current.x,
+                    y: origin.y + y,
+                };
+                self.line(p);
+            }
+            b'C' => {
+                let p1 = point(s)?;
+                let p2 = point(s)?;
+                let p3 = point(s)?;
+                self.cubic(p1, p2, p3);
+                control = Some((b'C', p2));
+            }
+            b'S' => {
+                let p1 = self.reflect(b'C');
+                let p2 = point(s)?;
+                let p3 = point(s)?;
+                self.cubic(p1, p2, p3);
+                control = Some((b'C', p2));
+            }
+            b'Q' => {
+                let p1 = point(s)?;
+                let p2 = point(s)?;
+                self.quadratic(p1, p2);
+                control = Some((b'Q', p1));
+            }
+            b'T' => {
+                let p1 = self.reflect(b'Q');
+                let p2 = point(s)?;
+                self.quadratic(p1, p2);
+                control = Some((b'Q', p1));
+            }
+            b'A' => {
+                let rx = number(s)?.abs();
+                let ry = number(s)?.abs();
+                let rotation_angle = number(s)?;
+                let large = s.flag().ok_or(SvgError::PathData(start))?;
+                let sweep = s.flag().ok_or(SvgError::PathData(start))?;
+                let p = point(s)?;
+                if p != self.current {
+                    if rx == 0.0 || ry == 0.0 {
+                        self.line(p);
+                    } else {
+                        self.begin();
+                        self.path.add_arc(&ArcSegment {
+                            point: p,
+                            size: Sizef::new(rx, ry),
+                            rotation_angle,
+                            sweep_direction: if sweep {
+                                SweepDirection::Clockwise
+                            } else {
+                                SweepDirection::CounterClockwise
+                            },
+                            arc_size: if large {
+                                ArcSize::Large
+                            } else {
+                                ArcSize::Small
+                            },
+                        });
+                        self.current = p;
+                    }
+                }
+            }
+            b'Z' => {
+                if self.open {
+                    self.path.end_figure(FigureEnd::Closed);
+                    self.open = false;
+                }
+                self.current = self.start;
+            }
+            _ => return Err(SvgError::PathData(start - 1)),
+        }
+        self.last_control = control;
+        Ok(())
+    }
+
+    /// Begins a figure at the start of the subpath if one isn't open.
+    fn begin(&mut self) {
+        if !self.open {
+            self.start = self.current;
+            self.path.begin_figure(self.current, FigureBegin::Filled);
+            self.open = true;
+        }
+    }
+
+    fn line(&mut self, p: Point2f) {
+        self.begin();
+        self.path.add_line(p);
+        self.current = p;
+    }
+
+    fn cubic(&mut self, p1: Point2f, p2: Point2f, p3: Point2f) {
+        self.begin();
+        self.path.add_bezier(&BezierSegment { p1, p2, p3 });
+        self.current = p3;
+    }
+
+    fn quadratic(&mut self, p1: Point2f, p2: Point2f) {
+        self.begin();
+        self.path
+            .add_quadratic_bezier(&QuadBezierSegment { p1, p2 });
+        self.current = p2;
+    }
+
+    /// The previous control point of the same kind reflected through the
+    /// current point, or the current point if there isn't one.
+    fn reflect(&self, kind: u8) -> Point2f {
+        match self.last_control {
+            Some((k, c)) if k == kind => Point2f {
+                x: 2.0 * self.current.x - c.x,
+                y: 2.0 * self.current.y - c.y,
+            },
+            _ => self.current,
+        }
+    }
+}
@@ -0,0 +1,315 @@
+//! Attribute value syntax: numbers, lengths, colors and transforms.
+
+use math2d::{Color, Matrix3x2f};
+
+/// Reads numbers and flags separated by whitespace and commas.
+pub(crate) struct Scanner<'a> {
+    text: &'a [u8],
+    pub pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(text: &'a str) -> Scanner<'a> {
+        Scanner {
+            text: text.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    /// Skips whitespace and commas.
+    pub fn skip_separators(&mut self) {
+        while self.pos < self.text.len()
+            && (self.text[self.pos].is_ascii_whitespace() || self.text[self.pos] == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    pub fn is_done(&mut self) -> bool {
+        self.skip_separators();
+        self.pos >= self.text.len()
+    }
+
+    pub fn peek(&mut self) -> Option<u8> {
+        self.skip_separators();
+        self.text.get(self.pos).cloned()
+    }
+
+    /// The next number. On failure nothing is consumed but separators.
+    pub fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.pos;
+        let mut i = start;
+        let digits = |text: &[u8], mut i: usize| {
+            while i < text.len() && text[i].is_ascii_digit() {
+                i += 1;
+            }
+            i
+        };
+        if i < self.text.len() && (self.text[i] == b'+' || self.text[i] == b'-') {
+            i += 1;
+        }
+        let int_end = digits(self.text, i);
+        let mut end = int_end;
+        if end < self.text.len() && self.text[end] == b'.' {
+            end = digits(self.text, end + 1);
+        }
+        if end == i || (end == i + 1 && self.text[i] == b'.') {
+            return None;
+        }
+        if end < self.text.len() && (self.text[end] == b'e' || self.text[end] == b'E') {
+            let mut e = end + 1;
+            if e < self.text.len() && (self.text[e] == b'+' || self.text[e] == b'-') {
+                e += 1;
+            }
+            let e_end = digits(self.text, e);
+            if e_end > e {
+                end = e_end;
+            }
+        }
+        let value = std::str::from_utf8(&self.text[start..end])
+            .ok()?
+            .parse()
+            .ok()?;
+        self.pos = end;
+        Some(value)
+    }
+
+    /// An arc flag, which is a single `0` or `1` that needn't be separated
+    /// from what follows.
+    pub fn flag(&mut self) -> Option<bool> {
+        match self.peek()? {
+            b'0' => {
+                self.pos += 1;
+                Some(false)
+            }
+            b'1' => {
+                self.pos += 1;
+                Some(true)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Every number in a list such as `points` or `viewBox`, stopping at the
+/// first thing which isn't one.
+pub fn parse_numbers(text: &str) -> Vec<f32> {
+    let mut scanner = Scanner::new(text);
+    let mut numbers = Vec::new();
+    while let Some(n) = scanner.number() {
+        numbers.push(n);
+    }
+    numbers
+}
+
+/// A length or percentage.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Length {
+    /// In user units (DIPs).
+    Number(f32),
+    Percent(f32),
+}
+
+impl Length {
+    /// The length in user units, with percentages taken of `reference`.
+    pub fn resolve(self, reference: f32) -> f32 {
+        match self {
+            Length::Number(n) => n,
+            Length::Percent(p) => p / 100.0 * reference,
+        }
+    }
+}
+
+/// Parses a length with an optional unit. Absolute units are converted at
+/// 96 user units to the inch, and `em` is taken as 16.
+pub fn parse_length(text: &str) -> Option<Length> {
+    let text = text.trim();
+    let mut scanner = Scanner::new(text);
+    let n = scanner.number()?;
+    let scale = match &text[scanner.pos..] {
+        "" | "px" => 1.0,
+        "%" => return Some(Length::Percent(n)),
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        "em" => 16.0,
+        "ex" => 8.0,
+        _ => return None,
+    };
+    Some(Length::Number(n * scale))
+}
+
+/// Parses a transform list, such as `translate(10 20) rotate(45)`.
+pub fn parse_transform(text: &str) -> Option<Matrix3x2f> {
+    let mut result = Matrix3x2f::IDENTITY;
+    let mut rest = text.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    while !rest.is_empty() {
+        let open = rest.find('(')?;
+        let close = rest.find(')')?;
+        if close < open {
+            return None;
+        }
+        let name = rest[..open].trim();
+        let args = parse_numbers(&rest[open + 1..close]);
+        let matrix = |a, b, c, d, x, y| Matrix3x2f { a, b, c, d, x, y };
+        let m = match (name, args.as_slice()) {
+            ("matrix", &[a, b, c, d, x, y]) => matrix(a, b, c, d, x, y),
+            ("translate", &[x]) => matrix(1.0, 0.0, 0.0, 1.0, x, 0.0),
+            ("translate", &[x, y]) => matrix(1.0, 0.0, 0.0, 1.0, x, y),
+            ("scale", &[s]) => matrix(s, 0.0, 0.0, s, 0.0, 0.0),
+            ("scale", &[sx, sy]) => matrix(sx, 0.0, 0.0, sy, 0.0, 0.0),
+            ("rotate", &[angle]) => rotation(angle, 0.0, 0.0),
+            ("rotate", &[angle, cx, cy]) => rotation(angle, cx, cy),
+            ("skewX", &[angle]) => matrix(1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0),
+            ("skewY", &[angle]) => matrix(1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0),
+            _ => return None,
+        };
+        // Later transforms in the list apply first.
+        result = m * result;
+        rest = rest[close + 1..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Some(result)
+}
+
+fn rotation(degrees: f32, cx: f32, cy: f32) -> Matrix3x2f {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    Matrix3x2f {
+        a: cos,
+        b: sin,
+        c: -sin,
+        d: cos,
+        x: cx - cx * cos + cy * sin,
+        y: cy - cx * sin - cy * cos,
+    }
+}
+
+/// Parses `#rgb`, `#rrggbb`, their forms with alpha, `rgb()`, `rgba()` and
+/// the basic color keywords.
+pub fn parse_color(text: &str) -> Option<Color> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix('#') {
+        let digits: Vec<u32> = hex.chars().map(|c| c.to_digit(16)).collect::<Option<_>>()?;
+        let channel = |hi: u32, lo: u32| (hi * 16 + lo) as f32 / 255.0;
+        return match *digits.as_slice() {
+            [r, g, b] => Some(Color::new(channel(r, r), channel(g, g), channel(b, b), 1.0)),
+            [r, g, b, a] => Some(Color::new(
+                channel(r, r),
+                channel(g, g),
+                channel(b, b),
+                channel(a, a),
+            )),
+            [r1, r2, g1, g2, b1, b2] => Some(Color::new(
+                channel(r1, r2),
+                channel(g1, g2),
+                channel(b1, b2),
+                1.0,
+            )),
+            [r1, r2, g1, g2, b1, b2, a1, a2] => Some(Color::new(
+                channel(r1, r2),
+                channel(g1, g2),
+                channel(b1, b2),
+                channel(a1, a2),
+            )),
+            _ => None,
+        };
+    }
+
+    let lower = text.to_ascii_lowercase();
+    if lower.starts_with("rgb(") || lower.starts_with("rgba(") {
+        let args = &lower[lower.find('(')? + 1..lower.rfind(')')?];
+        let parts: Vec<&str> = args
+            .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if parts.len() != 3 && parts.len() != 4 {
+            return None;
+        }
+        let channel = |s: &str| -> Option<f32> {
+            match s.strip_suffix('%') {
+                Some(p) => p.parse::<f32>().ok().map(|p| p / 100.0),
+                None => s.parse::<f32>().ok().map(|v| v / 255.0),
+            }
+        };
+        let alpha = match parts.get(3) {
+            Some(s) => match s.strip_suffix('%') {
+                Some(p) => p.parse::<f32>().ok()? / 100.0,
+                None => s.parse::<f32>().ok()?,
+            },
+            None => 1.0,
+        };
+        let clamp = |v: f32| v.clamp(0.0, 1.0);
+        return Some(Color::new(
+            clamp(channel(parts[0])?),
+            clamp(channel(parts[1])?),
+            clamp(channel(parts[2])?),
+            clamp(alpha),
+        ));
+    }
+
+    let rgb = match lower.as_str() {
+        "black" => 0x000000,
+        "silver" => 0xc0c0c0,
+        "gray" | "grey" => 0x808080,
+        "white" => 0xffffff,
+        "maroon" => 0x800000,
+        "red" => 0xff0000,
+        "purple" => 0x800080,
+        "fuchsia" | "magenta" => 0xff00ff,
+        "green" => 0x008000,
+        "lime" => 0x00ff00,
+        "olive" => 0x808000,
+        "yellow" => 0xffff00,
+        "navy" => 0x000080,
+        "blue" => 0x0000ff,
+        "teal" => 0x008080,
+        "aqua" | "cyan" => 0x00ffff,
+        "orange" => 0xffa500,
+        "darkgray" | "darkgrey" => 0xa9a9a9,
+        "lightgray" | "lightgrey" => 0xd3d3d3,
+        "transparent" => return Some(Color::new(0.0, 0.0, 0.0, 0.0)),
+        _ => return None,
+    };
+    let channel = |shift: u32| ((rgb >> shift) & 0xff) as f32 / 255.0;
+    Some(Color::new(channel(16), channel(8), channel(0), 1.0))
+}
+
+/// How a fill or stroke is painted, before references are resolved.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Paint {
+    None,
+    Color(Color),
+    CurrentColor,
+    /// A gradient's id, and the paint to use if it doesn't exist.
+    Url(String, Option<Box<Paint>>),
+}
+
+pub fn parse_paint(text: &str) -> Option<Paint> {
+    let text = text.trim();
+    match text {
+        "none" => return Some(Paint::None),
+        "currentColor" => return Some(Paint::CurrentColor),
+        _ => {}
+    }
+    if let Some(rest) = text.strip_prefix("url(") {
+        let close = rest.find(')')?;
+        let id = parse_url(&text[..close + 5])?;
+        let fallback = rest[close + 1..].trim();
+        let fallback = if fallback.is_empty() {
+            None
+        } else {
+            Some(Box::new(parse_paint(fallback)?))
+        };
+        return Some(Paint::Url(id, fallback));
+    }
+    parse_color(text).map(Paint::Color)
+}
+
+/// The id in a local reference such as `url(#clip)` or `url('#clip')`.
+pub fn parse_url(text: &str) -> Option<String> {
+    let inner = text.trim().strip_prefix("url(")?.strip_suffix(')')?.trim();
+    let inner = inner.trim_matches(|c| c == '"' || c == '\'');
+    inner.strip_prefix('#').map(str::to_string)
+}
@@ -0,0 +1,252 @@
+//! Just enough XML to read SVG files: elements and attributes, with
+//! comments, processing instructions, doctypes, CDATA and text skipped.
+
+use crate::svg::SvgError;
+
+/// Deeper nesting than this is rejected rather than risking the stack.
+const MAX_DEPTH: usize = 256;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Element {
+    /// The name without any namespace prefix.
+    pub name: String,
+    /// Names as written, values with entities decoded.
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+}
+
+impl Element {
+    /// The value of an attribute, matching `name` with or without a
+    /// namespace prefix (so `href` finds `xlink:href`).
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name || n.rsplit(':').next() == Some(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Calls `f` with this element and everything below it, in document
+    /// order.
+    pub fn walk<'a>(&'a self, f: &mut dyn FnMut(&'a Element)) {
+        f(self);
+        for child in &self.children {
+            child.walk(f);
+        }
+    }
+}
+
+/// Parses a document, returning its root element.
+pub fn parse(text: &str) -> Result<Element, SvgError> {
+    let mut parser = Parser { text, pos: 0 };
+    parser.skip_misc()?;
+    if !parser.rest().starts_with('<') {
+        return Err(parser.error("expected an element"));
+    }
+    let root = parser.element(0)?;
+    parser.skip_misc()?;
+    if parser.pos < text.len() {
+        return Err(parser.error("content after the root element"));
+    }
+    Ok(root)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn error(&self, message: &'static str) -> SvgError {
+        SvgError::Xml(self.pos, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Moves past `end`, which must come later in the text.
+    fn skip_past(&mut self, end: &str, message: &'static str) -> Result<(), SvgError> {
+        match self.rest().find(end) {
+            Some(i) => {
+                self.pos += i + end.len();
+                Ok(())
+            }
+            None => Err(self.error(message)),
+        }
+    }
+
+    /// Skips whitespace, comments, processing instructions and doctypes
+    /// outside the root element.
+    fn skip_misc(&mut self) -> Result<(), SvgError> {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("<!--") {
+                self.skip_past("-->", "unterminated comment")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>", "unterminated processing instruction")?;
+            } else if rest.starts_with("<!DOCTYPE") {
+                self.skip_doctype()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_doctype(&mut self) -> Result<(), SvgError> {
+        let mut in_subset = false;
+        for (i, c) in self.rest().char_indices() {
+            match c {
+                '[' => in_subset = true,
+                ']' => in_subset = false,
+                '>' if !in_subset => {
+                    self.pos += i + 1;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        Err(self.error("unterminated doctype"))
+    }
+
+    fn name(&mut self) -> Result<&'a str, SvgError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || "=/>".contains(c))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn expect(&mut self, c: char, message: &'static str) -> Result<(), SvgError> {
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, SvgError> {
+        let quote = match self.rest().chars().next() {
+            Some(c @ '"') | Some(c @ '\'') => c,
+            _ => return Err(self.error("expected a quoted value")),
+        };
+        self.pos += 1;
+        let rest = self.rest();
+        match rest.find(quote) {
+            Some(end) => {
+                self.pos += end + 1;
+                Ok(decode_entities(&rest[..end]))
+            }
+            None => Err(self.error("unterminated attribute value")),
+        }
+    }
+
+    fn element(&mut self, depth: usize) -> Result<Element, SvgError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("elements nested too deeply"));
+        }
+        self.expect('<', "expected an element")?;
+        let tag = self.name()?;
+        let mut element = Element {
+            name: tag.rsplit(':').next().unwrap_or(tag).to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        };
+
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            } else if rest.starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let name = self.name()?;
+            self.skip_whitespace();
+            self.expect('=', "expected '=' after an attribute name")?;
+            self.skip_whitespace();
+            let value = self.quoted()?;
+            element.attributes.push((name.to_string(), value));
+        }
+
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error("unclosed element"));
+            } else if rest.starts_with("</") {
+                self.pos += 2;
+                let at = self.pos;
+                if self.name()? != tag {
+                    return Err(SvgError::Xml(at, "mismatched end tag"));
+                }
+                self.skip_whitespace();
+                self.expect('>', "expected '>'")?;
+                return Ok(element);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->", "unterminated comment")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.skip_past("]]>", "unterminated CDATA section")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>", "unterminated processing instruction")?;
+            } else if rest.starts_with('<') {
+                let child = self.element(depth + 1)?;
+                element.children.push(child);
+            } else {
+                self.pos += rest.find('<').unwrap_or(rest.len());
+            }
+        }
+    }
+}
+
+/// Replaces the predefined entities and character references. Anything
+/// else starting with `&` is left as it is.
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(end) => end,
+            None => break,
+        };
+        let decoded = match &rest[1..end] {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            entity if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16)
+                .ok()
+                .and_then(std::char::from_u32),
+            entity if entity.starts_with('#') => {
+                entity[1..].parse().ok().and_then(std::char::from_u32)
+            }
+            _ => None,
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,13 @@
+//! Presenting a `DeviceContext` to a window through a DXGI swap chain.
+//!
+//! `SwapChainTarget` does the DXGI work; the bookkeeping around resizing,
+//! minimizing and partial presents lives in `SwapChainState`, which works
+//! with any `SwapChainBackend`.
+
+pub use self::state::{PresentParams, PresentStatus, Scroll, SwapChainBackend, SwapChainState};
+#[cfg(windows)]
+pub use self::target::{SwapChainTarget, SwapChainTargetBuilder};
+
+pub mod state;
+#[cfg(windows)]
+pub mod target;
@@ -0,0 +1,279 @@
+use crate::error::{DXGI_STATUS_OCCLUDED, E_INVALIDARG, HRESULT};
+use crate::render_target::WindowRect;
+
+use math2d::Sizeu;
+
+/// The operations `SwapChainState` needs from a swap chain. `SwapChainTarget`
+/// implements this over DXGI; tests can implement it with a fake.
+pub trait SwapChainBackend {
+    /// What gets drawn to, such as a `Bitmap1` wrapping the back buffer.
+    type Target;
+    type Error: From<HRESULT>;
+
+    /// Wraps the current back buffer so it can be drawn to.
+    fn create_target(&mut self) -> Result<Self::Target, Self::Error>;
+
+    /// Gives up a target before the buffers are resized. Every reference to
+    /// the back buffer must be released by the time this returns.
+    fn release_target(&mut self, target: Self::Target);
+
+    fn resize_buffers(&mut self, size: Sizeu) -> Result<(), Self::Error>;
+
+    fn present(&mut self, params: &PresentParams) -> Result<PresentStatus, Self::Error>;
+}
+
+/// The outcome of a successful `SwapChainState::present`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PresentStatus {
+    Presented,
+    /// The window is hidden. Rendering can be throttled until it isn't.
+    Occluded,
+    /// Nothing was presented because the window is minimized.
+    Skipped,
+}
+
+impl PresentStatus {
+    /// Interprets the success code returned by `Present` or `Present1`.
+    pub fn from_hresult(hr: HRESULT) -> PresentStatus {
+        if hr == DXGI_STATUS_OCCLUDED {
+            PresentStatus::Occluded
+        } else {
+            PresentStatus::Presented
+        }
+    }
+}
+
+/// A region of the back buffer that moved since the last frame, so only the
+/// newly exposed part needs to be redrawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Scroll {
+    pub rect: WindowRect,
+    pub dx: i32,
+    pub dy: i32,
+}
+
+/// How a frame should be presented.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PresentParams<'a> {
+    /// 0 presents immediately, 1-4 waits for that many vertical blanks.
+    pub sync_interval: u32,
+    /// Lets an immediate present tear on displays with variable refresh rate.
+    /// The swap chain must have been created with tearing allowed.
+    pub allow_tearing: bool,
+    /// The parts of the frame that changed. Empty means all of it.
+    pub dirty_rects: &'a [WindowRect],
+    pub scroll: Option<Scroll>,
+}
+
+impl<'a> PresentParams<'a> {
+    /// Waits for the next vertical blank.
+    pub fn vsync() -> PresentParams<'a> {
+        PresentParams {
+            sync_interval: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Presents without waiting, tearing if `allow_tearing` is set.
+    pub fn immediate(allow_tearing: bool) -> PresentParams<'a> {
+        PresentParams {
+            sync_interval: 0,
+            allow_tearing,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_dirty_rects(mut self, rects: &'a [WindowRect]) -> Self {
+        self.dirty_rects = rects;
+        self
+    }
+
+    pub fn with_scroll(mut self, scroll: Scroll) -> Self {
+        self.scroll = Some(scroll);
+        self
+    }
+
+    /// Whether only part of the frame is being presented.
+    pub fn is_partial(&self) -> bool {
+        !self.dirty_rects.is_empty() || self.scroll.is_some()
+    }
+
+    /// The same parameters, presenting the whole frame.
+    pub fn full(&self) -> PresentParams<'a> {
+        PresentParams {
+            dirty_rects: &[],
+            scroll: None,
+            ..*self
+        }
+    }
+
+    /// Checks the rules DXGI enforces: a sync interval of at most 4, tearing
+    /// only without vsync, and every rect inside a buffer of `size`.
+    pub fn validate(&self, size: Sizeu) -> Result<(), HRESULT> {
+        let inside = |r: &WindowRect| {
+            r.left >= 0
+                && r.top >= 0
+                && r.left <= r.right
+                && r.top <= r.bottom
+                && r.right as i64 <= size.width as i64
+                && r.bottom as i64 <= size.height as i64
+        };
+
+        if self.sync_interval > 4 || (self.allow_tearing && self.sync_interval != 0) {
+            return Err(E_INVALIDARG);
+        }
+        if !self.dirty_rects.iter().all(inside) {
+            return Err(E_INVALIDARG);
+        }
+        if let Some(scroll) = self.scroll {
+            let moved = WindowRect::new(
+                scroll.rect.left + scroll.dx,
+                scroll.rect.top + scroll.dy,
+                scroll.rect.right + scroll.dx,
+                scroll.rect.bottom + scroll.dy,
+            );
+            if !inside(&scroll.rect) || !inside(&moved) {
+                return Err(E_INVALIDARG);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tracks a swap chain's size and back buffer target through resizes,
+/// minimizing and presents.
+///
+/// The target is created lazily and released before every resize, since DXGI
+/// refuses to resize while anything still refers to the back buffer. A resize
+/// that fails is retried the next time the target is needed, and the first
+/// present after a resize is always a full one because the new buffers start
+/// out undefined.
+#[derive(Debug)]
+pub struct SwapChainState<B: SwapChainBackend> {
+    backend: B,
+    target: Option<B::Target>,
+    size: Sizeu,
+    pending_size: Option<Sizeu>,
+    minimized: bool,
+    occluded: bool,
+    needs_full_present: bool,
+}
+
+impl<B: SwapChainBackend> SwapChainState<B> {
+    /// Wraps a backend whose buffers are currently `size`.
+    pub fn new(backend: B, size: Sizeu) -> SwapChainState<B> {
+        SwapChainState {
+            backend,
+            target: None,
+            size,
+            pending_size: None,
+            minimized: false,
+            occluded: false,
+            needs_full_present: true,
+        }
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// The current size of the buffers.
+    pub fn size(&self) -> Sizeu {
+        self.size
+    }
+
+    /// A size that was asked for but hasn't been applied yet.
+    pub fn pending_size(&self) -> Option<Sizeu> {
+        self.pending_size
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        self.minimized
+    }
+
+    /// Whether the last present reported the window as hidden.
+    pub fn is_occluded(&self) -> bool {
+        self.occluded
+    }
+
+    /// Whether a target for the current back buffer exists.
+    pub fn has_target(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// The target for the current back buffer, creating it first if needed.
+    pub fn target(&mut self) -> Result<&mut B::Target, B::Error> {
+        if let Some(size) = self.pending_size {
+            self.resize(size)?;
+        }
+        if self.target.is_none() {
+            self.target = Some(self.backend.create_target()?);
+        }
+        Ok(self.target.as_mut().unwrap())
+    }
+
+    /// Resizes the buffers, returning whether anything changed. A zero width
+    /// or height means the window was minimized; the buffers are kept as they
+    /// are until it's restored.
+    pub fn resize(&mut self, size: Sizeu) -> Result<bool, B::Error> {
+        if size.width == 0 || size.height == 0 {
+            self.minimized = true;
+            return Ok(false);
+        }
+        self.minimized = false;
+
+        if size == self.size && self.pending_size.is_none() {
+            return Ok(false);
+        }
+
+        self.release_target();
+        match self.backend.resize_buffers(size) {
+            Ok(()) => {
+                self.size = size;
+                self.pending_size = None;
+                self.needs_full_present = true;
+                Ok(true)
+            }
+            Err(e) => {
+                self.pending_size = Some(size);
+                Err(e)
+            }
+        }
+    }
+
+    /// Presents the back buffer. Partial presents are widened to the whole
+    /// frame when the buffer contents can't be relied on, and nothing happens
+    /// while minimized.
+    pub fn present(&mut self, params: &PresentParams) -> Result<PresentStatus, B::Error> {
+        if self.minimized {
+            return Ok(PresentStatus::Skipped);
+        }
+        params.validate(self.size)?;
+
+        let status = if self.needs_full_present && params.is_partial() {
+            self.backend.present(&params.full())?
+        } else {
+            self.backend.present(params)?
+        };
+        self.needs_full_present = false;
+        self.occluded = status == PresentStatus::Occluded;
+        Ok(status)
+    }
+
+    /// Releases the target, for example before recreating the device.
+    pub fn release_target(&mut self) {
+        if let Some(target) = self.target.take() {
+            self.backend.release_target(target);
+        }
+    }
+
+    /// Releases the target and gives back the backend.
+    pub fn into_backend(mut self) -> B {
+        self.release_target();
+        self.backend
+    }
+}
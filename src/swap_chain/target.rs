@@ -0,0 +1,332 @@
+use crate::device_context::{DeviceContext, IDeviceContext};
+use crate::enums::{AlphaMode, BitmapOptions};
+use crate::image::Bitmap1;
+use crate::render_target::{IRenderTarget, RTState};
+use crate::swap_chain::{PresentParams, PresentStatus, SwapChainBackend, SwapChainState};
+
+use com_wrapper::ComWrapper;
+use dcommon::Error;
+use dxgi::device::IDevice as IDxgiDevice;
+use dxgi::enums::Format;
+use math2d::Sizeu;
+use winapi::shared::dxgi::{IDXGIAdapter, IDXGISurface, DXGI_SWAP_EFFECT_FLIP_SEQUENTIAL};
+use winapi::shared::dxgi1_2::{
+    IDXGIFactory2, IDXGISwapChain1, DXGI_ALPHA_MODE_IGNORE, DXGI_PRESENT_PARAMETERS,
+    DXGI_SCALING_STRETCH, DXGI_SWAP_CHAIN_DESC1,
+};
+use winapi::shared::dxgiformat::DXGI_FORMAT_UNKNOWN;
+use winapi::shared::dxgitype::{DXGI_SAMPLE_DESC, DXGI_USAGE_RENDER_TARGET_OUTPUT};
+use winapi::shared::windef::{HWND, POINT, RECT};
+use winapi::shared::winerror::SUCCEEDED;
+use winapi::Interface;
+use wio::com::ComPtr;
+
+// From dxgi1_5.h, which winapi doesn't cover.
+const DXGI_SWAP_CHAIN_FLAG_ALLOW_TEARING: u32 = 2048;
+const DXGI_PRESENT_ALLOW_TEARING: u32 = 0x200;
+
+/// Draws to a window through a DXGI flip-model swap chain. It owns the swap
+/// chain, the device context and the `Bitmap1` wrapping the back buffer,
+/// which is recreated whenever the window is resized.
+pub struct SwapChainTarget {
+    state: SwapChainState<DxgiSwapChain>,
+}
+
+struct DxgiSwapChain {
+    swap_chain: ComPtr<IDXGISwapChain1>,
+    context: DeviceContext,
+    flags: u32,
+}
+
+impl SwapChainTarget {
+    pub fn create<'a>(
+        context: DeviceContext,
+        device: &'a dyn IDxgiDevice,
+    ) -> SwapChainTargetBuilder<'a> {
+        SwapChainTargetBuilder::new(context, device)
+    }
+
+    /// The context to draw with, with the back buffer set as its target.
+    pub fn context(&mut self) -> Result<&mut DeviceContext, Error> {
+        self.state.target()?;
+        Ok(&mut self.state.backend_mut().context)
+    }
+
+    /// Resizes the swap chain to a new client area size, returning whether
+    /// anything changed. Call it from `WM_SIZE`; a size of zero is treated as
+    /// the window being minimized.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<bool, Error> {
+        self.state.resize(Sizeu { width, height })
+    }
+
+    pub fn present(&mut self, params: &PresentParams) -> Result<PresentStatus, Error> {
+        self.state.present(params)
+    }
+
+    pub fn size(&self) -> Sizeu {
+        self.state.size()
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        self.state.is_minimized()
+    }
+
+    pub fn is_occluded(&self) -> bool {
+        self.state.is_occluded()
+    }
+
+    /// Whether the swap chain was created with tearing allowed.
+    pub fn allows_tearing(&self) -> bool {
+        self.state.backend().flags & DXGI_SWAP_CHAIN_FLAG_ALLOW_TEARING != 0
+    }
+
+    pub unsafe fn raw_swap_chain(&self) -> &IDXGISwapChain1 {
+        &self.state.backend().swap_chain
+    }
+
+    /// Releases the back buffer and gives back the device context.
+    pub fn into_context(self) -> DeviceContext {
+        self.state.into_backend().context
+    }
+}
+
+impl SwapChainBackend for DxgiSwapChain {
+    type Target = Bitmap1;
+    type Error = Error;
+
+    fn create_target(&mut self) -> Result<Bitmap1, Error> {
+        unsafe {
+            let mut ptr = std::ptr::null_mut();
+            let hr = self
+                .swap_chain
+                .GetBuffer(0, &IDXGISurface::uuidof(), &mut ptr);
+            if !SUCCEEDED(hr) {
+                return Err(hr.into());
+            }
+            let surface = dxgi::surface::Surface::from_raw(ptr as *mut IDXGISurface);
+
+            let (dpi_x, dpi_y) = self.context.dpi();
+            let bitmap = Bitmap1::create(&self.context)
+                .with_dxgi_surface(&surface)
+                .with_alpha_mode(AlphaMode::Ignore)
+                .with_dpi(dpi_x, dpi_y)
+                .with_options(BitmapOptions::TARGET | BitmapOptions::CANNOT_DRAW)
+                .build()?;
+            self.context.set_target(&bitmap);
+            Ok(bitmap)
+        }
+    }
+
+    fn release_target(&mut self, target: Bitmap1) {
+        unsafe {
+            self.context.raw_dc().SetTarget(std::ptr::null_mut());
+        }
+        *self.context.draw_state_mut() |= RTState::NO_TARGET_IMAGE;
+        drop(target);
+    }
+
+    fn resize_buffers(&mut self, size: Sizeu) -> Result<(), Error> {
+        unsafe {
+            let hr = self.swap_chain.ResizeBuffers(
+                0,
+                size.width,
+                size.height,
+                DXGI_FORMAT_UNKNOWN,
+                self.flags,
+            );
+            if SUCCEEDED(hr) {
+                Ok(())
+            } else {
+                Err(hr.into())
+            }
+        }
+    }
+
+    fn present(&mut self, params: &PresentParams) -> Result<PresentStatus, Error> {
+        let flags = if params.allow_tearing {
+            DXGI_PRESENT_ALLOW_TEARING
+        } else {
+            0
+        };
+
+        unsafe {
+            let mut scroll_rect: RECT = std::mem::zeroed();
+            let mut scroll_offset = POINT { x: 0, y: 0 };
+            if let Some(scroll) = params.scroll {
+                scroll_rect = scroll.rect.into();
+                scroll_offset = POINT {
+                    x: scroll.dx,
+                    y: scroll.dy,
+                };
+            }
+
+            let present = DXGI_PRESENT_PARAMETERS {
+                DirtyRectsCount: params.dirty_rects.len() as u32,
+                pDirtyRects: if params.dirty_rects.is_empty() {
+                    std::ptr::null_mut()
+                } else {
+                    params.dirty_rects.as_ptr() as *mut RECT
+                },
+                pScrollRect: if params.scroll.is_some() {
+                    &mut scroll_rect
+                } else {
+                    std::ptr::null_mut()
+                },
+                pScrollOffset: if params.scroll.is_some() {
+                    &mut scroll_offset
+                } else {
+                    std::ptr::null_mut()
+                },
+            };
+
+            let hr = self
+                .swap_chain
+                .Present1(params.sync_interval, flags, &present);
+            if SUCCEEDED(hr) {
+                Ok(PresentStatus::from_hresult(hr))
+            } else {
+                Err(hr.into())
+            }
+        }
+    }
+}
+
+pub struct SwapChainTargetBuilder<'a> {
+    context: DeviceContext,
+    device: &'a dyn IDxgiDevice,
+    hwnd: HWND,
+    size: Sizeu,
+    format: Format,
+    buffer_count: u32,
+    allow_tearing: bool,
+}
+
+impl<'a> SwapChainTargetBuilder<'a> {
+    pub fn new(context: DeviceContext, device: &'a dyn IDxgiDevice) -> Self {
+        SwapChainTargetBuilder {
+            context,
+            device,
+            hwnd: std::ptr::null_mut(),
+            size: Sizeu {
+                width: 0,
+                height: 0,
+            },
+            format: Format::B8G8R8A8Unorm,
+            buffer_count: 2,
+            allow_tearing: false,
+        }
+    }
+
+    pub fn build(self) -> Result<SwapChainTarget, Error> {
+        unsafe {
+            let dxgi = self.device.raw_dev();
+
+            let mut adapter = std::ptr::null_mut();
+            let hr = dxgi.GetAdapter(&mut adapter);
+            if !SUCCEEDED(hr) {
+                return Err(hr.into());
+            }
+            let adapter = ComPtr::<IDXGIAdapter>::from_raw(adapter);
+
+            let mut factory = std::ptr::null_mut();
+            let hr = adapter.GetParent(&IDXGIFactory2::uuidof(), &mut factory);
+            if !SUCCEEDED(hr) {
+                return Err(hr.into());
+            }
+            let factory = ComPtr::<IDXGIFactory2>::from_raw(factory as *mut _);
+
+            let flags = if self.allow_tearing {
+                DXGI_SWAP_CHAIN_FLAG_ALLOW_TEARING
+            } else {
+                0
+            };
+            let desc = DXGI_SWAP_CHAIN_DESC1 {
+                Width: self.size.width,
+                Height: self.size.height,
+                Format: self.format as u32,
+                Stereo: 0,
+                SampleDesc: DXGI_SAMPLE_DESC {
+                    Count: 1,
+                    Quality: 0,
+                },
+                BufferUsage: DXGI_USAGE_RENDER_TARGET_OUTPUT,
+                BufferCount: self.buffer_count,
+                Scaling: DXGI_SCALING_STRETCH,
+                SwapEffect: DXGI_SWAP_EFFECT_FLIP_SEQUENTIAL,
+                AlphaMode: DXGI_ALPHA_MODE_IGNORE,
+                Flags: flags,
+            };
+
+            let mut ptr = std::ptr::null_mut();
+            let hr = factory.CreateSwapChainForHwnd(
+                dxgi as *const _ as *mut _,
+                self.hwnd,
+                &desc,
+                std::ptr::null(),
+                std::ptr::null_mut(),
+                &mut ptr,
+            );
+            if !SUCCEEDED(hr) {
+                return Err(hr.into());
+            }
+            let swap_chain = ComPtr::from_raw(ptr);
+
+            // A zero size means DXGI took it from the window.
+            let mut desc = std::mem::zeroed();
+            swap_chain.GetDesc1(&mut desc);
+            let size = Sizeu {
+                width: desc.Width,
+                height: desc.Height,
+            };
+
+            let backend = DxgiSwapChain {
+                swap_chain,
+                context: self.context,
+                flags,
+            };
+            Ok(SwapChainTarget {
+                state: SwapChainState::new(backend, size),
+            })
+        }
+    }
+
+    pub fn with_hwnd(mut self, hwnd: HWND) -> Self {
+        self.hwnd = hwnd;
+        self
+    }
+
+    /// The initial buffer size. Leave it unset to use the window's client area.
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = Sizeu { width, height };
+        self
+    }
+
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Between 2 and 16 buffers, as required by the flip model.
+    pub fn with_buffer_count(mut self, count: u32) -> Self {
+        self.buffer_count = count;
+        self
+    }
+
+    /// Allows `PresentParams::immediate(true)` to tear. Requires Windows 10
+    /// and a display which supports it.
+    pub fn with_tearing(mut self, allow_tearing: bool) -> Self {
+        self.allow_tearing = allow_tearing;
+        self
+    }
+}
+
+impl std::fmt::Debug for SwapChainTarget {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("SwapChainTarget")
+            .field("swap_chain", &self.state.backend().swap_chain.as_raw())
+            .field("size", &self.size())
+            .field("minimized", &self.is_minimized())
+            .field("occluded", &self.is_occluded())
+            .finish()
+    }
+}
@@ -0,0 +1,70 @@
+use crate::record::BitmapData;
+
+/// The result of comparing two bitmaps of the same size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    /// Pixels where some channel differs by more than the tolerance.
+    pub differing: usize,
+    /// The largest difference in any channel of any pixel, tolerated or not.
+    pub max_difference: u8,
+    /// A picture of where the bitmaps differ: red where a pixel is over the
+    /// tolerance, yellow where it differs within it, and a faded gray copy
+    /// of the expected image everywhere else.
+    pub diff: BitmapData,
+}
+
+impl Comparison {
+    pub fn is_identical(&self) -> bool {
+        self.max_difference == 0
+    }
+}
+
+const OVER: [u8; 4] = [0, 0, 255, 255];
+const WITHIN: [u8; 4] = [0, 255, 255, 255];
+
+/// Compares premultiplied BGRA pixels channel by channel, counting a pixel
+/// as differing when any channel is more than `tolerance` apart.
+///
+/// Panics if the bitmaps aren't the same size.
+pub fn compare(expected: &BitmapData, actual: &BitmapData, tolerance: u8) -> Comparison {
+    assert_eq!(
+        expected.size(),
+        actual.size(),
+        "only bitmaps of the same size can be compared"
+    );
+
+    let mut differing = 0;
+    let mut max_difference = 0;
+    let mut diff = Vec::with_capacity(expected.pixels().len());
+    let pixels = expected.pixels().chunks_exact(4);
+    for (e, a) in pixels.zip(actual.pixels().chunks_exact(4)) {
+        let difference = e
+            .iter()
+            .zip(a)
+            .map(|(&e, &a)| (e as i16 - a as i16).unsigned_abs() as u8)
+            .max()
+            .unwrap_or(0);
+        max_difference = max_difference.max(difference);
+
+        if difference > tolerance {
+            differing += 1;
+            diff.extend_from_slice(&OVER);
+        } else if difference > 0 {
+            diff.extend_from_slice(&WITHIN);
+        } else {
+            // Composite over white, then fade toward it so the marks stand
+            // out.
+            let (b, g, r, a) = (e[0] as u32, e[1] as u32, e[2] as u32, e[3] as u32);
+            let over_white = |c: u32| c + 255 - a;
+            let luma = (over_white(r) * 299 + over_white(g) * 587 + over_white(b) * 114) / 1000;
+            let faded = (192 + luma / 4) as u8;
+            diff.extend_from_slice(&[faded, faded, faded, 255]);
+        }
+    }
+
+    Comparison {
+        differing,
+        max_difference,
+        diff: BitmapData::new(expected.width(), expected.height(), diff),
+    }
+}
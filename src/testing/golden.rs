@@ -0,0 +1,299 @@
+use crate::record::{BitmapData, PngError};
+use crate::testing::compare::compare;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The environment variable that turns on bless mode for every `Golden`
+/// that isn't given an explicit setting.
+pub const BLESS_VAR: &str = "D2D_BLESS";
+
+/// Anything that can produce the pixels to check against a golden image.
+///
+/// Bitmaps are sources of themselves, and so is any closure returning a
+/// `Result<BitmapData, E>` with a displayable error, such as one calling
+/// `Offscreen::render`.
+pub trait PixelSource {
+    fn capture(&mut self) -> Result<BitmapData, GoldenError>;
+}
+
+impl PixelSource for BitmapData {
+    fn capture(&mut self) -> Result<BitmapData, GoldenError> {
+        Ok(self.clone())
+    }
+}
+
+impl<F, E> PixelSource for F
+where
+    F: FnMut() -> Result<BitmapData, E>,
+    E: fmt::Display,
+{
+    fn capture(&mut self) -> Result<BitmapData, GoldenError> {
+        self().map_err(|e| GoldenError::Source(e.to_string()))
+    }
+}
+
+/// A golden image stored as a PNG file, and how closely rendered pixels
+/// have to match it.
+///
+/// ```no_run
+/// # use direct2d::record::BitmapData;
+/// # use direct2d::testing::Golden;
+/// # let rendered = BitmapData::new(1, 1, vec![0; 4]);
+/// Golden::new("tests/golden/circle.png")
+///     .with_tolerance(2)
+///     .with_max_differing(16)
+///     .assert_matches(&rendered);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Golden {
+    path: PathBuf,
+    tolerance: u8,
+    max_differing: usize,
+    bless: bool,
+    output_dir: Option<PathBuf>,
+}
+
+impl Golden {
+    /// A golden at `path` that must match exactly. Bless mode is on if
+    /// `D2D_BLESS` is set to anything other than `0` or nothing.
+    pub fn new(path: impl Into<PathBuf>) -> Golden {
+        let bless = match std::env::var_os(BLESS_VAR) {
+            Some(value) => !value.is_empty() && value != "0",
+            None => false,
+        };
+        Golden {
+            path: path.into(),
+            tolerance: 0,
+            max_differing: 0,
+            bless,
+            output_dir: None,
+        }
+    }
+
+    /// How far apart any channel of a pixel can be before the pixel counts
+    /// as differing.
+    pub fn with_tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// How many pixels can differ before the check fails.
+    pub fn with_max_differing(mut self, max_differing: usize) -> Self {
+        self.max_differing = max_differing;
+        self
+    }
+
+    /// In bless mode a check that would fail writes the actual pixels over
+    /// the golden image and passes instead.
+    pub fn with_bless(mut self, bless: bool) -> Self {
+        self.bless = bless;
+        self
+    }
+
+    /// Where to write the actual and diff images of a failed check. They go
+    /// next to the golden by default.
+    pub fn with_output_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = Some(dir.into());
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Where a failed check writes the pixels it was given.
+    pub fn actual_path(&self) -> PathBuf {
+        self.output_path("actual")
+    }
+
+    /// Where a failed check writes its diff image.
+    pub fn diff_path(&self) -> PathBuf {
+        self.output_path("diff")
+    }
+
+    /// Compares `actual` against the golden image.
+    ///
+    /// On success any actual and diff images left by an earlier failure
+    /// are removed. In bless mode a missing, unreadable or mismatched
+    /// golden is replaced with `actual` and the check passes.
+    pub fn check(&self, actual: &BitmapData) -> Result<(), GoldenError> {
+        let mut diff = None;
+        let failure = match self.load() {
+            Ok(ref expected) if expected.size() != actual.size() => GoldenError::SizeMismatch {
+                expected: (expected.width(), expected.height()),
+                actual: (actual.width(), actual.height()),
+                actual_path: self.actual_path(),
+            },
+            Ok(ref expected) => {
+                let comparison = compare(expected, actual, self.tolerance);
+                if comparison.differing <= self.max_differing {
+                    self.remove_outputs();
+                    return Ok(());
+                }
+                diff = Some(comparison.diff);
+                GoldenError::Mismatch {
+                    differing: comparison.differing,
+                    allowed: self.max_differing,
+                    max_difference: comparison.max_difference,
+                    actual_path: self.actual_path(),
+                    diff_path: self.diff_path(),
+                }
+            }
+            Err(error @ GoldenError::Missing(_)) | Err(error @ GoldenError::Png(..)) => error,
+            Err(error) => return Err(error),
+        };
+
+        if self.bless {
+            write(&self.path, actual)?;
+            self.remove_outputs();
+            return Ok(());
+        }
+        if let GoldenError::SizeMismatch { .. } | GoldenError::Mismatch { .. } = failure {
+            write(&self.actual_path(), actual)?;
+        }
+        if let Some(diff) = diff {
+            write(&self.diff_path(), &diff)?;
+        }
+        Err(failure)
+    }
+
+    /// Captures pixels from `source` and checks them.
+    pub fn check_source(&self, source: &mut dyn PixelSource) -> Result<(), GoldenError> {
+        let actual = source.capture()?;
+        self.check(&actual)
+    }
+
+    /// Like `check`, but panics with the reason on failure, for use
+    /// directly in tests.
+    pub fn assert_matches(&self, actual: &BitmapData) {
+        if let Err(error) = self.check(actual) {
+            panic!("{}", error);
+        }
+    }
+
+    fn load(&self) -> Result<BitmapData, GoldenError> {
+        match fs::read(&self.path) {
+            Ok(png) => {
+                BitmapData::from_png(&png).map_err(|e| GoldenError::Png(self.path.clone(), e))
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                Err(GoldenError::Missing(self.path.clone()))
+            }
+            Err(e) => Err(GoldenError::Io(self.path.clone(), e)),
+        }
+    }
+
+    fn output_path(&self, kind: &str) -> PathBuf {
+        let stem = self
+            .path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let name = format!("{}.{}.png", stem, kind);
+        match (&self.output_dir, self.path.parent()) {
+            (Some(dir), _) => dir.join(name),
+            (None, Some(dir)) => dir.join(name),
+            (None, None) => PathBuf::from(name),
+        }
+    }
+
+    fn remove_outputs(&self) {
+        let _ = fs::remove_file(self.actual_path());
+        let _ = fs::remove_file(self.diff_path());
+    }
+}
+
+fn write(path: &Path, bitmap: &BitmapData) -> Result<(), GoldenError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| GoldenError::Io(dir.to_path_buf(), e))?;
+    }
+    fs::write(path, bitmap.to_png()).map_err(|e| GoldenError::Io(path.to_path_buf(), e))
+}
+
+/// Why a golden-image check failed.
+#[derive(Debug)]
+pub enum GoldenError {
+    /// There's no golden image at this path yet. Run in bless mode to
+    /// create it.
+    Missing(PathBuf),
+    /// Reading or writing this file failed.
+    Io(PathBuf, io::Error),
+    /// The golden image at this path couldn't be decoded.
+    Png(PathBuf, PngError),
+    /// The pixel source failed, for the reason given.
+    Source(String),
+    /// The actual image, written to `actual_path`, isn't the size of the
+    /// golden image.
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+        actual_path: PathBuf,
+    },
+    /// More pixels differ than are allowed. The actual and diff images were
+    /// written out.
+    Mismatch {
+        differing: usize,
+        allowed: usize,
+        max_difference: u8,
+        actual_path: PathBuf,
+        diff_path: PathBuf,
+    },
+}
+
+impl fmt::Display for GoldenError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoldenError::Missing(path) => write!(
+                fmt,
+                "golden image {} doesn't exist; set {}=1 to create it",
+                path.display(),
+                BLESS_VAR
+            ),
+            GoldenError::Io(path, error) => write!(fmt, "{}: {}", path.display(), error),
+            GoldenError::Png(path, error) => write!(fmt, "{}: {}", path.display(), error),
+            GoldenError::Source(reason) => write!(fmt, "capturing pixels failed: {}", reason),
+            GoldenError::SizeMismatch {
+                expected,
+                actual,
+                actual_path,
+            } => write!(
+                fmt,
+                "expected a {}x{} image but got {}x{} (written to {})",
+                expected.0,
+                expected.1,
+                actual.0,
+                actual.1,
+                actual_path.display()
+            ),
+            GoldenError::Mismatch {
+                differing,
+                allowed,
+                max_difference,
+                actual_path,
+                diff_path,
+            } => write!(
+                fmt,
+                "{} pixels differ where {} are allowed, by up to {} (actual image written to {}, \
+                 diff to {})",
+                differing,
+                allowed,
+                max_difference,
+                actual_path.display(),
+                diff_path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GoldenError::Io(_, error) => Some(error),
+            GoldenError::Png(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Golden-image tests: render something, then compare its pixels against a
+//! PNG checked in next to the tests.
+//!
+//! Enabled by the `testing` feature. `Golden` does the comparison and
+//! works with any `PixelSource`, so it runs anywhere; on Windows,
+//! `Offscreen` renders a closure with Direct2D and reads the result back.
+//!
+//! When a comparison fails, the actual image and a diff are written next
+//! to the golden. Set `D2D_BLESS=1` to accept the actual images as the new
+//! goldens instead.
+
+pub use self::compare::{compare, Comparison};
+pub use self::golden::{Golden, GoldenError, PixelSource, BLESS_VAR};
+#[cfg(windows)]
+pub use crate::render_target::Offscreen;
+
+pub mod compare;
+pub mod golden;
@@ -0,0 +1,289 @@
+//! Device-independent pixels (DIPs) and physical pixels as distinct types.
+//!
+//! Direct2D measures everything in DIPs, 1/96th of an inch, unless the unit
+//! mode of a device context is set to `UnitMode::Pixels`. Mixing the two up only
+//! shows at scale factors other than 100%, so values which cross between them
+//! should be wrapped in `Dip` or `Px` and converted with an explicit `Dpi`.
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use math2d::{Point2f, Rectf, Sizef, Vector2f};
+
+/// The DPI of a render target or monitor. 96 is 100% scaling.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Dpi {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Dpi {
+    /// 96 DPI, where one DIP is one pixel.
+    pub const DEFAULT: Dpi = Dpi { x: 96.0, y: 96.0 };
+
+    pub fn new(x: f32, y: f32) -> Dpi {
+        Dpi { x, y }
+    }
+
+    pub fn uniform(dpi: f32) -> Dpi {
+        Dpi { x: dpi, y: dpi }
+    }
+
+    /// The DPI for a scale factor such as 1.5 for 150%.
+    pub fn from_scale(scale: f32) -> Dpi {
+        Dpi::uniform(96.0 * scale)
+    }
+
+    /// Physical pixels per DIP horizontally.
+    pub fn scale_x(self) -> f32 {
+        self.x / 96.0
+    }
+
+    /// Physical pixels per DIP vertically.
+    pub fn scale_y(self) -> f32 {
+        self.y / 96.0
+    }
+}
+
+impl Default for Dpi {
+    fn default() -> Dpi {
+        Dpi::DEFAULT
+    }
+}
+
+impl From<(f32, f32)> for Dpi {
+    /// Takes the `(x, y)` pair returned by `IRenderTarget::dpi`.
+    fn from((x, y): (f32, f32)) -> Dpi {
+        Dpi { x, y }
+    }
+}
+
+impl From<Dpi> for (f32, f32) {
+    fn from(dpi: Dpi) -> (f32, f32) {
+        (dpi.x, dpi.y)
+    }
+}
+
+/// Geometry which can be wrapped in `Dip` or `Px`.
+pub trait Units: Copy {
+    /// Scales horizontal components by `sx` and vertical ones by `sy`. Plain
+    /// lengths use `sx`.
+    fn scale(self, sx: f32, sy: f32) -> Self;
+
+    /// Rounds every component to the nearest whole number.
+    fn round(self) -> Self;
+}
+
+impl Units for f32 {
+    fn scale(self, sx: f32, _sy: f32) -> f32 {
+        self * sx
+    }
+
+    fn round(self) -> f32 {
+        f32::round(self)
+    }
+}
+
+impl Units for Point2f {
+    fn scale(self, sx: f32, sy: f32) -> Point2f {
+        Point2f {
+            x: self.x * sx,
+            y: self.y * sy,
+        }
+    }
+
+    fn round(self) -> Point2f {
+        Point2f {
+            x: self.x.round(),
+            y: self.y.round(),
+        }
+    }
+}
+
+impl Units for Vector2f {
+    fn scale(self, sx: f32, sy: f32) -> Vector2f {
+        Vector2f {
+            x: self.x * sx,
+            y: self.y * sy,
+        }
+    }
+
+    fn round(self) -> Vector2f {
+        Vector2f {
+            x: self.x.round(),
+            y: self.y.round(),
+        }
+    }
+}
+
+impl Units for Sizef {
+    fn scale(self, sx: f32, sy: f32) -> Sizef {
+        Sizef {
+            width: self.width * sx,
+            height: self.height * sy,
+        }
+    }
+
+    fn round(self) -> Sizef {
+        Sizef {
+            width: self.width.round(),
+            height: self.height.round(),
+        }
+    }
+}
+
+impl Units for Rectf {
+    fn scale(self, sx: f32, sy: f32) -> Rectf {
+        Rectf {
+            left: self.left * sx,
+            top: self.top * sy,
+            right: self.right * sx,
+            bottom: self.bottom * sy,
+        }
+    }
+
+    fn round(self) -> Rectf {
+        Rectf {
+            left: self.left.round(),
+            top: self.top.round(),
+            right: self.right.round(),
+            bottom: self.bottom.round(),
+        }
+    }
+}
+
+/// A value measured in device-independent pixels.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Dip<T>(pub T);
+
+/// A value measured in physical pixels.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Px<T>(pub T);
+
+impl<T: Units> Dip<T> {
+    pub fn to_px(self, dpi: Dpi) -> Px<T> {
+        Px(self.0.scale(dpi.scale_x(), dpi.scale_y()))
+    }
+
+    /// Moves the value onto the nearest physical pixel boundaries.
+    pub fn snap(self, dpi: Dpi) -> Dip<T> {
+        self.to_px(dpi).round().to_dip(dpi)
+    }
+}
+
+impl<T: Units> Px<T> {
+    pub fn to_dip(self, dpi: Dpi) -> Dip<T> {
+        Dip(self.0.scale(1.0 / dpi.scale_x(), 1.0 / dpi.scale_y()))
+    }
+
+    pub fn round(self) -> Px<T> {
+        Px(self.0.round())
+    }
+}
+
+impl Px<Rectf> {
+    /// The smallest whole-pixel rect which contains this one.
+    pub fn round_out(self) -> Px<Rectf> {
+        let r = self.0;
+        Px(Rectf {
+            left: r.left.floor(),
+            top: r.top.floor(),
+            right: r.right.ceil(),
+            bottom: r.bottom.ceil(),
+        })
+    }
+
+    /// The largest whole-pixel rect contained in this one.
+    pub fn round_in(self) -> Px<Rectf> {
+        let r = self.0;
+        Px(Rectf {
+            left: r.left.ceil(),
+            top: r.top.ceil(),
+            right: r.right.floor(),
+            bottom: r.bottom.floor(),
+        })
+    }
+}
+
+macro_rules! unit_ops {
+    ($unit:ident) => {
+        impl<T: Add<Output = T>> Add for $unit<T> {
+            type Output = $unit<T>;
+            fn add(self, rhs: $unit<T>) -> $unit<T> {
+                $unit(self.0 + rhs.0)
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $unit<T> {
+            type Output = $unit<T>;
+            fn sub(self, rhs: $unit<T>) -> $unit<T> {
+                $unit(self.0 - rhs.0)
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $unit<T> {
+            type Output = $unit<T>;
+            fn neg(self) -> $unit<T> {
+                $unit(-self.0)
+            }
+        }
+
+        impl<T: Units> Mul<f32> for $unit<T> {
+            type Output = $unit<T>;
+            fn mul(self, rhs: f32) -> $unit<T> {
+                $unit(self.0.scale(rhs, rhs))
+            }
+        }
+
+        impl<T: Units> Div<f32> for $unit<T> {
+            type Output = $unit<T>;
+            fn div(self, rhs: f32) -> $unit<T> {
+                $unit(self.0.scale(1.0 / rhs, 1.0 / rhs))
+            }
+        }
+    };
+}
+
+unit_ops!(Dip);
+unit_ops!(Px);
+
+/// The width of one physical pixel, in DIPs. Use it as a stroke width for
+/// lines that should be exactly one pixel wide at any scale.
+pub fn hairline(dpi: Dpi) -> Dip<f32> {
+    Dip(1.0 / dpi.scale_x())
+}
+
+/// Moves one coordinate of a line so a stroke `stroke` pixels wide covers
+/// whole pixels: odd widths are centered on a pixel center, even widths on a
+/// pixel boundary. `scale` is the pixels per DIP along that axis.
+fn snap_coord(coord: f32, stroke: f32, scale: f32) -> f32 {
+    let px = coord * scale;
+    let width = stroke.round().max(1.0);
+    let snapped = if width % 2.0 == 1.0 {
+        px.floor() + 0.5
+    } else {
+        px.round()
+    };
+    snapped / scale
+}
+
+/// Snaps a point to be the end of a line `stroke` pixels wide, so the line
+/// renders crisp instead of being smeared across two rows of pixels.
+pub fn snap_point_for_stroke(point: Dip<Point2f>, stroke: Px<f32>, dpi: Dpi) -> Dip<Point2f> {
+    Dip(Point2f {
+        x: snap_coord(point.0.x, stroke.0, dpi.scale_x()),
+        y: snap_coord(point.0.y, stroke.0, dpi.scale_y()),
+    })
+}
+
+/// Snaps the edges of a rect which will be outlined with a stroke `stroke`
+/// pixels wide.
+pub fn snap_rect_for_stroke(rect: Dip<Rectf>, stroke: Px<f32>, dpi: Dpi) -> Dip<Rectf> {
+    let (sx, sy) = (dpi.scale_x(), dpi.scale_y());
+    let r = rect.0;
+    Dip(Rectf {
+        left: snap_coord(r.left, stroke.0, sx),
+        top: snap_coord(r.top, stroke.0, sy),
+        right: snap_coord(r.right, stroke.0, sx),
+        bottom: snap_coord(r.bottom, stroke.0, sy),
+    })
+}
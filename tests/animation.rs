@@ -0,0 +1,345 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::animation::*;
+use math2d::{Color, Matrix3x2f, Point2f};
+
+use std::cell::RefCell;
+use std::f32::consts::PI;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+fn close_matrix(a: &Matrix3x2f, b: &Matrix3x2f) -> bool {
+    close(a.a, b.a)
+        && close(a.b, b.b)
+        && close(a.c, b.c)
+        && close(a.d, b.d)
+        && close(a.x, b.x)
+        && close(a.y, b.y)
+}
+
+fn rotation(angle: f32) -> Matrix3x2f {
+    let (sin, cos) = angle.sin_cos();
+    Matrix3x2f {
+        a: cos,
+        b: sin,
+        c: -sin,
+        d: cos,
+        x: 0.0,
+        y: 0.0,
+    }
+}
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// A setter which records everything it's given.
+fn recorder<T: 'static>() -> (Rc<RefCell<Vec<T>>>, impl FnMut(T)) {
+    let values = Rc::new(RefCell::new(Vec::new()));
+    let sink = values.clone();
+    (values, move |v| sink.borrow_mut().push(v))
+}
+
+#[test]
+fn easing_curves_hit_their_ends() {
+    let curves = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::ExpoIn,
+        Easing::ExpoOut,
+        Easing::ExpoInOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::BackInOut,
+        Easing::EASE,
+        Easing::EASE_IN_OUT,
+        Easing::Steps(4),
+        Easing::Hold,
+    ];
+    for curve in &curves {
+        assert_eq!(curve.apply(0.0), 0.0, "{:?}", curve);
+        assert_eq!(curve.apply(1.0), 1.0, "{:?}", curve);
+        assert_eq!(curve.apply(-1.0), 0.0, "{:?}", curve);
+        assert_eq!(curve.apply(2.0), 1.0, "{:?}", curve);
+    }
+
+    assert!(close(Easing::QuadIn.apply(0.5), 0.25));
+    assert!(close(Easing::QuadOut.apply(0.5), 0.75));
+    assert!(close(Easing::CubicInOut.apply(0.5), 0.5));
+    assert!(Easing::BackIn.apply(0.2) < 0.0);
+    assert!(Easing::BackOut.apply(0.8) > 1.0);
+    assert_eq!(Easing::Steps(4).apply(0.3), 0.25);
+    assert_eq!(Easing::Hold.apply(0.99), 0.0);
+}
+
+#[test]
+fn cubic_bezier_matches_known_values() {
+    // A bezier with its control points on the diagonal is linear.
+    let linear = Easing::CubicBezier(0.25, 0.25, 0.75, 0.75);
+    for i in 1..10 {
+        let t = i as f32 / 10.0;
+        assert!(close(linear.apply(t), t));
+    }
+
+    // Reference values for CSS `ease` and `ease-in-out`.
+    assert!((Easing::EASE.apply(0.5) - 0.8024).abs() < 1e-3);
+    assert!((Easing::EASE_IN_OUT.apply(0.5) - 0.5).abs() < 1e-3);
+    assert!((Easing::EASE_IN_OUT.apply(0.25) - 0.1291).abs() < 1e-3);
+
+    // Steep and flat spots still solve to a monotonic curve.
+    let steep = Easing::CubicBezier(1.0, 0.0, 0.0, 1.0);
+    let mut last = 0.0;
+    for i in 1..=100 {
+        let y = steep.apply(i as f32 / 100.0);
+        assert!(y >= last - 1e-4);
+        last = y;
+    }
+}
+
+#[test]
+fn points_and_colors_interpolate() {
+    let p = Point2f { x: 0.0, y: 10.0 }.interpolate(&Point2f { x: 10.0, y: 30.0 }, 0.25);
+    assert_eq!(p, Point2f { x: 2.5, y: 15.0 });
+
+    let red = Color::new(1.0, 0.0, 0.0, 1.0);
+    let blue = Color::new(0.0, 0.0, 1.0, 1.0);
+    let mid = red.interpolate(&blue, 0.5);
+    assert!(close(mid.r, 0.5) && close(mid.b, 0.5) && close(mid.a, 1.0));
+
+    // Fading in from transparent black keeps the target's hue throughout.
+    let clear = Color::new(0.0, 0.0, 0.0, 0.0);
+    let fading = clear.interpolate(&red, 0.25);
+    assert!(close(fading.r, 1.0) && close(fading.a, 0.25));
+    assert_eq!(clear.interpolate(&clear, 0.5).a, 0.0);
+}
+
+#[test]
+fn transforms_interpolate_by_decomposition() {
+    // Halfway between 0 and 90 degrees is a 45 degree rotation, not the
+    // squashed matrix element-wise blending would give.
+    let half = Matrix3x2f::IDENTITY.interpolate(&rotation(PI / 2.0), 0.5);
+    assert!(close_matrix(&half, &rotation(PI / 4.0)));
+    assert!(close(half.a.hypot(half.b), 1.0));
+
+    // Rotation takes the short way around.
+    let wrapped = rotation(170f32.to_radians()).interpolate(&rotation(-170f32.to_radians()), 0.5);
+    assert!(close_matrix(&wrapped, &rotation(PI)));
+
+    // Scale, skew and translation round-trip through decomposition.
+    let m = Matrix3x2f {
+        a: 2.0,
+        b: 1.0,
+        c: -0.5,
+        d: 3.0,
+        x: 7.0,
+        y: -4.0,
+    };
+    let parts = Decomposed::new(&m).unwrap();
+    assert!(close_matrix(&parts.to_matrix(), &m));
+    assert!(close_matrix(&m.interpolate(&m, 0.3), &m));
+
+    let mirrored = Matrix3x2f {
+        a: -1.0,
+        ..Matrix3x2f::IDENTITY
+    };
+    assert!(close_matrix(
+        &Decomposed::new(&mirrored).unwrap().to_matrix(),
+        &mirrored
+    ));
+
+    // Singular matrices fall back to blending elements.
+    let flat = Matrix3x2f {
+        a: 0.0,
+        d: 0.0,
+        ..Matrix3x2f::IDENTITY
+    };
+    assert!(Decomposed::new(&flat).is_none());
+    let blended = flat.interpolate(&Matrix3x2f::IDENTITY, 0.5);
+    assert!(close(blended.a, 0.5) && close(blended.d, 0.5));
+}
+
+#[test]
+fn tracks_sample_between_keyframes() {
+    let track = Track::new()
+        .with_key(1.0, 10.0)
+        .with_key_eased(2.0, 20.0, Easing::QuadIn)
+        .with_key(0.0, 0.0)
+        .with_key(3.0, 40.0);
+
+    let times: Vec<_> = track.keyframes().iter().map(|k| k.time).collect();
+    assert_eq!(times, vec![0.0, 1.0, 2.0, 3.0]);
+    assert_eq!(track.end_time(), 3.0);
+
+    assert_eq!(track.sample(-1.0), Some(0.0));
+    assert_eq!(track.sample(0.5), Some(5.0));
+    assert_eq!(track.sample(1.0), Some(10.0));
+    assert_eq!(track.sample(2.5), Some(25.0));
+    assert_eq!(track.sample(5.0), Some(40.0));
+    assert_eq!(Track::<f32>::new().sample(1.0), None);
+    assert_eq!(track.sample(f32::NAN), Some(0.0));
+
+    // Two keyframes at the same time make a jump.
+    let jump = Track::new()
+        .with_key(0.0, 0.0)
+        .with_key(1.0, 1.0)
+        .with_key(1.0, 5.0)
+        .with_key(2.0, 6.0);
+    assert!(close(jump.sample(0.999).unwrap(), 0.999));
+    assert_eq!(jump.sample(1.0), Some(5.0));
+    assert_eq!(jump.sample(1.5), Some(5.5));
+}
+
+#[test]
+fn playback_maps_elapsed_time() {
+    let once = Playback::once().with_delay(1.0);
+    assert_eq!(once.local_time(0.5, 2.0), (0.0, false));
+    assert_eq!(once.local_time(2.0, 2.0), (1.0, false));
+    assert_eq!(once.local_time(10.0, 2.0), (2.0, true));
+
+    let three = Playback::once().with_repeat(Repeat::Count(3));
+    assert_eq!(three.local_time(2.5, 2.0), (0.5, false));
+    assert_eq!(three.local_time(6.0, 2.0), (2.0, true));
+
+    let bounce = Playback::forever().with_alternate(true);
+    assert_eq!(bounce.local_time(0.5, 2.0), (0.5, false));
+    assert_eq!(bounce.local_time(2.5, 2.0), (1.5, false));
+    assert_eq!(bounce.local_time(4.5, 2.0), (0.5, false));
+
+    // Alternating an even number of times ends where it started.
+    let there_and_back = Playback::once()
+        .with_repeat(Repeat::Count(2))
+        .with_alternate(true);
+    assert_eq!(there_and_back.local_time(100.0, 2.0), (0.0, true));
+
+    let no_delay = Playback::once().with_delay(f32::NAN);
+    assert_eq!(no_delay.delay, 0.0);
+    assert_eq!(no_delay.local_time(1.0, 2.0), (1.0, false));
+    let raw = Playback {
+        delay: f32::NAN,
+        ..Playback::once()
+    };
+    assert_eq!(raw.local_time(1.0, 2.0), (0.0, false));
+}
+
+#[test]
+fn timelines_apply_values_as_time_moves() {
+    let (values, setter) = recorder();
+    let mut timeline = Timeline::new();
+    timeline.animate(
+        Track::tween(0.0, 100.0, 1.0, Easing::Linear),
+        Playback::once(),
+        setter,
+    );
+
+    // The first value is set straight away.
+    assert_eq!(*values.borrow(), vec![0.0]);
+
+    timeline.advance(ms(250));
+    timeline.advance(ms(250));
+    timeline.advance(ms(600));
+    assert_eq!(*values.borrow(), vec![0.0, 25.0, 50.0, 100.0]);
+    assert!(timeline.is_finished());
+
+    // Finished animations don't keep setting the same value...
+    timeline.advance(ms(500));
+    assert_eq!(values.borrow().len(), 4);
+
+    // ...but seeking back plays them again.
+    timeline.seek(ms(750));
+    assert_eq!(values.borrow().last(), Some(&75.0));
+    assert!(!timeline.is_finished());
+}
+
+#[test]
+fn animations_start_when_added() {
+    let mut timeline = Timeline::new();
+    timeline.advance(Duration::from_secs(10));
+
+    let (values, setter) = recorder();
+    let id = timeline.animate(
+        Track::tween(
+            Point2f { x: 0.0, y: 0.0 },
+            Point2f { x: 10.0, y: 0.0 },
+            2.0,
+            Easing::Linear,
+        ),
+        Playback::once(),
+        setter,
+    );
+    timeline.advance(ms(1000));
+    assert_eq!(values.borrow().last(), Some(&Point2f { x: 5.0, y: 0.0 }));
+
+    // Before it was added, it holds its first value.
+    timeline.seek(ms(5000));
+    assert_eq!(values.borrow().last(), Some(&Point2f { x: 0.0, y: 0.0 }));
+
+    assert!(timeline.remove(id));
+    assert!(!timeline.remove(id));
+    timeline.advance(ms(1000));
+    assert_eq!(values.borrow().len(), 3);
+}
+
+#[test]
+fn sampling_is_deterministic() {
+    let run = |steps: &[u64]| {
+        let (values, setter) = recorder();
+        let mut timeline = Timeline::new();
+        timeline.animate(
+            Track::new()
+                .with_key_eased(0.0, rotation(0.0), Easing::EASE)
+                .with_key(0.7, rotation(2.0)),
+            Playback::forever().with_alternate(true),
+            setter,
+        );
+        for &step in steps {
+            timeline.advance(ms(step));
+        }
+        let last = *values.borrow().last().unwrap();
+        last
+    };
+
+    // However the same total time is reached, the result is identical.
+    let a = run(&[16, 17, 16, 17, 16, 17, 1000, 401]);
+    let b = run(&[1500]);
+    assert_eq!(a, b);
+    assert_eq!(run(&[1500]), b);
+}
+
+#[test]
+fn pausing_and_ticking() {
+    let (values, setter) = recorder();
+    let mut timeline = Timeline::new();
+    timeline.animate(
+        Track::tween(0.0, 1.0, 1.0, Easing::Linear),
+        Playback::once(),
+        setter,
+    );
+
+    let start = Instant::now();
+    timeline.tick(start);
+    assert_eq!(timeline.elapsed(), ms(0));
+    timeline.tick(start + ms(500));
+    assert_eq!(timeline.elapsed(), ms(500));
+
+    timeline.pause();
+    timeline.tick(start + ms(800));
+    assert_eq!(timeline.elapsed(), ms(500));
+    timeline.resume();
+    // The time spent paused doesn't count.
+    timeline.tick(start + ms(900));
+    assert_eq!(timeline.elapsed(), ms(600));
+    assert!(close(*values.borrow().last().unwrap(), 0.6));
+}
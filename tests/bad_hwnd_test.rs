@@ -1,3 +1,5 @@
+#![cfg(windows)]
+
 extern crate direct2d;
 extern crate rand;
 extern crate winapi;
@@ -1,3 +1,5 @@
+#![cfg(windows)]
+
 extern crate direct2d;
 extern crate direct3d11;
 extern crate dxgi;
@@ -0,0 +1,101 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::cpu::{blend, blend_bgra};
+use direct2d::enums::PrimitiveBlend;
+use math2d::Color;
+
+fn rgba(r: f32, g: f32, b: f32, a: f32) -> Color {
+    Color { r, g, b, a }
+}
+
+fn assert_close(actual: Color, expected: Color) {
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+    assert!(
+        close(actual.r, expected.r)
+            && close(actual.g, expected.g)
+            && close(actual.b, expected.b)
+            && close(actual.a, expected.a),
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+}
+
+// Half-transparent red and an opaque blue-green, both premultiplied.
+const SRC: Color = Color {
+    r: 0.5,
+    g: 0.0,
+    b: 0.0,
+    a: 0.5,
+};
+const DST: Color = Color {
+    r: 0.0,
+    g: 0.25,
+    b: 0.75,
+    a: 1.0,
+};
+
+#[test]
+fn source_over() {
+    assert_close(
+        blend(PrimitiveBlend::SourceOver, SRC, DST),
+        rgba(0.5, 0.125, 0.375, 1.0),
+    );
+
+    // An opaque source hides the destination; a transparent one leaves it alone.
+    let opaque = rgba(0.2, 0.4, 0.6, 1.0);
+    assert_close(blend(PrimitiveBlend::SourceOver, opaque, DST), opaque);
+    let clear = rgba(0.0, 0.0, 0.0, 0.0);
+    assert_close(blend(PrimitiveBlend::SourceOver, clear, DST), DST);
+}
+
+#[test]
+fn copy_replaces_the_destination() {
+    assert_close(blend(PrimitiveBlend::Copy, SRC, DST), SRC);
+}
+
+#[test]
+fn min_and_max_are_per_channel() {
+    assert_close(
+        blend(PrimitiveBlend::Min, SRC, DST),
+        rgba(0.0, 0.0, 0.0, 0.5),
+    );
+    assert_close(
+        blend(PrimitiveBlend::Max, SRC, DST),
+        rgba(0.5, 0.25, 0.75, 1.0),
+    );
+}
+
+#[test]
+fn add_is_unclamped_in_float() {
+    assert_close(
+        blend(PrimitiveBlend::Add, SRC, DST),
+        rgba(0.5, 0.25, 0.75, 1.5),
+    );
+}
+
+#[test]
+fn bgra_buffers() {
+    // Two pixels: premultiplied 50% red, then opaque white.
+    let source = [0, 0, 128, 128, 255, 255, 255, 255];
+
+    let mut dest = [255, 0, 0, 255, 10, 20, 30, 40];
+    blend_bgra(PrimitiveBlend::SourceOver, &source, &mut dest);
+    assert_eq!(dest, [127, 0, 128, 255, 255, 255, 255, 255]);
+
+    // Additive blending saturates instead of wrapping.
+    let mut dest = [255, 0, 200, 255, 0, 0, 0, 0];
+    blend_bgra(PrimitiveBlend::Add, &source, &mut dest);
+    assert_eq!(dest, [255, 0, 255, 255, 255, 255, 255, 255]);
+
+    let mut dest = [255, 0, 0, 255, 0, 0, 0, 0];
+    blend_bgra(PrimitiveBlend::Copy, &source, &mut dest);
+    assert_eq!(dest, source);
+}
+
+#[test]
+#[should_panic]
+fn mismatched_buffers_panic() {
+    blend_bgra(PrimitiveBlend::Copy, &[0; 8], &mut [0; 4]);
+}
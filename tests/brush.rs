@@ -1,12 +1,25 @@
+#![cfg(windows)]
+
 extern crate direct2d;
 extern crate direct3d11;
+extern crate dxgi;
 extern crate math2d;
 
-use direct2d::brush::SolidColorBrush;
+use direct2d::brush::{
+    BitmapBrush, BitmapBrush1, GradientStopCollection1, IBrush, ImageBrush, LinearGradientBrush,
+    RadialGradientBrush, SolidColorBrush,
+};
 use direct2d::device::Device;
+use direct2d::descriptions::GradientStop;
 use direct2d::device_context::DeviceContext;
+use direct2d::enums::{
+    BitmapInterpolationMode, BufferPrecision, ColorInterpolationMode, ColorSpace, ExtendMode,
+    InterpolationMode,
+};
+use direct2d::image::{Bitmap, IBitmap};
 use direct2d::factory::Factory1;
 use direct3d11::enums::CreateDeviceFlags;
+use dxgi::enums::Format;
 use math2d::*;
 
 #[test]
@@ -32,6 +45,154 @@ fn solid_color() {
     }
 }
 
+#[test]
+fn gradient_stop_collection1() {
+    let (_factory, context) = make_context();
+
+    let stops: [GradientStop; 2] = [
+        (
+            0.0,
+            Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 1.0,
+            },
+        )
+            .into(),
+        (
+            1.0,
+            Color {
+                r: 4.0,
+                g: 4.0,
+                b: 4.0,
+                a: 0.5,
+            },
+        )
+            .into(),
+    ];
+    let collection = GradientStopCollection1::create(&context)
+        .with_stops(&stops)
+        .with_pre_interpolation_space(ColorSpace::Scrgb)
+        .with_post_interpolation_space(ColorSpace::Scrgb)
+        .with_buffer_precision(BufferPrecision::_16BpcFloat)
+        .with_color_interpolation_mode(ColorInterpolationMode::Straight)
+        .build()
+        .unwrap();
+
+    assert_eq!(collection.len(), 2);
+    assert_eq!(collection.stops(), stops);
+    assert_eq!(collection.pre_interpolation_space(), ColorSpace::Scrgb);
+    assert_eq!(collection.post_interpolation_space(), ColorSpace::Scrgb);
+    assert_eq!(collection.buffer_precision(), BufferPrecision::_16BpcFloat);
+    assert_eq!(
+        collection.color_interpolation_mode(),
+        ColorInterpolationMode::Straight
+    );
+    assert_eq!(collection.as_collection().len(), 2);
+}
+
+#[test]
+fn brush_setters() {
+    let (_factory, context) = make_context();
+    let transform = Matrix3x2f::new([[2.0, 0.0], [0.0, 3.0], [4.0, 5.0]]);
+
+    let mut solid = SolidColorBrush::new(&context, 0xFF_00_00).unwrap();
+    solid.set_color(0x00_FF_00);
+    solid.set_opacity(0.5);
+    solid.set_transform(&transform);
+    assert_eq!(solid.color(), Color::from(0x00_FF_00));
+    assert_eq!(solid.opacity(), 0.5);
+    assert_eq!(solid.transform(), transform);
+
+    let mut linear = LinearGradientBrush::create(&context)
+        .with_stop((0.0, Color::from(0x00_00_00)))
+        .with_stop((1.0, Color::from(0xFF_FF_FF)))
+        .build()
+        .unwrap();
+    linear.set_start_point((1.0, 2.0).into());
+    linear.set_end_point((3.0, 4.0).into());
+    assert_eq!(linear.start_point(), (1.0, 2.0).into());
+    assert_eq!(linear.end_point(), (3.0, 4.0).into());
+
+    let mut radial = RadialGradientBrush::create(&context)
+        .with_stop((0.0, Color::from(0x00_00_00)))
+        .with_stop((1.0, Color::from(0xFF_FF_FF)))
+        .build()
+        .unwrap();
+    radial.set_center((10.0, 20.0).into());
+    radial.set_gradient_origin_offset((1.0, -1.0).into());
+    radial.set_radius_x(5.0);
+    radial.set_radius_y(6.0);
+    assert_eq!(radial.center(), (10.0, 20.0).into());
+    assert_eq!(radial.gradient_origin_offset(), (1.0, -1.0).into());
+    assert_eq!(radial.radius_x(), 5.0);
+    assert_eq!(radial.radius_y(), 6.0);
+
+    let first = Bitmap::create(&context)
+        .with_blank_image((16, 16))
+        .with_format(Format::B8G8R8A8Unorm)
+        .build()
+        .unwrap();
+    let second = Bitmap::create(&context)
+        .with_blank_image((32, 32))
+        .with_format(Format::B8G8R8A8Unorm)
+        .build()
+        .unwrap();
+    let mut bitmap = BitmapBrush::create(&context)
+        .with_bitmap(&first)
+        .build()
+        .unwrap();
+    bitmap.set_bitmap(&second);
+    bitmap.set_extend_mode_x(ExtendMode::Wrap);
+    bitmap.set_extend_mode_y(ExtendMode::Mirror);
+    bitmap.set_interpolation_mode(BitmapInterpolationMode::NearestNeighbor);
+    assert_eq!(bitmap.bitmap().unwrap().pixel_size(), (32, 32).into());
+    assert_eq!(bitmap.extend_mode_x(), ExtendMode::Wrap);
+    assert_eq!(bitmap.extend_mode_y(), ExtendMode::Mirror);
+    assert_eq!(
+        bitmap.interpolation_mode(),
+        BitmapInterpolationMode::NearestNeighbor
+    );
+}
+
+#[test]
+fn image_and_bitmap1_brushes() {
+    let (_factory, context) = make_context();
+
+    let bitmap = Bitmap::create(&context)
+        .with_blank_image((16, 16))
+        .with_format(Format::B8G8R8A8Unorm)
+        .build()
+        .unwrap();
+
+    let source = Rectf::new(0.0, 0.0, 8.0, 8.0);
+    let mut image = ImageBrush::create(&context)
+        .with_image(&bitmap)
+        .with_source_rect(source)
+        .with_extend_mode_x(ExtendMode::Wrap)
+        .with_interpolation_mode(InterpolationMode::HighQualityCubic)
+        .build()
+        .unwrap();
+    assert!(image.image().is_some());
+    assert_eq!(image.source_rect(), source);
+    assert_eq!(image.extend_mode_x(), ExtendMode::Wrap);
+    assert_eq!(image.extend_mode_y(), ExtendMode::Clamp);
+    assert_eq!(image.interpolation_mode(), InterpolationMode::HighQualityCubic);
+    image.set_source_rect(Rectf::new(4.0, 4.0, 12.0, 12.0));
+    assert_eq!(image.source_rect(), Rectf::new(4.0, 4.0, 12.0, 12.0));
+
+    let mut brush = BitmapBrush1::create(&context)
+        .with_bitmap(&bitmap)
+        .with_interpolation_mode(InterpolationMode::Anisotropic)
+        .build()
+        .unwrap();
+    assert_eq!(brush.interpolation_mode(), InterpolationMode::Anisotropic);
+    brush.set_interpolation_mode(InterpolationMode::Cubic);
+    assert_eq!(brush.interpolation_mode(), InterpolationMode::Cubic);
+    assert_eq!(brush.as_bitmap_brush().extend_mode_x(), ExtendMode::Clamp);
+}
+
 fn make_context() -> (Factory1, DeviceContext) {
     let (_, d3d, _) = direct3d11::device::Device::create()
         .with_flags(CreateDeviceFlags::BGRA_SUPPORT)
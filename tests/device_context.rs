@@ -0,0 +1,110 @@
+#![cfg(windows)]
+
+extern crate direct2d;
+extern crate direct3d11;
+extern crate dxgi;
+extern crate math2d;
+
+use direct2d::descriptions::RenderingControls;
+use direct2d::device::Device;
+use direct2d::device_context::{DeviceContext, DeviceContext1, DeviceContext6, IDeviceContext};
+use direct2d::enums::{
+    BitmapOptions, BufferPrecision, CompositeMode, InterpolationMode, PrimitiveBlend, UnitMode,
+};
+use direct2d::factory::Factory1;
+use direct2d::image::{Bitmap, Bitmap1};
+use direct2d::render_target::IRenderTarget;
+use direct3d11::enums::CreateDeviceFlags;
+use dxgi::enums::Format;
+use math2d::*;
+
+#[test]
+fn context_state() {
+    let (_factory, mut context) = make_context();
+
+    context.set_unit_mode(UnitMode::Pixels);
+    assert_eq!(context.unit_mode(), UnitMode::Pixels);
+
+    context.set_primitive_blend(PrimitiveBlend::Copy);
+    assert_eq!(context.primitive_blend(), PrimitiveBlend::Copy);
+
+    context.set_rendering_controls(&RenderingControls {
+        buffer_precision: BufferPrecision::_16BpcFloat.into(),
+        tile_size: Sizeu {
+            width: 512,
+            height: 512,
+        },
+    });
+    let controls = context.rendering_controls();
+    assert_eq!(controls.buffer_precision, BufferPrecision::_16BpcFloat);
+    assert_eq!(controls.tile_size.width, 512);
+
+    assert!(context.is_dxgi_format_supported(Format::B8G8R8A8Unorm));
+    assert!(context.is_buffer_precision_supported(BufferPrecision::_8BpcUnorm));
+    assert!(context.maximum_bitmap_size() >= 2048);
+}
+
+#[test]
+fn draw_image() {
+    let (_factory, mut context) = make_context();
+    assert!(context.target().is_none());
+
+    let target = Bitmap1::create(&context)
+        .with_image_data((32, 32), &[0; 32 * 32 * 4], 32 * 4)
+        .with_format(Format::B8G8R8A8Unorm)
+        .with_options(BitmapOptions::TARGET)
+        .build()
+        .unwrap();
+    context.set_target(&target);
+    assert!(context.target().is_some());
+
+    let image = Bitmap::create(&context)
+        .with_blank_image((16, 16))
+        .with_format(Format::B8G8R8A8Unorm)
+        .build()
+        .unwrap();
+    let bounds = context.image_local_bounds(&image).unwrap();
+    assert_eq!(bounds, Rectf::new(0.0, 0.0, 16.0, 16.0));
+
+    context.set_transform(&Matrix3x2f::new([[1.0, 0.0], [0.0, 1.0], [8.0, 8.0]]));
+    let bounds = context.image_world_bounds(&image).unwrap();
+    assert_eq!(bounds, Rectf::new(8.0, 8.0, 24.0, 24.0));
+
+    context.begin_draw();
+    context.draw_image(
+        &image,
+        Point2f { x: 0.0, y: 0.0 },
+        Some(Rectf::new(0.0, 0.0, 8.0, 8.0)),
+        InterpolationMode::Linear,
+        CompositeMode::SourceOver,
+    );
+    // Wrappers don't share their drawing state, so can't be made mid-draw.
+    assert!(context.cast::<DeviceContext>().is_none());
+    context.end_draw().unwrap();
+}
+
+#[test]
+fn cast_to_newer_contexts() {
+    let (_factory, context) = make_context();
+
+    // Every system with a device context can hand out the same interface again.
+    let same: DeviceContext = context.cast().unwrap();
+    assert_eq!(same.draw_state().0, context.draw_state().0);
+
+    // Newer interfaces depend on the OS, but asking must never fail loudly.
+    let newest: Option<DeviceContext6> = context.cast();
+    if newest.is_some() {
+        assert!(context.cast::<DeviceContext1>().is_some());
+    }
+}
+
+fn make_context() -> (Factory1, DeviceContext) {
+    let (_, d3d, _) = direct3d11::device::Device::create()
+        .with_flags(CreateDeviceFlags::BGRA_SUPPORT)
+        .build()
+        .unwrap();
+    let factory = Factory1::new().unwrap();
+    let dev = Device::create(&factory, &d3d.as_dxgi()).unwrap();
+    let ctx = DeviceContext::create(&dev).unwrap();
+    (factory, ctx)
+}
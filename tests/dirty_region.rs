@@ -0,0 +1,256 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::render_target::{DirtyRegion, WindowRect};
+use direct2d::units::Dpi;
+use math2d::{Matrix3x2f, Rectf, Sizeu};
+
+fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Rectf {
+    Rectf {
+        left,
+        top,
+        right,
+        bottom,
+    }
+}
+
+fn area(r: &Rectf) -> f32 {
+    (r.right - r.left) * (r.bottom - r.top)
+}
+
+fn contains(outer: &Rectf, inner: &Rectf) -> bool {
+    outer.left <= inner.left
+        && outer.top <= inner.top
+        && outer.right >= inner.right
+        && outer.bottom >= inner.bottom
+}
+
+const BIG: Sizeu = Sizeu {
+    width: 1000,
+    height: 1000,
+};
+
+#[test]
+fn empty_rects_are_ignored() {
+    let mut region = DirtyRegion::new();
+    region.add(rect(10.0, 10.0, 10.0, 20.0));
+    region.add(rect(10.0, 10.0, 5.0, 20.0));
+    region.add(rect(f32::NAN, 0.0, 10.0, 10.0));
+    assert!(region.is_empty());
+    assert_eq!(region.bounds(), None);
+}
+
+#[test]
+fn distant_rects_stay_separate() {
+    let mut region = DirtyRegion::new();
+    region.add(rect(0.0, 0.0, 10.0, 10.0));
+    region.add(rect(100.0, 100.0, 110.0, 110.0));
+    assert_eq!(region.len(), 2);
+    assert_eq!(region.bounds(), Some(rect(0.0, 0.0, 110.0, 110.0)));
+}
+
+#[test]
+fn overlapping_rects_are_coalesced() {
+    let mut region = DirtyRegion::new();
+    region.add(rect(0.0, 0.0, 10.0, 10.0));
+    region.add(rect(5.0, 5.0, 15.0, 15.0));
+    assert_eq!(region.rects(), &[rect(0.0, 0.0, 15.0, 15.0)]);
+
+    // Contained rects disappear into what's already there.
+    region.add(rect(1.0, 1.0, 2.0, 2.0));
+    assert_eq!(region.rects(), &[rect(0.0, 0.0, 15.0, 15.0)]);
+}
+
+#[test]
+fn adjacent_and_nearby_rects_are_coalesced() {
+    let mut region = DirtyRegion::new();
+    region.add(rect(0.0, 0.0, 10.0, 10.0));
+    region.add(rect(10.0, 0.0, 20.0, 10.0));
+    assert_eq!(region.rects(), &[rect(0.0, 0.0, 20.0, 10.0)]);
+
+    // A one unit gap wastes little enough to be worth merging.
+    region.add(rect(21.0, 0.0, 30.0, 10.0));
+    assert_eq!(region.rects(), &[rect(0.0, 0.0, 30.0, 10.0)]);
+}
+
+#[test]
+fn merges_cascade() {
+    let mut region = DirtyRegion::new();
+    region.add(rect(0.0, 0.0, 10.0, 10.0));
+    region.add(rect(40.0, 0.0, 50.0, 10.0));
+    assert_eq!(region.len(), 2);
+
+    // Bridges the two, so all three become one.
+    region.add(rect(5.0, 0.0, 45.0, 10.0));
+    assert_eq!(region.rects(), &[rect(0.0, 0.0, 50.0, 10.0)]);
+}
+
+#[test]
+fn the_rect_limit_is_kept() {
+    let mut region = DirtyRegion::with_max_rects(3);
+    let added: Vec<_> = (0..10)
+        .map(|i| {
+            let x = (i * 100) as f32;
+            rect(x, x, x + 10.0, x + 10.0)
+        })
+        .collect();
+    region.extend(added.iter().cloned());
+
+    assert!(region.len() <= 3);
+    for r in &added {
+        assert!(region.iter().any(|d| contains(d, r)), "{:?} was lost", r);
+    }
+
+    let mut bounds_only = DirtyRegion::with_max_rects(0);
+    assert_eq!(bounds_only.max_rects(), 1);
+    bounds_only.extend(added.iter().cloned());
+    assert_eq!(bounds_only.rects(), &[rect(0.0, 0.0, 910.0, 910.0)]);
+}
+
+#[test]
+fn the_cheapest_pair_is_merged_first() {
+    let mut region = DirtyRegion::with_max_rects(2);
+    region.add(rect(0.0, 0.0, 10.0, 10.0));
+    region.add(rect(500.0, 500.0, 510.0, 510.0));
+    region.add(rect(0.0, 30.0, 10.0, 40.0));
+
+    assert_eq!(region.len(), 2);
+    assert!(region.rects().contains(&rect(0.0, 0.0, 10.0, 40.0)));
+    assert!(region.rects().contains(&rect(500.0, 500.0, 510.0, 510.0)));
+}
+
+#[test]
+fn coverage_is_never_lost() {
+    // A fixed pseudo-random scatter of rects of all shapes.
+    let mut seed = 0x2545_F491u32;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        (seed % 1000) as f32
+    };
+
+    for &max in &[1, 2, 4, 8, 32] {
+        let mut region = DirtyRegion::with_max_rects(max);
+        let mut added = Vec::new();
+        for _ in 0..200 {
+            let (x, y) = (next(), next());
+            let r = rect(x, y, x + next() / 10.0 + 1.0, y + next() / 10.0 + 1.0);
+            region.add(r);
+            added.push(r);
+        }
+
+        assert!(region.len() <= max);
+        for r in &added {
+            assert!(region.iter().any(|d| contains(d, r)));
+        }
+
+        // Nothing left in the region overlaps anything else in it.
+        let rects = region.rects();
+        for (i, a) in rects.iter().enumerate() {
+            assert!(area(a) > 0.0);
+            for b in &rects[i + 1..] {
+                let w = a.right.min(b.right) - a.left.max(b.left);
+                let h = a.bottom.min(b.bottom) - a.top.max(b.top);
+                assert!(w <= 0.0 || h <= 0.0, "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+}
+
+#[test]
+fn intersection_queries() {
+    let mut region = DirtyRegion::new();
+    region.add(rect(0.0, 0.0, 10.0, 10.0));
+    assert!(region.intersects(&rect(5.0, 5.0, 20.0, 20.0)));
+    assert!(!region.intersects(&rect(10.0, 0.0, 20.0, 10.0)));
+
+    region.invalidate_all(rect(0.0, 0.0, 100.0, 100.0));
+    assert_eq!(region.rects(), &[rect(0.0, 0.0, 100.0, 100.0)]);
+    region.clear();
+    assert!(region.is_empty());
+}
+
+#[test]
+fn pixel_rects_are_rounded_out() {
+    let mut region = DirtyRegion::new();
+    region.add(rect(0.4, 0.6, 10.2, 20.5));
+    assert_eq!(
+        region.to_pixel_rects(&Matrix3x2f::IDENTITY, Dpi::DEFAULT, BIG),
+        vec![WindowRect::new(0, 0, 11, 21)]
+    );
+
+    // 150%: 0.4 * 1.5 = 0.6 and 10.2 * 1.5 = 15.3.
+    assert_eq!(
+        region.to_pixel_rects(&Matrix3x2f::IDENTITY, Dpi::from_scale(1.5), BIG),
+        vec![WindowRect::new(0, 0, 16, 31)]
+    );
+}
+
+#[test]
+fn pixel_rects_follow_the_transform() {
+    let mut region = DirtyRegion::new();
+    region.add(rect(0.0, 0.0, 10.0, 20.0));
+
+    let translate = Matrix3x2f {
+        x: 5.0,
+        y: 7.0,
+        ..Matrix3x2f::IDENTITY
+    };
+    assert_eq!(
+        region.to_pixel_rects(&translate, Dpi::uniform(192.0), BIG),
+        vec![WindowRect::new(10, 14, 30, 54)]
+    );
+
+    // Rotating 90 degrees swaps the axes: (x, y) -> (-y, x), then moved back
+    // on screen.
+    let rotate = Matrix3x2f {
+        a: 0.0,
+        b: 1.0,
+        c: -1.0,
+        d: 0.0,
+        x: 100.0,
+        y: 0.0,
+    };
+    assert_eq!(
+        region.to_pixel_rects(&rotate, Dpi::DEFAULT, BIG),
+        vec![WindowRect::new(80, 0, 100, 10)]
+    );
+}
+
+#[test]
+fn pixel_rects_are_clipped_to_the_target() {
+    let mut region = DirtyRegion::new();
+    region.add(rect(-10.0, -10.0, 5.0, 5.0));
+    region.add(rect(90.0, 90.0, 200.0, 200.0));
+    region.add(rect(300.0, 300.0, 400.0, 400.0));
+
+    let size = Sizeu {
+        width: 100,
+        height: 100,
+    };
+    let mut pixels = region.to_pixel_rects(&Matrix3x2f::IDENTITY, Dpi::DEFAULT, size);
+    pixels.sort_by_key(|r| r.left);
+    assert_eq!(
+        pixels,
+        vec![
+            WindowRect::new(0, 0, 5, 5),
+            WindowRect::new(90, 90, 100, 100)
+        ]
+    );
+}
+
+#[test]
+fn rounding_recoalesces_in_pixels() {
+    // Too different to merge in DIPs, but both round out to cover pixel 10.
+    let mut region = DirtyRegion::new();
+    region.add(rect(0.0, 0.0, 10.2, 10.0));
+    region.add(rect(10.8, 0.0, 20.0, 1.0));
+    region.add(rect(0.0, 50.0, 1.0, 51.0));
+    assert_eq!(region.len(), 3);
+
+    let pixels = region.to_pixel_rects(&Matrix3x2f::IDENTITY, Dpi::DEFAULT, BIG);
+    assert_eq!(pixels.len(), 2);
+    assert!(pixels.contains(&WindowRect::new(0, 0, 20, 10)));
+    assert!(pixels.contains(&WindowRect::new(0, 50, 1, 51)));
+}
@@ -0,0 +1,62 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::render_target::dpi::*;
+use direct2d::units::Dpi;
+use math2d::Sizeu;
+
+#[test]
+fn wparam_unpacking() {
+    assert_eq!(dpi_from_wparam(0x0060_0060), Dpi::uniform(96.0));
+    assert_eq!(dpi_from_wparam(0x0090_0078), Dpi::new(120.0, 144.0));
+    // Anything above the high word is ignored on 64-bit targets.
+    assert_eq!(
+        dpi_from_wparam(0xFFFF_0000_00C0_00C0u64 as usize),
+        Dpi::uniform(192.0)
+    );
+}
+
+#[test]
+fn pixel_sizes_scale_with_dpi() {
+    let size = Sizeu {
+        width: 800,
+        height: 600,
+    };
+    let scaled = scale_pixel_size(size, Dpi::DEFAULT, Dpi::from_scale(1.5));
+    assert_eq!((scaled.width, scaled.height), (1200, 900));
+
+    let back = scale_pixel_size(scaled, Dpi::from_scale(1.5), Dpi::DEFAULT);
+    assert_eq!((back.width, back.height), (800, 600));
+
+    let odd = scale_pixel_size(
+        Sizeu {
+            width: 101,
+            height: 3,
+        },
+        Dpi::DEFAULT,
+        Dpi::from_scale(1.25),
+    );
+    assert_eq!((odd.width, odd.height), (126, 4));
+}
+
+#[test]
+fn pixel_sizes_never_collapse() {
+    let tiny = Sizeu {
+        width: 1,
+        height: 0,
+    };
+    let scaled = scale_pixel_size(tiny, Dpi::uniform(192.0), Dpi::DEFAULT);
+    assert_eq!((scaled.width, scaled.height), (1, 0));
+}
+
+#[test]
+fn window_rects_keep_their_origin() {
+    let rect = WindowRect::new(-100, 50, 540, 530);
+    assert_eq!((rect.width(), rect.height()), (640, 480));
+
+    let scaled = scale_window_rect(rect, Dpi::DEFAULT, Dpi::new(192.0, 144.0));
+    assert_eq!(scaled, WindowRect::new(-100, 50, 1180, 770));
+
+    let same = scale_window_rect(rect, Dpi::uniform(120.0), Dpi::uniform(120.0));
+    assert_eq!(same, rect);
+}
@@ -1,3 +1,5 @@
+#![cfg(windows)]
+
 use dcommon::Error;
 use direct2d::brush::SolidColorBrush;
 use direct2d::device::Device;
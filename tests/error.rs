@@ -0,0 +1,83 @@
+extern crate direct2d;
+
+use direct2d::error::*;
+
+use std::collections::HashSet;
+
+#[test]
+fn codes_round_trip() {
+    for &error in D2dError::ALL {
+        assert_eq!(D2dError::from(error.code()), error);
+        assert_eq!(i32::from(error), error.code());
+    }
+}
+
+#[test]
+fn codes_are_unique_and_in_the_d2d_facility() {
+    let mut seen = HashSet::new();
+    for &error in D2dError::ALL {
+        assert!(seen.insert(error.code()), "{:?} is listed twice", error);
+        assert_eq!(error.code() as u32 & 0xFFFF_0000, 0x8899_0000);
+    }
+    assert_eq!(seen.len(), 0x2E);
+}
+
+#[test]
+fn known_codes() {
+    let table = [
+        (0x8899_0001u32, D2dError::WrongState),
+        (0x8899_0002, D2dError::NotInitialized),
+        (0x8899_0006, D2dError::DisplayStateInvalid),
+        (0x8899_000C, D2dError::RecreateTarget),
+        (0x8899_000F, D2dError::MaxTextureSizeExceeded),
+        (0x8899_0011, D2dError::BadNumber),
+        (0x8899_0012, D2dError::WrongFactory),
+        (0x8899_0013, D2dError::LayerAlreadyInUse),
+        (0x8899_0014, D2dError::PopCallDidNotMatchPush),
+        (0x8899_0015, D2dError::WrongResourceDomain),
+        (0x8899_0016, D2dError::PushPopUnbalanced),
+    ];
+    for &(hr, error) in &table {
+        assert_eq!(D2dError::from(hr as i32), error);
+    }
+    assert_eq!(D2DERR_RECREATE_TARGET, 0x8899_000Cu32 as i32);
+}
+
+#[test]
+fn unknown_codes_are_kept() {
+    let e_outofmemory = 0x8007_000Eu32 as i32;
+    assert_eq!(
+        D2dError::from(e_outofmemory),
+        D2dError::Other(e_outofmemory)
+    );
+    assert_eq!(D2dError::Other(e_outofmemory).code(), e_outofmemory);
+    assert_eq!(
+        D2dError::from(0x8899_0000u32 as i32),
+        D2dError::Other(0x8899_0000u32 as i32)
+    );
+}
+
+#[test]
+fn recoverability() {
+    assert!(D2dError::RecreateTarget.is_recoverable());
+    assert!(D2dError::from(DXGI_ERROR_DEVICE_REMOVED).is_recoverable());
+    assert!(D2dError::from(DXGI_ERROR_DEVICE_RESET).is_recoverable());
+    assert!(!D2dError::WrongState.is_recoverable());
+    assert!(!D2dError::PushPopUnbalanced.is_recoverable());
+    assert!(!D2dError::Other(0x8007_000Eu32 as i32).is_recoverable());
+}
+
+#[test]
+fn display() {
+    assert_eq!(
+        D2dError::BadNumber.to_string(),
+        "Invalid number (0x88990011)"
+    );
+    assert_eq!(
+        D2dError::Other(0x8007_000Eu32 as i32).to_string(),
+        "Unrecognized HRESULT (0x8007000E)"
+    );
+    for &error in D2dError::ALL {
+        assert!(!error.description().is_empty());
+    }
+}
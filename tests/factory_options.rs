@@ -0,0 +1,115 @@
+extern crate direct2d;
+
+use direct2d::enums::{DebugLevel, FactoryType};
+use direct2d::error::{E_INVALIDARG, E_NOINTERFACE};
+use direct2d::factory::{FactoryOptions, FactoryVersion};
+
+const E_FAIL: i32 = 0x8000_4005u32 as i32;
+
+/// Pretends to be a system which supports factories up to `newest`, recording
+/// every version asked for.
+fn system(
+    newest: FactoryVersion,
+    tried: &mut Vec<FactoryVersion>,
+) -> impl FnMut(FactoryVersion) -> Result<FactoryVersion, i32> + '_ {
+    move |version| {
+        tried.push(version);
+        if version <= newest {
+            Ok(version)
+        } else {
+            Err(E_NOINTERFACE)
+        }
+    }
+}
+
+#[test]
+fn defaults_match_factory_new() {
+    let options = FactoryOptions::default();
+    assert_eq!(options.factory_type, FactoryType::MultiThreaded);
+    assert_eq!(options.debug_level, DebugLevel::Warning);
+    assert_eq!(options.min_version, FactoryVersion::Factory);
+    assert_eq!(options.max_version, FactoryVersion::LATEST);
+    assert_eq!(options.validate(), Ok(()));
+}
+
+#[test]
+fn empty_version_range_is_rejected() {
+    let options = FactoryOptions {
+        min_version: FactoryVersion::Factory3,
+        max_version: FactoryVersion::Factory2,
+        ..FactoryOptions::default()
+    };
+    assert_eq!(options.validate(), Err(E_INVALIDARG));
+
+    let mut tried = Vec::new();
+    let result = options.negotiate(system(FactoryVersion::LATEST, &mut tried));
+    assert_eq!(result, Err(E_INVALIDARG));
+    assert!(tried.is_empty());
+}
+
+#[test]
+fn negotiation_picks_the_newest_supported() {
+    let mut tried = Vec::new();
+    let result = FactoryOptions::default().negotiate(system(FactoryVersion::Factory3, &mut tried));
+    assert_eq!(
+        result,
+        Ok((FactoryVersion::Factory3, FactoryVersion::Factory3))
+    );
+    assert_eq!(
+        tried,
+        [
+            FactoryVersion::Factory7,
+            FactoryVersion::Factory6,
+            FactoryVersion::Factory5,
+            FactoryVersion::Factory4,
+            FactoryVersion::Factory3,
+        ]
+    );
+}
+
+#[test]
+fn negotiation_respects_the_range() {
+    let options = FactoryOptions {
+        min_version: FactoryVersion::Factory4,
+        max_version: FactoryVersion::Factory5,
+        ..FactoryOptions::default()
+    };
+
+    let mut tried = Vec::new();
+    assert_eq!(
+        options.negotiate(system(FactoryVersion::Factory2, &mut tried)),
+        Err(E_NOINTERFACE)
+    );
+    assert_eq!(tried, [FactoryVersion::Factory5, FactoryVersion::Factory4]);
+
+    let mut tried = Vec::new();
+    assert_eq!(
+        options.negotiate(system(FactoryVersion::LATEST, &mut tried)),
+        Ok((FactoryVersion::Factory5, FactoryVersion::Factory5))
+    );
+}
+
+#[test]
+fn other_errors_stop_negotiation() {
+    let mut tried = Vec::new();
+    let result = FactoryOptions::default().negotiate(|version| {
+        tried.push(version);
+        Err::<(), _>(E_FAIL)
+    });
+    assert_eq!(result, Err(E_FAIL));
+    assert_eq!(tried, [FactoryVersion::LATEST]);
+}
+
+#[test]
+fn versions_are_ordered() {
+    let all = FactoryVersion::ALL;
+    assert_eq!(all[0].older(), None);
+    assert_eq!(FactoryVersion::LATEST.newer(), None);
+    for pair in all.windows(2) {
+        assert!(pair[0] < pair[1]);
+        assert_eq!(pair[0].newer(), Some(pair[1]));
+        assert_eq!(pair[1].older(), Some(pair[0]));
+    }
+    assert_eq!(FactoryVersion::Factory.interface_name(), "ID2D1Factory");
+    assert_eq!(FactoryVersion::Factory6.interface_name(), "ID2D1Factory6");
+}
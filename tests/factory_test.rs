@@ -1,3 +1,5 @@
+#![cfg(windows)]
+
 extern crate direct2d;
 
 #[test]
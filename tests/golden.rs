@@ -0,0 +1,446 @@
+#![cfg(feature = "testing")]
+
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::cpu::gradient::rasterize_bgra;
+use direct2d::cpu::{GradientRamp, LinearGradient};
+use direct2d::descriptions::GradientStop;
+use direct2d::enums::{ExtendMode, Gamma};
+use direct2d::properties::LinearGradientBrushProperties;
+use direct2d::record::{BitmapData, PngError};
+use direct2d::testing::{compare, Golden, GoldenError, PixelSource};
+use math2d::*;
+
+use std::fs;
+use std::path::PathBuf;
+
+/// A 3x5 RGBA image compressed with fixed Huffman codes, one row for each
+/// filter type.
+const FILTERED_RGBA: &str = "89504e470d0a1a0a0000000d4948445200000003000000050806000000807156a2000000\
+404944415478da63606038d1a0c1bbea7f8054cf7f4676a313ff35781f3100712313bb110303103702713d335f4a0aa384c2\
+c77a2066600189b0f3026579191a01797f1089233307c70000000049454e44ae426082";
+
+/// A 4x2 palette image with a transparent second entry.
+const PALETTE: &str = "89504e470d0a1a0a0000000d494844520000000400000002080300000048768d510000000950\
+4c5445ff000000ff000000ff2d4acd8a0000000274524e53ff00e5b7304a000000124944415478da63606064626060626264\
+04000031000a45126b540000000049454e44ae426082";
+
+/// A 40x20 gray image compressed with dynamic Huffman codes.
+const DYNAMIC_GRAY: &str = "89504e470d0a1a0a0000000d4948445200000028000000140800000000da2d2067000001\
+504944415478da0dc1456308001800d08fe93163276d2cd469bafba436d3b193ee184e7a4c73d2dd4ea673eac430dd2731dd\
+fc80bdf722a847160398c062b67292dbbc252a509fb60c64224bd8461177784754a401ed18c42496b29d5394f09e48a021ed\
+19cc6496b183d3dce5035189467460085358ce4ece708f32a2328de948365359c12ece729f8f44159ad0891ca6b192dd9ca3\
+944f44559ad299a14ca7803d9ce7019f896aa4d2855c66b08abd5ce0215f88ea34a32bc398c96af67191477c256ad09c6e0c\
+671685ece7128ff9462492467746309b351ce0324ff84ed4249d1e8c640e6b39c8159ef283a845063d19c55cd67188ab3ce3\
+27914426bd18cd3cd67398629ef38ba84d0b7a3386f96ce008d778c16f229996f4612cf96ce428d779c91fa20eade8cb3816\
+b08963dce0157f89bab4a61fe359c8668e7393d7fc235268437ff258c4164e708b37fca71c07a47d50d5c96e5f0000000049\
+454e44ae426082";
+
+fn hex(text: &str) -> Vec<u8> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// A fresh directory for one test's files.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("d2d-golden-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn gradient(width: u32, height: u32) -> BitmapData {
+    let stops = [
+        GradientStop {
+            position: 0.0,
+            color: Color::new(1.0, 0.0, 0.0, 1.0),
+        },
+        GradientStop {
+            position: 1.0,
+            color: Color::new(0.0, 0.0, 1.0, 0.5),
+        },
+    ];
+    let source = LinearGradient::new(
+        GradientRamp::new(&stops, Gamma::_2_2, ExtendMode::Clamp),
+        LinearGradientBrushProperties {
+            start: Point2f::new(0.0, 0.0),
+            end: Point2f::new(width as f32, 0.0),
+        },
+    );
+    BitmapData::new(width, height, rasterize_bgra(&source, width, height, 96.0))
+}
+
+/// `bitmap` with the blue channel of one pixel moved by `delta`.
+fn nudged(bitmap: &BitmapData, x: u32, y: u32, delta: i16) -> BitmapData {
+    let mut pixels = bitmap.pixels().to_vec();
+    let i = (y * bitmap.width() + x) as usize * 4;
+    pixels[i] = (pixels[i] as i16 + delta).clamp(0, pixels[i + 3] as i16) as u8;
+    BitmapData::new(bitmap.width(), bitmap.height(), pixels)
+}
+
+#[test]
+fn png_round_trip() {
+    let bitmap = gradient(17, 3);
+    assert!(bitmap.pixels().chunks(4).any(|px| px[3] < 255));
+    assert_eq!(BitmapData::from_png(&bitmap.to_png()).unwrap(), bitmap);
+
+    let empty = BitmapData::new(0, 0, Vec::new());
+    assert_eq!(BitmapData::from_png(&empty.to_png()).unwrap(), empty);
+}
+
+#[test]
+fn png_filters() {
+    let bitmap = BitmapData::from_png(&hex(FILTERED_RGBA)).unwrap();
+    assert_eq!((bitmap.width(), bitmap.height()), (3, 5));
+    let mut expected = Vec::new();
+    for y in 0..5u32 {
+        for x in 0..3u32 {
+            let a = if (x + y) % 3 != 0 { 255 } else { 128 };
+            let premultiplied = |c: u32| ((c * a + 127) / 255) as u8;
+            expected.extend_from_slice(&[
+                premultiplied(200 - x * 30),
+                premultiplied(x * 13 + y * 50),
+                premultiplied(x * 40 + y * 7),
+                a as u8,
+            ]);
+        }
+    }
+    assert_eq!(bitmap.pixels(), &expected[..]);
+}
+
+#[test]
+fn png_palette_and_gray() {
+    let bitmap = BitmapData::from_png(&hex(PALETTE)).unwrap();
+    #[rustfmt::skip]
+    let expected = [
+        0, 0, 255, 255,  0, 0, 0, 0,  255, 0, 0, 255,  0, 0, 255, 255,
+        255, 0, 0, 255,  255, 0, 0, 255,  0, 0, 0, 0,  0, 0, 0, 0,
+    ];
+    assert_eq!(bitmap.pixels(), &expected[..]);
+
+    let bitmap = BitmapData::from_png(&hex(DYNAMIC_GRAY)).unwrap();
+    assert_eq!((bitmap.width(), bitmap.height()), (40, 20));
+    for (i, px) in bitmap.pixels().chunks(4).enumerate() {
+        let (x, y) = (i % 40, i / 40);
+        let v = ((x / 4) * 25 + y) as u8;
+        assert_eq!(px, [v, v, v, 255], "pixel ({}, {})", x, y);
+    }
+}
+
+#[test]
+fn png_errors() {
+    assert_eq!(BitmapData::from_png(b"GIF89a"), Err(PngError::NotPng));
+
+    let mut png = hex(FILTERED_RGBA);
+    let last = png.len() - 13;
+    png[last] ^= 1;
+    assert!(matches!(
+        BitmapData::from_png(&png),
+        Err(PngError::Malformed(_))
+    ));
+
+    let png = hex(FILTERED_RGBA);
+    assert!(matches!(
+        BitmapData::from_png(&png[..png.len() - 20]),
+        Err(PngError::Malformed(_))
+    ));
+
+    // The same header with 16 bits per channel.
+    let mut png = hex(FILTERED_RGBA);
+    png[24] = 16;
+    let crc = &png[12..29];
+    let crc = crc32(crc).to_be_bytes();
+    png[29..33].copy_from_slice(&crc);
+    assert_eq!(
+        BitmapData::from_png(&png),
+        Err(PngError::Unsupported("bit depths other than 8"))
+    );
+
+    // A header one row tall, so the image data decompresses past its size.
+    let mut png = hex(DYNAMIC_GRAY);
+    png[20..24].copy_from_slice(&1u32.to_be_bytes());
+    let crc = crc32(&png[12..29]).to_be_bytes();
+    png[29..33].copy_from_slice(&crc);
+    assert_eq!(
+        BitmapData::from_png(&png),
+        Err(PngError::Malformed(
+            "zlib stream decompresses to more data than expected"
+        ))
+    );
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[test]
+fn comparison() {
+    let expected = gradient(8, 4);
+    let same = compare(&expected, &expected, 0);
+    assert_eq!(same.differing, 0);
+    assert!(same.is_identical());
+
+    let actual = nudged(&nudged(&expected, 1, 1, -2), 6, 3, -9);
+    let result = compare(&expected, &actual, 2);
+    assert_eq!(result.differing, 1);
+    assert_eq!(result.max_difference, 9);
+    assert_eq!(compare(&expected, &actual, 9).differing, 0);
+    assert_eq!(compare(&expected, &actual, 1).differing, 2);
+
+    let diff = result.diff.pixels();
+    let at = |x: usize, y: usize| &diff[(y * 8 + x) * 4..(y * 8 + x) * 4 + 4];
+    assert_eq!(at(6, 3), [0, 0, 255, 255]);
+    assert_eq!(at(1, 1), [0, 255, 255, 255]);
+    assert!(at(0, 0).iter().take(3).all(|&c| c >= 192));
+}
+
+#[test]
+#[should_panic(expected = "same size")]
+fn comparing_different_sizes() {
+    compare(&gradient(2, 2), &gradient(3, 2), 0);
+}
+
+#[test]
+fn missing_golden() {
+    let dir = scratch("missing");
+    let golden = Golden::new(dir.join("gradient.png")).with_bless(false);
+    match golden.check(&gradient(4, 4)) {
+        Err(GoldenError::Missing(path)) => assert_eq!(path, dir.join("gradient.png")),
+        other => panic!("{:?}", other),
+    }
+    assert!(!golden.path().exists());
+}
+
+#[test]
+fn bless_then_check() {
+    let dir = scratch("bless");
+    let path = dir.join("nested").join("gradient.png");
+    let bitmap = gradient(12, 6);
+
+    Golden::new(&path).with_bless(true).assert_matches(&bitmap);
+    assert_eq!(
+        BitmapData::from_png(&fs::read(&path).unwrap()).unwrap(),
+        bitmap
+    );
+
+    let golden = Golden::new(&path).with_bless(false);
+    golden.assert_matches(&bitmap);
+    assert!(!golden.actual_path().exists());
+    assert!(!golden.diff_path().exists());
+
+    // Blessing a changed image replaces the golden.
+    let changed = nudged(&bitmap, 3, 2, -40);
+    assert!(golden.check(&changed).is_err());
+    Golden::new(&path).with_bless(true).assert_matches(&changed);
+    golden.assert_matches(&changed);
+    assert!(!golden.actual_path().exists());
+}
+
+#[test]
+fn mismatch_writes_actual_and_diff() {
+    let dir = scratch("mismatch");
+    let path = dir.join("gradient.png");
+    let bitmap = gradient(10, 5);
+    fs::write(&path, bitmap.to_png()).unwrap();
+
+    let actual = nudged(&nudged(&bitmap, 0, 0, -30), 9, 4, -30);
+    let golden = Golden::new(&path)
+        .with_bless(false)
+        .with_output_dir(dir.join("out"));
+    match golden.check(&actual) {
+        Err(GoldenError::Mismatch {
+            differing,
+            allowed,
+            max_difference,
+            actual_path,
+            diff_path,
+        }) => {
+            assert_eq!((differing, allowed, max_difference), (2, 0, 30));
+            assert_eq!(actual_path, dir.join("out").join("gradient.actual.png"));
+            assert_eq!(diff_path, dir.join("out").join("gradient.diff.png"));
+            let written = BitmapData::from_png(&fs::read(&actual_path).unwrap()).unwrap();
+            assert_eq!(written, actual);
+            let diff = BitmapData::from_png(&fs::read(&diff_path).unwrap()).unwrap();
+            assert_eq!(&diff.pixels()[..4], [0, 0, 255, 255]);
+        }
+        other => panic!("{:?}", other),
+    }
+    // The golden itself is untouched.
+    assert_eq!(
+        BitmapData::from_png(&fs::read(&path).unwrap()).unwrap(),
+        bitmap
+    );
+
+    // Allowing the two pixels passes and cleans up the outputs.
+    let lenient = golden.clone().with_max_differing(2);
+    lenient.assert_matches(&actual);
+    assert!(!golden.actual_path().exists());
+    assert!(!golden.diff_path().exists());
+}
+
+#[test]
+fn size_mismatch() {
+    let dir = scratch("size");
+    let path = dir.join("gradient.png");
+    fs::write(&path, gradient(4, 4).to_png()).unwrap();
+
+    let golden = Golden::new(&path).with_bless(false);
+    match golden.check(&gradient(4, 3)) {
+        Err(GoldenError::SizeMismatch {
+            expected,
+            actual,
+            actual_path,
+        }) => {
+            assert_eq!((expected, actual), ((4, 4), (4, 3)));
+            assert!(actual_path.exists());
+        }
+        other => panic!("{:?}", other),
+    }
+    assert!(!golden.diff_path().exists());
+}
+
+#[test]
+fn pixel_sources() {
+    let dir = scratch("sources");
+    let path = dir.join("gradient.png");
+    fs::write(&path, gradient(6, 6).to_png()).unwrap();
+    let golden = Golden::new(&path).with_bless(false);
+
+    golden.check_source(&mut gradient(6, 6)).unwrap();
+
+    let mut renders = 0;
+    let mut source = || {
+        renders += 1;
+        Ok::<_, String>(gradient(6, 6))
+    };
+    golden.check_source(&mut source).unwrap();
+    assert_eq!(renders, 1);
+
+    let mut failing = || Err::<BitmapData, _>("device removed");
+    match golden.check_source(&mut failing) {
+        Err(GoldenError::Source(reason)) => assert_eq!(reason, "device removed"),
+        other => panic!("{:?}", other),
+    }
+    let error = failing.capture().unwrap_err();
+    assert_eq!(error.to_string(), "capturing pixels failed: device removed");
+}
+
+/// A 4x4 checkerboard of opaque blue and green, as premultiplied BGRA.
+fn checker() -> Vec<u8> {
+    let mut pixels = Vec::new();
+    for y in 0..4 {
+        for x in 0..4 {
+            let px = if (x + y) % 2 == 0 {
+                [255, 0, 0, 255]
+            } else {
+                [0, 255, 0, 255]
+            };
+            pixels.extend_from_slice(&px);
+        }
+    }
+    pixels
+}
+
+/// What `offscreen_rect.png` holds: an opaque red square 8 pixels wide in
+/// the middle of a transparent 16x16 image.
+fn red_square() -> BitmapData {
+    let mut pixels = Vec::new();
+    for y in 0..16 {
+        for x in 0..16 {
+            let inside = (4..12).contains(&x) && (4..12).contains(&y);
+            let px = if inside { [0, 0, 255, 255] } else { [0; 4] };
+            pixels.extend_from_slice(&px);
+        }
+    }
+    BitmapData::new(16, 16, pixels)
+}
+
+/// What `offscreen_bitmap.png` holds: the checkerboard two pixels in from
+/// the corner of a transparent 8x8 image.
+fn framed_checker() -> BitmapData {
+    let checker = checker();
+    let mut pixels = vec![0; 8 * 8 * 4];
+    for (y, row) in checker.chunks(16).enumerate() {
+        let start = ((y + 2) * 8 + 2) * 4;
+        pixels[start..start + 16].copy_from_slice(row);
+    }
+    BitmapData::new(8, 8, pixels)
+}
+
+#[test]
+fn checked_in_goldens() {
+    for (name, expected) in &[
+        ("offscreen_rect", red_square()),
+        ("offscreen_bitmap", framed_checker()),
+    ] {
+        let path = format!("tests/golden/{}.png", name);
+        let golden = BitmapData::from_png(&fs::read(&path).unwrap()).unwrap();
+        assert!(compare(expected, &golden, 0).is_identical(), "{}", path);
+    }
+}
+
+#[cfg(windows)]
+#[test]
+fn offscreen_fill() {
+    use direct2d::brush::SolidColorBrush;
+    use direct2d::enums::AntialiasMode;
+    use direct2d::render_target::IRenderTarget;
+    use direct2d::testing::Offscreen;
+
+    let mut offscreen = Offscreen::new((16, 16), 96.0).unwrap();
+    let actual = offscreen
+        .render(|context| {
+            let brush = SolidColorBrush::create(&*context)
+                .with_color(0xFF_00_00)
+                .build()
+                .unwrap();
+            context.set_antialias_mode(AntialiasMode::Aliased);
+            context.fill_rectangle(Rectf::new(4.0, 4.0, 12.0, 12.0), &brush);
+        })
+        .unwrap();
+    Golden::new("tests/golden/offscreen_rect.png").assert_matches(&actual);
+}
+
+#[cfg(windows)]
+#[test]
+fn offscreen_bitmap() {
+    use direct2d::enums::BitmapInterpolationMode;
+    use direct2d::image::Bitmap;
+    use direct2d::render_target::IRenderTarget;
+    use direct2d::testing::Offscreen;
+    use dxgi::enums::Format;
+
+    let checker = checker();
+    let mut offscreen = Offscreen::new((8, 8), 96.0).unwrap();
+    let actual = offscreen
+        .render(|context| {
+            let bitmap = Bitmap::create(&*context)
+                .with_raw_data((4, 4), &checker, 4 * 4)
+                .with_format(Format::B8G8R8A8Unorm)
+                .build()
+                .unwrap();
+            context.draw_bitmap(
+                &bitmap,
+                Rectf::new(2.0, 2.0, 6.0, 6.0),
+                1.0,
+                BitmapInterpolationMode::NearestNeighbor,
+                Rectf::new(0.0, 0.0, 4.0, 4.0),
+            );
+        })
+        .unwrap();
+    Golden::new("tests/golden/offscreen_bitmap.png").assert_matches(&actual);
+}
@@ -0,0 +1,271 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::cpu::gradient::{extend, rasterize_bgra};
+use direct2d::cpu::{ColorSource, GradientRamp, LinearGradient, RadialGradient};
+use direct2d::descriptions::GradientStop;
+use direct2d::enums::{ExtendMode, Gamma};
+use direct2d::properties::{
+    BrushProperties, LinearGradientBrushProperties, RadialGradientBrushProperties,
+};
+use math2d::*;
+
+const EPSILON: f32 = 0.0001;
+
+const BLACK: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+fn black_to_white(gamma: Gamma, mode: ExtendMode) -> GradientRamp {
+    let stops = [
+        GradientStop {
+            position: 1.0,
+            color: WHITE,
+        },
+        GradientStop {
+            position: 0.0,
+            color: BLACK,
+        },
+    ];
+    GradientRamp::new(&stops, gamma, mode)
+}
+
+fn assert_color(actual: Color, expected: Color) {
+    let close = (actual.r - expected.r).abs() <= EPSILON
+        && (actual.g - expected.g).abs() <= EPSILON
+        && (actual.b - expected.b).abs() <= EPSILON
+        && (actual.a - expected.a).abs() <= EPSILON;
+    assert!(close, "expected {:?}, got {:?}", expected, actual);
+}
+
+fn gray(v: f32) -> Color {
+    Color {
+        r: v,
+        g: v,
+        b: v,
+        a: 1.0,
+    }
+}
+
+#[test]
+fn stops_are_sorted() {
+    let ramp = black_to_white(Gamma::_2_2, ExtendMode::Clamp);
+    assert_eq!(ramp.stops()[0].position, 0.0);
+    assert_eq!(ramp.stops()[1].position, 1.0);
+}
+
+#[test]
+fn nan_stops_are_left_out() {
+    let stops = [
+        GradientStop {
+            position: f32::NAN,
+            color: WHITE,
+        },
+        GradientStop {
+            position: 1.0,
+            color: WHITE,
+        },
+        GradientStop {
+            position: 0.0,
+            color: BLACK,
+        },
+    ];
+    let ramp = GradientRamp::new(&stops, Gamma::_2_2, ExtendMode::Clamp);
+    assert_eq!(ramp.stops().len(), 2);
+    assert_color(ramp.color_at(0.5), gray(0.5));
+}
+
+#[test]
+fn gamma_2_2_interpolates_encoded_values() {
+    let ramp = black_to_white(Gamma::_2_2, ExtendMode::Clamp);
+    assert_color(ramp.color_at(0.0), BLACK);
+    assert_color(ramp.color_at(0.25), gray(0.25));
+    assert_color(ramp.color_at(0.5), gray(0.5));
+    assert_color(ramp.color_at(1.0), WHITE);
+}
+
+#[test]
+fn gamma_1_0_interpolates_linear_light() {
+    let ramp = black_to_white(Gamma::_1_0, ExtendMode::Clamp);
+    // Half of the linear intensity of white, encoded back to sRGB.
+    assert_color(ramp.color_at(0.5), gray(0.735_356_6));
+    assert_color(ramp.color_at(0.0), BLACK);
+    assert_color(ramp.color_at(1.0), WHITE);
+}
+
+#[test]
+fn interpolation_is_premultiplied() {
+    let stops = [
+        GradientStop {
+            position: 0.0,
+            color: Color {
+                r: 1.0,
+                g: 0.0,
+                b: 0.0,
+                a: 1.0,
+            },
+        },
+        GradientStop {
+            position: 1.0,
+            color: Color {
+                r: 0.0,
+                g: 0.0,
+                b: 1.0,
+                a: 0.0,
+            },
+        },
+    ];
+    let ramp = GradientRamp::new(&stops, Gamma::_2_2, ExtendMode::Clamp);
+
+    // The fully transparent blue contributes no color, only transparency.
+    let mid = ramp.color_at(0.5);
+    assert_color(
+        mid,
+        Color {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 0.5,
+        },
+    );
+}
+
+#[test]
+fn extend_modes() {
+    assert!((extend(ExtendMode::Clamp, -0.5) - 0.0).abs() <= EPSILON);
+    assert!((extend(ExtendMode::Clamp, 1.5) - 1.0).abs() <= EPSILON);
+    assert!((extend(ExtendMode::Wrap, 1.25) - 0.25).abs() <= EPSILON);
+    assert!((extend(ExtendMode::Wrap, -0.25) - 0.75).abs() <= EPSILON);
+    assert!((extend(ExtendMode::Mirror, 1.25) - 0.75).abs() <= EPSILON);
+    assert!((extend(ExtendMode::Mirror, 2.25) - 0.25).abs() <= EPSILON);
+    assert!((extend(ExtendMode::Mirror, -0.25) - 0.25).abs() <= EPSILON);
+}
+
+#[test]
+fn linear_gradient() {
+    let ramp = black_to_white(Gamma::_2_2, ExtendMode::Mirror);
+    let gradient = LinearGradient::new(
+        ramp,
+        LinearGradientBrushProperties {
+            start: (10.0, 0.0).into(),
+            end: (20.0, 0.0).into(),
+        },
+    );
+
+    assert!((gradient.position_at((15.0, 99.0).into()) - 0.5).abs() <= EPSILON);
+    assert_color(gradient.color_at((12.5, 0.0).into()), gray(0.25));
+    // Mirrored back from 1.25
+    assert_color(gradient.color_at((22.5, 0.0).into()), gray(0.75));
+}
+
+#[test]
+fn linear_gradient_brush_properties() {
+    let ramp = black_to_white(Gamma::_2_2, ExtendMode::Clamp);
+    let gradient = LinearGradient::new(
+        ramp,
+        LinearGradientBrushProperties {
+            start: (0.0, 0.0).into(),
+            end: (10.0, 0.0).into(),
+        },
+    )
+    .with_brush_properties(BrushProperties::new(
+        0.5,
+        &Matrix3x2f::new([[2.0, 0.0], [0.0, 1.0], [100.0, 0.0]]),
+    ));
+
+    // x = 110 maps back to x = 5 in brush space.
+    assert_color(
+        gradient.color_at((110.0, 0.0).into()),
+        Color {
+            r: 0.5,
+            g: 0.5,
+            b: 0.5,
+            a: 0.5,
+        },
+    );
+}
+
+#[test]
+fn radial_gradient_centered() {
+    let ramp = black_to_white(Gamma::_2_2, ExtendMode::Clamp);
+    let gradient = RadialGradient::new(
+        ramp,
+        RadialGradientBrushProperties {
+            center: (50.0, 50.0).into(),
+            origin_offset: (0.0, 0.0).into(),
+            radius_x: 10.0,
+            radius_y: 20.0,
+        },
+    );
+
+    assert!(gradient.position_at((50.0, 50.0).into()).abs() <= EPSILON);
+    assert!((gradient.position_at((55.0, 50.0).into()) - 0.5).abs() <= EPSILON);
+    assert!((gradient.position_at((50.0, 60.0).into()) - 0.5).abs() <= EPSILON);
+    assert!((gradient.position_at((50.0, 30.0).into()) - 1.0).abs() <= EPSILON);
+    assert_color(gradient.color_at((100.0, 100.0).into()), WHITE);
+}
+
+#[test]
+fn radial_gradient_origin_offset() {
+    let ramp = black_to_white(Gamma::_2_2, ExtendMode::Clamp);
+    let gradient = RadialGradient::new(
+        ramp,
+        RadialGradientBrushProperties {
+            center: (0.0, 0.0).into(),
+            origin_offset: (5.0, 0.0).into(),
+            radius_x: 10.0,
+            radius_y: 10.0,
+        },
+    );
+
+    // The origin is at (5, 0): halfway to the right edge is 7.5,
+    // halfway to the left edge is -2.5.
+    assert!(gradient.position_at((5.0, 0.0).into()).abs() <= EPSILON);
+    assert!((gradient.position_at((7.5, 0.0).into()) - 0.5).abs() <= EPSILON);
+    assert!((gradient.position_at((-2.5, 0.0).into()) - 0.5).abs() <= EPSILON);
+    assert!((gradient.position_at((-10.0, 0.0).into()) - 1.0).abs() <= EPSILON);
+}
+
+#[test]
+fn rasterize_premultiplied_bgra() {
+    let stops = [
+        GradientStop {
+            position: 0.0,
+            color: Color {
+                r: 1.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.5,
+            },
+        },
+        GradientStop {
+            position: 1.0,
+            color: Color {
+                r: 1.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.5,
+            },
+        },
+    ];
+    let gradient = LinearGradient::new(
+        GradientRamp::new(&stops, Gamma::_2_2, ExtendMode::Clamp),
+        LinearGradientBrushProperties {
+            start: (0.0, 0.0).into(),
+            end: (4.0, 0.0).into(),
+        },
+    );
+
+    let data = rasterize_bgra(&gradient, 4, 2, 192.0);
+    assert_eq!(data.len(), 4 * 2 * 4);
+    assert_eq!(&data[0..4], &[0, 0, 128, 128]);
+}
@@ -0,0 +1,168 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::brush::palette::TargetId;
+use direct2d::brush::{Palette, PaletteKey, Theme};
+use math2d::Color;
+
+use std::cell::Cell;
+
+/// Stands in for a device brush, remembering what it was created with.
+#[derive(Debug, PartialEq)]
+struct FakeBrush {
+    target: TargetId,
+    color: Color,
+}
+
+fn light() -> Theme {
+    Theme::new()
+        .with_color("background", 0xFF_FF_FF)
+        .with_color("text", 0x00_00_00)
+        .with_color("accent", 0x00_78_D7)
+}
+
+fn dark() -> Theme {
+    Theme::new()
+        .with_color("background", 0x1E_1E_1E)
+        .with_color("text", 0xFF_FF_FF)
+        .with_color("accent", 0x00_78_D7)
+}
+
+fn get(
+    palette: &mut Palette<FakeBrush>,
+    target: TargetId,
+    key: impl Into<PaletteKey>,
+    created: &Cell<u32>,
+) -> Option<Color> {
+    palette
+        .get_or_create::<()>(target, key.into(), |color| {
+            created.set(created.get() + 1);
+            Ok(FakeBrush { target, color })
+        })
+        .unwrap()
+        .map(|brush| {
+            assert_eq!(brush.target, target);
+            brush.color
+        })
+}
+
+#[test]
+fn brushes_are_created_lazily_and_cached() {
+    let created = Cell::new(0);
+    let mut palette = Palette::new(light());
+    let rt = TargetId(1);
+
+    assert!(palette.is_empty());
+    assert_eq!(get(&mut palette, rt, "text", &created), Some(0x00_00_00.into()));
+    assert_eq!(get(&mut palette, rt, "text", &created), Some(0x00_00_00.into()));
+    assert_eq!(created.get(), 1);
+
+    let red = Color::from(0xFF_00_00);
+    assert_eq!(get(&mut palette, rt, red, &created), Some(red));
+    assert_eq!(get(&mut palette, rt, red, &created), Some(red));
+    assert_eq!(created.get(), 2);
+    assert_eq!(palette.len(), 2);
+}
+
+#[test]
+fn named_and_color_keys_are_distinct() {
+    let created = Cell::new(0);
+    let mut palette = Palette::new(light());
+    let rt = TargetId(1);
+
+    // Same color, but the named key follows the theme so it gets its own brush.
+    get(&mut palette, rt, "background", &created);
+    get(&mut palette, rt, Color::from(0xFF_FF_FF), &created);
+    assert_eq!(created.get(), 2);
+}
+
+#[test]
+fn unknown_names_resolve_to_nothing() {
+    let created = Cell::new(0);
+    let mut palette = Palette::new(light());
+
+    assert_eq!(get(&mut palette, TargetId(1), "missing", &created), None);
+    assert_eq!(created.get(), 0);
+    assert_eq!(palette.resolve(&"missing".into()), None);
+}
+
+#[test]
+fn new_target_invalidates_cache() {
+    let created = Cell::new(0);
+    let mut palette = Palette::new(light());
+
+    get(&mut palette, TargetId(1), "text", &created);
+    get(&mut palette, TargetId(1), "accent", &created);
+    assert_eq!(palette.target(), Some(TargetId(1)));
+    assert!(palette.is_cached(TargetId(1), &"text".into()));
+
+    // The recreated target gets fresh brushes.
+    get(&mut palette, TargetId(2), "text", &created);
+    assert_eq!(created.get(), 3);
+    assert_eq!(palette.target(), Some(TargetId(2)));
+    assert_eq!(palette.len(), 1);
+    assert!(!palette.is_cached(TargetId(1), &"text".into()));
+    assert!(!palette.is_cached(TargetId(2), &"accent".into()));
+}
+
+#[test]
+fn explicit_invalidation() {
+    let created = Cell::new(0);
+    let mut palette = Palette::new(light());
+
+    get(&mut palette, TargetId(1), "text", &created);
+    palette.invalidate();
+    assert!(palette.is_empty());
+    assert_eq!(palette.target(), None);
+
+    get(&mut palette, TargetId(1), "text", &created);
+    assert_eq!(created.get(), 2);
+}
+
+#[test]
+fn theme_switch_only_drops_changed_colors() {
+    let created = Cell::new(0);
+    let mut palette = Palette::new(light());
+    let rt = TargetId(1);
+    let literal = Color::from(0x12_34_56);
+
+    get(&mut palette, rt, "background", &created);
+    get(&mut palette, rt, "text", &created);
+    get(&mut palette, rt, "accent", &created);
+    get(&mut palette, rt, literal, &created);
+    assert_eq!(created.get(), 4);
+
+    palette.set_theme(dark());
+    assert!(!palette.is_cached(rt, &"background".into()));
+    assert!(!palette.is_cached(rt, &"text".into()));
+    assert!(palette.is_cached(rt, &"accent".into()));
+    assert!(palette.is_cached(rt, &literal.into()));
+
+    assert_eq!(
+        get(&mut palette, rt, "background", &created),
+        Some(0x1E_1E_1E.into())
+    );
+    assert_eq!(get(&mut palette, rt, "accent", &created), Some(0x00_78_D7.into()));
+    assert_eq!(created.get(), 5);
+}
+
+#[test]
+fn theme_switch_drops_removed_names() {
+    let created = Cell::new(0);
+    let mut palette = Palette::new(light());
+    let rt = TargetId(1);
+
+    get(&mut palette, rt, "accent", &created);
+    palette.set_theme(Theme::new().with_color("text", 0x00_00_00));
+    assert!(palette.is_empty());
+    assert_eq!(get(&mut palette, rt, "accent", &created), None);
+}
+
+#[test]
+fn creation_errors_are_not_cached() {
+    let mut palette: Palette<FakeBrush> = Palette::new(light());
+
+    let result = palette.get_or_create(TargetId(1), "text".into(), |_| Err("device lost"));
+    assert_eq!(result.unwrap_err(), "device lost");
+    assert!(palette.is_empty());
+}
@@ -0,0 +1,334 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::enums::{FigureBegin, FigureEnd, FillMode};
+use direct2d::geometry::path_data::arc_to_beziers;
+use direct2d::geometry::{CubicFigure, PathData, PathEvent, PathMorph};
+use math2d::*;
+
+fn point(x: f32, y: f32) -> Point2f {
+    Point2f { x, y }
+}
+
+fn cubic_at(p0: Point2f, s: &BezierSegment, t: f32) -> Point2f {
+    let u = 1.0 - t;
+    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+    point(
+        a * p0.x + b * s.p1.x + c * s.p2.x + d * s.p3.x,
+        a * p0.y + b * s.p1.y + c * s.p2.y + d * s.p3.y,
+    )
+}
+
+/// Each figure as a closed polygon, following the curves closely.
+fn polygons(path: &PathData) -> Vec<Vec<Point2f>> {
+    path.to_cubics()
+        .iter()
+        .map(|figure: &CubicFigure| {
+            let mut points = vec![figure.start];
+            let mut last = figure.start;
+            for s in &figure.segments {
+                for i in 1..=32 {
+                    points.push(cubic_at(last, s, i as f32 / 32.0));
+                }
+                last = s.p3;
+            }
+            points
+        })
+        .collect()
+}
+
+/// The filled area, assuming figures don't overlap each other.
+fn area(path: &PathData) -> f32 {
+    polygons(path)
+        .iter()
+        .map(|poly| {
+            let mut twice = 0.0;
+            for i in 0..poly.len() {
+                let (a, b) = (poly[i], poly[(i + 1) % poly.len()]);
+                twice += a.x * b.y - b.x * a.y;
+            }
+            (twice / 2.0).abs()
+        })
+        .sum()
+}
+
+/// Even-odd containment.
+fn contains(path: &PathData, p: Point2f) -> bool {
+    let mut inside = false;
+    for poly in polygons(path) {
+        for i in 0..poly.len() {
+            let (a, b) = (poly[i], poly[(i + 1) % poly.len()]);
+            if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn close_to(actual: f32, expected: f32, tolerance: f32) -> bool {
+    (actual - expected).abs() <= tolerance
+}
+
+fn polygon(points: &[(f32, f32)]) -> PathData {
+    let points: Vec<_> = points.iter().map(|&p| p.into()).collect();
+    let mut path = PathData::new();
+    path.add_polygon(&points);
+    path
+}
+
+fn circle(cx: f32, cy: f32, r: f32) -> PathData {
+    let half = |to: Point2f| ArcSegment {
+        point: to,
+        size: Sizef::new(r, r),
+        rotation_angle: 0.0,
+        sweep_direction: SweepDirection::Clockwise,
+        arc_size: ArcSize::Small,
+    };
+    let mut path = PathData::new();
+    path.begin_figure((cx + r, cy), FigureBegin::Filled);
+    path.add_arc(&half(point(cx - r, cy)));
+    path.add_arc(&half(point(cx + r, cy)));
+    path.end_figure(FigureEnd::Closed);
+    path
+}
+
+fn play() -> PathData {
+    polygon(&[(0.0, 0.0), (16.0, 10.0), (0.0, 20.0)])
+}
+
+fn pause() -> PathData {
+    let mut path = polygon(&[(0.0, 0.0), (6.0, 0.0), (6.0, 20.0), (0.0, 20.0)]);
+    path.add_polygon(&[
+        point(10.0, 0.0),
+        point(16.0, 0.0),
+        point(16.0, 20.0),
+        point(10.0, 20.0),
+    ]);
+    path
+}
+
+#[test]
+fn recording_follows_the_builder() {
+    let mut path = PathData::new();
+    path.begin_figure((0.0, 0.0), FigureBegin::Hollow);
+    path.add_line((1.0, 0.0));
+    // Beginning another figure ends the first as open.
+    path.begin_figure((5.0, 5.0), FigureBegin::Filled);
+    path.add_quadratic_bezier(&QuadBezierSegment {
+        p1: point(6.0, 8.0),
+        p2: point(8.0, 5.0),
+    });
+    path.end_figure(FigureEnd::Closed);
+    path.end_figure(FigureEnd::Closed);
+
+    assert_eq!(
+        path.events(),
+        &[
+            PathEvent::Begin {
+                start: point(0.0, 0.0),
+                begin: FigureBegin::Hollow
+            },
+            PathEvent::Line(point(1.0, 0.0)),
+            PathEvent::End(FigureEnd::Open),
+            PathEvent::Begin {
+                start: point(5.0, 5.0),
+                begin: FigureBegin::Filled
+            },
+            PathEvent::Quadratic(QuadBezierSegment {
+                p1: point(6.0, 8.0),
+                p2: point(8.0, 5.0),
+            }),
+            PathEvent::End(FigureEnd::Closed),
+        ]
+    );
+}
+
+#[test]
+#[should_panic]
+fn segments_need_a_figure() {
+    PathData::new().add_line((1.0, 1.0));
+}
+
+#[test]
+fn converting_to_cubics_keeps_the_shape() {
+    let figures = play().to_cubics();
+    assert_eq!(figures.len(), 1);
+    // Two lines plus the implicit closing one.
+    assert_eq!(figures[0].segments.len(), 3);
+    assert_eq!(figures[0].segments[2].p3, point(0.0, 0.0));
+    assert!(close_to(area(&play()), 160.0, 1e-3));
+
+    // A quadratic raised to a cubic passes through the same midpoint.
+    let mut quad = PathData::new();
+    quad.begin_figure((0.0, 0.0), FigureBegin::Hollow);
+    quad.add_quadratic_bezier(&QuadBezierSegment {
+        p1: point(10.0, 20.0),
+        p2: point(20.0, 0.0),
+    });
+    quad.end_figure(FigureEnd::Open);
+    let cubic = &quad.to_cubics()[0];
+    let mid = cubic_at(cubic.start, &cubic.segments[0], 0.5);
+    assert!(close_to(mid.x, 10.0, 1e-4) && close_to(mid.y, 10.0, 1e-4));
+
+    // Arcs become close approximations of the circle.
+    let pi_r2 = std::f32::consts::PI * 100.0;
+    assert!(close_to(
+        area(&circle(50.0, 50.0, 10.0)),
+        pi_r2,
+        pi_r2 * 0.001
+    ));
+    assert!(contains(&circle(50.0, 50.0, 10.0), point(57.0, 57.0)));
+    assert!(!contains(&circle(50.0, 50.0, 10.0), point(58.0, 58.0)));
+}
+
+#[test]
+fn arcs_follow_their_ellipse() {
+    // Too small to reach, so the radius is scaled up to make a half circle.
+    let arc = ArcSegment {
+        point: point(20.0, 0.0),
+        size: Sizef::new(1.0, 1.0),
+        rotation_angle: 0.0,
+        sweep_direction: SweepDirection::Clockwise,
+        arc_size: ArcSize::Small,
+    };
+    let beziers = arc_to_beziers(point(0.0, 0.0), &arc);
+    assert_eq!(beziers.len(), 2);
+    assert_eq!(beziers[1].p3, point(20.0, 0.0));
+    // Clockwise on screen from the left goes over the top, where y < 0.
+    assert!(close_to(beziers[0].p3.x, 10.0, 1e-3) && close_to(beziers[0].p3.y, -10.0, 1e-3));
+
+    // A rotated ellipse, exactly wide enough to reach: half of it, centered
+    // between the ends, and counter-clockwise goes under (+y, locally).
+    let (sin, cos) = 30f32.to_radians().sin_cos();
+    let rotate = |x: f32, y: f32| point(x * cos - y * sin, x * sin + y * cos);
+    let start = point(0.0, 0.0);
+    let arc = ArcSegment {
+        point: rotate(40.0, 0.0),
+        size: Sizef::new(20.0, 10.0),
+        rotation_angle: 30.0,
+        sweep_direction: SweepDirection::CounterClockwise,
+        arc_size: ArcSize::Small,
+    };
+    let beziers = arc_to_beziers(start, &arc);
+    assert_eq!(beziers.len(), 2);
+    let bottom = rotate(20.0, 10.0);
+    assert!(close_to(beziers[0].p3.x, bottom.x, 1e-3) && close_to(beziers[0].p3.y, bottom.y, 1e-3));
+    let mut last = start;
+    for b in &beziers {
+        for i in 0..8 {
+            let p = cubic_at(last, b, i as f32 / 8.0);
+            // Back into the ellipse's own frame, centered on (20, 0).
+            let (x, y) = (p.x * cos + p.y * sin - 20.0, -p.x * sin + p.y * cos);
+            let on = (x / 20.0).powi(2) + (y / 10.0).powi(2);
+            assert!(close_to(on, 1.0, 2e-3), "{:?} is off the ellipse", p);
+        }
+        last = b.p3;
+    }
+
+    // Between the same two points of a circle, the large arc takes three
+    // quarter turns and the small one a single quarter.
+    let quarter = |arc_size| ArcSegment {
+        point: point(10.0, 10.0),
+        size: Sizef::new(10.0, 10.0),
+        rotation_angle: 0.0,
+        sweep_direction: SweepDirection::Clockwise,
+        arc_size,
+    };
+    assert_eq!(arc_to_beziers(start, &quarter(ArcSize::Small)).len(), 1);
+    assert_eq!(arc_to_beziers(start, &quarter(ArcSize::Large)).len(), 3);
+
+    // Zero radii draw a line, and an arc to where it starts is nothing.
+    let line = ArcSegment {
+        size: Sizef::new(0.0, 5.0),
+        ..arc
+    };
+    assert_eq!(arc_to_beziers(start, &line).len(), 1);
+    assert!(arc_to_beziers(arc.point, &arc).is_empty());
+}
+
+#[test]
+fn play_morphs_into_pause() {
+    let morph = PathMorph::new(&play(), &pause());
+    assert_eq!(morph.figure_count(), 2);
+
+    let start = morph.at(0.0);
+    assert!(close_to(area(&start), 160.0, 1e-2));
+    assert!(contains(&start, point(2.0, 10.0)));
+    assert!(contains(&start, point(13.0, 10.0)));
+    assert!(!contains(&start, point(13.0, 3.0)));
+
+    let end = morph.at(1.0);
+    assert!(close_to(area(&end), 240.0, 1e-2));
+    assert!(contains(&end, point(3.0, 10.0)));
+    assert!(contains(&end, point(13.0, 3.0)));
+    assert!(!contains(&end, point(8.0, 10.0)));
+
+    // The second bar grows out of nothing.
+    let grown = |t: f32| {
+        let mut path = PathData::new();
+        morph.at(t).to_cubics()[1].append_to(&mut path);
+        area(&path)
+    };
+    assert!(grown(0.0) < 1e-3);
+    assert!(grown(0.25) < grown(0.5) && grown(0.5) < grown(0.75));
+    assert!(close_to(grown(1.0), 120.0, 1e-2));
+}
+
+#[test]
+fn closed_figures_are_aligned_before_blending() {
+    let square = polygon(&[(0.0, 0.0), (20.0, 0.0), (20.0, 20.0), (0.0, 20.0)]);
+    // The same square, started from the opposite corner.
+    let shifted = polygon(&[(20.0, 20.0), (0.0, 20.0), (0.0, 0.0), (20.0, 0.0)]);
+
+    let morph = PathMorph::new(&square, &shifted);
+    for &t in &[0.0, 0.25, 0.5, 0.75, 1.0] {
+        assert!(
+            close_to(area(&morph.at(t)), 400.0, 1e-2),
+            "twisted at {}",
+            t
+        );
+    }
+}
+
+#[test]
+fn squares_become_circles() {
+    let square = polygon(&[(40.0, 40.0), (60.0, 40.0), (60.0, 60.0), (40.0, 60.0)]);
+    let circle = circle(50.0, 50.0, 10.0);
+    let morph = PathMorph::new(&square, &circle);
+
+    assert!(close_to(area(&morph.at(0.0)), 400.0, 1e-2));
+    assert!(close_to(area(&morph.at(1.0)), area(&circle), 0.1));
+    let middle = area(&morph.at(0.5));
+    assert!(middle > area(&circle) && middle < 400.0);
+    for &t in &[0.0, 0.5, 1.0] {
+        assert!(contains(&morph.at(t), point(50.0, 50.0)));
+        assert!(!contains(&morph.at(t), point(61.0, 50.0)));
+    }
+    // The corners are cut off as it rounds out.
+    assert!(contains(&morph.at(0.0), point(59.0, 59.0)));
+    assert!(!contains(&morph.at(1.0), point(59.0, 59.0)));
+}
+
+#[test]
+fn fill_mode_and_figure_flags_switch_halfway() {
+    let from = play().with_fill_mode(FillMode::Winding);
+    let mut to = PathData::new();
+    to.begin_figure((0.0, 0.0), FigureBegin::Hollow);
+    to.add_line((16.0, 0.0));
+    to.end_figure(FigureEnd::Open);
+
+    let morph = PathMorph::new(&from, &to);
+    assert_eq!(morph.at(0.4).fill_mode(), FillMode::Winding);
+    assert_eq!(morph.at(0.6).fill_mode(), FillMode::Alternate);
+
+    let figure = &morph.at(0.4).to_cubics()[0];
+    assert_eq!(figure.begin, FigureBegin::Filled);
+    assert_eq!(figure.end, FigureEnd::Open);
+    assert_eq!(morph.at(0.6).to_cubics()[0].begin, FigureBegin::Hollow);
+
+    // The end of the line is reached exactly, and overshooting continues on.
+    let end = morph.at(1.0).to_cubics()[0].clone();
+    assert_eq!(end.points().last(), Some(point(16.0, 0.0)));
+    assert!(morph.at(1.2).to_cubics()[0].points().all(|p| p.y <= 1e-3));
+}
@@ -0,0 +1,563 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::cpu::{GradientRamp, LinearGradient, RadialGradient};
+use direct2d::descriptions::GradientStop;
+use direct2d::enums::{
+    BitmapInterpolationMode, CapStyle, DashStyle, ExtendMode, FigureBegin, FigureEnd, FillMode,
+    Gamma, LineJoin,
+};
+use direct2d::geometry::PathData;
+use direct2d::pdf::{to_pdf, PdfDocument};
+use direct2d::properties::{LinearGradientBrushProperties, RadialGradientBrushProperties};
+use direct2d::record::{BitmapData, BrushDesc, LayerDesc, Recording, StrokeStyleDesc, TextDesc};
+use math2d::*;
+
+use std::sync::Arc;
+
+const RED: Color = Color {
+    r: 1.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+const BLUE: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Rectf {
+    Rectf {
+        left,
+        top,
+        right,
+        bottom,
+    }
+}
+
+fn point(x: f32, y: f32) -> Point2f {
+    Point2f { x, y }
+}
+
+fn ramp(stops: &[(f32, Color)], mode: ExtendMode) -> GradientRamp {
+    let stops: Vec<GradientStop> = stops
+        .iter()
+        .map(|&(position, color)| GradientStop { position, color })
+        .collect();
+    GradientRamp::new(&stops, Gamma::_2_2, mode)
+}
+
+/// A PDF file checked against its cross-reference table.
+struct Pdf {
+    bytes: Vec<u8>,
+    /// Indexed by object number; entry 0 is the free head of the list.
+    offsets: Vec<usize>,
+}
+
+impl Pdf {
+    fn parse(bytes: Vec<u8>) -> Pdf {
+        assert!(bytes.starts_with(b"%PDF-1.4\n"));
+        assert!(bytes.ends_with(b"%%EOF\n"));
+        let text = ascii(&bytes);
+
+        let startxref = text.rfind("startxref\n").expect("no startxref");
+        let xref: usize = text[startxref + 10..]
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let table = &text[xref..];
+        assert!(
+            table.starts_with("xref\n0 "),
+            "startxref doesn't point at xref"
+        );
+        let mut lines = table.lines().skip(1);
+        let count: usize = lines.next().unwrap()[2..].parse().unwrap();
+        let mut offsets = Vec::new();
+        for (i, line) in lines.by_ref().take(count).enumerate() {
+            assert_eq!(line.len(), 19, "xref entries are 20 bytes");
+            if i == 0 {
+                assert_eq!(line, "0000000000 65535 f ");
+                offsets.push(0);
+                continue;
+            }
+            assert!(line.ends_with(" 00000 n "));
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(
+                text[offset..].starts_with(&format!("{} 0 obj\n", i)),
+                "object {} isn't at its offset",
+                i
+            );
+            offsets.push(offset);
+        }
+        assert_eq!(lines.next(), Some("trailer"));
+        assert_eq!(
+            lines.next().unwrap(),
+            format!("<< /Size {} /Root 1 0 R >>", count)
+        );
+
+        Pdf { bytes, offsets }
+    }
+
+    /// The text of an object, up to any stream data.
+    fn object(&self, id: usize) -> String {
+        let start = self.offsets[id];
+        let text = ascii(&self.bytes[start..]);
+        let body_start = text.find("obj\n").unwrap() + 4;
+        let end = text
+            .find("\nstream\n")
+            .into_iter()
+            .chain(text.find("\nendobj\n"))
+            .min()
+            .unwrap();
+        text[body_start..end].to_string()
+    }
+
+    /// The data of a stream object, checked against its length.
+    fn stream(&self, id: usize) -> &[u8] {
+        let dict = self.object(id);
+        let length = integer_after(&dict, "/Length ");
+        let start = self.offsets[id];
+        let data_start = start
+            + self.bytes[start..]
+                .windows(8)
+                .position(|w| w == b"\nstream\n")
+                .unwrap()
+            + 8;
+        let data = &self.bytes[data_start..data_start + length];
+        assert!(self.bytes[data_start + length..].starts_with(b"\nendstream\nendobj\n"));
+        data
+    }
+
+    fn pages(&self) -> Vec<usize> {
+        let catalog = self.object(1);
+        assert!(catalog.contains("/Type /Catalog"));
+        let pages = self.object(reference(&catalog, "/Pages"));
+        assert!(pages.contains("/Type /Pages"));
+        let kids = &pages[pages.find("/Kids [").unwrap() + 7..];
+        let kids: Vec<usize> = kids[..kids.find(']').unwrap()]
+            .split(" 0 R")
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().unwrap())
+            .collect();
+        assert_eq!(integer_after(&pages, "/Count "), kids.len());
+        kids
+    }
+
+    fn content(&self, page: usize) -> String {
+        let page = self.object(self.pages()[page]);
+        String::from_utf8(self.stream(reference(&page, "/Contents")).to_vec()).unwrap()
+    }
+
+    fn resources(&self, page: usize) -> String {
+        let page = self.object(self.pages()[page]);
+        self.object(reference(&page, "/Resources"))
+    }
+}
+
+/// The bytes as text, with anything outside ASCII replaced so that offsets
+/// stay the same.
+fn ascii(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| if b.is_ascii() { b as char } else { '?' })
+        .collect()
+}
+
+fn integer_after(text: &str, key: &str) -> usize {
+    let start = text.find(key).unwrap_or_else(|| panic!("no {}", key)) + key.len();
+    let digits: String = text[start..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().unwrap()
+}
+
+/// The object `key` refers to, as in `/Key 12 0 R`.
+fn reference(text: &str, key: &str) -> usize {
+    integer_after(text, &format!("{} ", key))
+}
+
+/// Every `q` has its `Q`, and never comes after it.
+fn assert_balanced(content: &str) {
+    let mut depth = 0;
+    for line in content.lines() {
+        match line {
+            "q" => depth += 1,
+            "Q" => depth -= 1,
+            _ => {}
+        }
+        assert!(depth >= 0, "Q without q in {}", content);
+    }
+    assert_eq!(depth, 0, "q without Q in {}", content);
+}
+
+#[test]
+fn pages_are_the_size_of_their_recordings() {
+    let mut letter = Recording::new((816.0, 1056.0));
+    letter.clear(Color::new(1.0, 1.0, 1.0, 1.0));
+    let pdf = Pdf::parse(to_pdf(&letter));
+
+    assert_eq!(pdf.pages().len(), 1);
+    let page = pdf.object(pdf.pages()[0]);
+    assert!(page.contains("/Type /Page "));
+    assert!(page.contains("/MediaBox [0 0 612 792]"));
+    // Drawing happens in DIPs with y pointing down.
+    assert_eq!(
+        pdf.content(0),
+        "0.75 0 0 -0.75 0 792 cm\nq\n1 1 1 rg\n0 0 816 1056 re f\nQ\n"
+    );
+}
+
+#[test]
+fn multiple_pages() {
+    let mut document = PdfDocument::new();
+    for &(width, height) in &[(100.0, 200.0), (400.0, 40.0), (96.0, 96.0)] {
+        let mut recording = Recording::new((width, height));
+        recording.fill_rectangle(rect(0.0, 0.0, 10.0, 10.0), RED);
+        document.add_page(&recording);
+    }
+    assert_eq!(document.page_count(), 3);
+    let pdf = Pdf::parse(document.finish());
+
+    let boxes: Vec<String> = pdf
+        .pages()
+        .iter()
+        .map(|&page| {
+            let page = pdf.object(page);
+            let start = page.find("/MediaBox [").unwrap() + 11;
+            page[start..start + page[start..].find(']').unwrap()].to_string()
+        })
+        .collect();
+    assert_eq!(boxes, ["0 0 75 150", "0 0 300 30", "0 0 72 72"]);
+    for i in 0..3 {
+        assert!(pdf.content(i).contains("1 0 0 rg\n"));
+    }
+}
+
+#[test]
+fn fills_use_the_fill_mode() {
+    let mut recording = Recording::new((100.0, 100.0));
+    recording.fill_rectangle(rect(10.0, 20.0, 30.0, 40.0), RED);
+    let mut path = PathData::new().with_fill_mode(FillMode::Winding);
+    path.begin_figure(point(0.0, 0.0), FigureBegin::Filled);
+    path.add_lines(&[point(10.0, 0.0), point(5.0, 8.5)]);
+    path.end_figure(FigureEnd::Closed);
+    // Hollow figures aren't filled.
+    path.begin_figure(point(50.0, 50.0), FigureBegin::Hollow);
+    path.add_line(point(60.0, 60.0));
+    path.end_figure(FigureEnd::Open);
+    recording.fill_geometry(&path, BLUE);
+    // Lines can't be filled at all.
+    recording.push(direct2d::record::DrawOp::Fill {
+        shape: direct2d::record::Shape::Line(point(0.0, 0.0), point(1.0, 1.0)),
+        brush: RED.into(),
+    });
+
+    let content = Pdf::parse(to_pdf(&recording)).content(0);
+    assert!(content.contains("q\n1 0 0 rg\n10 20 m\n30 20 l\n30 40 l\n10 40 l\nh\nf*\nQ\n"));
+    assert!(content.contains("q\n0 0 1 rg\n0 0 m\n10 0 l\n5 8.5 l\nh\nf\nQ\n"));
+    assert!(!content.contains("50 50 m"));
+    assert_eq!(content.matches("\nf").count(), 2);
+}
+
+#[test]
+fn curves_and_arcs_become_beziers() {
+    let mut recording = Recording::new((100.0, 100.0));
+    recording.fill_ellipse(
+        Ellipse {
+            center: point(50.0, 50.0),
+            radius_x: 20.0,
+            radius_y: 10.0,
+        },
+        RED,
+    );
+    let content = Pdf::parse(to_pdf(&recording)).content(0);
+    assert!(content.contains("70 50 m\n"));
+    assert!(content.lines().filter(|l| l.ends_with(" c")).count() >= 4);
+    assert!(content.lines().any(|l| l.ends_with(" 30 50 c")));
+}
+
+#[test]
+fn strokes_carry_their_style() {
+    let mut recording = Recording::new((100.0, 100.0));
+    let style = StrokeStyleDesc::new()
+        .with_caps(CapStyle::Round)
+        .with_line_join(LineJoin::Bevel)
+        .with_dash_style(DashStyle::Dash)
+        .with_dash_offset(1.0);
+    recording.draw_line(point(0.0, 0.0), point(50.0, 0.0), RED, 2.0, Some(&style));
+    let solid = StrokeStyleDesc::new()
+        .with_start_cap(CapStyle::Square)
+        .with_miter_limit(4.0);
+    recording.draw_rectangle(rect(1.0, 1.0, 9.0, 9.0), BLUE, 1.5, Some(&solid));
+    recording.draw_ellipse(
+        Ellipse {
+            center: point(5.0, 5.0),
+            radius_x: 1.0,
+            radius_y: 1.0,
+        },
+        BrushDesc::solid(RED),
+        1.0,
+        None,
+    );
+
+    let content = Pdf::parse(to_pdf(&recording)).content(0);
+    assert!(content.contains("1 0 0 RG\n2 w 1 J 2 j 10 M\n[4 4] 2 d\n0 0 m\n50 0 l\nS\n"));
+    assert!(content.contains("0 0 1 RG\n1.5 w 2 J 0 j 4 M\n1 1 m\n"));
+    assert!(content.contains("1 w 0 J 0 j 10 M\n6 5 m\n"));
+    assert_eq!(content.matches("\nS\n").count(), 3);
+    assert!(!content.contains(" d\n6 5 m"));
+}
+
+#[test]
+fn transforms_and_opacity() {
+    let mut recording = Recording::new((100.0, 100.0));
+    recording.set_transform(&Matrix3x2f {
+        a: 2.0,
+        b: 0.0,
+        c: 0.0,
+        d: 2.0,
+        x: 5.0,
+        y: 6.0,
+    });
+    recording.fill_rectangle(
+        rect(0.0, 0.0, 1.0, 1.0),
+        BrushDesc::solid(RED).with_opacity(0.5),
+    );
+    recording.set_transform(&Matrix3x2f::IDENTITY);
+    recording.fill_rectangle(rect(0.0, 0.0, 1.0, 1.0), Color::new(0.0, 0.0, 1.0, 0.25));
+
+    let pdf = Pdf::parse(to_pdf(&recording));
+    let content = pdf.content(0);
+    assert!(content.contains("q\n/GS0 gs\n2 0 0 2 5 6 cm\n1 0 0 rg\n0 0 m\n"));
+    assert!(content.contains("q\n/GS1 gs\n0 0 1 rg\n0 0 m\n"));
+    let resources = pdf.resources(0);
+    assert!(resources.contains("/GS0 << /Type /ExtGState /ca 0.5 /CA 0.5 >>"));
+    assert!(resources.contains("/GS1 << /Type /ExtGState /ca 0.25 /CA 0.25 >>"));
+}
+
+#[test]
+fn linear_gradients_are_shading_patterns() {
+    let mut recording = Recording::new((100.0, 100.0));
+    let gradient = LinearGradient::new(
+        ramp(&[(0.0, RED), (1.0, BLUE)], ExtendMode::Clamp),
+        LinearGradientBrushProperties {
+            start: point(0.0, 0.0),
+            end: point(100.0, 0.0),
+        },
+    );
+    recording.fill_rectangle(rect(0.0, 0.0, 100.0, 100.0), gradient.clone());
+    recording.draw_line(point(0.0, 0.0), point(1.0, 1.0), gradient, 1.0, None);
+
+    let pdf = Pdf::parse(to_pdf(&recording));
+    let content = pdf.content(0);
+    assert!(content.contains("/Pattern cs /P0 scn\n"));
+    assert!(content.contains("/Pattern CS /P1 SCN\n"));
+
+    let resources = pdf.resources(0);
+    let pattern = pdf.object(reference(&resources, "/P0"));
+    assert!(pattern.contains("/Type /Pattern /PatternType 2"));
+    assert!(pattern.contains("/ShadingType 2 /ColorSpace /DeviceRGB /Coords [0 0 100 0]"));
+    assert!(pattern.contains("/C0 [1 0 0] /C1 [0 0 1]"));
+    // Patterns live in the page's default space, in points.
+    assert!(pattern.contains("/Matrix [0.75 0 0 -0.75 0 75]"));
+    // Opaque stops don't need a mask.
+    assert!(!resources.contains("/SMask"));
+}
+
+#[test]
+fn gradient_alpha_becomes_a_soft_mask() {
+    let mut recording = Recording::new((100.0, 100.0));
+    let gradient = LinearGradient::new(
+        ramp(
+            &[(0.0, RED), (1.0, Color::new(0.0, 0.0, 1.0, 0.0))],
+            ExtendMode::Clamp,
+        ),
+        LinearGradientBrushProperties {
+            start: point(0.0, 0.0),
+            end: point(0.0, 100.0),
+        },
+    );
+    recording.fill_rectangle(rect(0.0, 0.0, 100.0, 100.0), gradient);
+
+    let pdf = Pdf::parse(to_pdf(&recording));
+    assert!(pdf
+        .content(0)
+        .starts_with("0.75 0 0 -0.75 0 75 cm\nq\n/GS0 gs\n"));
+    let resources = pdf.resources(0);
+    assert!(resources.contains("/SMask << /Type /Mask /S /Luminosity /G "));
+    let mask = reference(&resources, "/G");
+    let form = pdf.object(mask);
+    assert!(form.contains("/Subtype /Form"));
+    assert!(form.contains("/ColorSpace /DeviceGray"));
+    assert!(form.contains("/C0 [1] /C1 ["));
+    assert_eq!(pdf.stream(mask), b"q\n/Sh0 sh\nQ\n");
+}
+
+#[test]
+fn repeating_gradients_cover_the_page() {
+    let mut recording = Recording::new((100.0, 100.0));
+    let gradient = LinearGradient::new(
+        ramp(&[(0.0, RED), (1.0, BLUE)], ExtendMode::Mirror),
+        LinearGradientBrushProperties {
+            start: point(0.0, 0.0),
+            end: point(25.0, 0.0),
+        },
+    );
+    recording.fill_rectangle(rect(0.0, 0.0, 100.0, 100.0), gradient);
+
+    let pdf = Pdf::parse(to_pdf(&recording));
+    let pattern = pdf.object(reference(&pdf.resources(0), "/P0"));
+    assert!(pattern.contains("/Coords [0 0 100 0] /Domain [0 4]"));
+    assert!(pattern.contains("/Bounds [1 2 3]"));
+    // Every other period runs backwards.
+    assert!(pattern.contains(
+        "<< /FunctionType 2 /Domain [0 1] /C0 [1 0 0] /C1 [0 0 1] /N 1 >> \
+         << /FunctionType 2 /Domain [0 1] /C0 [0 0 1] /C1 [1 0 0] /N 1 >>"
+    ));
+}
+
+#[test]
+fn radial_gradients() {
+    let mut recording = Recording::new((100.0, 100.0));
+    let gradient = RadialGradient::new(
+        ramp(&[(0.0, RED), (1.0, BLUE)], ExtendMode::Clamp),
+        RadialGradientBrushProperties {
+            center: point(50.0, 50.0),
+            origin_offset: point(10.0, 0.0),
+            radius_x: 20.0,
+            radius_y: 10.0,
+        },
+    );
+    recording.fill_rectangle(rect(0.0, 0.0, 100.0, 100.0), gradient);
+
+    let pdf = Pdf::parse(to_pdf(&recording));
+    let pattern = pdf.object(reference(&pdf.resources(0), "/P0"));
+    assert!(pattern.contains("/ShadingType 3"));
+    // From the origin to the unit circle, scaled out to the ellipse.
+    assert!(pattern.contains("/Coords [0.5 0 0 0 0 1]"));
+    assert!(pattern.contains("/Matrix [15 0 0 -7.5 37.5 37.5]"));
+}
+
+#[test]
+fn gradients_without_stops_paint_nothing() {
+    let mut recording = Recording::new((10.0, 10.0));
+    let gradient = LinearGradient::new(
+        ramp(&[], ExtendMode::Clamp),
+        LinearGradientBrushProperties {
+            start: point(0.0, 0.0),
+            end: point(10.0, 0.0),
+        },
+    );
+    recording.fill_rectangle(rect(0.0, 0.0, 10.0, 10.0), gradient);
+    assert_eq!(
+        Pdf::parse(to_pdf(&recording)).content(0),
+        "0.75 0 0 -0.75 0 7.5 cm\n"
+    );
+}
+
+#[test]
+fn clips_and_layers() {
+    let mut recording = Recording::new((100.0, 100.0));
+    recording.set_transform(&Matrix3x2f {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        x: 10.0,
+        y: 0.0,
+    });
+    recording.push_axis_aligned_clip(rect(0.0, 0.0, 50.0, 50.0));
+    recording.push_layer(LayerDesc::new());
+    recording.fill_rectangle(rect(0.0, 0.0, 5.0, 5.0), RED);
+    recording.pop_layer();
+    let mut mask = PathData::new();
+    mask.add_polygon(&[point(0.0, 0.0), point(4.0, 0.0), point(0.0, 4.0)]);
+    recording.push_layer(LayerDesc::new().with_mask(mask).with_opacity(0.5));
+    recording.fill_rectangle(rect(0.0, 0.0, 5.0, 5.0), BLUE);
+    // Left pushed, and popped at the end of the page.
+    recording.push_axis_aligned_clip(rect(0.0, 0.0, 1.0, 1.0));
+
+    let pdf = Pdf::parse(to_pdf(&recording));
+    let content = pdf.content(0);
+    assert_balanced(&content);
+    assert!(content.contains("q\n10 0 50 50 re W n\nq\nq\n1 0 0 1 10 0 cm\n1 0 0 rg\n"));
+    assert!(content.contains("q\n/GS0 gs\n/X0 Do\nQ\n"));
+
+    let resources = pdf.resources(0);
+    assert!(resources.contains("/GS0 << /Type /ExtGState /ca 0.5 /CA 0.5 >>"));
+    let form = reference(&resources, "/X0");
+    let dict = pdf.object(form);
+    assert!(dict.contains("/Subtype /Form /BBox [0 0 100 100]"));
+    assert!(dict.contains("/Group << /S /Transparency /I true >>"));
+    let group = String::from_utf8(pdf.stream(form).to_vec()).unwrap();
+    assert_balanced(&group);
+    // The mask is in the layer's coordinates.
+    assert!(group.starts_with("q\n10 0 m\n14 0 l\n10 4 l\nh\nW* n\n"));
+    assert!(group.contains("0 0 1 rg\n"));
+    assert!(group.contains("10 0 1 1 re W n\n"));
+}
+
+#[test]
+fn bitmaps_are_embedded_once() {
+    // An opaque red pixel and a half transparent one, premultiplied BGRA.
+    let bitmap = Arc::new(BitmapData::new(2, 1, vec![0, 0, 255, 255, 0, 0, 64, 128]));
+    let mut recording = Recording::new((100.0, 100.0));
+    recording.draw_bitmap(
+        &bitmap,
+        rect(0.0, 0.0, 20.0, 10.0),
+        1.0,
+        BitmapInterpolationMode::Linear,
+        bitmap.rect(),
+    );
+    recording.draw_bitmap(
+        &bitmap,
+        rect(50.0, 50.0, 60.0, 60.0),
+        0.5,
+        BitmapInterpolationMode::Linear,
+        rect(1.0, 0.0, 2.0, 1.0),
+    );
+
+    let pdf = Pdf::parse(to_pdf(&recording));
+    let content = pdf.content(0);
+    assert!(content.contains("q\n20 0 0 -10 0 10 cm\n/X0 Do\nQ\n"));
+    // Only the second pixel shows, clipped to where it lands.
+    assert!(content.contains("q\n/GS0 gs\n50 50 10 10 re W n\n20 0 0 -10 40 60 cm\n/X0 Do\nQ\n"));
+
+    let resources = pdf.resources(0);
+    assert_eq!(resources.matches(" 0 R").count(), 1);
+    let image = reference(&resources, "/X0");
+    let dict = pdf.object(image);
+    assert!(dict.contains("/Subtype /Image /Width 2 /Height 1 /ColorSpace /DeviceRGB"));
+    assert!(dict.contains("/Interpolate true"));
+    assert_eq!(pdf.stream(image), &[255, 0, 0, 128, 0, 0][..]);
+    let mask = reference(&dict, "/SMask");
+    assert!(pdf.object(mask).contains("/ColorSpace /DeviceGray"));
+    assert_eq!(pdf.stream(mask), &[255, 128][..]);
+}
+
+#[test]
+fn text_uses_standard_fonts() {
+    let mut recording = Recording::new((100.0, 100.0));
+    recording.draw_text(
+        TextDesc::new("(caf\u{e9})", "Courier New", 10.0).with_origin(point(5.0, 20.0)),
+        BLUE,
+    );
+    recording.draw_text(TextDesc::new("\u{263a}", "Arial", 12.0), RED);
+    let pdf = Pdf::parse(to_pdf(&recording));
+
+    let content = pdf.content(0);
+    assert_balanced(&content);
+    assert!(content.contains("BT\n/F0 10 Tf\n1 0 0 -1 5 29 Tm\n(\\(caf\\351\\)) Tj\nET\n"));
+    assert!(content.contains("/F1 12 Tf\n"));
+    assert!(content.contains("(?) Tj\n"));
+
+    let resources = pdf.resources(0);
+    assert!(resources.contains("/F0 << /Type /Font /Subtype /Type1 /BaseFont /Courier "));
+    assert!(resources.contains("/F1 << /Type /Font /Subtype /Type1 /BaseFont /Helvetica "));
+}
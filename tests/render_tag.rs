@@ -0,0 +1,100 @@
+extern crate direct2d;
+
+use direct2d::make_render_tag;
+use direct2d::render_target::{Breadcrumbs, RenderTag, TagId};
+
+#[test]
+fn tags_round_trip_through_raw_values() {
+    let tag = RenderTag::new("widgets.rs:10");
+    let (tag1, tag2) = tag.to_raw();
+    assert_eq!(RenderTag::from_raw(tag1, tag2), Some(tag));
+
+    let tag = tag.with_payload(1234);
+    let (tag1, tag2) = tag.to_raw();
+    assert_eq!(tag2, 1234);
+    assert_eq!(RenderTag::from_raw(tag1, tag2), Some(tag));
+}
+
+#[test]
+fn zero_payload_is_distinct_from_no_payload() {
+    let plain = RenderTag::new("widgets.rs:20");
+    let zero = plain.with_payload(0);
+    assert_ne!(plain.to_raw(), zero.to_raw());
+    assert_eq!(
+        RenderTag::from_raw(zero.to_raw().0, 0).unwrap().payload,
+        Some(0)
+    );
+    assert_eq!(
+        RenderTag::from_raw(plain.to_raw().0, 0).unwrap().payload,
+        None
+    );
+}
+
+#[test]
+fn foreign_tags_are_rejected() {
+    // Untagged, a pointer/length pair like the old encoding, and a made up
+    // index into the table.
+    assert_eq!(RenderTag::from_raw(0, 0), None);
+    let s = "not a tag";
+    assert_eq!(RenderTag::from_raw(s.as_ptr() as u64, s.len() as u64), None);
+    assert_eq!(RenderTag::from_raw(0x6432_6474_7FFF_FFF0, 0), None);
+}
+
+#[test]
+fn registration_is_idempotent() {
+    let a = TagId::register("scene.rs:1");
+    let b = TagId::register("scene.rs:2");
+    assert_ne!(a, b);
+    assert_eq!(TagId::register("scene.rs:1"), a);
+    assert_eq!(a.loc(), "scene.rs:1");
+    assert_eq!(RenderTag::new("scene.rs:2").id(), b);
+}
+
+#[test]
+fn macro_records_location() {
+    let tag = make_render_tag!();
+    assert!(tag.loc.starts_with(file!()), "{}", tag.loc);
+    assert_eq!(tag.payload, None);
+}
+
+#[test]
+fn display() {
+    assert_eq!(RenderTag::new("a.rs:1").to_string(), "'a.rs:1'");
+    assert_eq!(
+        RenderTag::new("a.rs:1").with_payload(7).to_string(),
+        "'a.rs:1' (7)"
+    );
+}
+
+#[test]
+fn breadcrumbs_are_disabled_by_default() {
+    let mut crumbs = Breadcrumbs::default();
+    assert!(!crumbs.is_enabled());
+    crumbs.push(RenderTag::new("a.rs:1"));
+    assert!(crumbs.is_empty());
+}
+
+#[test]
+fn breadcrumbs_keep_the_most_recent() {
+    let tags: Vec<_> = (0..5)
+        .map(|i| RenderTag::new("frame.rs:1").with_payload(i))
+        .collect();
+
+    let mut crumbs = Breadcrumbs::new(3);
+    for &tag in &tags {
+        crumbs.push(tag);
+    }
+    assert_eq!(crumbs.to_vec(), &tags[2..]);
+    assert_eq!(crumbs.last(), Some(&tags[4]));
+
+    crumbs.set_capacity(2);
+    assert_eq!(crumbs.to_vec(), &tags[3..]);
+    assert_eq!(
+        crumbs.to_string(),
+        "   0: 'frame.rs:1' (3)\n   1: 'frame.rs:1' (4)"
+    );
+
+    crumbs.clear();
+    assert!(crumbs.is_empty());
+    assert_eq!(crumbs.capacity(), 2);
+}
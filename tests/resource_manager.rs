@@ -0,0 +1,198 @@
+extern crate direct2d;
+
+use direct2d::resource::manager::*;
+
+use std::cell::Cell;
+
+/// Stands in for a render target: counts what's been created from it and can
+/// be told to start failing.
+struct FakeTarget {
+    id: u32,
+    created: Cell<u32>,
+    fail: Cell<bool>,
+}
+
+impl FakeTarget {
+    fn new(id: u32) -> FakeTarget {
+        FakeTarget {
+            id,
+            created: Cell::new(0),
+            fail: Cell::new(false),
+        }
+    }
+
+    fn create<R>(&self, r: R) -> Result<R, &'static str> {
+        if self.fail.get() {
+            return Err("out of memory");
+        }
+        self.created.set(self.created.get() + 1);
+        Ok(r)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct FakeBrush {
+    target: u32,
+    color: u32,
+}
+
+#[derive(Debug, PartialEq)]
+struct FakeLayer {
+    target: u32,
+}
+
+type Manager = ResourceManager<FakeTarget, &'static str>;
+
+fn brush(color: u32) -> impl Fn(&FakeTarget) -> Result<FakeBrush, &'static str> {
+    move |t| {
+        t.create(FakeBrush {
+            target: t.id,
+            color,
+        })
+    }
+}
+
+#[test]
+fn resources_are_built_by_ensure() {
+    let mut manager = Manager::new();
+    let red = manager.register(brush(0xFF_00_00));
+    let layer = manager.register(|t: &FakeTarget| t.create(FakeLayer { target: t.id }));
+
+    assert_eq!(manager.len(), 2);
+    assert!(!manager.is_built(red));
+    assert_eq!(manager.get(red), None);
+
+    let target = FakeTarget::new(1);
+    manager.ensure(&target).unwrap();
+    assert_eq!(target.created.get(), 2);
+    assert_eq!(
+        manager.get(red),
+        Some(&FakeBrush {
+            target: 1,
+            color: 0xFF_00_00
+        })
+    );
+    assert_eq!(manager.get(layer), Some(&FakeLayer { target: 1 }));
+
+    // Already built, so nothing new is created.
+    manager.ensure(&target).unwrap();
+    assert_eq!(target.created.get(), 2);
+}
+
+#[test]
+fn handles_survive_rebuilds() {
+    let mut manager = Manager::new();
+    let red = manager.register(brush(0xFF_00_00));
+    let blue = manager.register(brush(0x00_00_FF));
+
+    manager.ensure(&FakeTarget::new(1)).unwrap();
+    assert_eq!(manager.generation(), 0);
+
+    manager.invalidate();
+    assert_eq!(manager.generation(), 1);
+    assert!(!manager.is_built(red));
+    assert!(!manager.is_built(blue));
+
+    let replacement = FakeTarget::new(2);
+    manager.ensure(&replacement).unwrap();
+    assert_eq!(replacement.created.get(), 2);
+    assert_eq!(manager.get(red).unwrap().target, 2);
+    assert_eq!(manager.get(blue).unwrap().color, 0x00_00_FF);
+}
+
+#[test]
+fn get_mut_and_unregister() {
+    let mut manager = Manager::new();
+    let red = manager.register(brush(0xFF_00_00));
+    let blue = manager.register(brush(0x00_00_FF));
+    manager.ensure(&FakeTarget::new(1)).unwrap();
+
+    manager.get_mut(red).unwrap().color = 0x7F_00_00;
+    assert_eq!(manager.get(red).unwrap().color, 0x7F_00_00);
+
+    let removed = manager.unregister(red).unwrap();
+    assert_eq!(removed.color, 0x7F_00_00);
+    assert!(!manager.contains(red));
+    assert_eq!(manager.get(red), None);
+    assert_eq!(manager.unregister(red), None);
+    assert_eq!(manager.len(), 1);
+
+    // Handles aren't reused after removal.
+    let green = manager.register(brush(0x00_FF_00));
+    assert_ne!(green, red);
+    assert!(manager.contains(blue));
+}
+
+#[test]
+fn failed_builds_are_retried() {
+    let mut manager = Manager::new();
+    let red = manager.register(brush(0xFF_00_00));
+    let target = FakeTarget::new(1);
+
+    target.fail.set(true);
+    assert_eq!(manager.ensure(&target), Err("out of memory"));
+    assert!(!manager.is_built(red));
+
+    target.fail.set(false);
+    manager.ensure(&target).unwrap();
+    assert!(manager.is_built(red));
+}
+
+#[test]
+fn partial_builds_are_kept() {
+    let mut manager = Manager::new();
+    let first = manager.register(brush(1));
+    let second = manager.register(|t: &FakeTarget| {
+        if t.created.get() < 2 {
+            Err("not yet")
+        } else {
+            t.create(FakeBrush {
+                target: t.id,
+                color: 2,
+            })
+        }
+    });
+    let target = FakeTarget::new(1);
+
+    assert_eq!(manager.ensure(&target), Err("not yet"));
+    assert!(manager.is_built(first));
+    assert!(!manager.is_built(second));
+
+    target.created.set(2);
+    manager.ensure(&target).unwrap();
+    assert!(manager.is_built(second));
+    assert_eq!(target.created.get(), 3);
+}
+
+#[test]
+fn error_classification() {
+    assert_eq!(classify(D2DERR_RECREATE_TARGET), Recovery::RecreateTarget);
+    assert_eq!(classify(DXGI_ERROR_DEVICE_REMOVED), Recovery::RecreateDevice);
+    assert_eq!(classify(DXGI_ERROR_DEVICE_RESET), Recovery::RecreateDevice);
+    assert_eq!(classify(DXGI_ERROR_DEVICE_HUNG), Recovery::RecreateDevice);
+
+    // E_OUTOFMEMORY, D2DERR_WRONG_STATE
+    assert_eq!(classify(0x8007_000Eu32 as i32), Recovery::Fatal);
+    assert_eq!(classify(0x8899_0001u32 as i32), Recovery::Fatal);
+
+    assert!(Recovery::RecreateTarget.is_recoverable());
+    assert!(!Recovery::Fatal.is_recoverable());
+}
+
+#[test]
+fn handled_errors_invalidate_when_recoverable() {
+    let mut manager = Manager::new();
+    let red = manager.register(brush(0xFF_00_00));
+    manager.ensure(&FakeTarget::new(1)).unwrap();
+
+    assert_eq!(manager.handle_hresult(0x8007_000Eu32 as i32), Recovery::Fatal);
+    assert!(manager.is_built(red));
+    assert_eq!(manager.generation(), 0);
+
+    assert_eq!(
+        manager.handle_hresult(D2DERR_RECREATE_TARGET),
+        Recovery::RecreateTarget
+    );
+    assert!(!manager.is_built(red));
+    assert_eq!(manager.generation(), 1);
+}
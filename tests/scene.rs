@@ -0,0 +1,289 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::scene::*;
+use math2d::{Matrix3x2f, Point2f, Rectf};
+
+/// A filled rect, or a ring when `hole` is set, standing in for geometry.
+#[derive(Clone, Debug, PartialEq)]
+struct Shape {
+    rect: Rectf,
+    hole: Option<Rectf>,
+}
+
+fn inside(r: &Rectf, p: Point2f) -> bool {
+    p.x >= r.left && p.x < r.right && p.y >= r.top && p.y < r.bottom
+}
+
+impl SceneContent for Shape {
+    fn bounds(&self) -> Option<Rectf> {
+        Some(self.rect)
+    }
+
+    fn contains(&self, p: Point2f) -> bool {
+        inside(&self.rect, p) && !matches!(self.hole, Some(h) if inside(&h, p))
+    }
+}
+
+fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Rectf {
+    Rectf {
+        left,
+        top,
+        right,
+        bottom,
+    }
+}
+
+fn shape(left: f32, top: f32, right: f32, bottom: f32) -> Node<Shape> {
+    Node::new().with_content(Shape {
+        rect: rect(left, top, right, bottom),
+        hole: None,
+    })
+}
+
+fn translation(x: f32, y: f32) -> Matrix3x2f {
+    Matrix3x2f {
+        x,
+        y,
+        ..Matrix3x2f::IDENTITY
+    }
+}
+
+fn scale(s: f32) -> Matrix3x2f {
+    Matrix3x2f {
+        a: s,
+        d: s,
+        ..Matrix3x2f::IDENTITY
+    }
+}
+
+/// 90 degrees clockwise on screen: (x, y) -> (-y, x).
+const ROTATE_90: Matrix3x2f = Matrix3x2f {
+    a: 0.0,
+    b: 1.0,
+    c: -1.0,
+    d: 0.0,
+    x: 0.0,
+    y: 0.0,
+};
+
+fn point(x: f32, y: f32) -> Point2f {
+    Point2f { x, y }
+}
+
+#[test]
+fn tree_structure() {
+    let mut scene = Scene::new();
+    let root = scene.root();
+    let a = scene.add(root, shape(0.0, 0.0, 1.0, 1.0));
+    let b = scene.add(root, Node::new());
+    let c = scene.add(b, shape(0.0, 0.0, 1.0, 1.0));
+
+    assert_eq!(scene.node_count(), 4);
+    assert_eq!(scene.children(root), &[a, b]);
+    assert_eq!(scene.parent(c), Some(b));
+    assert_eq!(scene.parent(root), None);
+
+    assert!(scene.remove(root).is_none());
+    assert!(scene.remove(b).is_some());
+    assert!(!scene.contains(b));
+    assert!(!scene.contains(c));
+    assert_eq!(scene.children(root), &[a]);
+    assert_eq!(scene.node_count(), 2);
+
+    // Ids of removed nodes aren't handed out again.
+    let d = scene.add(root, Node::new());
+    assert_ne!(d, b);
+    assert_ne!(d, c);
+    assert!(scene.node(c).is_none());
+}
+
+#[test]
+#[should_panic]
+fn adding_to_a_removed_node_panics() {
+    let mut scene: Scene<Shape> = Scene::new();
+    let root = scene.root();
+    let gone = scene.add(root, Node::new());
+    scene.remove(gone);
+    scene.add(gone, Node::new());
+}
+
+#[test]
+fn world_transforms_compose_child_first() {
+    let mut scene = Scene::new();
+    let root = scene.root();
+    let parent = scene.add(root, Node::new().with_transform(translation(100.0, 0.0)));
+    let child = scene.add(
+        parent,
+        shape(0.0, 0.0, 10.0, 10.0).with_transform(scale(2.0)),
+    );
+
+    // Scaled first, then moved.
+    let world = scene.world_transform(child).unwrap();
+    assert_eq!(
+        world,
+        Matrix3x2f {
+            a: 2.0,
+            d: 2.0,
+            x: 100.0,
+            ..Matrix3x2f::IDENTITY
+        }
+    );
+    assert_eq!(
+        scene.content_bounds(child),
+        Some(rect(100.0, 0.0, 120.0, 20.0))
+    );
+
+    scene.set_transform(parent, ROTATE_90);
+    assert_eq!(
+        scene.content_bounds(child),
+        Some(rect(-20.0, 0.0, 0.0, 20.0))
+    );
+}
+
+#[test]
+fn bounds_cover_subtrees_and_respect_clips() {
+    let mut scene = Scene::new();
+    let root = scene.root();
+    let group = scene.add(root, Node::new().with_clip(rect(0.0, 0.0, 50.0, 50.0)));
+    let a = scene.add(group, shape(10.0, 10.0, 20.0, 20.0));
+    let b = scene.add(group, shape(40.0, 40.0, 100.0, 100.0));
+    let hidden = scene.add(group, shape(0.0, 0.0, 500.0, 500.0).with_visible(false));
+    let outside = scene.add(
+        root,
+        shape(60.0, 0.0, 70.0, 10.0).with_clip(rect(0.0, 0.0, 1.0, 1.0)),
+    );
+
+    assert_eq!(scene.content_bounds(a), Some(rect(10.0, 10.0, 20.0, 20.0)));
+    assert_eq!(scene.content_bounds(b), Some(rect(40.0, 40.0, 50.0, 50.0)));
+    assert_eq!(scene.content_bounds(hidden), None);
+    assert_eq!(scene.content_bounds(outside), None);
+    assert_eq!(
+        scene.world_bounds(group),
+        Some(rect(10.0, 10.0, 50.0, 50.0))
+    );
+    assert_eq!(scene.world_bounds(root), Some(rect(10.0, 10.0, 50.0, 50.0)));
+
+    // Hiding or fading out a parent hides everything under it.
+    scene.node_mut(group).unwrap().opacity = 0.0;
+    assert!(!scene.is_drawn(a));
+    assert_eq!(scene.world_bounds(root), None);
+}
+
+#[test]
+fn hit_testing_finds_the_topmost_node() {
+    let mut scene = Scene::new();
+    let root = scene.root();
+    let back = scene.add(root, shape(0.0, 0.0, 100.0, 100.0));
+    let front = scene.add(root, shape(50.0, 50.0, 150.0, 150.0));
+    let ring = scene.add(
+        front,
+        Node::new().with_content(Shape {
+            rect: rect(60.0, 60.0, 90.0, 90.0),
+            hole: Some(rect(70.0, 70.0, 80.0, 80.0)),
+        }),
+    );
+
+    assert_eq!(scene.hit_test(point(10.0, 10.0)), Some(back));
+    assert_eq!(scene.hit_test(point(99.0, 99.0)), Some(front));
+    // Children draw over their parent.
+    assert_eq!(scene.hit_test(point(65.0, 65.0)), Some(ring));
+    // Through the hole to the parent.
+    assert_eq!(scene.hit_test(point(75.0, 75.0)), Some(front));
+    assert_eq!(scene.hit_test(point(500.0, 500.0)), None);
+
+    scene.node_mut(front).unwrap().visible = false;
+    assert_eq!(scene.hit_test(point(99.0, 99.0)), Some(back));
+    assert_eq!(scene.hit_test(point(65.0, 65.0)), Some(back));
+}
+
+#[test]
+fn hit_testing_uses_local_coordinates_and_clips() {
+    let mut scene = Scene::new();
+    let root = scene.root();
+    let rotated = scene.add(
+        root,
+        Node::new()
+            .with_transform(Matrix3x2f {
+                x: 100.0,
+                ..ROTATE_90
+            })
+            .with_clip(rect(0.0, 0.0, 20.0, 5.0)),
+    );
+    let bar = scene.add(rotated, shape(0.0, 0.0, 20.0, 10.0));
+
+    // Local (10, 2) lands at (98, 10) on screen.
+    assert_eq!(scene.hit_test(point(98.0, 10.0)), Some(bar));
+    // Local (10, 8) is on the bar but outside the clip.
+    assert_eq!(scene.hit_test(point(92.0, 10.0)), None);
+
+    // A transform that can't be inverted can't be hit.
+    scene.set_transform(rotated, scale(0.0));
+    assert_eq!(scene.hit_test(point(100.0, 0.0)), None);
+}
+
+#[test]
+fn everything_is_dirty_at_first() {
+    let mut scene = Scene::new();
+    let root = scene.root();
+    scene.add(root, shape(0.0, 0.0, 10.0, 10.0));
+    scene.add(root, shape(200.0, 200.0, 210.0, 210.0));
+
+    let dirty = scene.take_dirty();
+    assert_eq!(dirty.bounds(), Some(rect(0.0, 0.0, 210.0, 210.0)));
+    assert!(scene.take_dirty().is_empty());
+}
+
+#[test]
+fn moving_a_node_dirties_both_positions() {
+    let mut scene = Scene::new();
+    let root = scene.root();
+    let moving = scene.add(root, shape(0.0, 0.0, 10.0, 10.0));
+    let still = scene.add(root, shape(500.0, 500.0, 510.0, 510.0));
+    scene.take_dirty();
+
+    scene.set_transform(moving, translation(100.0, 0.0));
+    let dirty = scene.take_dirty();
+    assert!(dirty.intersects(&rect(0.0, 0.0, 10.0, 10.0)));
+    assert!(dirty.intersects(&rect(100.0, 0.0, 110.0, 10.0)));
+    assert!(!dirty.intersects(&rect(50.0, 0.0, 60.0, 10.0)));
+    assert!(!dirty.intersects(&scene.content_bounds(still).unwrap()));
+}
+
+#[test]
+fn parent_changes_dirty_descendants() {
+    let mut scene = Scene::new();
+    let root = scene.root();
+    let group = scene.add(root, Node::new());
+    scene.add(group, shape(0.0, 0.0, 10.0, 10.0));
+    scene.add(group, shape(20.0, 0.0, 30.0, 10.0));
+    scene.take_dirty();
+
+    // Same bounds, but every pixel of the group changes.
+    scene.node_mut(group).unwrap().opacity = 0.5;
+    assert_eq!(
+        scene.take_dirty().bounds(),
+        Some(rect(0.0, 0.0, 30.0, 10.0))
+    );
+}
+
+#[test]
+fn removed_nodes_leave_dirty_areas() {
+    let mut scene = Scene::new();
+    let root = scene.root();
+    let group = scene.add(root, shape(0.0, 0.0, 10.0, 10.0));
+    scene.add(group, shape(40.0, 40.0, 50.0, 50.0));
+    let never_drawn = scene.add(root, shape(300.0, 300.0, 310.0, 310.0));
+    scene.take_dirty();
+
+    scene.remove(group);
+    let dirty = scene.take_dirty();
+    assert!(dirty.intersects(&rect(0.0, 0.0, 10.0, 10.0)));
+    assert!(dirty.intersects(&rect(40.0, 40.0, 50.0, 50.0)));
+    assert!(!dirty.intersects(&rect(300.0, 300.0, 310.0, 310.0)));
+
+    // Nodes removed before they were ever reported dirty nothing.
+    let fresh = scene.add(never_drawn, shape(0.0, 0.0, 1.0, 1.0));
+    scene.remove(fresh);
+    assert!(scene.take_dirty().is_empty());
+}
@@ -0,0 +1,390 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::enums::{CapStyle, DashStyle, ExtendMode, FillMode, Gamma, LineJoin};
+use direct2d::record::{BrushDesc, DrawOp, Shape};
+use direct2d::scene_file::value::parse;
+use direct2d::scene_file::{Item, SceneError, SceneFile, Value};
+use direct2d::svg::to_svg;
+use math2d::*;
+
+const JSON: &str = r##"{
+    "size": [200, 100],
+    "background": "white",
+    "items": [
+        { "type": "rect", "rect": [10, 10, 80, 80], "radius": 8,
+          "fill": { "type": "linear", "start": [10, 10], "end": [90, 90],
+                    "stops": [[0, "#3060c0"], [1, [0.5, 0.75, 1]]], "extend": "mirror" } },
+        { "type": "path", "data": "M 110 90 L 150 10 L 190 90 Z", "fill_rule": "evenodd",
+          "stroke": "black", "stroke_width": 4,
+          "stroke_style": { "join": "round", "cap": "square", "dash": [2, 1] } },
+        { "type": "group", "opacity": 0.5, "clip": [0, 0, 100, 50],
+          "transform": "translate(5 5)",
+          "items": [
+              { "type": "ellipse", "center": [50, 50], "radius": [20, 10],
+                "fill": { "type": "radial", "center": [50, 50], "radius": 20,
+                          "stops": [{ "offset": 0, "color": "red" }], "gamma": "1.0" } },
+              { "type": "line", "from": [0, 0], "to": [10, 10], "stroke": "blue",
+                "stroke_style": { "dash": "dash_dot" } }
+          ] },
+        { "type": "text", "text": "Hello \u00e9", "position": [110, 60],
+          "font": "Segoe UI", "font_size": 20, "fill": "navy" }
+    ]
+}"##;
+
+const RON: &str = r##"#![enable(implicit_some)]
+// The same scene as JSON, written the way RON would.
+Scene(
+    size: (200, 100),
+    background: Some("white"),
+    items: [
+        Rect(rect: (10, 10, 80, 80), radius: 8, fill: Linear(
+            start: (10, 10), end: (90, 90),
+            stops: [(0, "#3060c0"), (1, (0.5, 0.75, 1))],
+            extend: Mirror,
+        )),
+        Path(
+            data: "M 110 90 L 150 10 L 190 90 Z",
+            fill_rule: EvenOdd,
+            stroke: "black",
+            stroke_width: 4,
+            stroke_style: (join: Round, cap: Square, dash: [2, 1]),
+        ),
+        Group(opacity: 0.5, clip: (0, 0, 100, 50), transform: "translate(5 5)", items: [
+            Ellipse(center: (50, 50), radius: (20, 10), fill: Radial(
+                center: (50, 50), radius: 20, stops: [(offset: 0, color: "red")], gamma: 1.0,
+            )),
+            Line(from: (0, 0), to: (10, 10), stroke: "blue", stroke_style: (dash: DashDot)),
+        ]),
+        /* Text keeps its defaults for anything left out. */
+        Text(text: "Hello é", position: (110, 60), font: "Segoe UI", font_size: 20, fill: "navy"),
+    ],
+)
+"##;
+
+fn invalid_at(text: &str) -> (String, String) {
+    match SceneFile::parse(text) {
+        Err(SceneError::Invalid { at, message }) => (at, message),
+        other => panic!("expected a schema error, got {:?}", other.map(|_| ())),
+    }
+}
+
+fn syntax_error_at(text: &str) -> (usize, usize, &'static str) {
+    match SceneFile::parse(text) {
+        Err(SceneError::Syntax {
+            line,
+            column,
+            message,
+        }) => (line, column, message),
+        other => panic!("expected a syntax error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn json_and_ron_describe_the_same_scene() {
+    let json = SceneFile::parse(JSON).unwrap();
+    let ron = SceneFile::parse(RON).unwrap();
+    assert_eq!(json.size(), Sizef::new(200.0, 100.0));
+    assert_eq!(json.background(), Some(Color::new(1.0, 1.0, 1.0, 1.0)));
+    assert_eq!(json.items().len(), 4);
+    assert_eq!(to_svg(&json.to_recording()), to_svg(&ron.to_recording()));
+}
+
+#[test]
+fn items_are_loaded_with_their_properties() {
+    let scene = SceneFile::parse(JSON).unwrap();
+
+    let rect = match &scene.items()[0] {
+        Item::Shape(shape) => shape,
+        _ => panic!("expected a shape"),
+    };
+    assert_eq!(
+        rect.shape,
+        Shape::RoundedRectangle(RoundedRect {
+            rect: Rectf::new(10.0, 10.0, 90.0, 90.0),
+            radius_x: 8.0,
+            radius_y: 8.0,
+        })
+    );
+    match &rect.fill {
+        Some(BrushDesc::LinearGradient(gradient)) => {
+            assert_eq!(gradient.ramp.extend_mode(), ExtendMode::Mirror);
+            assert_eq!(gradient.ramp.stops().len(), 2);
+            assert_eq!(
+                gradient.ramp.stops()[1].color,
+                Color::new(0.5, 0.75, 1.0, 1.0)
+            );
+        }
+        other => panic!("expected a linear gradient, got {:?}", other),
+    }
+    assert!(rect.stroke.is_none());
+
+    let path = match &scene.items()[1] {
+        Item::Shape(shape) => shape,
+        _ => panic!("expected a shape"),
+    };
+    match &path.shape {
+        Shape::Path(data) => assert_eq!(data.fill_mode(), FillMode::Alternate),
+        other => panic!("expected a path, got {:?}", other),
+    }
+    let stroke = path.stroke.as_ref().unwrap();
+    assert_eq!(stroke.width, 4.0);
+    assert_eq!(stroke.style.line_join, LineJoin::Round);
+    assert_eq!(stroke.style.start_cap, CapStyle::Square);
+    assert_eq!(stroke.style.dash_cap, CapStyle::Square);
+    assert_eq!(stroke.style.dash_style, DashStyle::Custom);
+    assert_eq!(stroke.style.dashes, [2.0, 1.0]);
+
+    let group = match &scene.items()[2] {
+        Item::Group(group) => group,
+        _ => panic!("expected a group"),
+    };
+    assert_eq!(group.opacity, 0.5);
+    assert!(group.clip.is_some());
+    assert_eq!(
+        group.transform,
+        Matrix3x2f {
+            a: 1.0,
+            b: 0.0,
+            c: 0.0,
+            d: 1.0,
+            x: 5.0,
+            y: 5.0,
+        }
+    );
+    match &group.items[0] {
+        Item::Shape(ellipse) => match &ellipse.fill {
+            Some(BrushDesc::RadialGradient(gradient)) => {
+                assert_eq!(gradient.ramp.gamma(), Gamma::_1_0)
+            }
+            other => panic!("expected a radial gradient, got {:?}", other),
+        },
+        _ => panic!("expected a shape"),
+    }
+    match &group.items[1] {
+        Item::Shape(line) => {
+            assert_eq!(line.stroke.as_ref().unwrap().width, 1.0);
+            assert_eq!(
+                line.stroke.as_ref().unwrap().style.dash_style,
+                DashStyle::DashDot
+            );
+        }
+        _ => panic!("expected a shape"),
+    }
+
+    match &scene.items()[3] {
+        Item::Text(text) => {
+            assert_eq!(text.text.text, "Hello \u{e9}");
+            assert_eq!(text.text.font_family, "Segoe UI");
+            assert_eq!(text.text.font_size, 20.0);
+            assert_eq!(text.text.origin, Point2f::new(110.0, 60.0));
+        }
+        _ => panic!("expected text"),
+    }
+}
+
+#[test]
+fn recording_follows_the_tree() {
+    let recording = SceneFile::parse(JSON).unwrap().to_recording();
+    let kinds: Vec<&str> = recording
+        .ops()
+        .iter()
+        .map(|op| match op {
+            DrawOp::Clear(_) => "clear",
+            DrawOp::SetTransform(_) => "transform",
+            DrawOp::Fill { .. } => "fill",
+            DrawOp::Stroke { .. } => "stroke",
+            DrawOp::Text { .. } => "text",
+            DrawOp::PushLayer(_) => "push",
+            DrawOp::PopLayer => "pop",
+            _ => "other",
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            "clear",
+            "fill",
+            "stroke",
+            "transform",
+            "push",
+            "fill",
+            "stroke",
+            "pop",
+            "transform",
+            "text"
+        ]
+    );
+    // The transform is put back for whatever is recorded next.
+    assert_eq!(recording.transform(), Matrix3x2f::IDENTITY);
+}
+
+#[test]
+fn values_read_json_and_ron() {
+    let value = parse(
+        "// comment\n{ \"a\": [1, -2.5e1, true, null,], b: Some(\"\\ud83d\\ude00\"), c: None, d: Point(x: 1) }",
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        Value::Object {
+            name: None,
+            fields: vec![
+                (
+                    "a".to_string(),
+                    Value::List(vec![
+                        Value::Number(1.0),
+                        Value::Number(-25.0),
+                        Value::Bool(true),
+                        Value::Null,
+                    ])
+                ),
+                ("b".to_string(), Value::String("\u{1f600}".to_string())),
+                ("c".to_string(), Value::Null),
+                (
+                    "d".to_string(),
+                    Value::Object {
+                        name: Some("Point".to_string()),
+                        fields: vec![("x".to_string(), Value::Number(1.0))],
+                    }
+                ),
+            ],
+        }
+    );
+    assert_eq!(parse("(1, Round)").unwrap().kind(), "a list");
+    assert_eq!(parse("Unit()").unwrap().kind(), "an object");
+}
+
+#[test]
+fn syntax_errors_have_positions() {
+    assert_eq!(
+        syntax_error_at("{\n  \"size\": [1, 2\n}"),
+        (3, 1, "expected ',' or the end of the list")
+    );
+    assert_eq!(
+        syntax_error_at("{ \"a\": 1, \"a\": 2 }"),
+        (1, 11, "duplicate field")
+    );
+    assert_eq!(syntax_error_at("\"abc"), (1, 1, "unterminated string"));
+    assert_eq!(syntax_error_at("\"\\u12\""), (1, 2, "bad unicode escape"));
+    assert_eq!(syntax_error_at("{} {}"), (1, 4, "content after the value"));
+    assert_eq!(syntax_error_at("/* open"), (1, 1, "unterminated comment"));
+    assert_eq!(syntax_error_at("[1e999]"), (1, 2, "malformed number"));
+    assert_eq!(
+        syntax_error_at(&"[".repeat(1000)),
+        (1, 130, "values nested too deeply")
+    );
+}
+
+#[test]
+fn schema_errors_have_paths() {
+    assert_eq!(
+        invalid_at("{ \"items\": [] }"),
+        (String::new(), "missing `size`".to_string())
+    );
+    assert_eq!(
+        invalid_at("{ \"size\": [0, 10] }"),
+        ("size".to_string(), "the size must be positive".to_string())
+    );
+    assert_eq!(
+        invalid_at("{ \"size\": [10, 10], \"colour\": \"red\" }"),
+        ("colour".to_string(), "unknown field `colour`".to_string())
+    );
+    assert_eq!(
+        invalid_at("{ size: (10, 10), items: [Star(points: 5)] }"),
+        (
+            "items[0]".to_string(),
+            "unknown item type `star`".to_string()
+        )
+    );
+    assert_eq!(
+        invalid_at("{ size: (10, 10), items: [{ type: \"blob\" }] }"),
+        (
+            "items[0].type".to_string(),
+            "unknown item type `blob`".to_string()
+        )
+    );
+    assert_eq!(
+        invalid_at("{ size: (10, 10), items: [Rect(rect: (0, 0, 1, 1))] }"),
+        (
+            "items[0]".to_string(),
+            "needs a `fill` or a `stroke`".to_string()
+        )
+    );
+    assert_eq!(
+        invalid_at("{ size: (10, 10), items: [Line(from: (0, 0), to: (1, 1), fill: \"red\")] }"),
+        (
+            "items[0].fill".to_string(),
+            "lines can't be filled".to_string()
+        )
+    );
+    assert_eq!(
+        invalid_at(
+            "{ size: (10, 10), items: [Group(items: [Rect(rect: (0, 0, 1, 1), fill: \"reddish\")])] }"
+        ),
+        ("items[0].items[0].fill".to_string(), "unknown color".to_string())
+    );
+    assert_eq!(
+        invalid_at(
+            "{ size: (10, 10), items: [Rect(rect: (0, 0, 1, 1), \
+             fill: Linear(start: (0, 0), end: (1, 1), stops: [(2, \"red\")]))] }"
+        ),
+        (
+            "items[0].fill.stops[0][0]".to_string(),
+            "expected a number from 0 to 1".to_string()
+        )
+    );
+    assert_eq!(
+        invalid_at(
+            "{ size: (10, 10), items: [Rect(rect: (0, 0, 1, 1), \
+             fill: Linear(start: (0, 0), end: (1, 1), stops: [(0, \"red\")], gamma: Linear))] }"
+        ),
+        (
+            "items[0].fill.gamma".to_string(),
+            "expected a gamma of `2.2` or `1.0`".to_string()
+        )
+    );
+    assert_eq!(
+        invalid_at("{ size: (10, 10), items: [Rect(rect: (0, 0, 1, 1), stroke_width: 2)] }"),
+        (
+            "items[0]".to_string(),
+            "a stroke width or style needs a `stroke`".to_string()
+        )
+    );
+    assert_eq!(
+        invalid_at(
+            "{ size: (10, 10), items: [Rect(rect: (0, 0, 1, 1), stroke: \"red\", \
+             stroke_style: (join: Pointy))] }"
+        ),
+        (
+            "items[0].stroke_style.join".to_string(),
+            "unknown line join".to_string()
+        )
+    );
+    assert_eq!(
+        invalid_at("{ size: (10, 10), items: [Text(text: \"hi\", font_size: 0)] }"),
+        (
+            "items[0].font_size".to_string(),
+            "the font size must be positive".to_string()
+        )
+    );
+    assert_eq!(
+        invalid_at("{ size: (10, 10), items: [Path(data: \"M 0 0 Q\", fill: \"red\")] }").0,
+        "items[0].data"
+    );
+    assert_eq!(
+        invalid_at("{ size: (10, 10), items: [Ellipse(center: 5, radius: 1, fill: \"red\")] }"),
+        (
+            "items[0].center".to_string(),
+            "expected a list of 2 numbers, found a number".to_string()
+        )
+    );
+}
+
+#[test]
+fn errors_display_their_location() {
+    let error = SceneFile::parse("{ size: (10, 10), items: [Rect()] }").unwrap_err();
+    assert_eq!(error.to_string(), "items[0]: missing `rect`");
+    let error = SceneFile::parse("{ size: }").unwrap_err();
+    assert_eq!(error.to_string(), "expected a value at line 1, column 9");
+}
@@ -0,0 +1,249 @@
+#![cfg(feature = "serde")]
+
+extern crate direct2d;
+extern crate math2d;
+extern crate serde_json;
+
+use direct2d::cpu::{GradientRamp, LinearGradient};
+use direct2d::descriptions::GradientStop;
+use direct2d::enums::*;
+use direct2d::properties::{
+    BrushProperties, LinearGradientBrushProperties, RadialGradientBrushProperties,
+};
+use direct2d::record::{BrushDesc, StrokeStyleDesc};
+use math2d::*;
+use serde_json::json;
+
+const RED: Color = Color {
+    r: 1.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+#[test]
+fn enums_use_snake_case_names() {
+    assert_eq!(serde_json::to_value(CapStyle::Round).unwrap(), "round");
+    assert_eq!(
+        serde_json::to_value(LineJoin::MiterOrBevel).unwrap(),
+        "miter_or_bevel"
+    );
+    assert_eq!(
+        serde_json::to_value(DashStyle::DashDotDot).unwrap(),
+        "dash_dot_dot"
+    );
+    assert_eq!(serde_json::to_value(ExtendMode::Mirror).unwrap(), "mirror");
+    assert_eq!(serde_json::to_value(FillMode::Winding).unwrap(), "winding");
+    assert_eq!(serde_json::to_value(Gamma::_2_2).unwrap(), "2.2");
+    assert_eq!(
+        serde_json::to_value(BufferPrecision::_8BpcUnormSrgb).unwrap(),
+        "8bpc_unorm_srgb"
+    );
+
+    let gamma: Gamma = serde_json::from_str("\"1.0\"").unwrap();
+    assert_eq!(gamma, Gamma::_1_0);
+    let join: LineJoin = serde_json::from_str("\"bevel\"").unwrap();
+    assert_eq!(join, LineJoin::Bevel);
+    assert!(serde_json::from_str::<CapStyle>("\"Round\"").is_err());
+}
+
+#[test]
+fn flags_are_lists_of_names() {
+    let options = BitmapOptions::TARGET | BitmapOptions::CPU_READ;
+    assert_eq!(
+        serde_json::to_value(options).unwrap(),
+        json!(["target", "cpu_read"])
+    );
+    assert_eq!(
+        serde_json::to_value(BitmapOptions::NONE).unwrap(),
+        json!([])
+    );
+    // Bits without a name are kept as a number.
+    assert_eq!(
+        serde_json::to_value(DrawTextOptions(0x1 | 0x40)).unwrap(),
+        json!(["no_snap", 64])
+    );
+
+    let options: BitmapOptions =
+        serde_json::from_value(json!(["cannot_draw", "cpu_read"])).unwrap();
+    assert_eq!(
+        options,
+        BitmapOptions::CANNOT_DRAW | BitmapOptions::CPU_READ
+    );
+    let options: PresentOptions = serde_json::from_value(json!(["immediately", 16])).unwrap();
+    assert_eq!(options, PresentOptions(0x2 | 0x10));
+
+    let error = serde_json::from_value::<BitmapOptions>(json!(["sparkly"])).unwrap_err();
+    assert_eq!(error.to_string(), "unknown bitmap option `sparkly`");
+}
+
+#[test]
+fn properties_round_trip() {
+    let stop = GradientStop {
+        position: 0.25,
+        color: RED,
+    };
+    let value = serde_json::to_value(stop).unwrap();
+    assert_eq!(
+        value,
+        json!({ "position": 0.25, "color": { "r": 1.0, "g": 0.0, "b": 0.0, "a": 1.0 } })
+    );
+    assert_eq!(serde_json::from_value::<GradientStop>(value).unwrap(), stop);
+
+    // Alpha defaults to opaque.
+    let stop: GradientStop =
+        serde_json::from_value(json!({ "position": 1, "color": { "r": 0, "g": 0, "b": 1 } }))
+            .unwrap();
+    assert_eq!(stop.color, Color::new(0.0, 0.0, 1.0, 1.0));
+
+    let linear: LinearGradientBrushProperties =
+        serde_json::from_value(json!({ "start": { "x": 1, "y": 2 }, "end": { "x": 3, "y": 4 } }))
+            .unwrap();
+    assert_eq!(linear.start, Point2f::new(1.0, 2.0));
+    assert_eq!(linear.end, Point2f::new(3.0, 4.0));
+
+    let radial: RadialGradientBrushProperties = serde_json::from_value(json!({
+        "center": { "x": 5, "y": 5 },
+        "radius_x": 2,
+        "radius_y": 3,
+    }))
+    .unwrap();
+    assert_eq!(radial.center, Point2f::new(5.0, 5.0));
+    assert_eq!(radial.origin_offset, Point2f::new(0.0, 0.0));
+    assert_eq!((radial.radius_x, radial.radius_y), (2.0, 3.0));
+
+    let brush: BrushProperties = serde_json::from_value(json!({ "opacity": 0.5 })).unwrap();
+    assert_eq!(brush.opacity, 0.5);
+    assert_eq!(brush.transform, Matrix3x2f::IDENTITY);
+    assert_eq!(
+        serde_json::to_value(BrushProperties::default()).unwrap(),
+        json!({
+            "opacity": 1.0,
+            "transform": { "a": 1.0, "b": 0.0, "c": 0.0, "d": 1.0, "x": 0.0, "y": 0.0 }
+        })
+    );
+}
+
+#[test]
+fn stroke_style_recipes_fill_in_defaults() {
+    let style: StrokeStyleDesc = serde_json::from_value(json!({
+        "line_join": "round",
+        "dash_style": "custom",
+        "dashes": [2, 1],
+    }))
+    .unwrap();
+    assert_eq!(
+        style,
+        StrokeStyleDesc::new()
+            .with_line_join(LineJoin::Round)
+            .with_dashes(&[2.0, 1.0])
+    );
+
+    let style = StrokeStyleDesc::new()
+        .with_caps(CapStyle::Triangle)
+        .with_dash_style(DashStyle::Dot)
+        .with_miter_limit(4.0);
+    let json = serde_json::to_string(&style).unwrap();
+    assert_eq!(
+        serde_json::from_str::<StrokeStyleDesc>(&json).unwrap(),
+        style
+    );
+}
+
+#[test]
+fn brush_recipes_round_trip() {
+    let solid: BrushDesc =
+        serde_json::from_value(json!({ "solid": { "color": { "r": 1, "g": 0, "b": 0 } } }))
+            .unwrap();
+    match solid {
+        BrushDesc::Solid { color, brush } => {
+            assert_eq!(color, RED);
+            assert_eq!(brush.opacity, 1.0);
+        }
+        other => panic!("expected a solid brush, got {:?}", other),
+    }
+
+    let gradient = BrushDesc::LinearGradient(LinearGradient::new(
+        GradientRamp::new(
+            &[
+                GradientStop {
+                    position: 0.0,
+                    color: RED,
+                },
+                GradientStop {
+                    position: 1.0,
+                    color: Color::new(0.0, 0.0, 1.0, 1.0),
+                },
+            ],
+            Gamma::_1_0,
+            ExtendMode::Wrap,
+        ),
+        LinearGradientBrushProperties {
+            start: Point2f::new(0.0, 0.0),
+            end: Point2f::new(10.0, 0.0),
+        },
+    ))
+    .with_opacity(0.5);
+    let value = serde_json::to_value(&gradient).unwrap();
+    assert_eq!(value["linear_gradient"]["ramp"]["gamma"], "1.0");
+    assert_eq!(value["linear_gradient"]["ramp"]["extend_mode"], "wrap");
+    assert_eq!(value["linear_gradient"]["brush"]["opacity"], 0.5);
+    let back: BrushDesc = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&back).unwrap(), value);
+}
+
+#[test]
+fn gradient_ramps_sort_their_stops() {
+    let ramp: GradientRamp = serde_json::from_value(json!({
+        "stops": [
+            { "position": 1, "color": { "r": 0, "g": 0, "b": 1 } },
+            { "position": 0, "color": { "r": 1, "g": 0, "b": 0 } },
+        ],
+    }))
+    .unwrap();
+    let positions: Vec<f32> = ramp.stops().iter().map(|s| s.position).collect();
+    assert_eq!(positions, [0.0, 1.0]);
+    assert_eq!(ramp.gamma(), Gamma::_2_2);
+    assert_eq!(ramp.extend_mode(), ExtendMode::Clamp);
+    assert_eq!(ramp.color_at(0.0), RED);
+}
+
+#[test]
+fn gradient_ramps_reject_stops_off_the_gradient() {
+    // Too big for an f32, so it becomes infinity.
+    let error = serde_json::from_value::<GradientRamp>(json!({
+        "stops": [{ "position": 1e300, "color": { "r": 0, "g": 0, "b": 1 } }],
+    }))
+    .unwrap_err();
+    assert_eq!(error.to_string(), "gradient stop position inf isn't finite");
+}
+
+#[cfg(windows)]
+#[test]
+fn bitmap_properties_name_their_formats() {
+    use direct2d::descriptions::PixelFormat;
+    use direct2d::properties::BitmapProperties1;
+    use dxgi::enums::Format;
+
+    let properties = BitmapProperties1 {
+        pixel_format: PixelFormat {
+            format: Format::B8G8R8A8Unorm.into(),
+            alpha_mode: AlphaMode::Premultiplied.into(),
+        },
+        dpi_x: 96.0,
+        dpi_y: 96.0,
+        options: BitmapOptions::TARGET,
+    };
+    let value = serde_json::to_value(properties).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "pixel_format": { "format": "b8g8r8a8_unorm", "alpha_mode": "premultiplied" },
+            "dpi_x": 96.0,
+            "dpi_y": 96.0,
+            "options": ["target"],
+        })
+    );
+    let back: BitmapProperties1 = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(serde_json::to_value(back).unwrap(), value);
+}
@@ -0,0 +1,438 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::cpu::{GradientRamp, LinearGradient, RadialGradient};
+use direct2d::descriptions::GradientStop;
+use direct2d::enums::{
+    BitmapInterpolationMode, CapStyle, DashStyle, ExtendMode, FigureBegin, FigureEnd, FillMode,
+    Gamma, LineJoin,
+};
+use direct2d::geometry::PathData;
+use direct2d::properties::{LinearGradientBrushProperties, RadialGradientBrushProperties};
+use direct2d::record::{BitmapData, BrushDesc, LayerDesc, Recording, StrokeStyleDesc, TextDesc};
+use direct2d::svg::to_svg;
+use math2d::*;
+
+use std::sync::Arc;
+
+const RED: Color = Color {
+    r: 1.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+const BLUE: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Rectf {
+    Rectf {
+        left,
+        top,
+        right,
+        bottom,
+    }
+}
+
+fn point(x: f32, y: f32) -> Point2f {
+    Point2f { x, y }
+}
+
+fn red_to_blue(gamma: Gamma, mode: ExtendMode) -> GradientRamp {
+    let stops = [
+        GradientStop {
+            position: 0.0,
+            color: RED,
+        },
+        GradientStop {
+            position: 1.0,
+            color: BLUE,
+        },
+    ];
+    GradientRamp::new(&stops, gamma, mode)
+}
+
+fn triangle() -> PathData {
+    let mut path = PathData::new();
+    path.begin_figure(point(0.0, 0.0), FigureBegin::Filled);
+    path.add_lines(&[point(10.0, 0.0), point(5.0, 8.5)]);
+    path.end_figure(FigureEnd::Closed);
+    path
+}
+
+#[test]
+fn empty_document() {
+    let svg = to_svg(&Recording::new((64.0, 32.5)));
+    assert_eq!(
+        svg,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" \
+         width=\"64\" height=\"32.5\" viewBox=\"0 0 64 32.5\">\n\
+         </svg>\n"
+    );
+}
+
+#[test]
+fn shapes_and_solid_brushes() {
+    let mut rec = Recording::new((100.0, 100.0));
+    rec.clear(Color::new(1.0, 1.0, 1.0, 1.0));
+    rec.fill_rectangle(rect(30.0, 40.0, 10.0, 20.0), RED);
+    rec.fill_rounded_rectangle(
+        RoundedRect {
+            rect: rect(0.0, 0.0, 50.0, 25.0),
+            radius_x: 5.0,
+            radius_y: 2.5,
+        },
+        BrushDesc::solid(BLUE).with_opacity(0.5),
+    );
+    rec.fill_ellipse(
+        Ellipse {
+            center: point(50.0, 50.0),
+            radius_x: 20.0,
+            radius_y: 10.0,
+        },
+        Color::new(0.0, 0.5, 0.0, 0.5),
+    );
+    rec.draw_line((0.0, 0.0), (100.0, 100.0), RED, 1.0 / 3.0, None);
+
+    let svg = to_svg(&rec);
+    let body: Vec<_> = svg.lines().skip(2).collect();
+    assert_eq!(
+        body,
+        [
+            "  <rect width=\"100\" height=\"100\" fill=\"#ffffff\"/>",
+            "  <rect x=\"10\" y=\"20\" width=\"20\" height=\"20\" fill=\"#ff0000\"/>",
+            "  <rect x=\"0\" y=\"0\" width=\"50\" height=\"25\" rx=\"5\" ry=\"2.5\" \
+             fill=\"#0000ff\" fill-opacity=\"0.5\"/>",
+            "  <ellipse cx=\"50\" cy=\"50\" rx=\"20\" ry=\"10\" fill=\"#008000\" \
+             fill-opacity=\"0.5\"/>",
+            "  <line x1=\"0\" y1=\"0\" x2=\"100\" y2=\"100\" fill=\"none\" \
+             stroke=\"#ff0000\" stroke-width=\"0.333\" stroke-miterlimit=\"10\"/>",
+            "</svg>",
+        ]
+    );
+}
+
+#[test]
+fn paths_use_svg_commands() {
+    let mut path = PathData::new();
+    path.begin_figure(point(0.0, 0.0), FigureBegin::Filled);
+    path.add_quadratic_bezier(&QuadBezierSegment {
+        p1: point(5.0, -5.0),
+        p2: point(10.0, 0.0),
+    });
+    path.add_bezier(&BezierSegment {
+        p1: point(10.0, 5.0),
+        p2: point(5.0, 10.0),
+        p3: point(0.0, 10.0),
+    });
+    path.add_arc(&ArcSegment {
+        point: point(0.0, 0.0),
+        size: Sizef::new(5.0, 5.0),
+        rotation_angle: 0.0,
+        sweep_direction: SweepDirection::Clockwise,
+        arc_size: ArcSize::Large,
+    });
+    path.end_figure(FigureEnd::Closed);
+    path.begin_figure(point(20.0, 20.0), FigureBegin::Hollow);
+    path.add_line(point(30.0, 30.0));
+    path.end_figure(FigureEnd::Open);
+
+    let mut rec = Recording::new((50.0, 50.0));
+    rec.fill_geometry(&path, RED);
+    rec.draw_geometry(&path, RED, 2.0, None);
+    rec.fill_geometry(&triangle().with_fill_mode(FillMode::Winding), BLUE);
+    let svg = to_svg(&rec);
+
+    let curves = "M 0 0 Q 5 -5 10 0 C 10 5 5 10 0 10 A 5 5 0 1 1 0 0 Z";
+    // Hollow figures are stroked but not filled.
+    assert!(svg.contains(&format!(
+        "<path d=\"{}\" fill-rule=\"evenodd\" fill=\"#ff0000\"/>",
+        curves
+    )));
+    assert!(svg.contains(&format!(
+        "<path d=\"{} M 20 20 L 30 30\" fill=\"none\" stroke=\"#ff0000\"",
+        curves
+    )));
+    assert!(svg.contains("<path d=\"M 0 0 L 10 0 L 5 8.5 Z\" fill=\"#0000ff\"/>"));
+}
+
+#[test]
+fn transforms_apply_to_later_elements() {
+    let mut rec = Recording::new((50.0, 50.0));
+    rec.set_transform(&Matrix3x2f {
+        a: 2.0,
+        b: 0.0,
+        c: 0.0,
+        d: 2.0,
+        x: 5.0,
+        y: -2.5,
+    });
+    rec.fill_geometry(&triangle(), RED);
+    rec.set_transform(&Matrix3x2f::IDENTITY);
+    rec.fill_geometry(&triangle(), RED);
+
+    let svg = to_svg(&rec);
+    assert!(svg.contains(
+        "<path d=\"M 0 0 L 10 0 L 5 8.5 Z\" fill-rule=\"evenodd\" \
+         transform=\"matrix(2 0 0 2 5 -2.5)\" fill=\"#ff0000\"/>"
+    ));
+    assert!(
+        svg.contains("<path d=\"M 0 0 L 10 0 L 5 8.5 Z\" fill-rule=\"evenodd\" fill=\"#ff0000\"/>")
+    );
+}
+
+#[test]
+fn stroke_styles() {
+    let style = StrokeStyleDesc::new()
+        .with_caps(CapStyle::Round)
+        .with_line_join(LineJoin::Bevel)
+        .with_dash_style(DashStyle::DashDot)
+        .with_dash_offset(1.0);
+    let custom = StrokeStyleDesc::new()
+        .with_caps(CapStyle::Square)
+        .with_line_join(LineJoin::Miter)
+        .with_miter_limit(4.0)
+        .with_dashes(&[1.0, 0.5]);
+
+    let mut rec = Recording::new((50.0, 50.0));
+    rec.draw_rectangle(rect(0.0, 0.0, 10.0, 10.0), BLUE, 2.0, Some(&style));
+    rec.draw_rectangle(rect(0.0, 0.0, 10.0, 10.0), BLUE, 3.0, Some(&custom));
+
+    let svg = to_svg(&rec);
+    assert!(svg.contains(
+        "stroke-width=\"2\" stroke-linecap=\"round\" stroke-linejoin=\"bevel\" \
+         stroke-dasharray=\"4 4 0 4\" stroke-dashoffset=\"2\"/>"
+    ));
+    // 4 is SVG's default miter limit.
+    assert!(
+        svg.contains("stroke-width=\"3\" stroke-linecap=\"square\" stroke-dasharray=\"3 1.5\"/>")
+    );
+}
+
+#[test]
+fn linear_gradients_are_defined_once_per_use() {
+    let gradient = LinearGradient::new(
+        red_to_blue(Gamma::_1_0, ExtendMode::Mirror),
+        LinearGradientBrushProperties {
+            start: point(0.0, 0.0),
+            end: point(100.0, 0.0),
+        },
+    );
+    let mut rec = Recording::new((100.0, 100.0));
+    rec.fill_rectangle(rect(0.0, 0.0, 100.0, 50.0), gradient.clone());
+    rec.draw_rectangle(
+        rect(0.0, 50.0, 100.0, 100.0),
+        BrushDesc::from(gradient).with_opacity(0.25),
+        1.0,
+        None,
+    );
+
+    let svg = to_svg(&rec);
+    assert!(svg.contains(
+        "  <defs>\n    \
+         <linearGradient id=\"gradient1\" gradientUnits=\"userSpaceOnUse\" \
+         x1=\"0\" y1=\"0\" x2=\"100\" y2=\"0\" spreadMethod=\"reflect\" \
+         color-interpolation=\"linearRGB\">\n      \
+         <stop offset=\"0\" stop-color=\"#ff0000\"/>\n      \
+         <stop offset=\"1\" stop-color=\"#0000ff\"/>\n    \
+         </linearGradient>\n"
+    ));
+    assert!(svg.contains("id=\"gradient2\""));
+    assert!(svg.contains("fill=\"url(#gradient1)\"/>"));
+    assert!(svg.contains("stroke=\"url(#gradient2)\" stroke-opacity=\"0.25\""));
+}
+
+#[test]
+fn radial_gradients_squash_into_ellipses() {
+    let gradient = RadialGradient::new(
+        red_to_blue(Gamma::_2_2, ExtendMode::Wrap),
+        RadialGradientBrushProperties {
+            center: point(50.0, 40.0),
+            origin_offset: point(10.0, 5.0),
+            radius_x: 40.0,
+            radius_y: 20.0,
+        },
+    );
+    let mut rec = Recording::new((100.0, 100.0));
+    rec.fill_rectangle(rect(0.0, 0.0, 100.0, 100.0), gradient);
+
+    let svg = to_svg(&rec);
+    assert!(svg.contains(
+        "<radialGradient id=\"gradient1\" gradientUnits=\"userSpaceOnUse\" \
+         cx=\"50\" cy=\"40\" r=\"40\" fx=\"60\" fy=\"50\" spreadMethod=\"repeat\" \
+         gradientTransform=\"matrix(1 0 0 0.5 0 20)\">"
+    ));
+}
+
+#[test]
+fn empty_gradients_paint_nothing() {
+    let ramp = GradientRamp::new(&[], Gamma::_2_2, ExtendMode::Clamp);
+    let gradient = LinearGradient::new(
+        ramp,
+        LinearGradientBrushProperties {
+            start: point(0.0, 0.0),
+            end: point(1.0, 0.0),
+        },
+    );
+    let mut rec = Recording::new((10.0, 10.0));
+    rec.fill_rectangle(rect(0.0, 0.0, 10.0, 10.0), gradient);
+
+    let svg = to_svg(&rec);
+    assert!(!svg.contains("<defs>"));
+    assert!(svg.contains("fill=\"none\"/>"));
+}
+
+#[test]
+fn clips_and_layers_nest() {
+    let mut rec = Recording::new((100.0, 100.0));
+    rec.set_transform(&Matrix3x2f {
+        x: 10.0,
+        y: 10.0,
+        ..Matrix3x2f::IDENTITY
+    });
+    rec.push_axis_aligned_clip(rect(0.0, 0.0, 50.0, 50.0));
+    rec.push_layer(
+        LayerDesc::new()
+            .with_opacity(0.5)
+            .with_content_bounds(rect(0.0, 0.0, 20.0, 20.0))
+            .with_mask(triangle()),
+    );
+    rec.fill_rectangle(rect(0.0, 0.0, 30.0, 30.0), RED);
+    rec.pop_layer();
+    // A mismatched pop is ignored.
+    rec.pop_layer();
+    rec.fill_rectangle(rect(0.0, 0.0, 30.0, 30.0), BLUE);
+    rec.pop_axis_aligned_clip();
+    rec.fill_rectangle(rect(0.0, 0.0, 30.0, 30.0), RED);
+    // Pushes left open are closed at the end.
+    rec.push_layer(LayerDesc::new());
+
+    let svg = to_svg(&rec);
+    let defs = "  <defs>\n    \
+         <clipPath id=\"clip1\">\n      \
+         <rect x=\"10\" y=\"10\" width=\"50\" height=\"50\"/>\n    \
+         </clipPath>\n    \
+         <clipPath id=\"clip2\">\n      \
+         <rect x=\"0\" y=\"0\" width=\"20\" height=\"20\" \
+         transform=\"matrix(1 0 0 1 10 10)\"/>\n    \
+         </clipPath>\n    \
+         <clipPath id=\"clip3\">\n      \
+         <path d=\"M 0 0 L 10 0 L 5 8.5 Z\" clip-rule=\"evenodd\" \
+         transform=\"matrix(1 0 0 1 10 10)\"/>\n    \
+         </clipPath>\n  \
+         </defs>\n";
+    assert!(svg.contains(defs), "{}", svg);
+
+    let transform = "transform=\"matrix(1 0 0 1 10 10)\"";
+    let body: Vec<_> = svg.split(defs).nth(1).unwrap().lines().collect();
+    assert_eq!(
+        body,
+        [
+            "  <g clip-path=\"url(#clip1)\">".to_string(),
+            "    <g opacity=\"0.5\" clip-path=\"url(#clip2)\">".to_string(),
+            "      <g clip-path=\"url(#clip3)\">".to_string(),
+            format!(
+                "        <rect x=\"0\" y=\"0\" width=\"30\" height=\"30\" {} fill=\"#ff0000\"/>",
+                transform
+            ),
+            "      </g>".to_string(),
+            "    </g>".to_string(),
+            format!(
+                "    <rect x=\"0\" y=\"0\" width=\"30\" height=\"30\" {} fill=\"#0000ff\"/>",
+                transform
+            ),
+            "  </g>".to_string(),
+            format!(
+                "  <rect x=\"0\" y=\"0\" width=\"30\" height=\"30\" {} fill=\"#ff0000\"/>",
+                transform
+            ),
+            "  <g>".to_string(),
+            "  </g>".to_string(),
+            "</svg>".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn bitmaps_are_png_images() {
+    let bitmap = Arc::new(BitmapData::new(1, 1, vec![0, 0, 255, 255]));
+    let mut recording = Recording::new((100.0, 100.0));
+    recording.draw_bitmap(
+        &bitmap,
+        rect(10.0, 10.0, 30.0, 20.0),
+        0.5,
+        BitmapInterpolationMode::NearestNeighbor,
+        bitmap.rect(),
+    );
+    let svg = to_svg(&recording);
+    let image = svg.lines().find(|l| l.contains("<image")).unwrap();
+    assert!(image.starts_with(
+        "  <image x=\"10\" y=\"10\" width=\"20\" height=\"10\" preserveAspectRatio=\"none\" \
+         opacity=\"0.5\" image-rendering=\"optimizeSpeed\""
+    ));
+    assert!(image.contains("xlink:href=\"data:image/png;base64,iVBORw0KGgo"));
+    assert!(!svg.contains("clipPath"));
+
+    // Part of a bitmap is clipped to where it lands.
+    let mut recording = Recording::new((100.0, 100.0));
+    recording.draw_bitmap(
+        &Arc::new(BitmapData::new(2, 2, vec![255; 16])),
+        rect(0.0, 0.0, 10.0, 10.0),
+        1.0,
+        BitmapInterpolationMode::Linear,
+        rect(1.0, 1.0, 2.0, 2.0),
+    );
+    let svg = to_svg(&recording);
+    assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"10\" height=\"10\"/>"));
+    assert!(svg.contains(
+        "<g clip-path=\"url(#clip1)\">\n    <image x=\"-10\" y=\"-10\" width=\"20\" height=\"20\""
+    ));
+}
+
+#[test]
+fn png_encoding() {
+    // Premultiplied BGRA: opaque blue, then half transparent white.
+    let bitmap = BitmapData::new(2, 1, vec![255, 0, 0, 255, 128, 128, 128, 128]);
+    assert_eq!(bitmap.to_rgba(), [0, 0, 255, 255, 255, 255, 255, 128]);
+
+    let png = bitmap.to_png();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    let mut chunks = Vec::new();
+    let mut rest = &png[8..];
+    while !rest.is_empty() {
+        let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = String::from_utf8(rest[4..8].to_vec()).unwrap();
+        chunks.push((kind, rest[8..8 + len].to_vec()));
+        rest = &rest[12 + len..];
+    }
+    let kinds: Vec<&str> = chunks.iter().map(|(kind, _)| kind.as_str()).collect();
+    assert_eq!(kinds, ["IHDR", "IDAT", "IEND"]);
+    assert_eq!(chunks[0].1, [0, 0, 0, 2, 0, 0, 0, 1, 8, 6, 0, 0, 0]);
+
+    // A zlib header, one final stored block, then the checksum.
+    let idat = &chunks[1].1;
+    assert_eq!(&idat[..3], [0x78, 0x01, 0x01]);
+    assert_eq!(&idat[3..7], [9, 0, 0xf6, 0xff]);
+    assert_eq!(&idat[7..16], [0, 0, 0, 255, 255, 255, 255, 255, 128]);
+    assert_eq!(idat.len(), 20);
+}
+
+#[test]
+fn text_sits_on_its_baseline() {
+    let mut recording = Recording::new((100.0, 100.0));
+    recording.draw_text(
+        TextDesc::new("Fish & <chips>", "Segoe UI", 20.0).with_origin(point(10.0, 30.0)),
+        RED,
+    );
+    recording.draw_text(TextDesc::new("", "Segoe UI", 20.0), RED);
+    let svg = to_svg(&recording);
+    assert!(svg.contains(
+        "<text x=\"10\" y=\"48\" font-family=\"Segoe UI\" font-size=\"20\" fill=\"#ff0000\" \
+         xml:space=\"preserve\">Fish &amp; &lt;chips&gt;</text>"
+    ));
+    assert_eq!(svg.matches("<text").count(), 1);
+}
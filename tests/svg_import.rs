@@ -0,0 +1,541 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::enums::{
+    CapStyle, DashStyle, ExtendMode, FigureBegin, FigureEnd, FillMode, LineJoin,
+};
+use direct2d::geometry::{PathData, PathEvent};
+use direct2d::record::{BrushDesc, DrawOp, Shape};
+use direct2d::svg::path::parse_path_partial;
+use direct2d::svg::values::{parse_color, parse_transform};
+use direct2d::svg::{parse_path, to_svg, Document, Group, Node, ShapeNode, SvgError};
+use math2d::*;
+
+const EPSILON: f32 = 0.001;
+
+fn point(x: f32, y: f32) -> Point2f {
+    Point2f { x, y }
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() <= EPSILON,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+fn assert_point(actual: Point2f, expected: Point2f) {
+    assert!(
+        (actual.x - expected.x).abs() <= EPSILON && (actual.y - expected.y).abs() <= EPSILON,
+        "expected {:?}, got {:?}",
+        expected,
+        actual
+    );
+}
+
+fn apply(m: &Matrix3x2f, p: Point2f) -> Point2f {
+    point(p.x * m.a + p.y * m.c + m.x, p.x * m.b + p.y * m.d + m.y)
+}
+
+fn parse(body: &str) -> Document {
+    let text = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"100\">{}</svg>",
+        body
+    );
+    Document::parse(&text).unwrap()
+}
+
+/// The nodes directly under the root.
+fn children(document: &Document) -> &[Node] {
+    &document.root().children
+}
+
+fn shape(node: &Node) -> &ShapeNode {
+    match node {
+        Node::Shape(shape) => shape,
+        Node::Group(_) => panic!("expected a shape, got a group"),
+    }
+}
+
+fn group(node: &Node) -> &Group {
+    match node {
+        Node::Group(group) => group,
+        Node::Shape(_) => panic!("expected a group, got a shape"),
+    }
+}
+
+fn solid(brush: &BrushDesc) -> (Color, f32) {
+    match brush {
+        BrushDesc::Solid { color, brush } => (*color, brush.opacity),
+        _ => panic!("expected a solid brush, got {:?}", brush),
+    }
+}
+
+#[test]
+fn path_commands() {
+    let path = parse_path("M10 10 h10 v10 H10 z m5 5 l1-1 1 1").unwrap();
+    assert_eq!(
+        path.events(),
+        &[
+            PathEvent::Begin {
+                start: point(10.0, 10.0),
+                begin: FigureBegin::Filled
+            },
+            PathEvent::Line(point(20.0, 10.0)),
+            PathEvent::Line(point(20.0, 20.0)),
+            PathEvent::Line(point(10.0, 20.0)),
+            PathEvent::End(FigureEnd::Closed),
+            // Relative to where the closed figure started.
+            PathEvent::Begin {
+                start: point(15.0, 15.0),
+                begin: FigureBegin::Filled
+            },
+            PathEvent::Line(point(16.0, 14.0)),
+            PathEvent::Line(point(17.0, 15.0)),
+            PathEvent::End(FigureEnd::Open),
+        ][..]
+    );
+}
+
+#[test]
+fn smooth_curves_reflect_control_points() {
+    let path = parse_path("M0 0 C0 10 10 10 10 0 S20-10 20 0 Q25 5 30 0 T40 0").unwrap();
+    let events = path.events();
+    match events[2] {
+        PathEvent::Cubic(c) => {
+            assert_point(c.p1, point(10.0, -10.0));
+            assert_point(c.p3, point(20.0, 0.0));
+        }
+        e => panic!("expected a cubic, got {:?}", e),
+    }
+    match events[4] {
+        PathEvent::Quadratic(q) => assert_point(q.p1, point(35.0, -5.0)),
+        e => panic!("expected a quadratic, got {:?}", e),
+    }
+}
+
+#[test]
+fn arcs_and_compact_flags() {
+    let path = parse_path("M0 0a5 5 0 1010 0A0 5 0 0 0 20 0").unwrap();
+    match path.events()[1] {
+        PathEvent::Arc(arc) => {
+            assert_eq!(arc.arc_size, ArcSize::Large);
+            assert_eq!(arc.sweep_direction, SweepDirection::CounterClockwise);
+            assert_point(arc.point, point(10.0, 0.0));
+        }
+        e => panic!("expected an arc, got {:?}", e),
+    }
+    // A zero radius makes a straight line.
+    assert_eq!(path.events()[2], PathEvent::Line(point(20.0, 0.0)));
+}
+
+#[test]
+fn bad_path_data() {
+    assert_eq!(parse_path("L 10 10"), Err(SvgError::PathData(0)));
+    assert_eq!(parse_path("M 0 0 L 10 x"), Err(SvgError::PathData(7)));
+
+    // Documents draw bad paths up to the error.
+    let (path, error) = parse_path_partial("M 0 0 L 10 0 L 10 10 L 5 #");
+    assert!(error.is_some());
+    assert_eq!(path.events().len(), 4);
+}
+
+#[test]
+fn transform_lists() {
+    let m = parse_transform("translate(10, 20) scale(2)").unwrap();
+    assert_point(apply(&m, point(1.0, 1.0)), point(12.0, 22.0));
+
+    let m = parse_transform("rotate(90 10 10)").unwrap();
+    assert_point(apply(&m, point(20.0, 10.0)), point(10.0, 20.0));
+
+    let m = parse_transform("matrix(1 0 0 1 5 6) skewX(45)").unwrap();
+    assert_point(apply(&m, point(0.0, 10.0)), point(15.0, 16.0));
+
+    assert!(parse_transform("spin(4)").is_none());
+}
+
+#[test]
+fn colors() {
+    assert_eq!(parse_color("#f00"), Some(Color::new(1.0, 0.0, 0.0, 1.0)));
+    assert_eq!(parse_color("#0000FF"), Some(Color::new(0.0, 0.0, 1.0, 1.0)));
+    assert_eq!(
+        parse_color("rgb(255, 0, 0)"),
+        Some(Color::new(1.0, 0.0, 0.0, 1.0))
+    );
+    assert_eq!(
+        parse_color("rgba(0%, 100%, 0%, 0.5)"),
+        Some(Color::new(0.0, 1.0, 0.0, 0.5))
+    );
+    assert_eq!(parse_color("lime"), Some(Color::new(0.0, 1.0, 0.0, 1.0)));
+    assert_eq!(parse_color("#ff"), None);
+    assert_eq!(parse_color("chartreuse-ish"), None);
+}
+
+#[test]
+fn xml_errors() {
+    assert_eq!(
+        Document::parse("<svg><g></svg>").unwrap_err(),
+        SvgError::Xml(10, "mismatched end tag")
+    );
+    assert_eq!(
+        Document::parse("<html></html>").unwrap_err(),
+        SvgError::NotSvg
+    );
+    assert!(Document::parse("<svg width=100></svg>").is_err());
+    assert!(Document::parse("<svg/><svg/>").is_err());
+}
+
+#[test]
+fn prolog_comments_and_entities_are_handled() {
+    let document = Document::parse(
+        "<?xml version=\"1.0\"?>\n\
+         <!DOCTYPE svg PUBLIC \"-//W3C//DTD SVG 1.1//EN\" \"x\" [<!ENTITY a \"b\">]>\n\
+         <!-- an icon -->\n\
+         <svg:svg xmlns:svg=\"http://www.w3.org/2000/svg\" width=\"10\" height=\"10\">\
+         <svg:title>A &amp; B</svg:title>\
+         <![CDATA[ <rect/> ]]>\
+         <svg:rect width=\"10\" height=\"10\" fill=\"&#x23;00f\"/>\
+         </svg:svg>",
+    )
+    .unwrap();
+    let children = children(&document);
+    assert_eq!(children.len(), 1);
+    let node = shape(&children[0]);
+    assert_eq!(
+        solid(node.fill.as_ref().unwrap()).0,
+        Color::new(0.0, 0.0, 1.0, 1.0)
+    );
+}
+
+#[test]
+fn view_box_is_fitted_to_the_viewport() {
+    let document = Document::parse(
+        "<svg width=\"200\" height=\"100\" viewBox=\"10 10 50 50\"><rect width=\"1\" height=\"1\"/></svg>",
+    )
+    .unwrap();
+    assert_eq!(document.size(), Sizef::new(200.0, 100.0));
+    // Scaled by 2 and centered horizontally.
+    let m = document.root().transform;
+    assert_point(apply(&m, point(10.0, 10.0)), point(50.0, 0.0));
+    assert_point(apply(&m, point(60.0, 60.0)), point(150.0, 100.0));
+
+    let stretched = Document::parse(
+        "<svg width=\"200\" height=\"100\" viewBox=\"0 0 50 50\" preserveAspectRatio=\"none\"/>",
+    )
+    .unwrap();
+    assert_point(
+        apply(&stretched.root().transform, point(50.0, 50.0)),
+        point(200.0, 100.0),
+    );
+
+    // Without a size, the view box gives one.
+    let sized = Document::parse("<svg viewBox=\"0 0 24 16\"/>").unwrap();
+    assert_eq!(sized.size(), Sizef::new(24.0, 16.0));
+}
+
+#[test]
+fn basic_shapes() {
+    let document = parse(
+        "<rect x=\"1\" y=\"2\" width=\"10\" height=\"20\"/>\
+         <rect width=\"10\" height=\"4\" rx=\"3\"/>\
+         <rect width=\"0\" height=\"4\"/>\
+         <circle cx=\"5\" cy=\"6\" r=\"7\"/>\
+         <ellipse cx=\"5\" cy=\"6\" rx=\"7\" ry=\"8\"/>\
+         <line x1=\"0\" y1=\"0\" x2=\"10\" y2=\"10\" stroke=\"black\"/>\
+         <polyline points=\"0,0 10,0 10,10\"/>\
+         <polygon points=\"0 0 10 0 10 10\"/>",
+    );
+    let shapes: Vec<&Shape> = children(&document)
+        .iter()
+        .map(|n| &shape(n).shape)
+        .collect();
+    assert_eq!(shapes.len(), 7, "the empty rect is skipped");
+    assert_eq!(
+        *shapes[0],
+        Shape::Rectangle(Rectf {
+            left: 1.0,
+            top: 2.0,
+            right: 11.0,
+            bottom: 22.0
+        })
+    );
+    match shapes[1] {
+        // ry defaults to rx, then both are limited to half the size.
+        Shape::RoundedRectangle(r) => {
+            assert_eq!((r.radius_x, r.radius_y), (3.0, 2.0));
+        }
+        s => panic!("expected a rounded rectangle, got {:?}", s),
+    }
+    assert_eq!(
+        *shapes[2],
+        Shape::Ellipse(Ellipse {
+            center: point(5.0, 6.0),
+            radius_x: 7.0,
+            radius_y: 7.0
+        })
+    );
+    assert!(matches!(shapes[3], Shape::Ellipse(e) if e.radius_y == 8.0));
+    assert_eq!(*shapes[4], Shape::Line(point(0.0, 0.0), point(10.0, 10.0)));
+
+    let end = |shape: &Shape| match shape {
+        Shape::Path(path) => *path.events().last().unwrap(),
+        s => panic!("expected a path, got {:?}", s),
+    };
+    assert_eq!(end(shapes[5]), PathEvent::End(FigureEnd::Open));
+    assert_eq!(end(shapes[6]), PathEvent::End(FigureEnd::Closed));
+}
+
+#[test]
+fn styles_cascade() {
+    let document = parse(
+        "<g fill=\"red\" stroke=\"blue\" stroke-width=\"2\" color=\"lime\">\
+           <rect width=\"1\" height=\"1\"/>\
+           <rect width=\"1\" height=\"1\" fill=\"currentColor\" fill-opacity=\"0.5\"/>\
+           <rect width=\"1\" height=\"1\" fill=\"#000\" style=\"fill: none; stroke-opacity: 25%\"/>\
+           <path d=\"M0 0 L1 1 L0 1Z\" fill-rule=\"evenodd\" stroke=\"none\"/>\
+         </g>",
+    );
+    let g = group(&children(&document)[0]);
+    let nodes: Vec<&ShapeNode> = g.children.iter().map(shape).collect();
+
+    assert_eq!(
+        solid(nodes[0].fill.as_ref().unwrap()),
+        (Color::new(1.0, 0.0, 0.0, 1.0), 1.0)
+    );
+    let stroke = nodes[0].stroke.as_ref().unwrap();
+    assert_eq!(stroke.width, 2.0);
+    assert_eq!(solid(&stroke.brush).0, Color::new(0.0, 0.0, 1.0, 1.0));
+
+    assert_eq!(
+        solid(nodes[1].fill.as_ref().unwrap()),
+        (Color::new(0.0, 1.0, 0.0, 1.0), 0.5)
+    );
+
+    assert!(nodes[2].fill.is_none());
+    assert_eq!(solid(&nodes[2].stroke.as_ref().unwrap().brush).1, 0.25);
+
+    assert!(nodes[3].stroke.is_none());
+    match &nodes[3].shape {
+        Shape::Path(path) => assert_eq!(path.fill_mode(), FillMode::Alternate),
+        s => panic!("expected a path, got {:?}", s),
+    }
+}
+
+#[test]
+fn stroke_styles() {
+    let document = parse(
+        "<path d=\"M0 0 H10\" stroke=\"black\" stroke-width=\"2\" stroke-linecap=\"round\" \
+         stroke-linejoin=\"bevel\" stroke-miterlimit=\"8\" stroke-dasharray=\"4 2 1\" \
+         stroke-dashoffset=\"1\"/>\
+         <path d=\"M0 0 H10\" stroke=\"black\"/>",
+    );
+    let stroke = shape(&children(&document)[0]).stroke.clone().unwrap();
+    assert_eq!(stroke.style.start_cap, CapStyle::Round);
+    assert_eq!(stroke.style.end_cap, CapStyle::Round);
+    assert_eq!(stroke.style.line_join, LineJoin::Bevel);
+    assert_eq!(stroke.style.miter_limit, 8.0);
+    assert_eq!(stroke.style.dash_style, DashStyle::Custom);
+    // In multiples of the width, repeated to an even count.
+    assert_eq!(stroke.style.dashes, vec![2.0, 1.0, 0.5, 2.0, 1.0, 0.5]);
+    assert_eq!(stroke.style.dash_offset, 0.5);
+
+    let plain = shape(&children(&document)[1]).stroke.clone().unwrap();
+    assert_eq!(plain.width, 1.0);
+    assert_eq!(plain.style.start_cap, CapStyle::Flat);
+    assert_eq!(plain.style.line_join, LineJoin::MiterOrBevel);
+    assert_eq!(plain.style.miter_limit, 4.0);
+    assert_eq!(plain.style.dash_style, DashStyle::Solid);
+}
+
+#[test]
+fn bounding_box_linear_gradients() {
+    let document = parse(
+        "<defs>\
+           <linearGradient id=\"base\" spreadMethod=\"reflect\">\
+             <stop offset=\"0\" stop-color=\"red\"/>\
+             <stop offset=\"50%\" stop-color=\"blue\" stop-opacity=\"0.5\"/>\
+             <stop offset=\"0.25\" style=\"stop-color: lime\"/>\
+           </linearGradient>\
+           <linearGradient id=\"vertical\" xlink:href=\"#base\" x2=\"0\" y2=\"1\"/>\
+         </defs>\
+         <rect x=\"10\" y=\"20\" width=\"40\" height=\"80\" fill=\"url(#vertical)\"/>",
+    );
+    let fill = shape(&children(&document)[0]).fill.clone().unwrap();
+    let gradient = match fill {
+        BrushDesc::LinearGradient(gradient) => gradient,
+        b => panic!("expected a linear gradient, got {:?}", b),
+    };
+    assert_eq!(gradient.properties.start, point(0.0, 0.0));
+    assert_eq!(gradient.properties.end, point(0.0, 1.0));
+    assert_eq!(gradient.ramp.extend_mode(), ExtendMode::Mirror);
+
+    let stops = gradient.ramp.stops();
+    assert_eq!(stops.len(), 3);
+    assert_eq!(stops[1].color, Color::new(0.0, 0.0, 1.0, 0.5));
+    // Offsets can't go backwards.
+    assert_eq!(stops[2].position, 0.5);
+
+    // The unit square is mapped onto the rect.
+    let m = gradient.brush.transform;
+    assert_point(apply(&m, point(0.0, 1.0)), point(10.0, 100.0));
+    assert_point(apply(&m, point(1.0, 0.0)), point(50.0, 20.0));
+}
+
+#[test]
+fn user_space_radial_gradients() {
+    let document = parse(
+        "<radialGradient id=\"glow\" gradientUnits=\"userSpaceOnUse\" cx=\"50\" cy=\"40\" \
+         r=\"30\" fx=\"60\" gradientTransform=\"translate(5 0)\">\
+           <stop offset=\"0\" stop-color=\"white\"/>\
+           <stop offset=\"1\" stop-color=\"black\"/>\
+         </radialGradient>\
+         <circle cx=\"50\" cy=\"50\" r=\"40\" fill=\"url(#glow)\" fill-opacity=\"0.5\"/>\
+         <circle cx=\"50\" cy=\"50\" r=\"40\" fill=\"url(#missing) green\"/>\
+         <circle cx=\"50\" cy=\"50\" r=\"40\" fill=\"url(#missing)\"/>",
+    );
+    let children = children(&document);
+    assert_eq!(
+        children.len(),
+        2,
+        "a missing gradient without fallback paints nothing"
+    );
+
+    let gradient = match shape(&children[0]).fill.clone().unwrap() {
+        BrushDesc::RadialGradient(gradient) => gradient,
+        b => panic!("expected a radial gradient, got {:?}", b),
+    };
+    assert_eq!(gradient.properties.center, point(50.0, 40.0));
+    assert_eq!(gradient.properties.origin_offset, point(10.0, 0.0));
+    assert_eq!(
+        (gradient.properties.radius_x, gradient.properties.radius_y),
+        (30.0, 30.0)
+    );
+    assert_eq!(gradient.brush.opacity, 0.5);
+    assert_eq!(gradient.brush.transform.x, 5.0);
+
+    assert_eq!(
+        solid(shape(&children[1]).fill.as_ref().unwrap()).0,
+        Color::new(0.0, 128.0 / 255.0, 0.0, 1.0)
+    );
+}
+
+#[test]
+fn clip_paths() {
+    let document = parse(
+        "<clipPath id=\"half\" clip-rule=\"evenodd\">\
+           <rect width=\"50\" height=\"100\"/>\
+         </clipPath>\
+         <clipPath id=\"box\" clipPathUnits=\"objectBoundingBox\">\
+           <rect x=\"0.5\" width=\"0.5\" height=\"1\"/>\
+         </clipPath>\
+         <g clip-path=\"url(#half)\" opacity=\"0.5\"><rect width=\"100\" height=\"100\"/></g>\
+         <rect x=\"20\" y=\"0\" width=\"40\" height=\"10\" transform=\"translate(5 5)\" \
+          clip-path=\"url(#box)\"/>",
+    );
+    let children = children(&document);
+
+    let g = group(&children[0]);
+    assert_eq!(g.opacity, 0.5);
+    let clip = g.clip.as_ref().unwrap();
+    assert_eq!(clip.fill_mode(), FillMode::Alternate);
+    assert_eq!(
+        clip.bounds(),
+        Some(Rectf {
+            left: 0.0,
+            top: 0.0,
+            right: 50.0,
+            bottom: 100.0
+        })
+    );
+
+    // A clipped shape is wrapped in a group which takes its transform.
+    let wrapper = group(&children[1]);
+    assert_point(apply(&wrapper.transform, point(0.0, 0.0)), point(5.0, 5.0));
+    assert_eq!(shape(&wrapper.children[0]).transform, Matrix3x2f::IDENTITY);
+    let bounds = wrapper.clip.as_ref().unwrap().bounds().unwrap();
+    assert_close(bounds.left, 40.0);
+    assert_close(bounds.right, 60.0);
+    assert_close(bounds.bottom, 10.0);
+}
+
+#[test]
+fn hidden_and_unknown_elements_are_skipped() {
+    let document = parse(
+        "<rect width=\"1\" height=\"1\" display=\"none\"/>\
+         <g style=\"display:none\"><rect width=\"1\" height=\"1\"/></g>\
+         <text>hello</text>\
+         <defs><rect width=\"1\" height=\"1\"/></defs>\
+         <rect width=\"1\" height=\"1\" fill=\"none\"/>",
+    );
+    assert!(children(&document).is_empty());
+}
+
+#[test]
+fn recording_draws_the_tree() {
+    let document = parse(
+        "<g transform=\"translate(10 0)\" opacity=\"0.5\">\
+           <rect width=\"5\" height=\"5\" stroke=\"red\"/>\
+         </g>\
+         <circle cx=\"1\" cy=\"1\" r=\"1\"/>",
+    );
+    let recording = document.to_recording();
+    let ops = recording.ops();
+    let kinds: Vec<&str> = ops
+        .iter()
+        .map(|op| match op {
+            DrawOp::SetTransform(_) => "transform",
+            DrawOp::PushLayer(_) => "push",
+            DrawOp::PopLayer => "pop",
+            DrawOp::Fill { .. } => "fill",
+            DrawOp::Stroke { .. } => "stroke",
+            _ => "other",
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            "transform",
+            "push",
+            "fill",
+            "stroke",
+            "pop",
+            "transform",
+            "fill"
+        ]
+    );
+    match &ops[1] {
+        DrawOp::PushLayer(layer) => assert_eq!(layer.opacity, 0.5),
+        op => panic!("expected a layer, got {:?}", op),
+    }
+    assert_eq!(recording.transform(), Matrix3x2f::IDENTITY);
+}
+
+#[test]
+fn exported_svg_reads_back() {
+    let mut path = PathData::new();
+    path.begin_figure(point(0.0, 0.0), FigureBegin::Filled);
+    path.add_lines(&[point(10.0, 0.0), point(5.0, 8.0)]);
+    path.end_figure(FigureEnd::Closed);
+
+    let original = parse(
+        "<g transform=\"scale(2)\">\
+           <path d=\"M0 0 L10 0 L5 8 Z\" fill=\"#336699\" fill-rule=\"evenodd\"/>\
+           <ellipse cx=\"5\" cy=\"5\" rx=\"3\" ry=\"2\" fill=\"none\" stroke=\"red\" stroke-width=\"0.5\"/>\
+         </g>",
+    );
+    let svg = to_svg(&original.to_recording());
+    let reread = Document::parse(&svg).unwrap();
+
+    let nodes: Vec<&ShapeNode> = children(&reread).iter().map(shape).collect();
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0].shape, Shape::Path(path));
+    assert_eq!(nodes[0].transform.a, 2.0);
+    assert_eq!(
+        solid(nodes[0].fill.as_ref().unwrap()).0,
+        parse_color("#336699").unwrap()
+    );
+    assert!(nodes[1].fill.is_none());
+    assert_eq!(nodes[1].stroke.as_ref().unwrap().width, 0.5);
+}
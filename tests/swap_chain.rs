@@ -0,0 +1,223 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::error::{DXGI_ERROR_DEVICE_REMOVED, DXGI_STATUS_OCCLUDED, E_INVALIDARG, HRESULT};
+use direct2d::render_target::WindowRect;
+use direct2d::swap_chain::*;
+use math2d::Sizeu;
+
+#[derive(Debug, PartialEq)]
+enum Call {
+    CreateTarget,
+    ReleaseTarget(u32),
+    Resize(u32, u32),
+    Present { partial: bool },
+}
+
+/// Records what the state machine asks of it. Targets are numbered so
+/// releases can be matched to creations.
+#[derive(Default)]
+struct FakeSwapChain {
+    calls: Vec<Call>,
+    targets: u32,
+    live_targets: u32,
+    fail_resize: Option<HRESULT>,
+    present_status: Option<PresentStatus>,
+}
+
+impl SwapChainBackend for FakeSwapChain {
+    type Target = u32;
+    type Error = HRESULT;
+
+    fn create_target(&mut self) -> Result<u32, HRESULT> {
+        self.calls.push(Call::CreateTarget);
+        self.targets += 1;
+        self.live_targets += 1;
+        Ok(self.targets)
+    }
+
+    fn release_target(&mut self, target: u32) {
+        self.calls.push(Call::ReleaseTarget(target));
+        self.live_targets -= 1;
+    }
+
+    fn resize_buffers(&mut self, size: Sizeu) -> Result<(), HRESULT> {
+        // DXGI fails resizes while the back buffer is still referenced.
+        assert_eq!(self.live_targets, 0, "resized with a live target");
+        self.calls.push(Call::Resize(size.width, size.height));
+        match self.fail_resize {
+            Some(hr) => Err(hr),
+            None => Ok(()),
+        }
+    }
+
+    fn present(&mut self, params: &PresentParams) -> Result<PresentStatus, HRESULT> {
+        self.calls.push(Call::Present {
+            partial: params.is_partial(),
+        });
+        Ok(self.present_status.unwrap_or(PresentStatus::Presented))
+    }
+}
+
+fn size(width: u32, height: u32) -> Sizeu {
+    Sizeu { width, height }
+}
+
+fn new_state() -> SwapChainState<FakeSwapChain> {
+    SwapChainState::new(FakeSwapChain::default(), size(640, 480))
+}
+
+#[test]
+fn targets_are_created_lazily_and_reused() {
+    let mut state = new_state();
+    assert!(!state.has_target());
+
+    assert_eq!(*state.target().unwrap(), 1);
+    assert_eq!(*state.target().unwrap(), 1);
+    assert_eq!(state.backend().calls, vec![Call::CreateTarget]);
+}
+
+#[test]
+fn resizing_releases_and_recreates_the_target() {
+    let mut state = new_state();
+    state.target().unwrap();
+
+    assert_eq!(state.resize(size(640, 480)), Ok(false));
+    assert_eq!(state.resize(size(800, 600)), Ok(true));
+    assert!(!state.has_target());
+    assert_eq!(state.size(), size(800, 600));
+
+    assert_eq!(*state.target().unwrap(), 2);
+    assert_eq!(
+        state.backend().calls,
+        vec![
+            Call::CreateTarget,
+            Call::ReleaseTarget(1),
+            Call::Resize(800, 600),
+            Call::CreateTarget,
+        ]
+    );
+}
+
+#[test]
+fn minimizing_keeps_the_buffers() {
+    let mut state = new_state();
+    state.target().unwrap();
+
+    assert_eq!(state.resize(size(0, 0)), Ok(false));
+    assert!(state.is_minimized());
+    assert!(state.has_target());
+    assert_eq!(
+        state.present(&PresentParams::vsync()),
+        Ok(PresentStatus::Skipped)
+    );
+
+    // Restoring to the same size doesn't touch the buffers.
+    assert_eq!(state.resize(size(640, 480)), Ok(false));
+    assert!(!state.is_minimized());
+    assert_eq!(state.backend().calls, vec![Call::CreateTarget]);
+}
+
+#[test]
+fn failed_resizes_are_retried() {
+    let mut state = new_state();
+    state.target().unwrap();
+    state.backend_mut().fail_resize = Some(DXGI_ERROR_DEVICE_REMOVED);
+
+    assert_eq!(
+        state.resize(size(1024, 768)),
+        Err(DXGI_ERROR_DEVICE_REMOVED)
+    );
+    assert_eq!(state.size(), size(640, 480));
+    assert_eq!(state.pending_size(), Some(size(1024, 768)));
+    assert_eq!(state.target(), Err(DXGI_ERROR_DEVICE_REMOVED));
+
+    state.backend_mut().fail_resize = None;
+    assert_eq!(*state.target().unwrap(), 2);
+    assert_eq!(state.size(), size(1024, 768));
+    assert_eq!(state.pending_size(), None);
+}
+
+#[test]
+fn first_present_after_resize_is_full() {
+    let dirty = [WindowRect::new(0, 0, 10, 10)];
+    let partial = PresentParams::vsync().with_dirty_rects(&dirty);
+
+    let mut state = new_state();
+    state.present(&partial).unwrap();
+    state.present(&partial).unwrap();
+    state.resize(size(320, 240)).unwrap();
+    state.present(&partial).unwrap();
+    state.present(&partial).unwrap();
+
+    let presents: Vec<_> = state
+        .backend()
+        .calls
+        .iter()
+        .filter_map(|call| match call {
+            Call::Present { partial } => Some(*partial),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(presents, vec![false, true, false, true]);
+}
+
+#[test]
+fn occlusion_is_tracked() {
+    let mut state = new_state();
+    state.backend_mut().present_status = Some(PresentStatus::Occluded);
+    assert_eq!(
+        state.present(&PresentParams::vsync()),
+        Ok(PresentStatus::Occluded)
+    );
+    assert!(state.is_occluded());
+
+    state.backend_mut().present_status = None;
+    state.present(&PresentParams::vsync()).unwrap();
+    assert!(!state.is_occluded());
+
+    assert_eq!(
+        PresentStatus::from_hresult(DXGI_STATUS_OCCLUDED),
+        PresentStatus::Occluded
+    );
+    assert_eq!(PresentStatus::from_hresult(0), PresentStatus::Presented);
+}
+
+#[test]
+fn present_params_are_validated() {
+    let buffer = size(100, 100);
+    assert_eq!(PresentParams::vsync().validate(buffer), Ok(()));
+    assert_eq!(PresentParams::immediate(true).validate(buffer), Ok(()));
+
+    let tearing_with_vsync = PresentParams {
+        allow_tearing: true,
+        ..PresentParams::vsync()
+    };
+    assert_eq!(tearing_with_vsync.validate(buffer), Err(E_INVALIDARG));
+
+    let too_slow = PresentParams {
+        sync_interval: 5,
+        ..Default::default()
+    };
+    assert_eq!(too_slow.validate(buffer), Err(E_INVALIDARG));
+
+    let outside = [WindowRect::new(50, 50, 101, 60)];
+    let params = PresentParams::vsync().with_dirty_rects(&outside);
+    assert_eq!(params.validate(buffer), Err(E_INVALIDARG));
+
+    let scroll = Scroll {
+        rect: WindowRect::new(0, 10, 100, 100),
+        dx: 0,
+        dy: -10,
+    };
+    let params = PresentParams::vsync().with_scroll(scroll);
+    assert_eq!(params.validate(buffer), Ok(()));
+
+    let params = PresentParams::vsync().with_scroll(Scroll { dy: 10, ..scroll });
+    assert_eq!(params.validate(buffer), Err(E_INVALIDARG));
+
+    // Invalid parameters never reach the swap chain.
+    let mut state = new_state();
+    assert_eq!(state.present(&too_slow), Err(E_INVALIDARG));
+    assert!(state.backend().calls.is_empty());
+}
@@ -0,0 +1,116 @@
+extern crate direct2d;
+extern crate math2d;
+
+use direct2d::units::*;
+use math2d::{Point2f, Rectf, Sizef};
+
+fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Rectf {
+    Rectf {
+        left,
+        top,
+        right,
+        bottom,
+    }
+}
+
+#[test]
+fn dpi_scales() {
+    assert_eq!(Dpi::default(), Dpi::uniform(96.0));
+    assert_eq!(Dpi::from_scale(1.5), Dpi::uniform(144.0));
+    assert_eq!(Dpi::from((120.0, 96.0)).scale_x(), 1.25);
+    assert_eq!(Dpi::from((120.0, 96.0)).scale_y(), 1.0);
+}
+
+#[test]
+fn conversions_round_trip() {
+    let dpi = Dpi::from_scale(1.5);
+
+    assert_eq!(Dip(10.0).to_px(dpi), Px(15.0));
+    assert_eq!(Px(15.0).to_dip(dpi), Dip(10.0));
+
+    let point = Dip(Point2f { x: 2.0, y: 4.0 });
+    assert_eq!(point.to_px(dpi), Px(Point2f { x: 3.0, y: 6.0 }));
+    assert_eq!(point.to_px(dpi).to_dip(dpi), point);
+
+    let size = Dip(Sizef {
+        width: 100.0,
+        height: 50.0,
+    });
+    assert_eq!(
+        size.to_px(Dpi::new(192.0, 96.0)),
+        Px(Sizef {
+            width: 200.0,
+            height: 50.0
+        })
+    );
+
+    let r = Dip(rect(0.0, 0.0, 20.0, 10.0));
+    assert_eq!(r.to_px(dpi), Px(rect(0.0, 0.0, 30.0, 15.0)));
+}
+
+#[test]
+fn arithmetic_stays_in_units() {
+    assert_eq!(Dip(1.5) + Dip(2.0), Dip(3.5));
+    assert_eq!(Px(4.0) - Px(1.0), Px(3.0));
+    assert_eq!(-Dip(2.0), Dip(-2.0));
+    assert_eq!(Px(3.0) * 2.0, Px(6.0));
+    assert_eq!(
+        Dip(rect(2.0, 2.0, 4.0, 8.0)) / 2.0,
+        Dip(rect(1.0, 1.0, 2.0, 4.0))
+    );
+    assert!(Dip(1.0) < Dip(2.0));
+}
+
+#[test]
+fn pixel_rounding() {
+    let r = Px(rect(0.4, 0.6, 10.5, 10.4));
+    assert_eq!(r.round(), Px(rect(0.0, 1.0, 11.0, 10.0)));
+    assert_eq!(r.round_out(), Px(rect(0.0, 0.0, 11.0, 11.0)));
+    assert_eq!(r.round_in(), Px(rect(1.0, 1.0, 10.0, 10.0)));
+}
+
+#[test]
+fn snapping_to_pixels() {
+    // At 150%, 10.1 DIPs is 15.15 pixels, so the nearest boundary is 15px = 10 DIPs.
+    let dpi = Dpi::from_scale(1.5);
+    assert_eq!(Dip(10.1).snap(dpi), Dip(10.0));
+    assert_eq!(
+        Dip(Point2f { x: 1.0, y: 1.0 }).snap(dpi),
+        Dip(Point2f {
+            x: 4.0 / 3.0,
+            y: 4.0 / 3.0
+        })
+    );
+}
+
+#[test]
+fn hairlines() {
+    assert_eq!(hairline(Dpi::DEFAULT), Dip(1.0));
+    assert_eq!(hairline(Dpi::from_scale(2.0)), Dip(0.5));
+    assert_eq!(
+        hairline(Dpi::from_scale(1.5)).to_px(Dpi::from_scale(1.5)),
+        Px(1.0)
+    );
+}
+
+#[test]
+fn stroke_snapping() {
+    let dpi = Dpi::DEFAULT;
+
+    // Odd widths sit on pixel centers, even widths on pixel boundaries.
+    let p = Dip(Point2f { x: 10.2, y: 4.7 });
+    assert_eq!(
+        snap_point_for_stroke(p, Px(1.0), dpi),
+        Dip(Point2f { x: 10.5, y: 4.5 })
+    );
+    assert_eq!(
+        snap_point_for_stroke(p, Px(2.0), dpi),
+        Dip(Point2f { x: 10.0, y: 5.0 })
+    );
+
+    // At 200% a 1px line lands on the center of a physical pixel: 10.25 DIPs.
+    let dpi = Dpi::from_scale(2.0);
+    let r = snap_rect_for_stroke(Dip(rect(10.1, 10.1, 20.1, 20.1)), Px(1.0), dpi);
+    assert_eq!(r, Dip(rect(10.25, 10.25, 20.25, 20.25)));
+    assert_eq!(r.to_px(dpi), Px(rect(20.5, 20.5, 40.5, 40.5)));
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "raw-window-handle")]
+
+extern crate direct2d;
+extern crate raw_window_handle;
+
+use direct2d::render_target::window::*;
+use raw_window_handle::{RawWindowHandle, WaylandHandle, Win32Handle, XlibHandle};
+
+use std::os::raw::c_void;
+
+fn win32(hwnd: usize) -> RawWindowHandle {
+    let mut handle = Win32Handle::empty();
+    handle.hwnd = hwnd as *mut c_void;
+    RawWindowHandle::Win32(handle)
+}
+
+#[test]
+fn live_win32_handles_are_accepted() {
+    let hwnd = validate_handle(win32(0x1234), |hwnd| hwnd as usize == 0x1234);
+    assert_eq!(hwnd, Ok(0x1234 as *mut c_void));
+}
+
+#[test]
+fn other_platforms_are_rejected() {
+    let xlib = RawWindowHandle::Xlib(XlibHandle::empty());
+    let wayland = RawWindowHandle::Wayland(WaylandHandle::empty());
+
+    assert_eq!(
+        validate_handle(xlib, |_| panic!("shouldn't be asked")),
+        Err(WindowHandleError::NotWin32("Xlib"))
+    );
+    assert_eq!(
+        validate_handle(wayland, |_| true),
+        Err(WindowHandleError::NotWin32("Wayland"))
+    );
+}
+
+#[test]
+fn null_and_destroyed_windows_are_rejected() {
+    assert_eq!(
+        validate_handle(win32(0), |_| panic!("shouldn't be asked")),
+        Err(WindowHandleError::NullHandle)
+    );
+    assert_eq!(
+        validate_handle(win32(0xDEAD), |_| false),
+        Err(WindowHandleError::InvalidWindow)
+    );
+}
+
+#[test]
+fn errors_match_direct2d_codes() {
+    // Same code tests/bad_hwnd_test.rs sees from Direct2D for garbage HWNDs.
+    let invalid_window_handle = 0x8007_0578u32 as i32;
+    assert_eq!(
+        WindowHandleError::NullHandle.hresult(),
+        invalid_window_handle
+    );
+    assert_eq!(
+        WindowHandleError::InvalidWindow.hresult(),
+        invalid_window_handle
+    );
+    assert_ne!(
+        WindowHandleError::NotWin32("Xlib").hresult(),
+        invalid_window_handle
+    );
+    assert_eq!(
+        WindowHandleError::NotWin32("AppKit").to_string(),
+        "AppKit windows can't be drawn to by Direct2D"
+    );
+}